@@ -0,0 +1,35 @@
+use godfig::env_default;
+use serde::{Deserialize, Serialize};
+
+/// The transaction ingress configuration.
+/// This covers how the full node writes accepted transactions to the DA light node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+	/// The most unacknowledged batches kept in the outbox before ingress stops taking transactions.
+	#[serde(default = "default_outbox_max_batches")]
+	pub outbox_max_batches: u64,
+
+	/// The delay before retrying a batch the light node did not acknowledge, doubled on every failure.
+	#[serde(default = "default_retry_initial_backoff_ms")]
+	pub retry_initial_backoff_ms: u64,
+
+	/// The longest delay between retries of a batch.
+	#[serde(default = "default_retry_max_backoff_ms")]
+	pub retry_max_backoff_ms: u64,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			outbox_max_batches: default_outbox_max_batches(),
+			retry_initial_backoff_ms: default_retry_initial_backoff_ms(),
+			retry_max_backoff_ms: default_retry_max_backoff_ms(),
+		}
+	}
+}
+
+env_default!(default_outbox_max_batches, "SUZUKA_INGRESS_OUTBOX_MAX_BATCHES", u64, 64);
+
+env_default!(default_retry_initial_backoff_ms, "SUZUKA_INGRESS_RETRY_INITIAL_BACKOFF_MS", u64, 250);
+
+env_default!(default_retry_max_backoff_ms, "SUZUKA_INGRESS_RETRY_MAX_BACKOFF_MS", u64, 10_000);
@@ -1,5 +1,6 @@
 pub mod da_db;
 pub mod execution_extension;
+pub mod ingress;
 pub mod syncing;
 
 use serde::{Deserialize, Serialize};
@@ -29,6 +30,9 @@ pub struct Config {
 
 	#[serde(default)]
 	pub syncing: syncing::Config,
+
+	#[serde(default)]
+	pub ingress: ingress::Config,
 }
 
 impl Default for Config {
@@ -40,6 +44,7 @@ impl Default for Config {
 			da_db: da_db::Config::default(),
 			execution_extension: execution_extension::Config::default(),
 			syncing: syncing::Config::default(),
+			ingress: ingress::Config::default(),
 		}
 	}
 }
@@ -0,0 +1,148 @@
+use crate::admin::replay::ReplayedCommitment;
+use crate::common_args::MovementArgs;
+use anyhow::Context;
+use clap::Parser;
+use mcr_settlement_client::{McrSettlementClient, McrSettlementClientOperations};
+use movement_rest::CommitmentResponse;
+use tracing::info;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser, Clone)]
+#[clap(
+	rename_all = "kebab-case",
+	about = "Compares the commitments written by replay with another such file, or with the commitments accepted by the settlement contract, and reports the first height at which they diverge."
+)]
+pub struct Diff {
+	#[clap(flatten)]
+	pub movement_args: MovementArgs,
+	/// A file of commitments written by replay.
+	pub commitments: PathBuf,
+	/// Another file of commitments to compare with.
+	/// If not provided, the commitments accepted by the settlement contract are compared with.
+	pub other_commitments: Option<PathBuf>,
+}
+
+/// Where two sets of commitments stop agreeing.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Divergence {
+	pub replayed: ReplayedCommitment,
+	pub other: CommitmentResponse,
+}
+
+/// Finds the lowest height at which the commitments differ.
+///
+/// Heights which only one side has a commitment for are not compared.
+/// Returns the divergence, if any, and the number of heights compared.
+pub fn first_divergence(
+	replayed: &[ReplayedCommitment],
+	other: &BTreeMap<u64, CommitmentResponse>,
+) -> (Option<Divergence>, usize) {
+	let mut replayed = replayed.iter().collect::<Vec<_>>();
+	replayed.sort_by_key(|replayed| replayed.commitment.height);
+	let mut compared = 0;
+	for replayed in replayed {
+		if let Some(other) = other.get(&replayed.commitment.height) {
+			compared += 1;
+			if &replayed.commitment != other {
+				let divergence = Divergence { replayed: replayed.clone(), other: other.clone() };
+				return (Some(divergence), compared);
+			}
+		}
+	}
+	(None, compared)
+}
+
+impl Diff {
+	pub async fn execute(&self) -> Result<(), anyhow::Error> {
+		let replayed = ReplayedCommitment::read_file(&self.commitments)?;
+
+		let other = match &self.other_commitments {
+			Some(other_commitments) => ReplayedCommitment::read_file(other_commitments)?
+				.into_iter()
+				.map(|other| (other.commitment.height, other.commitment))
+				.collect::<BTreeMap<_, _>>(),
+			None => {
+				let config = self.movement_args.config().await?;
+				info!("Loaded config {:?}", config);
+				let settlement_client =
+					McrSettlementClient::build_with_config(&config.mcr)
+						.await
+						.context("Failed to build MCR settlement client with config")?;
+				let mut accepted = BTreeMap::new();
+				for replayed in &replayed {
+					let height = replayed.commitment.height;
+					if let Some(commitment) =
+						settlement_client.get_commitment_at_height(height).await?
+					{
+						accepted.insert(height, commitment.into());
+					}
+				}
+				accepted
+			}
+		};
+
+		// Use println as this is standard (non-logging output)
+		match first_divergence(&replayed, &other) {
+			(Some(divergence), _) => {
+				println!(
+					"Commitments diverge at height {} (DA height {}): block {} with commitment {}, against block {} with commitment {}",
+					divergence.replayed.commitment.height,
+					divergence.replayed.da_height,
+					divergence.replayed.commitment.block_id,
+					divergence.replayed.commitment.commitment,
+					divergence.other.block_id,
+					divergence.other.commitment,
+				);
+				anyhow::bail!(
+					"Commitments diverge at height {}",
+					divergence.replayed.commitment.height
+				)
+			}
+			(None, compared) => {
+				println!("No divergence in {} compared heights", compared);
+				Ok(())
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	fn commitment(height: u64, block_id: &str, commitment: &str) -> CommitmentResponse {
+		CommitmentResponse {
+			height,
+			block_id: block_id.to_string(),
+			commitment: commitment.to_string(),
+		}
+	}
+
+	#[test]
+	fn test_first_divergence() {
+		let replayed = vec![
+			ReplayedCommitment { da_height: 12, commitment: commitment(3, "c3", "33") },
+			ReplayedCommitment { da_height: 10, commitment: commitment(1, "c1", "11") },
+			ReplayedCommitment { da_height: 11, commitment: commitment(2, "c2", "22") },
+		];
+
+		// the other side has no commitment at height 2, so it is skipped
+		let other =
+			BTreeMap::from([(1, commitment(1, "c1", "11")), (3, commitment(3, "c3", "33"))]);
+		assert_eq!(first_divergence(&replayed, &other), (None, 2));
+
+		let other = BTreeMap::from([
+			(1, commitment(1, "c1", "11")),
+			(2, commitment(2, "d2", "ff")),
+			(3, commitment(3, "d3", "ee")),
+		]);
+		let (divergence, compared) = first_divergence(&replayed, &other);
+		let divergence = divergence.expect("the commitments should diverge");
+		assert_eq!(compared, 2);
+		assert_eq!(divergence.replayed.da_height, 11);
+		assert_eq!(divergence.other.block_id, "d2");
+	}
+}
@@ -1,16 +1,22 @@
+pub mod diff;
 pub mod force_commitment;
+pub mod replay;
 use clap::Subcommand;
 
 #[derive(Subcommand, Debug)]
 #[clap(rename_all = "kebab-case", about = "Commands for syncing")]
 pub enum Admin {
 	ForceCommitment(force_commitment::ForceCommitment),
+	Replay(replay::Replay),
+	Diff(diff::Diff),
 }
 
 impl Admin {
 	pub async fn execute(&self) -> Result<(), anyhow::Error> {
 		match self {
 			Admin::ForceCommitment(force_commitment) => force_commitment.execute().await,
+			Admin::Replay(replay) => replay.execute().await,
+			Admin::Diff(diff) => diff.execute().await,
 		}
 	}
 }
@@ -0,0 +1,138 @@
+use crate::common_args::MovementArgs;
+use crate::node::partial::{light_node_client, MovementPartialNode};
+use crate::node::tasks::execute_settle::{
+	decode_block, execute_block_with_retries, read_sequenced_blob,
+};
+use anyhow::Context;
+use clap::Parser;
+use movement_da_light_node_proto::{ReadAtHeightRequest, StreamReadFromHeightResponse};
+use movement_rest::CommitmentResponse;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// The commitment of a replayed block, as one line of the file written by replay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayedCommitment {
+	/// The DA height the block was read at.
+	pub da_height: u64,
+	#[serde(flatten)]
+	pub commitment: CommitmentResponse,
+}
+
+impl ReplayedCommitment {
+	/// Reads the commitments from a file written by replay.
+	pub fn read_file(path: &Path) -> Result<Vec<Self>, anyhow::Error> {
+		let file = File::open(path).context(format!("Failed to open {}", path.display()))?;
+		BufReader::new(file)
+			.lines()
+			.enumerate()
+			.filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+			.map(|(index, line)| {
+				serde_json::from_str(&line?).context(format!(
+					"Invalid commitment on line {} of {}",
+					index + 1,
+					path.display()
+				))
+			})
+			.collect()
+	}
+}
+
+#[derive(Debug, Parser, Clone)]
+#[clap(
+	rename_all = "kebab-case",
+	about = "Re-executes a range of DA heights with a fresh executor and writes the commitment of every block to a file. The executor starts from genesis, so the range should start where the chain started for the commitments to match those of the network."
+)]
+pub struct Replay {
+	#[clap(flatten)]
+	pub movement_args: MovementArgs,
+	/// The first DA height to replay.
+	#[clap(long, default_value_t = 0)]
+	pub from_da_height: u64,
+	/// The last DA height to replay.
+	#[clap(long)]
+	pub to_da_height: u64,
+	/// The file to write the commitments to, one JSON object per line.
+	#[clap(long)]
+	pub output: PathBuf,
+}
+
+impl Replay {
+	pub async fn execute(&self) -> Result<(), anyhow::Error> {
+		if self.from_da_height > self.to_da_height {
+			anyhow::bail!(
+				"The first DA height {} is above the last DA height {}",
+				self.from_da_height,
+				self.to_da_height
+			);
+		}
+		let mut config = self.movement_args.config().await?;
+		info!("Loaded config {:?}", config);
+
+		// execute into a temporary directory, leaving the ledger of the node untouched
+		let tempdir = tempfile::tempdir().context("Failed to create a temporary directory")?;
+		config
+			.execution_config
+			.maptos_config
+			.chain
+			.maptos_db_path
+			.replace(tempdir.path().to_path_buf());
+		let executor = MovementPartialNode::try_executor_from_config(config.clone())
+			.await
+			.context("Failed to create the executor")?;
+		let mut light_node_client = light_node_client(&config).await?;
+
+		let mut output = BufWriter::new(
+			File::create(&self.output)
+				.context(format!("Failed to create {}", self.output.display()))?,
+		);
+		// the heights are read one by one rather than streamed, as a stream waits for new heights
+		// and would never end when the range reaches the head of the DA
+		let mut executed_blobs = HashSet::new();
+		for height in self.from_da_height..=self.to_da_height {
+			let blobs = match light_node_client.read_at_height(ReadAtHeightRequest { height }).await
+			{
+				Ok(response) => response.blobs,
+				Err(status) if status.code() == tonic::Code::NotFound => continue,
+				Err(status) => {
+					return Err(status).context(format!("Failed to read DA height {}", height))
+				}
+			};
+			for blob in blobs {
+				let response = StreamReadFromHeightResponse { blob: Some(blob) };
+				let (block_bytes, block_timestamp, blob_id, da_height) =
+					read_sequenced_blob(response)?;
+				// the same checks as the node, so that the same blocks are executed
+				if !executed_blobs.insert(blob_id) {
+					continue;
+				}
+				if da_height < 2 {
+					anyhow::bail!("Invalid DA height: {:?}", da_height);
+				}
+
+				let (block, block_timestamp) = decode_block(&block_bytes, block_timestamp)?;
+				let (commitment, _) = execute_block_with_retries(
+					&executor,
+					&config.execution_extension,
+					block,
+					block_timestamp,
+				)
+				.await?;
+				info!(da_height, height = commitment.height(), "Replayed block");
+
+				// every line is flushed, so that an interrupted replay keeps the blocks replayed so far
+				let replayed = ReplayedCommitment { da_height, commitment: commitment.into() };
+				writeln!(output, "{}", serde_json::to_string(&replayed)?)?;
+				output.flush()?;
+			}
+		}
+		info!("Wrote the replayed commitments to {}", self.output.display());
+
+		Ok(())
+	}
+}
@@ -1,7 +1,7 @@
 #![forbid(unsafe_code)]
 
 use clap::*;
-use movement_full_node::MovementFullNode;
+use movement_full_node::{node::supervisor::TaskFailure, MovementFullNode};
 const TIMING_LOG_ENV: &str = "SUZUKA_TIMING_LOG";
 use std::env;
 
@@ -9,11 +9,19 @@ use std::env;
 async fn main() -> Result<(), anyhow::Error> {
 	let tracing_config =
 		movement_tracing::Config { timing_log_path: env::var_os(TIMING_LOG_ENV).map(Into::into) };
-	let _guard = movement_tracing::init_tracing_subscriber(tracing_config);
+	let guard = movement_tracing::init_tracing_subscriber(tracing_config);
 
 	let suzuka_util = MovementFullNode::parse();
 
-	suzuka_util.execute().await?;
+	if let Err(e) = suzuka_util.execute().await {
+		// exit with the code of the task which took the node down
+		if let Some(failure) = e.downcast_ref::<TaskFailure>() {
+			eprintln!("Error: {:?}", e);
+			drop(guard);
+			std::process::exit(failure.exit_code);
+		}
+		return Err(e);
+	}
 
 	Ok(())
 }
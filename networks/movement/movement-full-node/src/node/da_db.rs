@@ -1,4 +1,6 @@
-use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use movement_types::block::BlockCommitment;
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
 
 use std::path::Path;
 use std::sync::Arc;
@@ -6,9 +8,46 @@ use std::sync::Arc;
 mod column_families {
 	pub const EXECUTED_BLOCKS: &str = "executed_blocks";
 	pub const SYNCED_HEIGHT: &str = "synced_height";
+	pub const INGRESS_OUTBOX: &str = "ingress_outbox";
+	pub const INGRESS_DEAD_LETTERS: &str = "ingress_dead_letters";
+	pub const BLOCK_PROVENANCE: &str = "block_provenance";
+	pub const DA_HEIGHT_BLOCKS: &str = "da_height_blocks";
+	pub const LEDGER_HEIGHT_BLOCKS: &str = "ledger_height_blocks";
+	pub const TRANSACTION_BLOCKS: &str = "transaction_blocks";
 }
 use column_families::*;
 
+const ALL_COLUMN_FAMILIES: [&str; 8] = [
+	SYNCED_HEIGHT,
+	EXECUTED_BLOCKS,
+	INGRESS_OUTBOX,
+	INGRESS_DEAD_LETTERS,
+	BLOCK_PROVENANCE,
+	DA_HEIGHT_BLOCKS,
+	LEDGER_HEIGHT_BLOCKS,
+	TRANSACTION_BLOCKS,
+];
+
+/// The key of the id of the latest executed block, in the synced_height column family.
+const LATEST_EXECUTED_BLOCK: &str = "latest_executed_block";
+
+/// A block the node executed, with where it was read from the DA and where it went in the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutedBlock {
+	/// The id of the block, which is also its id in the ledger.
+	pub block_id: Vec<u8>,
+	/// The id of the DA blob the block was read from.
+	pub blob_id: Vec<u8>,
+	pub da_height: u64,
+	/// The timestamp of the block, in microseconds.
+	pub block_timestamp: u64,
+	pub ledger_height: u64,
+	/// The first ledger version of the block, which is its block metadata transaction.
+	pub ledger_start_version: u64,
+	pub ledger_end_version: u64,
+	pub commitment: BlockCommitment,
+}
+
 /// Simple data store for locally recorded DA events.
 ///
 /// An async access API is provided to avoid blocking async tasks.
@@ -24,28 +63,80 @@ impl DaDB {
 		options.create_if_missing(true);
 		options.create_missing_column_families(true);
 
-		let synced_height = ColumnFamilyDescriptor::new(SYNCED_HEIGHT, Options::default());
-		let executed_blocks = ColumnFamilyDescriptor::new(EXECUTED_BLOCKS, Options::default());
+		let column_families = ALL_COLUMN_FAMILIES
+			.iter()
+			.map(|name| ColumnFamilyDescriptor::new(*name, Options::default()))
+			.collect::<Vec<_>>();
+
+		let db = DB::open_cf_descriptors(&options, path, column_families)
+			.map_err(|e| anyhow::anyhow!("Failed to open DA DB: {:?}", e))?;
+		Ok(Self { inner: Arc::new(db) })
+	}
 
-		let db = DB::open_cf_descriptors(&options, path, vec![synced_height, executed_blocks])
+	/// Opens the DA DB to read it while a node may be writing to it.
+	///
+	/// The DB is read as it was when opened. Column families the DB does not have yet cannot be read.
+	pub fn open_read_only(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+		let column_families = DB::list_cf(&Options::default(), &path)
+			.map_err(|e| anyhow::anyhow!("Failed to list DA DB column families: {:?}", e))?;
+		let db = DB::open_cf_for_read_only(&Options::default(), &path, column_families, false)
 			.map_err(|e| anyhow::anyhow!("Failed to open DA DB: {:?}", e))?;
 		Ok(Self { inner: Arc::new(db) })
 	}
 
-	pub async fn add_executed_block(&self, id: Vec<u8>) -> Result<(), anyhow::Error> {
+	/// Marks a block as executed, indexing it by its id, its DA height, its ledger height
+	/// and the hashes of the transactions it executed.
+	pub async fn add_executed_block(
+		&self,
+		block: ExecutedBlock,
+		transaction_hashes: Vec<Vec<u8>>,
+	) -> Result<(), anyhow::Error> {
 		let da_db = self.inner.clone();
 		tokio::task::spawn_blocking(move || {
-			let cf = da_db
+			let executed_blocks = da_db
 				.cf_handle(EXECUTED_BLOCKS)
 				.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
+			let block_provenance = da_db
+				.cf_handle(BLOCK_PROVENANCE)
+				.ok_or(anyhow::anyhow!("No block_provenance column family"))?;
+			let da_height_blocks = da_db
+				.cf_handle(DA_HEIGHT_BLOCKS)
+				.ok_or(anyhow::anyhow!("No da_height_blocks column family"))?;
+			let ledger_height_blocks = da_db
+				.cf_handle(LEDGER_HEIGHT_BLOCKS)
+				.ok_or(anyhow::anyhow!("No ledger_height_blocks column family"))?;
+			let transaction_blocks = da_db
+				.cf_handle(TRANSACTION_BLOCKS)
+				.ok_or(anyhow::anyhow!("No transaction_blocks column family"))?;
+			let synced_height = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let provenance = serde_json::to_vec(&block)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize executed block: {:?}", e))?;
+
+			// the indexes are written at once, so that they never disagree
+			let mut batch = WriteBatch::default();
+			batch.put_cf(&executed_blocks, &block.blob_id, &block.block_id);
+			batch.put_cf(&block_provenance, &block.block_id, provenance);
+			batch.put_cf(
+				&da_height_blocks,
+				[&block.da_height.to_be_bytes()[..], &block.block_id].concat(),
+				b"",
+			);
+			batch.put_cf(&ledger_height_blocks, block.ledger_height.to_be_bytes(), &block.block_id);
+			for transaction_hash in transaction_hashes {
+				batch.put_cf(&transaction_blocks, transaction_hash, &block.block_id);
+			}
+			batch.put_cf(&synced_height, LATEST_EXECUTED_BLOCK, &block.block_id);
 			da_db
-				.put_cf(&cf, id.clone(), id)
+				.write(batch)
 				.map_err(|e| anyhow::anyhow!("Failed to add executed block: {:?}", e))
 		})
 		.await??;
 		Ok(())
 	}
 
+	/// Checks whether the block of a DA blob was executed.
 	pub async fn has_executed_block(&self, id: Vec<u8>) -> Result<bool, anyhow::Error> {
 		let da_db = self.inner.clone();
 		let id = tokio::task::spawn_blocking(move || {
@@ -60,6 +151,110 @@ impl DaDB {
 		Ok(id.is_some())
 	}
 
+	/// Gets an executed block by its id.
+	pub async fn get_executed_block(
+		&self,
+		id: Vec<u8>,
+	) -> Result<Option<ExecutedBlock>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let block = tokio::task::spawn_blocking(move || executed_block(&da_db, &id)).await??;
+		Ok(block)
+	}
+
+	/// Gets the block the node executed last.
+	pub async fn get_latest_executed_block(&self) -> Result<Option<ExecutedBlock>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let block = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(SYNCED_HEIGHT)
+				.ok_or(anyhow::anyhow!("No synced_height column family"))?;
+			let id = da_db
+				.get_cf(&cf, LATEST_EXECUTED_BLOCK)
+				.map_err(|e| anyhow::anyhow!("Failed to get latest executed block: {:?}", e))?;
+			match id {
+				Some(id) => executed_block(&da_db, &id),
+				None => Ok(None),
+			}
+		})
+		.await??;
+		Ok(block)
+	}
+
+	/// Gets the executed blocks which were read at a DA height, ordered by id.
+	pub async fn get_executed_blocks_at_da_height(
+		&self,
+		da_height: u64,
+	) -> Result<Vec<ExecutedBlock>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let blocks = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(DA_HEIGHT_BLOCKS)
+				.ok_or(anyhow::anyhow!("No da_height_blocks column family"))?;
+			let prefix = da_height.to_be_bytes();
+			let mut blocks = Vec::new();
+			for entry in da_db.iterator_cf(&cf, IteratorMode::From(&prefix, Direction::Forward)) {
+				let (key, _) = entry
+					.map_err(|e| anyhow::anyhow!("Failed to read DA height index: {:?}", e))?;
+				if !key.starts_with(&prefix) {
+					break;
+				}
+				let id = &key[prefix.len()..];
+				let block = executed_block(&da_db, id)?.ok_or(anyhow::anyhow!(
+					"Executed block {} is indexed but not recorded",
+					hex::encode(id)
+				))?;
+				blocks.push(block);
+			}
+			Ok::<Vec<ExecutedBlock>, anyhow::Error>(blocks)
+		})
+		.await??;
+		Ok(blocks)
+	}
+
+	/// Gets the executed block which executed a transaction, by the hash of the transaction.
+	pub async fn get_executed_block_by_transaction(
+		&self,
+		transaction_hash: Vec<u8>,
+	) -> Result<Option<ExecutedBlock>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let block = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(TRANSACTION_BLOCKS)
+				.ok_or(anyhow::anyhow!("No transaction_blocks column family"))?;
+			let id = da_db
+				.get_cf(&cf, transaction_hash)
+				.map_err(|e| anyhow::anyhow!("Failed to get transaction index: {:?}", e))?;
+			match id {
+				Some(id) => executed_block(&da_db, &id),
+				None => Ok(None),
+			}
+		})
+		.await??;
+		Ok(block)
+	}
+
+	/// Gets the executed block at a height of the ledger.
+	pub async fn get_executed_block_at_ledger_height(
+		&self,
+		ledger_height: u64,
+	) -> Result<Option<ExecutedBlock>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let block = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(LEDGER_HEIGHT_BLOCKS)
+				.ok_or(anyhow::anyhow!("No ledger_height_blocks column family"))?;
+			let id = da_db
+				.get_cf(&cf, ledger_height.to_be_bytes())
+				.map_err(|e| anyhow::anyhow!("Failed to get ledger height index: {:?}", e))?;
+			match id {
+				Some(id) => executed_block(&da_db, &id),
+				None => Ok(None),
+			}
+		})
+		.await??;
+		Ok(block)
+	}
+
 	pub async fn set_synced_height(&self, height: u64) -> Result<(), anyhow::Error> {
 		// This is heavy for this purpose, but progressively the contents of the DA DB will be used for more things
 		let da_db = self.inner.clone();
@@ -97,4 +292,222 @@ impl DaDB {
 		.await??;
 		Ok(height)
 	}
+
+	/// Flushes all writes to disk, so that nothing is left to recover from the write-ahead log.
+	pub async fn flush(&self) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			for name in ALL_COLUMN_FAMILIES {
+				let cf =
+					da_db.cf_handle(name).ok_or(anyhow::anyhow!("No {} column family", name))?;
+				da_db
+					.flush_cf(&cf)
+					.map_err(|e| anyhow::anyhow!("Failed to flush {}: {:?}", name, e))?;
+			}
+			Ok::<(), anyhow::Error>(())
+		})
+		.await??;
+		Ok(())
+	}
+
+	/// Adds a batch of transactions for the DA to the outbox, returning its id.
+	///
+	/// Ids increase in the order batches are added,
+	/// and are not reused while the outbox or the dead letters have batches.
+	pub async fn add_outbox_batch(&self, batch: Vec<u8>) -> Result<u64, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let id = tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(INGRESS_OUTBOX)
+				.ok_or(anyhow::anyhow!("No ingress_outbox column family"))?;
+			let dead_letters_cf = da_db
+				.cf_handle(INGRESS_DEAD_LETTERS)
+				.ok_or(anyhow::anyhow!("No ingress_dead_letters column family"))?;
+			let mut id = 0;
+			for family in [&cf, &dead_letters_cf] {
+				if let Some(entry) = da_db.iterator_cf(family, IteratorMode::End).next() {
+					let (key, _) = entry
+						.map_err(|e| anyhow::anyhow!("Failed to read outbox batch: {:?}", e))?;
+					id = id.max(outbox_id(&key)? + 1);
+				}
+			}
+			da_db
+				.put_cf(&cf, id.to_be_bytes(), batch)
+				.map_err(|e| anyhow::anyhow!("Failed to add outbox batch: {:?}", e))?;
+			Ok::<u64, anyhow::Error>(id)
+		})
+		.await??;
+		Ok(id)
+	}
+
+	/// Removes a batch the DA acknowledged from the outbox.
+	pub async fn remove_outbox_batch(&self, id: u64) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(INGRESS_OUTBOX)
+				.ok_or(anyhow::anyhow!("No ingress_outbox column family"))?;
+			da_db
+				.delete_cf(&cf, id.to_be_bytes())
+				.map_err(|e| anyhow::anyhow!("Failed to remove outbox batch: {:?}", e))
+		})
+		.await??;
+		Ok(())
+	}
+
+	/// Gets the batches in the outbox with their ids, oldest first.
+	pub async fn get_outbox_batches(&self) -> Result<Vec<(u64, Vec<u8>)>, anyhow::Error> {
+		self.get_batches(INGRESS_OUTBOX).await
+	}
+
+	/// Moves a batch the DA rejected for good from the outbox to the dead letters,
+	/// where it is kept for inspection but not written again.
+	pub async fn dead_letter_outbox_batch(&self, id: u64) -> Result<(), anyhow::Error> {
+		let da_db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = da_db
+				.cf_handle(INGRESS_OUTBOX)
+				.ok_or(anyhow::anyhow!("No ingress_outbox column family"))?;
+			let dead_letters_cf = da_db
+				.cf_handle(INGRESS_DEAD_LETTERS)
+				.ok_or(anyhow::anyhow!("No ingress_dead_letters column family"))?;
+			let batch = da_db
+				.get_cf(&cf, id.to_be_bytes())
+				.map_err(|e| anyhow::anyhow!("Failed to read outbox batch: {:?}", e))?
+				.ok_or(anyhow::anyhow!("No outbox batch {}", id))?;
+
+			let mut write_batch = WriteBatch::default();
+			write_batch.delete_cf(&cf, id.to_be_bytes());
+			write_batch.put_cf(&dead_letters_cf, id.to_be_bytes(), batch);
+			da_db
+				.write(write_batch)
+				.map_err(|e| anyhow::anyhow!("Failed to dead letter outbox batch: {:?}", e))
+		})
+		.await??;
+		Ok(())
+	}
+
+	/// Gets the batches in the dead letters with their ids, oldest first.
+	pub async fn get_dead_letter_batches(&self) -> Result<Vec<(u64, Vec<u8>)>, anyhow::Error> {
+		self.get_batches(INGRESS_DEAD_LETTERS).await
+	}
+
+	async fn get_batches(&self, name: &'static str) -> Result<Vec<(u64, Vec<u8>)>, anyhow::Error> {
+		let da_db = self.inner.clone();
+		let batches = tokio::task::spawn_blocking(move || {
+			let cf = da_db.cf_handle(name).ok_or(anyhow::anyhow!("No {} column family", name))?;
+			da_db
+				.iterator_cf(&cf, IteratorMode::Start)
+				.map(|entry| {
+					let (key, batch) = entry
+						.map_err(|e| anyhow::anyhow!("Failed to read outbox batch: {:?}", e))?;
+					Ok((outbox_id(&key)?, batch.to_vec()))
+				})
+				.collect::<Result<Vec<_>, anyhow::Error>>()
+		})
+		.await??;
+		Ok(batches)
+	}
+}
+
+/// Reads the provenance of an executed block.
+///
+/// Blocks executed before provenance was recorded are only known by their blob id, so they have none.
+fn executed_block(da_db: &DB, id: &[u8]) -> Result<Option<ExecutedBlock>, anyhow::Error> {
+	let cf = da_db
+		.cf_handle(BLOCK_PROVENANCE)
+		.ok_or(anyhow::anyhow!("No block_provenance column family"))?;
+	let block = da_db
+		.get_cf(&cf, id)
+		.map_err(|e| anyhow::anyhow!("Failed to get executed block: {:?}", e))?;
+	match block {
+		Some(block) => serde_json::from_slice(&block)
+			.map(Some)
+			.map_err(|e| anyhow::anyhow!("Failed to deserialize executed block: {:?}", e)),
+		None => Ok(None),
+	}
+}
+
+fn outbox_id(key: &[u8]) -> Result<u64, anyhow::Error> {
+	let bytes: [u8; 8] = key
+		.try_into()
+		.map_err(|_| anyhow::anyhow!("Invalid outbox batch id {:?}", key))?;
+	Ok(u64::from_be_bytes(bytes))
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use movement_types::block::{Commitment, Id};
+
+	fn block(id: u8, da_height: u64, ledger_height: u64) -> ExecutedBlock {
+		ExecutedBlock {
+			block_id: vec![id; 32],
+			blob_id: vec![id, id],
+			da_height,
+			block_timestamp: 1_000,
+			ledger_height,
+			ledger_start_version: ledger_height * 10,
+			ledger_end_version: ledger_height * 10 + 9,
+			commitment: BlockCommitment::new(
+				ledger_height,
+				Id::new([id; 32]),
+				Commitment::new([0; 32]),
+			),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_block_provenance() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(dir.path())?;
+
+		let first = block(1, 5, 1);
+		let second = block(2, 5, 2);
+		let third = block(3, 6, 3);
+		da_db.add_executed_block(first.clone(), vec![vec![10; 32]]).await?;
+		da_db
+			.add_executed_block(second.clone(), vec![vec![20; 32], vec![21; 32]])
+			.await?;
+		da_db.add_executed_block(third.clone(), vec![]).await?;
+
+		assert!(da_db.has_executed_block(vec![2, 2]).await?);
+		assert_eq!(da_db.get_executed_block(vec![1; 32]).await?, Some(first.clone()));
+		assert_eq!(da_db.get_latest_executed_block().await?, Some(third.clone()));
+		assert_eq!(da_db.get_executed_blocks_at_da_height(5).await?, vec![first, second.clone()]);
+		assert_eq!(da_db.get_executed_blocks_at_da_height(7).await?, vec![]);
+		assert_eq!(da_db.get_executed_block_at_ledger_height(3).await?, Some(third));
+		assert_eq!(da_db.get_executed_block_by_transaction(vec![21; 32]).await?, Some(second));
+		assert_eq!(da_db.get_executed_block_by_transaction(vec![22; 32]).await?, None);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_outbox() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let da_db = DaDB::open(dir.path())?;
+
+		assert_eq!(da_db.add_outbox_batch(vec![1]).await?, 0);
+		assert_eq!(da_db.add_outbox_batch(vec![2]).await?, 1);
+		assert_eq!(da_db.add_outbox_batch(vec![3]).await?, 2);
+		da_db.remove_outbox_batch(0).await?;
+		assert_eq!(da_db.get_outbox_batches().await?, vec![(1, vec![2]), (2, vec![3])]);
+
+		// a rejected batch leaves the outbox, and its id is not reused
+		da_db.dead_letter_outbox_batch(2).await?;
+		assert_eq!(da_db.get_outbox_batches().await?, vec![(1, vec![2])]);
+		assert_eq!(da_db.get_dead_letter_batches().await?, vec![(2, vec![3])]);
+		assert_eq!(da_db.add_outbox_batch(vec![4]).await?, 3);
+		assert!(da_db.dead_letter_outbox_batch(0).await.is_err());
+
+		// the batches which were not acknowledged are replayed after a restart
+		drop(da_db);
+		let da_db = DaDB::open(dir.path())?;
+		assert_eq!(da_db.get_outbox_batches().await?, vec![(1, vec![2]), (3, vec![4])]);
+		assert_eq!(da_db.get_dead_letter_batches().await?, vec![(2, vec![3])]);
+
+		Ok(())
+	}
 }
@@ -47,16 +47,11 @@ impl Manager {
 			.await
 			.context("Failed to create the executor")?;
 
-		let join_handle = tokio::spawn(node.run());
-
-		// Use tokio::select! to wait for either the handle or a cancellation signal
-		tokio::select! {
-			_ = stop_rx.changed() =>(),
-			// manage Movement node execution return.
-			res = join_handle => {
-				res??;
-			},
-		};
+		// the node shuts down in order on a stop signal
+		node.run(async move {
+			let _ = stop_rx.changed().await;
+		})
+		.await?;
 
 		Ok(())
 	}
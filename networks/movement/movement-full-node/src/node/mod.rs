@@ -1,4 +1,6 @@
-mod da_db;
+pub(crate) mod da_db;
 pub mod manager;
 pub mod partial;
-mod tasks;
+mod rest;
+pub mod supervisor;
+pub(crate) mod tasks;
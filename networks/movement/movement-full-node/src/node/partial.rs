@@ -1,17 +1,56 @@
-use crate::node::{da_db::DaDB, tasks};
+use crate::node::{
+	da_db::DaDB,
+	rest::RestNodeState,
+	supervisor::{RestartPolicy, Supervisor},
+	tasks,
+};
+use k256::Secp256k1;
 use maptos_dof_execution::MakeOptFinServices;
 use maptos_dof_execution::{v1::Executor, DynOptFinExecutor};
 use mcr_settlement_client::McrSettlementClient;
 use mcr_settlement_manager::CommitmentEventStream;
 use mcr_settlement_manager::McrSettlementManager;
+use movement_celestia_da_util::signers::SignerSchedule;
 use movement_config::Config;
-use movement_da_light_node_client::MovementDaLightNodeClient;
+use movement_da_light_node_client::{ClientOptions, IntentChecker, MovementDaLightNodeClient};
 use movement_rest::MovementRest;
 
 use anyhow::Context;
-use tokio::sync::mpsc;
-use tokio::try_join;
-use tracing::debug;
+use tokio::sync::{mpsc, watch, Mutex};
+use tracing::{debug, error, info};
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+const TRANSACTION_INGRESS: &str = "transaction_ingress";
+const EXECUTE_SETTLE: &str = "execute_settle";
+const EXECUTOR_BACKGROUND: &str = "executor_background";
+const SERVICES: &str = "services";
+
+/// How many executed blocks the intent checker keeps for intents acknowledged after their block was executed.
+const INTENT_RETAINED_BLOCKS: usize = 32;
+const MOVEMENT_REST: &str = "movement_rest";
+
+/// The codes the node exits with when a task fails, identifying the task.
+pub mod exit_codes {
+	pub const TRANSACTION_INGRESS: i32 = 10;
+	pub const EXECUTE_SETTLE: i32 = 11;
+	pub const EXECUTOR_BACKGROUND: i32 = 12;
+	pub const SERVICES: i32 = 13;
+	pub const MOVEMENT_REST: i32 = 14;
+}
+
+/// Ingress and execution recover by themselves after a failure, so they are restarted a few times,
+/// counting anew once they have run for ten minutes.
+const RESTART_ON_FAILURE: RestartPolicy = RestartPolicy::OnFailure {
+	max_restarts: 5,
+	backoff: Duration::from_secs(1),
+	reset_after: Duration::from_secs(600),
+};
+
+/// How long a task has to stop on shutdown before it is aborted.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
 
 pub struct MovementPartialNode<T> {
 	executor: T,
@@ -19,6 +58,8 @@ pub struct MovementPartialNode<T> {
 	settlement_manager: Option<McrSettlementManager>,
 	commitment_events: Option<CommitmentEventStream>,
 	movement_rest: MovementRest,
+	/// Reads the accepted commitments for the movement rest service, if the node settles.
+	rest_settlement_client: Option<McrSettlementClient>,
 	config: Config,
 	da_db: DaDB,
 }
@@ -36,8 +77,15 @@ where
 	}
 
 	// ! Currently this only implements opt.
-	/// Runs the executor until crash or shutdown.
-	pub async fn run(self) -> Result<(), anyhow::Error> {
+	/// Runs the executor until a task fails or `shutdown` completes, then shuts the node down in order.
+	///
+	/// The API and the mempool are stopped first along with the movement rest service,
+	/// so that no more transactions come in.
+	/// Then transaction ingress is stopped, draining the batches being written to the DA,
+	/// and the block being executed is finished, after which the DA DB is flushed.
+	/// The Aptos DB has no flush of its own: it is closed once the last reference to the executor is dropped,
+	/// and otherwise recovers from its write-ahead log on the next start.
+	pub async fn run(self, shutdown: impl Future<Output = ()>) -> Result<(), anyhow::Error> {
 		let (transaction_sender, transaction_receiver) = mpsc::channel(16);
 		let (context, exec_background) = self
 			.executor
@@ -45,38 +93,109 @@ where
 		let services = context.services();
 		let mut movement_rest = self.movement_rest;
 		movement_rest.set_context(services.opt_api_context());
-		let exec_settle_task = tasks::execute_settle::Task::new(
+		let da_db = self.da_db;
+		movement_rest.set_node_state(Arc::new(RestNodeState::new(
+			da_db.clone(),
+			self.rest_settlement_client,
+		)));
+		// the intents the sequencer signs on write are checked against the blocks executed from the DA,
+		// which are signed by the DA signers active at their height and in their slot
+		let light_node_config = &self.config.celestia_da_light_node.celestia_da_light_node_config;
+		let signers = light_node_config
+			.try_da_signer_registry::<Secp256k1>()
+			.context("Failed to read the DA signers")?;
+		let schedule =
+			SignerSchedule::try_from_rotation(&signers, &light_node_config.proposer_rotation())
+				.context("Failed to build the proposer schedule of the DA signers")?;
+		let intent_checker =
+			Arc::new(Mutex::new(IntentChecker::new(signers, schedule, INTENT_RETAINED_BLOCKS)));
+		let exec_settle_task = Arc::new(Mutex::new(tasks::execute_settle::Task::new(
 			self.executor,
 			self.settlement_manager,
-			self.da_db,
+			da_db.clone(),
 			self.light_node_client.clone(),
 			self.commitment_events,
 			self.config.execution_extension.clone(),
 			self.config.mcr.clone(),
-		);
-		let transaction_ingress_task = tasks::transaction_ingress::Task::new(
+			intent_checker.clone(),
+		)));
+		let transaction_ingress_task = Arc::new(Mutex::new(tasks::transaction_ingress::Task::new(
 			transaction_receiver,
 			self.light_node_client,
 			// FIXME: why are the struct member names so tautological?
 			self.config.celestia_da_light_node.celestia_da_light_node_config,
+			da_db.clone(),
+			self.config.ingress,
+			intent_checker,
+		)));
+
+		let (stop_ingress, ingress_stop) = watch::channel(false);
+		let (stop_execution, execution_stop) = watch::channel(false);
+		let start_transaction_ingress = {
+			let task = transaction_ingress_task.clone();
+			move || {
+				let task = task.clone();
+				let stop = ingress_stop.clone();
+				async move { task.lock().await.run(stop).await }
+			}
+		};
+		let start_execute_settle = {
+			let task = exec_settle_task.clone();
+			move || {
+				let task = task.clone();
+				let stop = execution_stop.clone();
+				async move { task.lock().await.run(stop).await }
+			}
+		};
+
+		let mut supervisor = Supervisor::new();
+		supervisor.spawn(
+			TRANSACTION_INGRESS,
+			exit_codes::TRANSACTION_INGRESS,
+			RESTART_ON_FAILURE,
+			start_transaction_ingress,
+		);
+		supervisor.spawn(
+			EXECUTE_SETTLE,
+			exit_codes::EXECUTE_SETTLE,
+			RESTART_ON_FAILURE,
+			start_execute_settle,
+		);
+		supervisor.spawn_once(
+			EXECUTOR_BACKGROUND,
+			exit_codes::EXECUTOR_BACKGROUND,
+			exec_background,
+		);
+		supervisor.spawn_once(SERVICES, exit_codes::SERVICES, services.run());
+		supervisor.spawn_once(
+			MOVEMENT_REST,
+			exit_codes::MOVEMENT_REST,
+			movement_rest.run_service(),
 		);
 
-		let (
-			execution_and_settlement_result,
-			transaction_ingress_result,
-			background_task_result,
-			services_result,
-		) = try_join!(
-			tokio::spawn(async move { exec_settle_task.run().await }),
-			tokio::spawn(async move { transaction_ingress_task.run().await }),
-			tokio::spawn(exec_background),
-			tokio::spawn(services.run()),
-			// tokio::spawn(async move { movement_rest.run_service().await }),
-		)?;
-		execution_and_settlement_result
-			.and(transaction_ingress_result)
-			.and(background_task_result)
-			.and(services_result)
+		let result = supervisor.run_until(shutdown).await;
+		if let Err(failure) = &result {
+			error!(task = failure.task, "Shutting down after a task failed: {:?}", failure.error);
+		}
+
+		// the services only stop by being dropped, which also drops their references to the executor
+		supervisor.abort(SERVICES).await;
+		supervisor.abort(MOVEMENT_REST).await;
+		let _ = stop_ingress.send(true);
+		supervisor.stop(TRANSACTION_INGRESS, SHUTDOWN_TIMEOUT).await;
+		let _ = stop_execution.send(true);
+		supervisor.stop(EXECUTE_SETTLE, SHUTDOWN_TIMEOUT).await;
+		supervisor.abort_all().await;
+
+		// dropping the last references to the executor closes the Aptos DB
+		drop(supervisor);
+		drop(exec_settle_task);
+		drop(transaction_ingress_task);
+		drop(context);
+		da_db.flush().await.context("Failed to flush the DA DB")?;
+		info!("The node has shut down");
+
+		result.map_err(Into::into)
 	}
 }
 
@@ -88,71 +207,29 @@ impl MovementPartialNode<Executor> {
 	}
 
 	pub async fn try_from_config(config: Config) -> Result<Self, anyhow::Error> {
-		let light_node_connection_protocol = config
-			.celestia_da_light_node
-			.celestia_da_light_node_config
-			.movement_da_light_node_connection_protocol();
-
-		// todo: extract into getter
-		let light_node_connection_hostname = config
-			.celestia_da_light_node
-			.celestia_da_light_node_config
-			.movement_da_light_node_connection_hostname();
-
-		// todo: extract into getter
-		let light_node_connection_port = config
-			.celestia_da_light_node
-			.celestia_da_light_node_config
-			.movement_da_light_node_connection_port();
-		// todo: extract into getter
-		debug!(
-			"Connecting to light node at {}:{}",
-			light_node_connection_hostname, light_node_connection_port
-		);
-		let light_node_client = if config
-			.celestia_da_light_node
-			.celestia_da_light_node_config
-			.movement_da_light_node_http1()
-		{
-			MovementDaLightNodeClient::try_http1(
-				format!(
-					"{}://{}:{}",
-					light_node_connection_protocol,
-					light_node_connection_hostname,
-					light_node_connection_port
-				)
-				.as_str(),
-			)
-			.context("Failed to connect to light node")?
-		} else {
-			MovementDaLightNodeClient::try_http2(
-				format!(
-					"{}://{}:{}",
-					light_node_connection_protocol,
-					light_node_connection_hostname,
-					light_node_connection_port
-				)
-				.as_str(),
-			)
-			.await
-			.context("Failed to connect to light node")?
-		};
+		let light_node_client = light_node_client(&config).await?;
 
 		debug!("Creating the executor");
 		let executor = Executor::try_from_config(config.execution_config.maptos_config.clone())
 			.context("Failed to create the inner executor")?;
 
-		let (settlement_manager, commitment_events) = if config.mcr.should_settle() {
-			debug!("Creating the settlement client");
-			let settlement_client = McrSettlementClient::build_with_config(&config.mcr)
-				.await
-				.context("Failed to build MCR settlement client with config")?;
-			let (settlement_manager, commitment_events) =
-				McrSettlementManager::new(settlement_client, &config.mcr);
-			(Some(settlement_manager), Some(commitment_events))
-		} else {
-			(None, None)
-		};
+		let (settlement_manager, commitment_events, rest_settlement_client) =
+			if config.mcr.should_settle() {
+				debug!("Creating the settlement client");
+				let settlement_client =
+					McrSettlementClient::build_with_config(&config.mcr)
+						.await
+						.context("Failed to build MCR settlement client with config")?;
+				let (settlement_manager, commitment_events) =
+					McrSettlementManager::new(settlement_client, &config.mcr);
+				// the settlement manager owns its client, so the rest service reads through another one
+				let rest_settlement_client = McrSettlementClient::build_with_config(&config.mcr)
+					.await
+					.context("Failed to build MCR settlement client for the rest service")?;
+				(Some(settlement_manager), Some(commitment_events), Some(rest_settlement_client))
+			} else {
+				(None, None, None)
+			};
 
 		debug!("Creating the movement rest service");
 		let movement_rest =
@@ -168,8 +245,75 @@ impl MovementPartialNode<Executor> {
 			settlement_manager,
 			commitment_events,
 			movement_rest,
+			rest_settlement_client,
 			config,
 			da_db,
 		})
 	}
 }
+
+/// Connects to the light node the node reads blocks from and writes transactions to.
+pub(crate) async fn light_node_client(
+	config: &Config,
+) -> Result<MovementDaLightNodeClient, anyhow::Error> {
+	let light_node_connection_protocol = config
+		.celestia_da_light_node
+		.celestia_da_light_node_config
+		.movement_da_light_node_connection_protocol();
+
+	// todo: extract into getter
+	let light_node_connection_hostname = config
+		.celestia_da_light_node
+		.celestia_da_light_node_config
+		.movement_da_light_node_connection_hostname();
+
+	// todo: extract into getter
+	let light_node_connection_port = config
+		.celestia_da_light_node
+		.celestia_da_light_node_config
+		.movement_da_light_node_connection_port();
+	// todo: extract into getter
+	debug!(
+		"Connecting to light node at {}:{}",
+		light_node_connection_hostname, light_node_connection_port
+	);
+	let light_node_client_options = match config
+		.celestia_da_light_node
+		.celestia_da_light_node_config
+		.movement_da_light_node_credentials()
+	{
+		Some(credentials) => ClientOptions::try_from_credentials(&credentials)
+			.context("Failed to read the light node client credentials")?,
+		None => ClientOptions::default(),
+	};
+	if config
+		.celestia_da_light_node
+		.celestia_da_light_node_config
+		.movement_da_light_node_http1()
+	{
+		MovementDaLightNodeClient::try_http1_with_options(
+			format!(
+				"{}://{}:{}",
+				light_node_connection_protocol,
+				light_node_connection_hostname,
+				light_node_connection_port
+			)
+			.as_str(),
+			light_node_client_options,
+		)
+		.context("Failed to connect to light node")
+	} else {
+		MovementDaLightNodeClient::try_http2_with_options(
+			format!(
+				"{}://{}:{}",
+				light_node_connection_protocol,
+				light_node_connection_hostname,
+				light_node_connection_port
+			)
+			.as_str(),
+			light_node_client_options,
+		)
+		.await
+		.context("Failed to connect to light node")
+	}
+}
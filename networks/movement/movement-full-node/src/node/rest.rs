@@ -0,0 +1,91 @@
+//! Serves the state the node keeps in the DA DB, and the accepted commitments, over the movement rest service.
+
+use crate::node::da_db::{DaDB, ExecutedBlock};
+
+use mcr_settlement_client::{McrSettlementClient, McrSettlementClientOperations};
+use movement_rest::{BlockProvenance, NodeState};
+use movement_types::block::BlockCommitment;
+
+pub struct RestNodeState {
+	da_db: DaDB,
+	settlement_client: Option<McrSettlementClient>,
+}
+
+impl RestNodeState {
+	pub fn new(da_db: DaDB, settlement_client: Option<McrSettlementClient>) -> Self {
+		Self { da_db, settlement_client }
+	}
+}
+
+impl From<ExecutedBlock> for BlockProvenance {
+	fn from(block: ExecutedBlock) -> Self {
+		Self {
+			block_id: hex::encode(block.block_id),
+			blob_id: hex::encode(block.blob_id),
+			da_height: block.da_height,
+			block_timestamp: block.block_timestamp,
+			ledger_height: block.ledger_height,
+			ledger_start_version: block.ledger_start_version,
+			ledger_end_version: block.ledger_end_version,
+			commitment: block.commitment.into(),
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl NodeState for RestNodeState {
+	async fn da_synced_height(&self) -> Result<u64, anyhow::Error> {
+		self.da_db.get_synced_height().await
+	}
+
+	async fn latest_executed_block(&self) -> Result<Option<BlockProvenance>, anyhow::Error> {
+		Ok(self.da_db.get_latest_executed_block().await?.map(Into::into))
+	}
+
+	async fn block_by_id(
+		&self,
+		block_id: Vec<u8>,
+	) -> Result<Option<BlockProvenance>, anyhow::Error> {
+		Ok(self.da_db.get_executed_block(block_id).await?.map(Into::into))
+	}
+
+	async fn block_by_transaction(
+		&self,
+		transaction_hash: Vec<u8>,
+	) -> Result<Option<BlockProvenance>, anyhow::Error> {
+		Ok(self
+			.da_db
+			.get_executed_block_by_transaction(transaction_hash)
+			.await?
+			.map(Into::into))
+	}
+
+	async fn blocks_at_da_height(
+		&self,
+		da_height: u64,
+	) -> Result<Vec<BlockProvenance>, anyhow::Error> {
+		let blocks = self.da_db.get_executed_blocks_at_da_height(da_height).await?;
+		Ok(blocks.into_iter().map(Into::into).collect())
+	}
+
+	async fn block_at_ledger_height(
+		&self,
+		ledger_height: u64,
+	) -> Result<Option<BlockProvenance>, anyhow::Error> {
+		Ok(self
+			.da_db
+			.get_executed_block_at_ledger_height(ledger_height)
+			.await?
+			.map(Into::into))
+	}
+
+	async fn accepted_commitment_at_height(
+		&self,
+		height: u64,
+	) -> Result<Option<BlockCommitment>, anyhow::Error> {
+		match &self.settlement_client {
+			Some(settlement_client) => settlement_client.get_commitment_at_height(height).await,
+			None => Ok(None),
+		}
+	}
+}
@@ -0,0 +1,294 @@
+//! Supervises the tasks of the full node, restarting them and shutting them down in order.
+
+use futures::FutureExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+
+/// What the supervisor does when a task fails, by returning an error or by panicking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+	/// The failure of the task takes the node down.
+	Never,
+	/// The task is started again, up to `max_restarts` times, waiting `backoff` before each restart.
+	///
+	/// The restarts are counted from zero again once the task has run for `reset_after` before failing,
+	/// so that failures far apart do not add up to taking the node down.
+	OnFailure { max_restarts: u32, backoff: Duration, reset_after: Duration },
+}
+
+/// The failure of a task which took the node down.
+#[derive(Debug)]
+pub struct TaskFailure {
+	pub task: &'static str,
+	/// The code the process exits with, which identifies the task.
+	pub exit_code: i32,
+	pub error: anyhow::Error,
+}
+
+impl fmt::Display for TaskFailure {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "task {} failed: {:?}", self.task, self.error)
+	}
+}
+
+impl std::error::Error for TaskFailure {}
+
+/// How a task ended, after any restarts.
+struct TaskExit {
+	task: &'static str,
+	result: Result<(), anyhow::Error>,
+}
+
+struct Supervised {
+	exit_code: i32,
+	handle: JoinHandle<()>,
+}
+
+pub struct Supervisor {
+	exit_sender: mpsc::UnboundedSender<TaskExit>,
+	exit_receiver: mpsc::UnboundedReceiver<TaskExit>,
+	tasks: HashMap<&'static str, Supervised>,
+}
+
+impl Supervisor {
+	pub fn new() -> Self {
+		let (exit_sender, exit_receiver) = mpsc::unbounded_channel();
+		Self { exit_sender, exit_receiver, tasks: HashMap::new() }
+	}
+
+	/// Spawns a task which is started by calling `start`, and started again by calling it on every restart.
+	pub fn spawn<F, Fut>(
+		&mut self,
+		task: &'static str,
+		exit_code: i32,
+		restart_policy: RestartPolicy,
+		mut start: F,
+	) where
+		F: FnMut() -> Fut + Send + 'static,
+		Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+	{
+		let exit_sender = self.exit_sender.clone();
+		let handle = tokio::spawn(async move {
+			let mut restarts = 0;
+			let result = loop {
+				let started = Instant::now();
+				let result = match AssertUnwindSafe(start()).catch_unwind().await {
+					Ok(result) => result,
+					Err(panic) => Err(anyhow::anyhow!("task panicked: {}", panic_message(&*panic))),
+				};
+				if let RestartPolicy::OnFailure { reset_after, .. } = restart_policy {
+					if started.elapsed() >= reset_after {
+						restarts = 0;
+					}
+				}
+				match (&result, restart_policy) {
+					(Err(e), RestartPolicy::OnFailure { max_restarts, backoff, .. })
+						if restarts < max_restarts =>
+					{
+						restarts += 1;
+						warn!(task, restarts, "task failed, restarting in {:?}: {:?}", backoff, e);
+						tokio::time::sleep(backoff).await;
+					}
+					_ => break result,
+				}
+			};
+			// the supervisor may be gone if the node is shutting down
+			let _ = exit_sender.send(TaskExit { task, result });
+		});
+		self.tasks.insert(task, Supervised { exit_code, handle });
+	}
+
+	/// Spawns a task which cannot be started again, so it is never restarted.
+	pub fn spawn_once<Fut>(&mut self, task: &'static str, exit_code: i32, future: Fut)
+	where
+		Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+	{
+		let mut future = Some(future);
+		self.spawn(task, exit_code, RestartPolicy::Never, move || {
+			let future = future.take();
+			async move {
+				match future {
+					Some(future) => future.await,
+					None => Err(anyhow::anyhow!("task cannot be started again")),
+				}
+			}
+		});
+	}
+
+	/// Waits until a task fails or `shutdown` completes.
+	///
+	/// Tasks which finish without an error are not waited for again.
+	/// Returns the first failure, after which the tasks are left running to be stopped in order.
+	pub async fn run_until(
+		&mut self,
+		shutdown: impl Future<Output = ()>,
+	) -> Result<(), TaskFailure> {
+		tokio::pin!(shutdown);
+		let mut running = self.tasks.len();
+		while running > 0 {
+			tokio::select! {
+				_ = &mut shutdown => {
+					info!("shutting down the node");
+					return Ok(());
+				}
+				Some(exit) = self.exit_receiver.recv() => {
+					running -= 1;
+					match exit.result {
+						Ok(()) => info!(task = exit.task, "task finished"),
+						Err(error) => {
+							let exit_code = self
+								.tasks
+								.get(exit.task)
+								.map(|supervised| supervised.exit_code)
+								.unwrap_or(1);
+							return Err(TaskFailure { task: exit.task, exit_code, error });
+						}
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Waits for a task which was told to stop, aborting it if it does not stop within the timeout.
+	pub async fn stop(&mut self, task: &'static str, timeout: Duration) {
+		if let Some(Supervised { mut handle, .. }) = self.tasks.remove(task) {
+			if tokio::time::timeout(timeout, &mut handle).await.is_err() {
+				warn!(task, "task did not stop within {:?}, aborting it", timeout);
+				handle.abort();
+				let _ = handle.await;
+			}
+			info!(task, "task stopped");
+		}
+	}
+
+	/// Aborts a task which cannot be told to stop, and waits until it is dropped.
+	pub async fn abort(&mut self, task: &'static str) {
+		if let Some(supervised) = self.tasks.remove(task) {
+			supervised.handle.abort();
+			let _ = supervised.handle.await;
+			info!(task, "task aborted");
+		}
+	}
+
+	/// Aborts the remaining tasks, and waits until they are dropped.
+	pub async fn abort_all(&mut self) {
+		for (task, supervised) in self.tasks.drain() {
+			supervised.handle.abort();
+			let _ = supervised.handle.await;
+			info!(task, "task aborted");
+		}
+	}
+}
+
+impl Default for Supervisor {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+	if let Some(message) = panic.downcast_ref::<&str>() {
+		message.to_string()
+	} else if let Some(message) = panic.downcast_ref::<String>() {
+		message.clone()
+	} else {
+		"unknown panic".to_string()
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use std::sync::atomic::{AtomicU32, Ordering};
+	use std::sync::Arc;
+
+	#[tokio::test]
+	async fn test_restarts_panicking_task() -> Result<(), anyhow::Error> {
+		let mut supervisor = Supervisor::new();
+		let starts = Arc::new(AtomicU32::new(0));
+		let task_starts = starts.clone();
+		supervisor.spawn(
+			"flaky",
+			10,
+			RestartPolicy::OnFailure {
+				max_restarts: 2,
+				backoff: Duration::from_millis(1),
+				reset_after: Duration::from_secs(60),
+			},
+			move || {
+				let starts = task_starts.clone();
+				async move {
+					if starts.fetch_add(1, Ordering::SeqCst) < 2 {
+						panic!("flaky task");
+					}
+					Ok(())
+				}
+			},
+		);
+
+		supervisor.run_until(std::future::pending()).await?;
+		assert_eq!(starts.load(Ordering::SeqCst), 3);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_restarts_are_reset_after_running_stably() -> Result<(), anyhow::Error> {
+		let mut supervisor = Supervisor::new();
+		let starts = Arc::new(AtomicU32::new(0));
+		let task_starts = starts.clone();
+		supervisor.spawn(
+			"stable",
+			10,
+			RestartPolicy::OnFailure {
+				max_restarts: 1,
+				backoff: Duration::from_millis(1),
+				reset_after: Duration::from_millis(10),
+			},
+			move || {
+				let starts = task_starts.clone();
+				async move {
+					// each run lasts long enough to reset the restarts before failing
+					tokio::time::sleep(Duration::from_millis(20)).await;
+					if starts.fetch_add(1, Ordering::SeqCst) < 3 {
+						anyhow::bail!("stable task failed");
+					}
+					Ok(())
+				}
+			},
+		);
+
+		supervisor.run_until(std::future::pending()).await?;
+		assert_eq!(starts.load(Ordering::SeqCst), 4);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_failure_reports_exit_code() -> Result<(), anyhow::Error> {
+		let mut supervisor = Supervisor::new();
+		supervisor.spawn_once("failing", 11, async { Err(anyhow::anyhow!("failed")) });
+		supervisor.spawn_once("pending", 12, std::future::pending());
+
+		let failure = supervisor
+			.run_until(std::future::pending())
+			.await
+			.expect_err("the failing task should take the node down");
+		assert_eq!(failure.task, "failing");
+		assert_eq!(failure.exit_code, 11);
+
+		supervisor.stop("failing", Duration::from_millis(100)).await;
+		supervisor.abort_all().await;
+
+		Ok(())
+	}
+}
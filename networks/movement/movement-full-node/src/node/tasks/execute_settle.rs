@@ -1,24 +1,27 @@
 //! Task module to execute blocks from the DA and process settlement.
 
-use crate::node::da_db::DaDB;
+use crate::node::da_db::{DaDB, ExecutedBlock};
 
+use k256::Secp256k1;
 use maptos_dof_execution::{
 	DynOptFinExecutor, ExecutableBlock, ExecutableTransactions, HashValue,
 	SignatureVerifiedTransaction, SignedTransaction, Transaction,
 };
 use mcr_settlement_manager::{CommitmentEventStream, McrSettlementManagerOperations};
-use movement_da_light_node_client::MovementDaLightNodeClient;
+use movement_da_light_node_client::{IntentChecker, MovementDaLightNodeClient};
 use movement_da_light_node_proto::{
-	blob_response, StreamReadFromHeightRequest, StreamReadFromHeightResponse,
+	blob_response, BlobResponse, StreamReadFromHeightRequest, StreamReadFromHeightResponse,
 };
 use movement_types::block::{Block, BlockCommitment, BlockCommitmentEvent};
 
 use anyhow::Context;
 use futures::{future::Either, stream};
 use movement_config::execution_extension;
+use std::sync::Arc;
 use tokio::select;
+use tokio::sync::{watch, Mutex};
 use tokio_stream::{Stream, StreamExt};
-use tracing::{debug, error, info, info_span, Instrument};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 pub struct Task<E, S> {
 	executor: E,
@@ -30,6 +33,8 @@ pub struct Task<E, S> {
 		Either<CommitmentEventStream, stream::Pending<<CommitmentEventStream as Stream>::Item>>,
 	execution_extension: execution_extension::Config,
 	settlement_config: mcr_settlement_config::Config,
+	/// Checks the intents the sequencer signed on write against the blocks from the DA.
+	intent_checker: Arc<Mutex<IntentChecker>>,
 }
 
 impl<E, S> Task<E, S> {
@@ -41,6 +46,7 @@ impl<E, S> Task<E, S> {
 		commitment_events: Option<CommitmentEventStream>,
 		execution_extension: execution_extension::Config,
 		settlement_config: mcr_settlement_config::Config,
+		intent_checker: Arc<Mutex<IntentChecker>>,
 	) -> Self {
 		let commitment_events = match commitment_events {
 			Some(stream) => Either::Left(stream),
@@ -54,12 +60,38 @@ impl<E, S> Task<E, S> {
 			commitment_events,
 			execution_extension,
 			settlement_config,
+			intent_checker,
 		}
 	}
 
 	fn settlement_enabled(&self) -> bool {
 		matches!(&self.commitment_events, Either::Left(_))
 	}
+
+	/// Observes a block from the DA with the intent checker, reporting the intents it shows to be broken.
+	///
+	/// There is no slashing yet, so the proofs are only logged.
+	async fn check_intents(&self, response: &StreamReadFromHeightResponse) {
+		let blob = match &response.blob {
+			Some(BlobResponse {
+				blob_type: Some(blob_response::BlobType::SequencedBlobBlock(blob)),
+			}) => blob.clone(),
+			_ => return,
+		};
+		let proofs = match self.intent_checker.lock().await.observe_blob::<Secp256k1>(blob.into()) {
+			Ok(proofs) => proofs,
+			Err(e) => {
+				warn!("Block cannot be checked against intents: {:?}", e);
+				return;
+			}
+		};
+		for proof in proofs {
+			match serde_json::to_string(&proof) {
+				Ok(proof) => error!(%proof, "Sequencer did not honor a signed intent"),
+				Err(e) => error!("Failed to serialize a proof of a broken intent: {:?}", e),
+			}
+		}
+	}
 }
 
 impl<E, S> Task<E, S>
@@ -67,7 +99,11 @@ where
 	E: DynOptFinExecutor,
 	S: McrSettlementManagerOperations,
 {
-	pub async fn run(mut self) -> anyhow::Result<()> {
+	/// Executes blocks from the DA and processes settlement until told to stop.
+	///
+	/// A block being executed when the stop signal arrives is finished first.
+	/// The task can be run again after it failed, and resumes from the synced height.
+	pub async fn run(&mut self, mut stop: watch::Receiver<bool>) -> anyhow::Result<()> {
 		let synced_height = self.da_db.get_synced_height().await?;
 		info!("Synced height: {:?}", synced_height);
 		let mut blocks_from_da = self
@@ -77,6 +113,10 @@ where
 
 		loop {
 			select! {
+				_ = stop.wait_for(|stop| *stop) => {
+					info!("Stopping execution");
+					break;
+				}
 				Some(res) = blocks_from_da.next() => {
 					let response = res.context("failed to get next block from DA")?;
 					self.process_block_from_da(response).await?;
@@ -95,20 +135,10 @@ where
 		&mut self,
 		response: StreamReadFromHeightResponse,
 	) -> anyhow::Result<()> {
+		self.check_intents(&response).await;
+
 		// get the block
-		let (block_bytes, block_timestamp, block_id, da_height) = match response
-			.blob
-			.ok_or(anyhow::anyhow!("No blob in response"))?
-			.blob_type
-			.ok_or(anyhow::anyhow!("No blob type in response"))?
-		{
-			blob_response::BlobType::SequencedBlobBlock(blob) => {
-				(blob.data, blob.timestamp, blob.blob_id, blob.height)
-			}
-			_ => {
-				anyhow::bail!("Invalid blob type in response")
-			}
-		};
+		let (block_bytes, block_timestamp, block_id, da_height) = read_sequenced_blob(response)?;
 
 		info!(
 			block_id = %hex::encode(block_id.clone()),
@@ -128,13 +158,22 @@ where
 			anyhow::bail!("Invalid DA height: {:?}", da_height);
 		}
 
-		let block: Block = bcs::from_bytes(&block_bytes[..])?;
+		let (block, block_timestamp) = decode_block(&block_bytes, block_timestamp)?;
 
 		// get the transactions
 		let transactions_count = block.transactions().len();
+		let executed_block_id = block.id().to_vec();
 		let span = info_span!(target: "movement_timing", "execute_block", id = ?block_id);
-		let commitment =
-			self.execute_block_with_retries(block, block_timestamp).instrument(span).await?;
+		let (commitment, transaction_hashes) = execute_block_with_retries(
+			&self.executor,
+			&self.execution_extension,
+			block,
+			block_timestamp,
+		)
+		.instrument(span)
+		.await?;
+		let (ledger_start_version, ledger_end_version) =
+			self.executor.get_block_version_range(commitment.height())?;
 
 		// decrement the number of transactions in flight on the executor
 		self.executor.decrement_transactions_in_flight(transactions_count as u64);
@@ -143,8 +182,22 @@ where
 		// we can't mark this height as synced because we must allow for the possibility of multiple blocks at the same height according to the m1 da specifications (which currently is built on celestia which itself allows more than one block at the same height)
 		self.da_db.set_synced_height(da_height - 1).await?;
 
-		// set the block as executed
-		self.da_db.add_executed_block(block_id.clone()).await?;
+		// set the block as executed, recording where it came from and where it went
+		self.da_db
+			.add_executed_block(
+				ExecutedBlock {
+					block_id: executed_block_id,
+					blob_id: block_id.clone(),
+					da_height,
+					block_timestamp,
+					ledger_height: commitment.height(),
+					ledger_start_version,
+					ledger_end_version,
+					commitment: commitment.clone(),
+				},
+				transaction_hashes.iter().map(|hash| hash.to_vec()).collect(),
+			)
+			.await?;
 
 		if self.settlement_enabled()
 			// only settle every super_block_size_heights 
@@ -177,76 +230,6 @@ impl<E, S> Task<E, S>
 where
 	E: DynOptFinExecutor,
 {
-	/// Retries executing a block several times.
-	/// This can be valid behavior if the block timestamps are too tightly clustered for the full node execution.
-	/// However, this has to be deterministic, otherwise nodes will not be able to agree on the block commitment.
-	async fn execute_block_with_retries(
-		&mut self,
-		block: Block,
-		mut block_timestamp: u64,
-	) -> anyhow::Result<BlockCommitment> {
-		for _ in 0..self.execution_extension.block_retry_count {
-			// we have to clone here because the block is supposed to be consumed by the executor
-			match self.execute_block(block.clone(), block_timestamp).await {
-				Ok(commitment) => return Ok(commitment),
-				Err(e) => {
-					info!("Failed to execute block: {:?}. Retrying", e);
-					block_timestamp += self.execution_extension.block_retry_increment_microseconds; // increase the timestamp by 5 ms (5000 microseconds)
-				}
-			}
-		}
-
-		anyhow::bail!("Failed to execute block after 5 retries")
-	}
-
-	async fn execute_block(
-		&mut self,
-		block: Block,
-		block_timestamp: u64,
-	) -> anyhow::Result<BlockCommitment> {
-		let block_id = block.id();
-		let block_hash = HashValue::from_slice(block.id())?;
-
-		// get the transactions
-		let mut block_transactions = Vec::new();
-		let block_metadata = self.executor.build_block_metadata(
-			HashValue::sha3_256_of(block_id.as_bytes().as_slice()),
-			block_timestamp,
-		)?;
-		let block_metadata_transaction =
-			SignatureVerifiedTransaction::Valid(Transaction::BlockMetadata(block_metadata));
-		block_transactions.push(block_metadata_transaction);
-
-		for transaction in block.transactions() {
-			let signed_transaction: SignedTransaction = bcs::from_bytes(transaction.data())?;
-
-			// check if the transaction has already been executed to prevent replays
-			if self
-				.executor
-				.has_executed_transaction_opt(signed_transaction.committed_hash())?
-			{
-				continue;
-			}
-
-			let signature_verified_transaction = SignatureVerifiedTransaction::Valid(
-				Transaction::UserTransaction(signed_transaction),
-			);
-			block_transactions.push(signature_verified_transaction);
-		}
-
-		// form the executable transactions vec
-		let block = ExecutableTransactions::Unsharded(block_transactions);
-
-		// form the executable block and execute it
-		let executable_block = ExecutableBlock::new(block_hash, block);
-		let block_id = executable_block.block_id;
-		let commitment = self.executor.execute_block_opt(executable_block).await?;
-
-		info!("Executed block: {}", block_id);
-
-		Ok(commitment)
-	}
-
 	async fn process_commitment_event(
 		&mut self,
 		event: BlockCommitmentEvent,
@@ -279,3 +262,104 @@ where
 		}
 	}
 }
+
+/// Reads the block bytes, the timestamp, the blob id and the DA height of a sequenced blob streamed from the DA.
+pub(crate) fn read_sequenced_blob(
+	response: StreamReadFromHeightResponse,
+) -> anyhow::Result<(Vec<u8>, u64, Vec<u8>, u64)> {
+	match response
+		.blob
+		.ok_or(anyhow::anyhow!("No blob in response"))?
+		.blob_type
+		.ok_or(anyhow::anyhow!("No blob type in response"))?
+	{
+		blob_response::BlobType::SequencedBlobBlock(blob) => {
+			Ok((blob.data, blob.timestamp, blob.blob_id, blob.height))
+		}
+		_ => {
+			anyhow::bail!("Invalid blob type in response")
+		}
+	}
+}
+
+/// Decodes a block read from the DA, with the timestamp it is executed with.
+pub(crate) fn decode_block(
+	block_bytes: &[u8],
+	blob_timestamp: u64,
+) -> anyhow::Result<(Block, u64)> {
+	let block: Block = bcs::from_bytes(block_bytes)?;
+
+	// prefer the proposer's timestamp, falling back to the DA blob timestamp for legacy blocks
+	let block_timestamp = block.metadata().timestamp().unwrap_or(blob_timestamp);
+	Ok((block, block_timestamp))
+}
+
+/// Retries executing a block several times.
+/// This can be valid behavior if the block timestamps are too tightly clustered for the full node execution.
+/// However, this has to be deterministic, otherwise nodes will not be able to agree on the block commitment.
+pub(crate) async fn execute_block_with_retries<E: DynOptFinExecutor>(
+	executor: &E,
+	execution_extension: &execution_extension::Config,
+	block: Block,
+	mut block_timestamp: u64,
+) -> anyhow::Result<(BlockCommitment, Vec<HashValue>)> {
+	for _ in 0..execution_extension.block_retry_count {
+		// we have to clone here because the block is supposed to be consumed by the executor
+		match execute_block(executor, block.clone(), block_timestamp).await {
+			Ok(executed) => return Ok(executed),
+			Err(e) => {
+				info!("Failed to execute block: {:?}. Retrying", e);
+				block_timestamp += execution_extension.block_retry_increment_microseconds; // increase the timestamp by 5 ms (5000 microseconds)
+			}
+		}
+	}
+
+	anyhow::bail!("Failed to execute block after 5 retries")
+}
+
+/// Executes a block, returning its commitment and the hashes of the user transactions it executed.
+pub(crate) async fn execute_block<E: DynOptFinExecutor>(
+	executor: &E,
+	block: Block,
+	block_timestamp: u64,
+) -> anyhow::Result<(BlockCommitment, Vec<HashValue>)> {
+	let block_id = block.id();
+	let block_hash = HashValue::from_slice(block.id())?;
+
+	// get the transactions
+	let mut block_transactions = Vec::new();
+	let mut transaction_hashes = Vec::new();
+	let block_metadata = executor.build_block_metadata(
+		HashValue::sha3_256_of(block_id.as_bytes().as_slice()),
+		block_timestamp,
+	)?;
+	let block_metadata_transaction =
+		SignatureVerifiedTransaction::Valid(Transaction::BlockMetadata(block_metadata));
+	block_transactions.push(block_metadata_transaction);
+
+	for transaction in block.transactions() {
+		let signed_transaction: SignedTransaction = bcs::from_bytes(transaction.data())?;
+
+		// check if the transaction has already been executed to prevent replays
+		if executor.has_executed_transaction_opt(signed_transaction.committed_hash())? {
+			continue;
+		}
+
+		transaction_hashes.push(signed_transaction.committed_hash());
+		let signature_verified_transaction =
+			SignatureVerifiedTransaction::Valid(Transaction::UserTransaction(signed_transaction));
+		block_transactions.push(signature_verified_transaction);
+	}
+
+	// form the executable transactions vec
+	let block = ExecutableTransactions::Unsharded(block_transactions);
+
+	// form the executable block and execute it
+	let executable_block = ExecutableBlock::new(block_hash, block);
+	let block_id = executable_block.block_id;
+	let commitment = executor.execute_block_opt(executable_block).await?;
+
+	info!("Executed block: {}", block_id);
+
+	Ok((commitment, transaction_hashes))
+}
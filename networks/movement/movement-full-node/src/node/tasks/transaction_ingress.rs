@@ -1,16 +1,23 @@
 //! Task to process incoming transactions and write to DA
 
+use crate::node::da_db::DaDB;
+use k256::Secp256k1;
 use maptos_dof_execution::SignedTransaction;
 use movement_celestia_da_util::config::Config as LightNodeConfig;
-use movement_da_light_node_client::MovementDaLightNodeClient;
-use movement_da_light_node_proto::{BatchWriteRequest, BlobWrite};
+use movement_config::ingress::Config as IngressConfig;
+use movement_da_light_node_client::{verify_intent, IntentChecker, MovementDaLightNodeClient};
+use movement_da_light_node_proto::{
+	blob_response::BlobType, BatchWriteRequest, BatchWriteResponse, BlobWrite,
+};
 
-use tokio::sync::mpsc;
-use tracing::{info, warn};
+use tokio::sync::{mpsc, watch, Mutex, OwnedSemaphorePermit, Semaphore};
+use tracing::{error, info, warn};
 
 use prost::Message;
+use std::future::Future;
 use std::ops::ControlFlow;
 use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 const LOGGING_UID: AtomicU64 = AtomicU64::new(0);
@@ -19,6 +26,15 @@ pub struct Task {
 	transaction_receiver: mpsc::Receiver<(u64, SignedTransaction)>,
 	da_light_node_client: MovementDaLightNodeClient,
 	da_light_node_config: LightNodeConfig,
+	/// Keeps the batches until the light node acknowledges them.
+	da_db: DaDB,
+	ingress_config: IngressConfig,
+	/// A permit for each batch the outbox has room for.
+	outbox_permits: Arc<Semaphore>,
+	/// Whether the outbox left by a previous run of the node was replayed.
+	replayed: bool,
+	/// Receives the intents the sequencer signed for the written transactions.
+	intent_checker: Arc<Mutex<IntentChecker>>,
 }
 
 impl Task {
@@ -26,26 +42,93 @@ impl Task {
 		transaction_receiver: mpsc::Receiver<(u64, SignedTransaction)>,
 		da_light_node_client: MovementDaLightNodeClient,
 		da_light_node_config: LightNodeConfig,
+		da_db: DaDB,
+		ingress_config: IngressConfig,
+		intent_checker: Arc<Mutex<IntentChecker>>,
 	) -> Self {
-		Task { transaction_receiver, da_light_node_client, da_light_node_config }
+		let outbox_permits =
+			Arc::new(Semaphore::new(ingress_config.outbox_max_batches.max(1) as usize));
+		Task {
+			transaction_receiver,
+			da_light_node_client,
+			da_light_node_config,
+			da_db,
+			ingress_config,
+			outbox_permits,
+			replayed: false,
+			intent_checker,
+		}
+	}
+
+	/// Writes transactions to the DA until told to stop.
+	///
+	/// On stop, no more transactions are taken and the batches being written are drained.
+	/// The task can be run again after it failed.
+	pub async fn run(&mut self, mut stop: watch::Receiver<bool>) -> anyhow::Result<()> {
+		if !self.replayed {
+			self.replay_outbox().await?;
+		}
+		while !*stop.borrow() {
+			if let ControlFlow::Break(()) =
+				self.spawn_write_next_transaction_batch(&mut stop).await?
+			{
+				break;
+			}
+		}
+		self.drain_outbox().await
 	}
 
-	pub async fn run(mut self) -> anyhow::Result<()> {
-		while let ControlFlow::Continue(()) = self.spawn_write_next_transaction_batch().await? {}
+	/// Waits until the light node acknowledged the batches being written.
+	///
+	/// Batches which are never acknowledged stay in the outbox, and are written again on restart.
+	async fn drain_outbox(&self) -> Result<(), anyhow::Error> {
+		info!("draining the batches being written to the DA");
+		let _permits = self
+			.outbox_permits
+			.acquire_many(self.ingress_config.outbox_max_batches.max(1) as u32)
+			.await?;
+		Ok(())
+	}
+
+	/// Writes the batches left in the outbox by a previous run of the node again.
+	async fn replay_outbox(&mut self) -> Result<(), anyhow::Error> {
+		let batches = self.da_db.get_outbox_batches().await?;
+		self.replayed = true;
+		if !batches.is_empty() {
+			info!("replaying {} unacknowledged batches from the outbox", batches.len());
+		}
+		let dead_letters = self.da_db.get_dead_letter_batches().await?;
+		if !dead_letters.is_empty() {
+			warn!("{} batches the DA rejected are kept in the dead letters", dead_letters.len());
+		}
+		for (outbox_id, batch) in batches {
+			let batch_write = BatchWriteRequest::decode(batch.as_slice())?;
+			let permit = self.outbox_permits.clone().acquire_owned().await?;
+			self.spawn_write_batch(outbox_id, batch_write, permit);
+		}
 		Ok(())
 	}
 
 	/// Constructs a batch of transactions then spawns the write request to the DA in the background.
 	async fn spawn_write_next_transaction_batch(
 		&mut self,
+		stop: &mut watch::Receiver<bool>,
 	) -> Result<ControlFlow<(), ()>, anyhow::Error> {
 		use ControlFlow::{Break, Continue};
 
+		// Stop taking transactions while the outbox is full.
+		// This holds back the transaction pipe, which waits for room in the channel to this task.
+		let permit = tokio::select! {
+			permit = self.outbox_permits.clone().acquire_owned() => permit?,
+			_ = stop.wait_for(|stop| *stop) => return Ok(Break(())),
+		};
+
 		// limit the total time batching transactions
 		let start = Instant::now();
 		let (_, half_building_time) = self.da_light_node_config.try_block_building_parameters()?;
 
 		let mut transactions = Vec::new();
+		let mut control_flow = Continue(());
 
 		let batch_id = LOGGING_UID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 		loop {
@@ -84,8 +167,9 @@ impl Task {
 						transactions.push(BlobWrite { data: serialized_transaction });
 					}
 					None => {
-						// The transaction stream is closed, terminate the task.
-						return Ok(Break(()));
+						// The transaction stream is closed, terminate the task after writing the batch.
+						control_flow = Break(());
+						break;
 					}
 				},
 				Err(_) => {
@@ -102,28 +186,188 @@ impl Task {
 				"built_batch_write"
 			);
 			let batch_write = BatchWriteRequest { blobs: transactions };
-			let mut buf = Vec::new();
-			batch_write.encode_raw(&mut buf);
+			let buf = batch_write.encode_to_vec();
 			info!("batch_write size: {}", buf.len());
-			// spawn the actual batch write request in the background
-			let mut da_light_node_client = self.da_light_node_client.clone();
-			tokio::spawn(async move {
-				match da_light_node_client.batch_write(batch_write.clone()).await {
-					Ok(_) => {
-						info!(
-							target: "movement_timing",
-							batch_id = %batch_id,
-							"batch_write_success"
-						);
-						return;
+			let outbox_id = self.da_db.add_outbox_batch(buf).await?;
+			info!(
+				target: "movement_timing",
+				batch_id = %batch_id,
+				outbox_id = %outbox_id,
+				"added_batch_to_outbox"
+			);
+			self.spawn_write_batch(outbox_id, batch_write, permit);
+		}
+
+		Ok(control_flow)
+	}
+
+	/// Spawns writing a batch to the DA in the background, retrying with backoff until the light node
+	/// acknowledges it. The batch is then removed from the outbox, which frees the permit.
+	///
+	/// A batch the light node rejects for good is moved to the dead letters instead.
+	fn spawn_write_batch(
+		&self,
+		outbox_id: u64,
+		batch_write: BatchWriteRequest,
+		permit: OwnedSemaphorePermit,
+	) {
+		let da_light_node_client = self.da_light_node_client.clone();
+		let da_db = self.da_db.clone();
+		let intent_checker = self.intent_checker.clone();
+		let backoff = Duration::from_millis(self.ingress_config.retry_initial_backoff_ms);
+		let max_backoff = Duration::from_millis(self.ingress_config.retry_max_backoff_ms);
+		tokio::spawn(async move {
+			let write = || {
+				let mut da_light_node_client = da_light_node_client.clone();
+				let batch_write = batch_write.clone();
+				async move { da_light_node_client.batch_write(batch_write).await }
+			};
+			match write_with_retry(write, outbox_id, backoff, max_backoff).await {
+				Ok(response) => {
+					info!(
+						target: "movement_timing",
+						outbox_id = %outbox_id,
+						"batch_write_success"
+					);
+					report_rejected(&response);
+					check_intents(&intent_checker, response).await;
+					// if this fails, the batch is written again on restart, which the DA tolerates
+					if let Err(e) = da_db.remove_outbox_batch(outbox_id).await {
+						warn!("failed to remove batch {} from the outbox: {:?}", outbox_id, e);
 					}
-					Err(e) => {
-						warn!("failed to write batch to DA: {:?} {:?}", e, batch_id);
+				}
+				Err(status) => {
+					error!(
+						"the DA rejected batch {} for good, moving it to the dead letters: {:?}",
+						outbox_id, status
+					);
+					if let Err(e) = da_db.dead_letter_outbox_batch(outbox_id).await {
+						warn!("failed to move batch {} to the dead letters: {:?}", outbox_id, e);
 					}
 				}
-			});
+			}
+			drop(permit);
+		});
+	}
+}
+
+/// Whether the light node rejected a write in a way that writing it again cannot fix.
+///
+/// Internal errors are not permanent, as the light node returns them when posting to the DA
+/// or signing the intent fails.
+fn is_permanent_failure(status: &tonic::Status) -> bool {
+	matches!(
+		status.code(),
+		tonic::Code::Unauthenticated | tonic::Code::PermissionDenied | tonic::Code::InvalidArgument
+	)
+}
+
+/// Writes a batch until the light node acknowledges it, backing off between transient failures.
+///
+/// Returns the failure when it is permanent.
+async fn write_with_retry<W, F>(
+	mut write: W,
+	outbox_id: u64,
+	mut backoff: Duration,
+	max_backoff: Duration,
+) -> Result<BatchWriteResponse, tonic::Status>
+where
+	W: FnMut() -> F,
+	F: Future<Output = Result<BatchWriteResponse, tonic::Status>>,
+{
+	loop {
+		match write().await {
+			Ok(response) => return Ok(response),
+			Err(status) if is_permanent_failure(&status) => return Err(status),
+			Err(status) => {
+				warn!(
+					"failed to write batch to DA, retrying in {:?}: {:?} {:?}",
+					backoff, status, outbox_id
+				);
+				tokio::time::sleep(backoff).await;
+				backoff = (backoff * 2).min(max_backoff);
+			}
 		}
+	}
+}
 
-		Ok(Continue(()))
+/// Logs the transactions of a written batch which the light node did not accept.
+///
+/// They were reported as accepted when they were submitted, but will not be executed.
+fn report_rejected(response: &BatchWriteResponse) {
+	for rejected in &response.rejected {
+		warn!(
+			transaction_id = %hex::encode(&rejected.transaction_id),
+			"the light node rejected a transaction: {}",
+			rejected.reason
+		);
+	}
+}
+
+/// Hands the intents the sequencer signed for a written batch to the checker, which follows the executed blocks.
+async fn check_intents(intent_checker: &Mutex<IntentChecker>, response: BatchWriteResponse) {
+	for blob in response.blobs {
+		if !matches!(blob.blob_type, Some(BlobType::SequencedBlobIntent(_))) {
+			continue;
+		}
+		match verify_intent::<Secp256k1>(&blob) {
+			Ok(intent) => intent_checker.lock().await.add_intent(intent),
+			Err(e) => warn!("the light node returned an invalid intent: {:?}", e),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[tokio::test]
+	async fn test_write_with_retry() -> Result<(), anyhow::Error> {
+		// transient failures are retried until the batch is acknowledged
+		let mut failures = vec![
+			tonic::Status::unavailable("down"),
+			tonic::Status::aborted("busy"),
+			tonic::Status::internal("failed to sign the intent"),
+		];
+		let mut attempts = 0;
+		let response = write_with_retry(
+			|| {
+				attempts += 1;
+				let result = match failures.pop() {
+					Some(status) => Err(status),
+					None => Ok(BatchWriteResponse { blobs: vec![], rejected: vec![] }),
+				};
+				async move { result }
+			},
+			0,
+			Duration::from_millis(1),
+			Duration::from_millis(2),
+		)
+		.await;
+		assert!(response.is_ok());
+		assert_eq!(attempts, 4);
+
+		// permanent failures are not
+		let mut attempts = 0;
+		let response = write_with_retry(
+			|| {
+				attempts += 1;
+				async {
+					Err::<BatchWriteResponse, _>(tonic::Status::permission_denied("not allowed"))
+				}
+			},
+			0,
+			Duration::from_millis(1),
+			Duration::from_millis(2),
+		)
+		.await;
+		assert_eq!(
+			response.map_err(|status| status.code()).err(),
+			Some(tonic::Code::PermissionDenied)
+		);
+		assert_eq!(attempts, 1);
+
+		Ok(())
 	}
 }
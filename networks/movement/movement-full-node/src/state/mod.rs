@@ -1,4 +1,5 @@
 pub mod node;
+pub mod provenance;
 pub mod settlement;
 
 use clap::Subcommand;
@@ -9,6 +10,8 @@ pub enum State {
 	#[clap(subcommand)]
 	Node(node::Node),
 	#[clap(subcommand)]
+	Provenance(provenance::Provenance),
+	#[clap(subcommand)]
 	Settlement(settlement::Settlement),
 }
 
@@ -16,6 +19,7 @@ impl State {
 	pub async fn execute(&self) -> Result<(), anyhow::Error> {
 		match self {
 			State::Node(node) => node.execute().await,
+			State::Provenance(provenance) => provenance.execute().await,
 			State::Settlement(settlement) => settlement.execute().await,
 		}
 	}
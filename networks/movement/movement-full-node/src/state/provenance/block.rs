@@ -0,0 +1,36 @@
+use crate::common_args::MovementArgs;
+use clap::Parser;
+use tracing::info;
+
+#[derive(Debug, Parser, Clone)]
+#[clap(
+	rename_all = "kebab-case",
+	about = "Gets where an executed block came from, by its id or its ledger height. If neither is provided, gets the latest executed block."
+)]
+pub struct Block {
+	#[clap(flatten)]
+	pub movement_args: MovementArgs,
+	/// The hex encoded id of the block.
+	#[clap(long, conflicts_with = "ledger_height")]
+	pub block_id: Option<String>,
+	/// The height of the block in the ledger.
+	#[clap(long)]
+	pub ledger_height: Option<u64>,
+}
+
+impl Block {
+	pub async fn execute(&self) -> Result<(), anyhow::Error> {
+		let config = self.movement_args.config().await?;
+		info!("Loaded config {:?}", config);
+		let da_db = super::open_da_db(&config)?;
+
+		let block = match (&self.block_id, self.ledger_height) {
+			(Some(block_id), _) => da_db.get_executed_block(super::decode_hex(block_id)?).await?,
+			(None, Some(ledger_height)) => {
+				da_db.get_executed_block_at_ledger_height(ledger_height).await?
+			}
+			(None, None) => da_db.get_latest_executed_block().await?,
+		};
+		super::print_blocks(block.into_iter().collect())
+	}
+}
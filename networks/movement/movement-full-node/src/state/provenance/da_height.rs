@@ -0,0 +1,25 @@
+use crate::common_args::MovementArgs;
+use clap::Parser;
+use tracing::info;
+
+#[derive(Debug, Parser, Clone)]
+#[clap(
+	rename_all = "kebab-case",
+	about = "Gets the executed blocks which were read at a DA height."
+)]
+pub struct DaHeight {
+	#[clap(flatten)]
+	pub movement_args: MovementArgs,
+	pub da_height: u64,
+}
+
+impl DaHeight {
+	pub async fn execute(&self) -> Result<(), anyhow::Error> {
+		let config = self.movement_args.config().await?;
+		info!("Loaded config {:?}", config);
+		let da_db = super::open_da_db(&config)?;
+
+		let blocks = da_db.get_executed_blocks_at_da_height(self.da_height).await?;
+		super::print_blocks(blocks)
+	}
+}
@@ -0,0 +1,49 @@
+pub mod block;
+pub mod da_height;
+pub mod transaction;
+
+use crate::node::da_db::{DaDB, ExecutedBlock};
+use anyhow::Context;
+use clap::Subcommand;
+use movement_config::Config;
+use movement_rest::BlockProvenance;
+
+#[derive(Subcommand, Debug)]
+#[clap(
+	rename_all = "kebab-case",
+	about = "Commands for finding where executed blocks and transactions came from on the DA"
+)]
+pub enum Provenance {
+	Block(block::Block),
+	DaHeight(da_height::DaHeight),
+	Transaction(transaction::Transaction),
+}
+
+impl Provenance {
+	pub async fn execute(&self) -> Result<(), anyhow::Error> {
+		match self {
+			Provenance::Block(block) => block.execute().await,
+			Provenance::DaHeight(da_height) => da_height.execute().await,
+			Provenance::Transaction(transaction) => transaction.execute().await,
+		}
+	}
+}
+
+/// Opens the DA DB of the node, which may be running.
+fn open_da_db(config: &Config) -> Result<DaDB, anyhow::Error> {
+	DaDB::open_read_only(&config.da_db.da_db_path).context("Failed to open the DA DB")
+}
+
+/// Decodes a hex encoded id or hash, with or without a 0x prefix.
+fn decode_hex(id: &str) -> Result<Vec<u8>, anyhow::Error> {
+	hex::decode(id.trim_start_matches("0x"))
+		.map_err(|e| anyhow::anyhow!("Invalid hex {}: {}", id, e))
+}
+
+/// Prints the provenance of executed blocks as JSON.
+fn print_blocks(blocks: Vec<ExecutedBlock>) -> Result<(), anyhow::Error> {
+	let blocks = blocks.into_iter().map(BlockProvenance::from).collect::<Vec<_>>();
+	// Use println as this is standard (non-logging output)
+	println!("{}", serde_json::to_string_pretty(&blocks)?);
+	Ok(())
+}
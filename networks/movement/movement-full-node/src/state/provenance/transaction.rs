@@ -0,0 +1,26 @@
+use crate::common_args::MovementArgs;
+use clap::Parser;
+use tracing::info;
+
+#[derive(Debug, Parser, Clone)]
+#[clap(
+	rename_all = "kebab-case",
+	about = "Gets the executed block, and the DA blob it came from, which executed a transaction."
+)]
+pub struct Transaction {
+	#[clap(flatten)]
+	pub movement_args: MovementArgs,
+	/// The hex encoded hash of the transaction.
+	pub hash: String,
+}
+
+impl Transaction {
+	pub async fn execute(&self) -> Result<(), anyhow::Error> {
+		let config = self.movement_args.config().await?;
+		info!("Loaded config {:?}", config);
+		let da_db = super::open_da_db(&config)?;
+
+		let block = da_db.get_executed_block_by_transaction(super::decode_hex(&self.hash)?).await?;
+		super::print_blocks(block.into_iter().collect())
+	}
+}
@@ -50,6 +50,10 @@ pub struct MovementConfig {
 	pub grpc_listener_hostname: String,
 	#[serde(default = "default_grpc_listener_port")]
 	pub grpc_port: u16,
+	/// Hex encoded ed25519 public keys allowed to query the gRPC service.
+	/// When empty, lookups do not require a signature.
+	#[serde(default = "default_grpc_authorized_keys")]
+	pub grpc_authorized_keys: Vec<String>,
 	#[serde(default = "rest_connection_timeout_secs")]
 	pub rest_connection_timeout_secs: u64,
 }
@@ -85,6 +89,18 @@ env_default!(
 
 env_default!(default_grpc_listener_port, "GRPC_LISTENER_PORT", u16, DEFAULT_GRPC_LISTENER_PORT);
 
+// Comma separated list of the keys allowed to query the gRPC service
+pub fn default_grpc_authorized_keys() -> Vec<String> {
+	match std::env::var("GRPC_AUTHORIZED_KEYS") {
+		Ok(val) => val
+			.split(',')
+			.map(|key| key.trim().to_string())
+			.filter(|key| !key.is_empty())
+			.collect(),
+		Err(_) => Vec::new(),
+	}
+}
+
 env_default!(
 	default_rest_listener_hostname,
 	"REST_LISTENER_HOSTNAME",
@@ -184,6 +200,7 @@ impl MovementConfig {
 			grpc_protocol: default_grpc_connection_protocol(),
 			grpc_listener_hostname: default_grpc_listener_hostname(),
 			grpc_port: default_grpc_listener_port(),
+			grpc_authorized_keys: default_grpc_authorized_keys(),
 			rest_connection_timeout_secs: rest_connection_timeout_secs(),
 		}
 	}
@@ -206,6 +223,7 @@ impl Default for MovementConfig {
 			grpc_protocol: default_grpc_connection_protocol(),
 			grpc_listener_hostname: default_grpc_listener_hostname(),
 			grpc_port: default_grpc_listener_port(),
+			grpc_authorized_keys: default_grpc_authorized_keys(),
 			rest_connection_timeout_secs: rest_connection_timeout_secs(),
 		}
 	}
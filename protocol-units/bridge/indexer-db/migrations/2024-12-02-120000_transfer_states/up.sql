@@ -0,0 +1,16 @@
+-- Latest state of each bridge transfer handled by the relayer, used to rebuild the runtime on restart.
+CREATE TABLE transfer_states (
+    bridge_transfer_id VARCHAR(64) PRIMARY KEY,
+    state VARCHAR(32) NOT NULL,                -- TransferStateType name
+    init_chain VARCHAR(8) NOT NULL,            -- ChainId name of the initiator chain
+    initiator VARCHAR(128) NOT NULL,
+    recipient VARCHAR(128) NOT NULL,
+    hash_lock VARCHAR(64) NOT NULL,
+    time_lock BIGINT NOT NULL,
+    amount NUMERIC NOT NULL,
+    contract_state SMALLINT NOT NULL,
+    retry_on_error INTEGER NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX transfer_states_state_idx ON transfer_states (state);
@@ -1,8 +1,10 @@
 use crate::migrations::run_migrations;
 use crate::models::*;
 use crate::schema::*;
+use bigdecimal::ToPrimitive;
 use bridge_util::chains::bridge_contracts::BridgeContractEvent;
-use bridge_util::types::BridgeTransferId;
+use bridge_util::states::{TransferAddress, TransferState, TransferStateType};
+use bridge_util::types::{Amount, BridgeTransferId, HashLock, HashLockPreImage, TimeLock};
 use bridge_util::TransferActionType;
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
@@ -216,6 +218,114 @@ impl Client {
 			refunded_events,
 		})
 	}
+
+	/// Inserts or updates the persisted state of a transfer.
+	pub fn upsert_transfer_state(
+		&mut self,
+		transfer_state: &TransferState,
+	) -> Result<(), diesel::result::Error> {
+		let entry = NewTransferStateEntry {
+			bridge_transfer_id: hex::encode(transfer_state.transfer_id.0.to_vec()),
+			state: transfer_state.state.to_string(),
+			init_chain: transfer_state.init_chain.to_string(),
+			initiator: hex::encode(transfer_state.intiator_address.as_bytes()),
+			recipient: hex::encode(transfer_state.counter_part_address.as_bytes()),
+			hash_lock: hex::encode(transfer_state.hash_lock.0.to_vec()),
+			time_lock: transfer_state.time_lock.0 as i64,
+			amount: transfer_state.amount.0.into(),
+			contract_state: transfer_state.contract_state as i16,
+			retry_on_error: transfer_state.retry_on_error as i32,
+			updated_at: chrono::Utc::now().naive_utc(),
+		};
+		diesel::insert_into(transfer_states::table)
+			.values(&entry)
+			.on_conflict(transfer_states::bridge_transfer_id)
+			.do_update()
+			.set(&entry)
+			.execute(&mut self.conn)?;
+		Ok(())
+	}
+
+	/// Loads the persisted state of every transfer that is still in progress.
+	///
+	/// Transfers which are done or being refunded are left out, as the relayer has nothing more to do for them.
+	pub fn load_transfer_states_in_progress(
+		&mut self,
+	) -> Result<Vec<TransferState>, anyhow::Error> {
+		let finished =
+			vec![TransferStateType::Done.to_string(), TransferStateType::Refund.to_string()];
+		let entries = transfer_states::table
+			.filter(transfer_states::state.ne_all(finished))
+			.load::<TransferStateEntry>(&mut self.conn)?;
+		entries.into_iter().map(transfer_state_from_entry).collect()
+	}
+
+	/// Gets the persisted state of a transfer, whatever its progress.
+	pub fn get_transfer_state(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId,
+	) -> Result<Option<TransferState>, anyhow::Error> {
+		let bridge_transfer_id = hex::encode(bridge_transfer_id.0.to_vec());
+		let entry = transfer_states::table
+			.filter(transfer_states::bridge_transfer_id.eq(bridge_transfer_id))
+			.first::<TransferStateEntry>(&mut self.conn)
+			.optional()?;
+		entry.map(transfer_state_from_entry).transpose()
+	}
+
+	/// Whether a state has been persisted for a transfer, whatever its progress.
+	pub fn has_transfer_state(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId,
+	) -> Result<bool, diesel::result::Error> {
+		let bridge_transfer_id = hex::encode(bridge_transfer_id.0.to_vec());
+		diesel::select(diesel::dsl::exists(
+			transfer_states::table
+				.filter(transfer_states::bridge_transfer_id.eq(bridge_transfer_id)),
+		))
+		.get_result(&mut self.conn)
+	}
+
+	/// Gets the pre image revealed by the counterparty completion of a transfer, if indexed.
+	pub fn get_counter_party_pre_image(
+		&mut self,
+		bridge_transfer_id: BridgeTransferId,
+	) -> Result<Option<HashLockPreImage>, anyhow::Error> {
+		let bridge_transfer_id = hex::encode(bridge_transfer_id.0.to_vec());
+		let event = counter_party_completed_events::table
+			.filter(counter_party_completed_events::bridge_transfer_id.eq(bridge_transfer_id))
+			.first::<CounterPartyCompletedEvent>(&mut self.conn)
+			.optional()?;
+		event
+			.map(|event| Ok(HashLockPreImage(decode_bytes32(&event.pre_image)?)))
+			.transpose()
+	}
+}
+
+fn decode_bytes32(value: &str) -> Result<[u8; 32], anyhow::Error> {
+	hex::decode(value)?
+		.try_into()
+		.map_err(|bytes: Vec<u8>| anyhow::anyhow!("Expected 32 bytes, got {}", bytes.len()))
+}
+
+fn transfer_state_from_entry(entry: TransferStateEntry) -> Result<TransferState, anyhow::Error> {
+	Ok(TransferState {
+		state: entry.state.parse().map_err(anyhow::Error::msg)?,
+		init_chain: entry.init_chain.parse().map_err(anyhow::Error::msg)?,
+		transfer_id: BridgeTransferId(decode_bytes32(&entry.bridge_transfer_id)?),
+		intiator_address: TransferAddress::new(hex::decode(&entry.initiator)?),
+		counter_part_address: TransferAddress::new(hex::decode(&entry.recipient)?),
+		hash_lock: HashLock(decode_bytes32(&entry.hash_lock)?),
+		time_lock: TimeLock(entry.time_lock as u64),
+		amount: Amount(
+			entry
+				.amount
+				.to_u64()
+				.ok_or_else(|| anyhow::anyhow!("Invalid amount: {}", entry.amount))?,
+		),
+		contract_state: entry.contract_state as u8,
+		retry_on_error: entry.retry_on_error as usize,
+	})
 }
 
 /*#[cfg(test)]
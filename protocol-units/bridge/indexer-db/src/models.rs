@@ -163,3 +163,36 @@ pub struct RefundedEvent {
 	pub bridge_transfer_id: String,
 	pub created_at: chrono::NaiveDateTime,
 }
+
+// TransferState mapping
+#[derive(Debug, Insertable, AsChangeset, Default)]
+#[diesel(table_name = transfer_states)]
+pub struct NewTransferStateEntry {
+	pub bridge_transfer_id: String,
+	pub state: String,
+	pub init_chain: String,
+	pub initiator: String,
+	pub recipient: String,
+	pub hash_lock: String,
+	pub time_lock: i64,
+	pub amount: BigDecimal,
+	pub contract_state: i16,
+	pub retry_on_error: i32,
+	pub updated_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Queryable, Insertable)]
+#[diesel(table_name = transfer_states)]
+pub struct TransferStateEntry {
+	pub bridge_transfer_id: String,
+	pub state: String,
+	pub init_chain: String,
+	pub initiator: String,
+	pub recipient: String,
+	pub hash_lock: String,
+	pub time_lock: i64,
+	pub amount: BigDecimal,
+	pub contract_state: i16,
+	pub retry_on_error: i32,
+	pub updated_at: chrono::NaiveDateTime,
+}
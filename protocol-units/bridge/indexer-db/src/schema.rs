@@ -80,3 +80,19 @@ table! {
 		created_at -> Timestamp,
 	}
 }
+
+table! {
+	transfer_states (bridge_transfer_id) {
+		bridge_transfer_id -> Text,
+		state -> Text,
+		init_chain -> Text,
+		initiator -> Text,
+		recipient -> Text,
+		hash_lock -> Text,
+		time_lock -> BigInt,
+		amount -> Numeric,
+		contract_state -> Int2,
+		retry_on_error -> Int4,
+		updated_at -> Timestamp,
+	}
+}
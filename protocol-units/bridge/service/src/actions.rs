@@ -31,7 +31,7 @@ where
 					}
 				}
 				tracing::info!("Before client.lock_bridge_transfer");
-				client
+				let lock_result = client
 					.lock_bridge_transfer(
 						bridge_transfer_id,
 						hash_lock,
@@ -44,8 +44,27 @@ where
 						})?),
 						amount,
 					)
-					.await
-					.map_err(|err| ActionExecError(action, err))
+					.await;
+				let Err(err) = lock_result else {
+					return Ok(());
+				};
+				// A lock sent again after a restart may have landed before the stop.
+				// Its Locked event is replayed by the monitoring, so the transfer must not be refunded.
+				match client.get_bridge_transfer_details_counterparty(bridge_transfer_id).await {
+					Ok(Some(_)) => {
+						tracing::info!(
+							"Lock of transfer:{bridge_transfer_id} failed but the transfer is already locked: {err}"
+						);
+						Ok(())
+					}
+					Ok(None) => Err(ActionExecError(action, err)),
+					Err(check_err) => {
+						tracing::warn!(
+							"Lock of transfer:{bridge_transfer_id} failed: {err} and its lock cannot be checked: {check_err}"
+						);
+						Err(ActionExecError(action, BridgeContractError::LockUnknown))
+					}
+				}
 			};
 			Some(Box::pin(future))
 		}
@@ -13,14 +13,12 @@ use alloy::{
 use alloy_primitives::Uint;
 use alloy_rlp::Decodable;
 use bridge_config::common::eth::EthConfig;
-use bridge_grpc::bridge_server::BridgeServer;
 use bridge_util::chains::bridge_contracts::{BridgeContractError, BridgeContractResult};
 use bridge_util::types::{
 	Amount, BridgeAddress, BridgeTransferDetails, BridgeTransferDetailsCounterparty,
 	BridgeTransferId, HashLock, HashLockPreImage, TimeLock,
 };
-use std::{fmt::Debug, net::SocketAddr};
-use tonic::transport::Server;
+use std::fmt::Debug;
 use tracing::info;
 use url::Url;
 
@@ -109,21 +107,6 @@ impl EthClient {
 		})
 	}
 
-	/// Start the gRPC server
-	/// internally this passes a cloned self `EthClient` as the service.
-	pub async fn serve_grpc(
-		&self,
-		grpc_addr: SocketAddr,
-	) -> Result<(), Box<dyn std::error::Error>> {
-		tracing::info!("Starting gRPC server at: {:?}", grpc_addr);
-		Server::builder()
-			.add_service(BridgeServer::new(self.clone()))
-			.serve(grpc_addr)
-			.await?;
-
-		Ok(())
-	}
-
 	pub async fn initialize_counterparty_contract(
 		&self,
 		initiator_address: Address,
@@ -16,9 +16,56 @@ use bridge_util::types::LockDetails;
 use bridge_util::types::{BridgeAddress, BridgeTransferDetails, BridgeTransferId, HashLock};
 use futures::SinkExt;
 use futures::{channel::mpsc::UnboundedReceiver, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::{pin::Pin, task::Poll};
+use tokio::fs::{self, File};
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
 use tokio::sync::{mpsc, oneshot};
 
+const PULL_STATE_FILE_NAME: &str = "eth_pullstate.store";
+
+/// The last Eth block whose events have been pushed to the relayer.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct EthPullingState {
+	last_processed_block: u64,
+}
+
+impl EthPullingState {
+	async fn save_to_store_file(&self) -> io::Result<()> {
+		let path = EthPullingState::get_store_file_path();
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+
+		let json = serde_json::to_string(self)?;
+		let mut file = File::create(path.as_path()).await?;
+		file.write_all(json.as_bytes()).await?;
+		Ok(())
+	}
+
+	// Read the state from a JSON file
+	async fn build_from_store_file() -> io::Result<EthPullingState> {
+		let path = EthPullingState::get_store_file_path();
+
+		let state = if fs::try_exists(&path).await? {
+			let mut file = File::open(path.as_path()).await?;
+			let mut json = String::new();
+			file.read_to_string(&mut json).await?;
+			serde_json::from_str(&json)?
+		} else {
+			// Return a default state if the file does not exist
+			EthPullingState::default()
+		};
+		Ok(state)
+	}
+
+	fn get_store_file_path() -> std::path::PathBuf {
+		let dot_movement = dot_movement::DotMovement::try_from_env()
+			.unwrap_or(dot_movement::DotMovement::new(".movement"));
+		bridge_config::get_config_path(&dot_movement).join(PULL_STATE_FILE_NAME)
+	}
+}
+
 pub struct EthMonitoring {
 	listener: UnboundedReceiver<BridgeContractResult<BridgeContractEvent<EthAddress>>>,
 }
@@ -49,6 +96,9 @@ impl EthMonitoring {
 			BridgeContractResult<BridgeContractEvent<EthAddress>>,
 		>();
 
+		//read the pull state
+		let mut pull_state = EthPullingState::build_from_store_file().await?;
+
 		tokio::spawn({
 			let config = config.clone();
 			async move {
@@ -60,7 +110,6 @@ impl EthMonitoring {
 					config.eth_counterparty_contract.parse().unwrap(), //If unwrap start fail. Config must be updated.
 					rpc_provider.clone(),
 				);
-				let mut last_processed_block = 0;
 				loop {
 					//Check if there's a health check request
 					match health_check_rx.try_recv() {
@@ -114,29 +163,42 @@ impl EthMonitoring {
 							continue;
 						}
 					};
-					if last_processed_block < block_number {
-						last_processed_block = block_number;
+					if pull_state.last_processed_block < block_number {
+						// Resume right after the saved cursor so that blocks produced while the
+						// relayer was down are replayed. A fresh relayer starts at the head.
+						let from_block = match pull_state.last_processed_block {
+							0 => block_number,
+							last_processed_block => last_processed_block + 1,
+						};
+						// Keep the cursor in place if a query fails so the range is fetched again.
+						let mut query_failed = false;
 						let initiator_initiate_event_filter = initiator_contract
 							.BridgeTransferInitiated_filter()
-							.from_block(BlockNumberOrTag::Number(last_processed_block));
+							.from_block(BlockNumberOrTag::Number(from_block))
+							.to_block(BlockNumberOrTag::Number(block_number));
 						// event BridgeTransferCompleted(bytes32 indexed _bridgeTransferId, bytes32 pre_image);
 						let initiator_trcompleted_event_filter = initiator_contract
 							.BridgeTransferCompleted_filter()
-							.from_block(BlockNumberOrTag::Number(last_processed_block));
+							.from_block(BlockNumberOrTag::Number(from_block))
+							.to_block(BlockNumberOrTag::Number(block_number));
 						// event BridgeTransferRefunded(bytes32 indexed _bridgeTransferId);
 						let initiator_trrefund_event_filter = initiator_contract
 							.BridgeTransferRefunded_filter()
-							.from_block(BlockNumberOrTag::Number(last_processed_block));
+							.from_block(BlockNumberOrTag::Number(from_block))
+							.to_block(BlockNumberOrTag::Number(block_number));
 						let counterpart_trlocked_event_filter = counterpart_contract
 							.BridgeTransferLocked_filter()
-							.from_block(BlockNumberOrTag::Number(last_processed_block));
+							.from_block(BlockNumberOrTag::Number(from_block))
+							.to_block(BlockNumberOrTag::Number(block_number));
 						let counterpart_trcompleted_event_filter = counterpart_contract
 							.BridgeTransferCompleted_filter()
-							.from_block(BlockNumberOrTag::Number(last_processed_block));
+							.from_block(BlockNumberOrTag::Number(from_block))
+							.to_block(BlockNumberOrTag::Number(block_number));
 						//event BridgeTransferAborted(bytes32 indexed bridgeTransferId);
 						let counterpart_trcaborted_event_filter = counterpart_contract
 							.BridgeTransferAborted_filter()
-							.from_block(BlockNumberOrTag::Number(last_processed_block));
+							.from_block(BlockNumberOrTag::Number(from_block))
+							.to_block(BlockNumberOrTag::Number(block_number));
 
 						//Initiator event stream
 						match tokio::time::timeout(
@@ -174,6 +236,7 @@ impl EthMonitoring {
 								}
 							}
 							Ok(Err(_)) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError("Eth monitoring query initiator_initiate_event_filter timeout.".to_string())))
 									.await
@@ -184,6 +247,7 @@ impl EthMonitoring {
 								}
 							}
 							Err(err) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError(err.to_string())))
 									.await
@@ -195,7 +259,11 @@ impl EthMonitoring {
 							}
 						}
 						match tokio::time::timeout(
-							tokio::time::Duration::from_secs(config.rest_connection_timeout_secs),initiator_trcompleted_event_filter.query()).await {
+							tokio::time::Duration::from_secs(config.rest_connection_timeout_secs),
+							initiator_trcompleted_event_filter.query(),
+						)
+						.await
+						{
 							Ok(Ok(events)) => {
 								for (completed, _log) in events {
 									if sender
@@ -211,6 +279,7 @@ impl EthMonitoring {
 								}
 							}
 							Ok(Err(_)) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError("Eth monitoring query initiator_trcompleted_event_filter timeout.".to_string())))
 									.await
@@ -221,6 +290,7 @@ impl EthMonitoring {
 								}
 							}
 							Err(err) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError(err.to_string())))
 									.await
@@ -252,6 +322,7 @@ impl EthMonitoring {
 								}
 							}
 							Ok(Err(_)) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError("Eth monitoring query initiator_trrefund_event_filter timeout.".to_string())))
 									.await
@@ -262,6 +333,7 @@ impl EthMonitoring {
 								}
 							}
 							Err(err) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError(err.to_string())))
 									.await
@@ -309,6 +381,7 @@ impl EthMonitoring {
 								}
 							}
 							Ok(Err(_)) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError("Eth monitoring query counterpart_trlocked_event_filter timeout.".to_string())))
 									.await
@@ -319,6 +392,7 @@ impl EthMonitoring {
 								}
 							}
 							Err(err) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError(err.to_string())))
 									.await
@@ -351,6 +425,7 @@ impl EthMonitoring {
 								}
 							}
 							Ok(Err(_)) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError("Eth monitoring query counterpart_trcompleted_event_filter timeout.".to_string())))
 									.await
@@ -361,6 +436,7 @@ impl EthMonitoring {
 								}
 							}
 							Err(err) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError(err.to_string())))
 									.await
@@ -392,6 +468,7 @@ impl EthMonitoring {
 								}
 							}
 							Ok(Err(_)) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError("Eth monitoring query counterpart_trcaborted_event_filter timeout.".to_string())))
 									.await
@@ -402,6 +479,7 @@ impl EthMonitoring {
 								}
 							}
 							Err(err) => {
+								query_failed = true;
 								if sender
 									.send(Err(BridgeContractError::OnChainError(err.to_string())))
 									.await
@@ -412,6 +490,13 @@ impl EthMonitoring {
 								}
 							}
 						}
+
+						if !query_failed {
+							pull_state.last_processed_block = block_number;
+							if let Err(err) = pull_state.save_to_store_file().await {
+								tracing::error!("Eth monitoring unable to store the file state because:{err} for state:{pull_state:?}");
+							}
+						}
 					} // end match
 
 					let _ = tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
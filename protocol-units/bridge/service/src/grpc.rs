@@ -1,12 +1,26 @@
-use crate::chains::ethereum::client::EthClient;
+use crate::chains::{
+	ethereum::client::EthClient, movement::client_framework::MovementClientFramework,
+};
+use crate::TransferStateUpdate;
+use aptos_sdk::crypto::{
+	ed25519::{Ed25519PublicKey, Ed25519Signature},
+	Signature, ValidCryptoMaterialStringExt,
+};
 use bridge_grpc::{
 	bridge_server::Bridge, health_check_response::ServingStatus, health_server::Health,
 	BridgeTransferDetailsResponse, GetBridgeTransferDetailsRequest, HealthCheckRequest,
-	HealthCheckResponse,
+	HealthCheckResponse, StreamTransferStateRequest, TransferStateResponse,
+};
+use bridge_indexer_db::client::Client as IndexerClient;
+use bridge_util::chains::bridge_contracts::{BridgeContract, BridgeContractResult};
+use bridge_util::types::{
+	BridgeTransferDetails, BridgeTransferDetailsCounterparty, BridgeTransferId,
 };
 use futures::Stream;
 use std::pin::Pin;
-use tokio::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc};
 use tonic::{Request, Response, Status};
 
 /// A gRPC Health Check Service
@@ -67,34 +81,375 @@ impl HealthCheckService {
 	}
 }
 
-/// Implement the gRPC service `Bridge` for the Ethereum Bridge Client
+/// How far the timestamp of a signed request may be from the time of the service, in seconds.
+///
+/// A signed request can be replayed within this window, but only for the same method and transfer.
+const MAX_REQUEST_AGE_SECS: u64 = 30;
+
+/// The message an authorized key signs for a request of the given method about the given transfer.
+///
+/// The method is the name of the rpc, e.g. `StreamTransferState`, and the timestamp is in unix seconds.
+pub fn request_message(method: &str, timestamp: u64, bridge_transfer_id: &[u8]) -> Vec<u8> {
+	let mut message = Vec::with_capacity(method.len() + 8 + bridge_transfer_id.len());
+	message.extend_from_slice(method.as_bytes());
+	message.extend_from_slice(&timestamp.to_be_bytes());
+	message.extend_from_slice(bridge_transfer_id);
+	message
+}
+
+/// The gRPC `Bridge` service, looking transfers up on both bridge contracts.
+#[derive(Clone)]
+pub struct BridgeGrpcService {
+	eth_client: EthClient,
+	movement_client: MovementClientFramework,
+	// Keys allowed to query the service. Empty when lookups are public.
+	authorized_keys: Vec<Ed25519PublicKey>,
+	transfer_state_tx: broadcast::Sender<TransferStateUpdate>,
+	// Where the relayer persists transfer states, to start streams from the current state.
+	indexer_db_client: Option<Arc<Mutex<IndexerClient>>>,
+}
+
+impl BridgeGrpcService {
+	pub fn new(
+		eth_client: EthClient,
+		movement_client: MovementClientFramework,
+		authorized_keys: &[String],
+		transfer_state_tx: broadcast::Sender<TransferStateUpdate>,
+	) -> Result<Self, anyhow::Error> {
+		let authorized_keys = authorized_keys
+			.iter()
+			.map(|key| Ed25519PublicKey::from_encoded_string(key))
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(Self {
+			eth_client,
+			movement_client,
+			authorized_keys,
+			transfer_state_tx,
+			indexer_db_client: None,
+		})
+	}
+
+	pub fn with_indexer_db_client(mut self, indexer_db_client: IndexerClient) -> Self {
+		self.indexer_db_client = Some(Arc::new(Mutex::new(indexer_db_client)));
+		self
+	}
+
+	/// Checks that the signature of the request is a signature of its method, timestamp and
+	/// transfer id by one of the authorized keys, when the service requires one.
+	fn authenticate(
+		&self,
+		method: &str,
+		bridge_transfer_id: &[u8],
+		timestamp: u64,
+		signature: &[u8],
+	) -> Result<(), Status> {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_err(|err| Status::internal(err.to_string()))?
+			.as_secs();
+		authenticate_at(
+			&self.authorized_keys,
+			now,
+			method,
+			bridge_transfer_id,
+			timestamp,
+			signature,
+		)
+	}
+
+	/// Reads the persisted state of a transfer, if the service has access to the indexer db.
+	async fn current_transfer_state(
+		&self,
+		bridge_transfer_id: BridgeTransferId,
+	) -> Result<Option<TransferStateUpdate>, Status> {
+		let Some(indexer_db_client) = self.indexer_db_client.clone() else {
+			return Ok(None);
+		};
+		let state = tokio::task::spawn_blocking(move || {
+			let mut client = indexer_db_client
+				.lock()
+				.map_err(|_| anyhow::anyhow!("indexer db lock poisoned"))?;
+			client.get_transfer_state(bridge_transfer_id)
+		})
+		.await
+		.map_err(|err| Status::internal(err.to_string()))?
+		.map_err(|err| Status::internal(err.to_string()))?;
+		Ok(state.as_ref().map(TransferStateUpdate::from))
+	}
+}
+
+fn authenticate_at(
+	authorized_keys: &[Ed25519PublicKey],
+	now: u64,
+	method: &str,
+	bridge_transfer_id: &[u8],
+	timestamp: u64,
+	signature: &[u8],
+) -> Result<(), Status> {
+	if authorized_keys.is_empty() {
+		return Ok(());
+	}
+	let signature = Ed25519Signature::try_from(signature)
+		.map_err(|_| Status::unauthenticated("missing or malformed signature"))?;
+	if now.abs_diff(timestamp) > MAX_REQUEST_AGE_SECS {
+		return Err(Status::unauthenticated("request timestamp is too far from the service time"));
+	}
+	let message = request_message(method, timestamp, bridge_transfer_id);
+	authorized_keys
+		.iter()
+		.any(|key| signature.verify_arbitrary_msg(&message, key).is_ok())
+		.then_some(())
+		.ok_or_else(|| Status::permission_denied("signature does not match an authorized key"))
+}
+
+fn transfer_state_response(update: &TransferStateUpdate) -> TransferStateResponse {
+	TransferStateResponse {
+		bridge_transfer_id: update.transfer_id.0.to_vec(),
+		state: update.state.to_string(),
+		init_chain: update.init_chain.to_string(),
+		retry_on_error: update.retry_on_error as u64,
+	}
+}
+
+fn parse_transfer_id(bridge_transfer_id: Vec<u8>) -> Result<BridgeTransferId, Status> {
+	BridgeTransferId::try_from(bridge_transfer_id)
+		.map_err(|_| Status::invalid_argument("bridge transfer id must be 32 bytes"))
+}
+
+fn encode_address(address: Vec<u8>) -> String {
+	format!("0x{}", hex::encode(address))
+}
+
+fn initiator_details_response<A>(
+	details: BridgeContractResult<Option<BridgeTransferDetails<A>>>,
+) -> Result<Response<BridgeTransferDetailsResponse>, Status>
+where
+	Vec<u8>: From<A>,
+{
+	match details {
+		Ok(Some(details)) => Ok(Response::new(BridgeTransferDetailsResponse {
+			initiator: encode_address(details.initiator.0.into()),
+			recipient: details.recipient.0,
+			hash_lock: details.hash_lock.0.to_vec(),
+			time_lock: details.time_lock.0,
+			amount: details.amount.0,
+			state: details.state.into(),
+			error_message: String::new(),
+		})),
+		Ok(None) => Err(Status::not_found("bridge transfer not found")),
+		Err(err) => Err(Status::internal(err.to_string())),
+	}
+}
+
+fn counterparty_details_response<A>(
+	details: BridgeContractResult<Option<BridgeTransferDetailsCounterparty<A>>>,
+) -> Result<Response<BridgeTransferDetailsResponse>, Status>
+where
+	Vec<u8>: From<A>,
+{
+	match details {
+		Ok(Some(details)) => Ok(Response::new(BridgeTransferDetailsResponse {
+			initiator: encode_address(details.initiator.0),
+			recipient: details.recipient.0.into(),
+			hash_lock: details.hash_lock.0.to_vec(),
+			time_lock: details.time_lock.0,
+			amount: details.amount.0,
+			state: details.state.into(),
+			error_message: String::new(),
+		})),
+		Ok(None) => Err(Status::not_found("bridge transfer not found")),
+		Err(err) => Err(Status::internal(err.to_string())),
+	}
+}
+
 #[tonic::async_trait]
-impl Bridge for EthClient {
+impl Bridge for BridgeGrpcService {
+	type StreamTransferStateStream =
+		Pin<Box<dyn Stream<Item = Result<TransferStateResponse, Status>> + Send + 'static>>;
+
 	async fn get_bridge_transfer_details_initiator_eth(
 		&self,
-		_request: Request<GetBridgeTransferDetailsRequest>,
+		request: Request<GetBridgeTransferDetailsRequest>,
 	) -> Result<Response<BridgeTransferDetailsResponse>, Status> {
-		unimplemented!()
+		let request = request.into_inner();
+		self.authenticate(
+			"GetBridgeTransferDetailsInitiatorEth",
+			&request.bridge_transfer_id,
+			request.timestamp,
+			&request.signature,
+		)?;
+		let bridge_transfer_id = parse_transfer_id(request.bridge_transfer_id)?;
+		let details = self
+			.eth_client
+			.clone()
+			.get_bridge_transfer_details_initiator(bridge_transfer_id)
+			.await;
+		initiator_details_response(details)
 	}
 
 	async fn get_bridge_transfer_details_counterparty_eth(
 		&self,
-		_request: Request<GetBridgeTransferDetailsRequest>,
+		request: Request<GetBridgeTransferDetailsRequest>,
 	) -> Result<Response<BridgeTransferDetailsResponse>, Status> {
-		unimplemented!()
+		let request = request.into_inner();
+		self.authenticate(
+			"GetBridgeTransferDetailsCounterpartyEth",
+			&request.bridge_transfer_id,
+			request.timestamp,
+			&request.signature,
+		)?;
+		let bridge_transfer_id = parse_transfer_id(request.bridge_transfer_id)?;
+		let details = self
+			.eth_client
+			.clone()
+			.get_bridge_transfer_details_counterparty(bridge_transfer_id)
+			.await;
+		counterparty_details_response(details)
 	}
 
 	async fn get_bridge_transfer_details_initiator_movement(
 		&self,
-		_request: Request<GetBridgeTransferDetailsRequest>,
+		request: Request<GetBridgeTransferDetailsRequest>,
 	) -> Result<Response<BridgeTransferDetailsResponse>, Status> {
-		unimplemented!()
+		let request = request.into_inner();
+		self.authenticate(
+			"GetBridgeTransferDetailsInitiatorMovement",
+			&request.bridge_transfer_id,
+			request.timestamp,
+			&request.signature,
+		)?;
+		let bridge_transfer_id = parse_transfer_id(request.bridge_transfer_id)?;
+		let details = self
+			.movement_client
+			.clone()
+			.get_bridge_transfer_details_initiator(bridge_transfer_id)
+			.await;
+		initiator_details_response(details)
 	}
 
 	async fn get_bridge_transfer_details_counterparty_movement(
 		&self,
-		_request: Request<GetBridgeTransferDetailsRequest>,
+		request: Request<GetBridgeTransferDetailsRequest>,
 	) -> Result<Response<BridgeTransferDetailsResponse>, Status> {
-		unimplemented!()
+		let request = request.into_inner();
+		self.authenticate(
+			"GetBridgeTransferDetailsCounterpartyMovement",
+			&request.bridge_transfer_id,
+			request.timestamp,
+			&request.signature,
+		)?;
+		let bridge_transfer_id = parse_transfer_id(request.bridge_transfer_id)?;
+		let details = self
+			.movement_client
+			.clone()
+			.get_bridge_transfer_details_counterparty(bridge_transfer_id)
+			.await;
+		counterparty_details_response(details)
+	}
+
+	async fn stream_transfer_state(
+		&self,
+		request: Request<StreamTransferStateRequest>,
+	) -> Result<Response<Self::StreamTransferStateStream>, Status> {
+		let request = request.into_inner();
+		self.authenticate(
+			"StreamTransferState",
+			&request.bridge_transfer_id,
+			request.timestamp,
+			&request.signature,
+		)?;
+		let bridge_transfer_id = parse_transfer_id(request.bridge_transfer_id)?;
+		// subscribe before reading the current state, so that no change in between is missed
+		let mut receiver = self.transfer_state_tx.subscribe();
+		let current = self.current_transfer_state(bridge_transfer_id).await?;
+
+		let output = async_stream::try_stream! {
+			// A completed or refunded transfer does not change anymore.
+			let mut done = false;
+			if let Some(current) = current {
+				done = current.state.is_final();
+				yield transfer_state_response(&current);
+			}
+			while !done {
+				let update = match receiver.recv().await {
+					Ok(update) => update,
+					Err(broadcast::error::RecvError::Lagged(skipped)) => {
+						tracing::warn!("Transfer state subscriber lagged, {skipped} updates skipped");
+						continue;
+					}
+					Err(broadcast::error::RecvError::Closed) => break,
+				};
+				if update.transfer_id != bridge_transfer_id {
+					continue;
+				}
+				done = update.state.is_final();
+				yield transfer_state_response(&update);
+			}
+		};
+
+		Ok(Response::new(Box::pin(output)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use aptos_sdk::crypto::{ed25519::Ed25519PrivateKey, SigningKey, Uniform};
+
+	#[test]
+	fn test_parse_transfer_id() {
+		assert!(parse_transfer_id(vec![1; 32]).is_ok());
+		let err = parse_transfer_id(vec![1; 31]).unwrap_err();
+		assert_eq!(err.code(), tonic::Code::InvalidArgument);
+	}
+
+	#[test]
+	fn test_authenticate() {
+		let private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		let authorized_keys = vec![Ed25519PublicKey::from(&private_key)];
+		let bridge_transfer_id = [7u8; 32];
+		let now = 1_000;
+		let sign = |method: &str, timestamp: u64| {
+			private_key
+				.sign_arbitrary_message(&request_message(method, timestamp, &bridge_transfer_id))
+				.to_bytes()
+				.to_vec()
+		};
+		let authenticate = |method: &str, transfer_id: &[u8], timestamp: u64, signature: &[u8]| {
+			authenticate_at(&authorized_keys, now, method, transfer_id, timestamp, signature)
+		};
+
+		let signature = sign("StreamTransferState", now);
+		assert!(authenticate("StreamTransferState", &bridge_transfer_id, now, &signature).is_ok());
+
+		// the signature is bound to the method, the transfer and the timestamp
+		let err = authenticate(
+			"GetBridgeTransferDetailsInitiatorEth",
+			&bridge_transfer_id,
+			now,
+			&signature,
+		)
+		.unwrap_err();
+		assert_eq!(err.code(), tonic::Code::PermissionDenied);
+		let err = authenticate("StreamTransferState", &[8u8; 32], now, &signature).unwrap_err();
+		assert_eq!(err.code(), tonic::Code::PermissionDenied);
+		let err = authenticate("StreamTransferState", &bridge_transfer_id, now + 1, &signature)
+			.unwrap_err();
+		assert_eq!(err.code(), tonic::Code::PermissionDenied);
+
+		// a signature of an old request is not accepted anymore
+		let stale = now - MAX_REQUEST_AGE_SECS - 1;
+		let signature = sign("StreamTransferState", stale);
+		let err = authenticate("StreamTransferState", &bridge_transfer_id, stale, &signature)
+			.unwrap_err();
+		assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+		let err = authenticate("StreamTransferState", &bridge_transfer_id, now, &[]).unwrap_err();
+		assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+		// lookups are public without authorized keys
+		assert!(
+			authenticate_at(&[], now, "StreamTransferState", &bridge_transfer_id, 0, &[]).is_ok()
+		);
 	}
 }
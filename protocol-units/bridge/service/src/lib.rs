@@ -2,13 +2,16 @@ use crate::actions::process_action;
 use bridge_indexer_db::client::Client as IndexerClient;
 use bridge_util::{
 	actions::{ActionExecError, TransferAction, TransferActionType},
-	chains::bridge_contracts::{BridgeContract, BridgeContractEvent, BridgeContractMonitoring},
+	chains::bridge_contracts::{
+		BridgeContract, BridgeContractError, BridgeContractEvent, BridgeContractMonitoring,
+	},
 	events::{InvalidEventError, TransferEvent},
 	states::{TransferState, TransferStateType},
 	types::{BridgeTransferId, ChainId},
 };
 use futures::stream::FuturesUnordered;
 use std::{collections::HashMap, sync::Arc};
+use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::{select, sync::Mutex};
@@ -21,6 +24,26 @@ pub mod chains;
 pub mod grpc;
 pub mod rest;
 
+/// A change of the state of a relayed transfer, published to the gRPC subscribers.
+#[derive(Debug, Clone)]
+pub struct TransferStateUpdate {
+	pub transfer_id: BridgeTransferId,
+	pub state: TransferStateType,
+	pub init_chain: ChainId,
+	pub retry_on_error: usize,
+}
+
+impl From<&TransferState> for TransferStateUpdate {
+	fn from(state: &TransferState) -> Self {
+		TransferStateUpdate {
+			transfer_id: state.transfer_id,
+			state: state.state,
+			init_chain: state.init_chain,
+			retry_on_error: state.retry_on_error,
+		}
+	}
+}
+
 #[derive(Debug)]
 struct HeathCheckStatus {
 	chain_one: bool,
@@ -55,12 +78,13 @@ pub async fn run_bridge<
 	indexer_db_client: Option<IndexerClient>,
 	healthcheck_tx_one: mpsc::Sender<oneshot::Sender<bool>>,
 	healthcheck_tx_two: mpsc::Sender<oneshot::Sender<bool>>,
+	transfer_state_tx: broadcast::Sender<TransferStateUpdate>,
 ) -> Result<(), anyhow::Error>
 where
 	Vec<u8>: From<A1>,
 	Vec<u8>: From<A2>,
 {
-	let mut state_runtime = Runtime::new(indexer_db_client);
+	let mut state_runtime = Runtime::new(indexer_db_client, transfer_state_tx);
 
 	let mut client_exec_result_futures_one = FuturesUnordered::new();
 	let mut client_exec_result_futures_two = FuturesUnordered::new();
@@ -70,6 +94,38 @@ where
 	let client_lock_one = Arc::new(Mutex::new(()));
 	let client_lock_two = Arc::new(Mutex::new(()));
 
+	// Rebuild the in-progress transfers persisted before the last stop and
+	// re-run the actions they were waiting on.
+	for action in state_runtime.restore() {
+		tracing::info!("Resume action for restored transfer:{action}");
+		match action.chain {
+			ChainId::ONE => {
+				if let Some(fut) = process_action(action, client_one.clone()) {
+					let jh = tokio::spawn({
+						let client_lock_clone = client_lock_one.clone();
+						async move {
+							let _lock = client_lock_clone.lock().await;
+							fut.await
+						}
+					});
+					client_exec_result_futures_one.push(jh);
+				}
+			}
+			ChainId::TWO => {
+				if let Some(fut) = process_action(action, client_two.clone()) {
+					let jh = tokio::spawn({
+						let client_lock_clone = client_lock_two.clone();
+						async move {
+							let _lock = client_lock_clone.lock().await;
+							fut.await
+						}
+					});
+					client_exec_result_futures_two.push(jh);
+				}
+			}
+		}
+	}
+
 	let mut tranfer_log_interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
 	let mut monitoring_health_check_interval =
 		tokio::time::interval(tokio::time::Duration::from_secs(5));
@@ -275,17 +331,86 @@ async fn check_monitoring_loop_heath(
 struct Runtime {
 	swap_state_map: HashMap<BridgeTransferId, TransferState>,
 	indexer_db_client: Option<IndexerClient>,
+	transfer_state_tx: broadcast::Sender<TransferStateUpdate>,
 }
 
 impl Runtime {
-	pub fn new(indexer_db_client: Option<IndexerClient>) -> Self {
-		Runtime { swap_state_map: HashMap::new(), indexer_db_client }
+	pub fn new(
+		indexer_db_client: Option<IndexerClient>,
+		transfer_state_tx: broadcast::Sender<TransferStateUpdate>,
+	) -> Self {
+		Runtime { swap_state_map: HashMap::new(), indexer_db_client, transfer_state_tx }
 	}
 
 	pub fn iter_state(&self) -> impl Iterator<Item = &TransferState> {
 		self.swap_state_map.values()
 	}
 
+	/// Loads the transfers persisted in the indexer db and returns the actions
+	/// that must be executed again to move them forward.
+	///
+	/// Initialized transfers are locked again, as the lock may not have been sent before the stop,
+	/// and a lock that already landed fails without changing the transfer.
+	/// Transfers waiting on a chain event are only restored: the event is replayed
+	/// by the monitoring from its saved cursor.
+	pub fn restore(&mut self) -> Vec<TransferAction> {
+		let Some(ref mut client) = self.indexer_db_client else {
+			tracing::warn!("No indexer db client found. Transfer states not restored");
+			return Vec::new();
+		};
+		let states = match client.load_transfer_states_in_progress() {
+			Ok(states) => states,
+			Err(err) => {
+				tracing::error!("Fail to load persisted transfer states:{err}");
+				return Vec::new();
+			}
+		};
+
+		let mut actions = Vec::new();
+		for state in states {
+			tracing::info!("Restore transfer:{state}");
+			match state.state {
+				TransferStateType::Initialized => actions.push(state.lock_action()),
+				// The initiator still has to be completed before its time lock expires.
+				TransferStateType::SecretReceived => {
+					match client.get_counter_party_pre_image(state.transfer_id) {
+						Ok(Some(secret)) => actions.push(TransferAction {
+							chain: state.init_chain,
+							transfer_id: state.transfer_id,
+							kind: TransferActionType::WaitAndCompleteInitiator(0, secret),
+						}),
+						Ok(None) => tracing::warn!(
+							"No pre image indexed for restored transfer:{}. Initiator not completed.",
+							state.transfer_id
+						),
+						Err(err) => tracing::warn!(
+							"Fail to get pre image for restored transfer:{} err:{err}",
+							state.transfer_id
+						),
+					}
+				}
+				_ => (),
+			}
+			self.swap_state_map.insert(state.transfer_id, state);
+		}
+		actions
+	}
+
+	fn persist_state(&mut self, state: &TransferState) -> Result<(), InvalidEventError> {
+		// An error only means that nobody is subscribed.
+		let _ = self.transfer_state_tx.send(state.into());
+		match self.indexer_db_client {
+			Some(ref mut client) => {
+				client.upsert_transfer_state(state).map_err(|err| {
+					tracing::warn!("Fail to persist transfer state :{err}");
+					InvalidEventError::IndexingFailed(err.to_string())
+				})?;
+				Ok(())
+			}
+			None => Ok(()),
+		}
+	}
+
 	fn index_event<A>(&mut self, event: TransferEvent<A>) -> Result<(), InvalidEventError>
 	where
 		A: Into<Vec<u8>> + std::clone::Clone + std::fmt::Debug,
@@ -347,6 +472,7 @@ impl Runtime {
 			let (state, mut action) =
 				TransferState::transition_from_initiated(event.chain, event_transfer_id, detail);
 			action.chain = state.init_chain.other();
+			self.persist_state(&state)?;
 			self.swap_state_map.insert(state.transfer_id, state);
 			self.index_transfer_action(action.clone())?;
 			return Ok(action);
@@ -396,6 +522,7 @@ impl Runtime {
 		// index action
 		// todo: really this should come after process_action completion, but the current use of process_action is hacky
 		self.index_transfer_action(action.clone())?;
+		self.persist_state(&state)?;
 
 		if state.state != TransferStateType::Done {
 			self.swap_state_map.insert(state.transfer_id, state);
@@ -421,6 +548,19 @@ impl Runtime {
 		} else {
 			tracing::info!("No existing state found for transfer ID {:?}", event_transfer_id);
 		}
+		// An Initiated event replayed after a restart can belong to a transfer that
+		// has already completed and been dropped from the map.
+		if swap_state_opt.is_none() && event.contract_event.is_initiated_event() {
+			if let Some(ref mut client) = self.indexer_db_client {
+				let known = client
+					.has_transfer_state(event_transfer_id)
+					.map_err(|err| InvalidEventError::IndexingFailed(err.to_string()))?;
+				if known {
+					return Err(InvalidEventError::InitAnAlreadyExist);
+				}
+			}
+		}
+
 		//validate the associated swap_state.
 		swap_state_opt
 			.as_ref()
@@ -443,42 +583,214 @@ impl Runtime {
 		let (action, err) = action_err.inner();
 		tracing::warn!("Client execution error for action:{action} err:{err}");
 		// retry 5 time an action in error then abort.
-		match self.swap_state_map.get_mut(&action.transfer_id) {
-			Some(state) => {
-				state.retry_on_error += 1;
-				if state.retry_on_error > 5 {
-					// Depending on the action cancel transfer
-					match action.kind {
-						TransferActionType::LockBridgeTransfer { .. } => {
-							//Lock fail. Refund initiator
-							let (new_state_type, action_kind) = state.transition_to_refund();
-							state.state = new_state_type;
-							let action = TransferAction {
-								chain: state.init_chain,
-								transfer_id: state.transfer_id,
-								kind: action_kind,
-							};
-							Some(action)
-						}
-						TransferActionType::WaitAndCompleteInitiator(..) => {
-							todo!()
-						}
-						TransferActionType::RefundInitiator => None, //will wait automatic refund
-						TransferActionType::TransferDone => None,
-						TransferActionType::NoAction => None,
-					}
-				} else {
-					//Rerun the action.
+		let Some(state) = self.swap_state_map.get_mut(&action.transfer_id) else {
+			tracing::warn!(
+				"Receive an error for action but no state found for id:{:?}",
+				action.transfer_id
+			);
+			return None;
+		};
+		state.retry_on_error += 1;
+		let transfer_id = action.transfer_id;
+		let next_action = if state.retry_on_error > 5 {
+			// Depending on the action cancel transfer
+			match action.kind {
+				// The counterpart chain could not tell whether the lock landed, so refunding could pay twice.
+				TransferActionType::LockBridgeTransfer { .. }
+					if matches!(err, BridgeContractError::LockUnknown) =>
+				{
+					tracing::error!(
+						"Fail to lock transfer:{} after retries and its lock cannot be checked, it needs a relayer restart or a manual check",
+						transfer_id
+					);
+					None
+				}
+				// The counterpart chain has no lock for the transfer.
+				TransferActionType::LockBridgeTransfer { .. }
+					if state.state == TransferStateType::Initialized =>
+				{
+					//Lock fail. Refund initiator
+					let (new_state_type, action_kind) = state.transition_to_refund();
+					state.state = new_state_type;
+					let action = TransferAction {
+						chain: state.init_chain,
+						transfer_id: state.transfer_id,
+						kind: action_kind,
+					};
 					Some(action)
 				}
+				// A lock retried after a restart already landed: the transfer moves on with its events.
+				TransferActionType::LockBridgeTransfer { .. } => None,
+				TransferActionType::WaitAndCompleteInitiator(..) => {
+					// The counterpart is already completed, so the initiator must not be refunded.
+					// The transfer stays in progress and is completed again on the next restart.
+					tracing::error!(
+						"Fail to complete initiator for transfer:{} after retries, it needs a relayer restart or a manual completion",
+						transfer_id
+					);
+					None
+				}
+				TransferActionType::RefundInitiator => None, //will wait automatic refund
+				TransferActionType::TransferDone => None,
+				TransferActionType::NoAction => None,
 			}
-			None => {
-				tracing::warn!(
-					"Receive an error for action but no state found for id:{:?}",
-					action.transfer_id
-				);
-				None
+		} else {
+			//Rerun the action.
+			Some(action)
+		};
+
+		// persist and publish the retry count and any refund transition
+		if let Some(state) = self.swap_state_map.get(&transfer_id) {
+			let _ = self.transfer_state_tx.send(state.into());
+			if let Some(client) = self.indexer_db_client.as_mut() {
+				if let Err(err) = client.upsert_transfer_state(state) {
+					tracing::warn!("Fail to persist transfer state :{err}");
+				}
 			}
 		}
+		next_action
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+	use super::*;
+	use bridge_util::types::{
+		Amount, BridgeAddress, BridgeTransferDetails, HashLock, HashLockPreImage, LockDetails,
+		TimeLock,
+	};
+
+	fn runtime(indexer_db_client: Option<IndexerClient>) -> Runtime {
+		let (transfer_state_tx, _) = broadcast::channel(16);
+		Runtime::new(indexer_db_client, transfer_state_tx)
+	}
+
+	/// Connects to the indexer db of the environment, if there is one.
+	fn indexer_db_client() -> Result<Option<IndexerClient>, anyhow::Error> {
+		if std::env::var("BRIDGE_INDEXER_DATABASE_URL").is_err() {
+			return Ok(None);
+		}
+		let mut client = IndexerClient::from_env()?;
+		client.run_migrations()?;
+		Ok(Some(client))
+	}
+
+	fn initiated_event(transfer_id: BridgeTransferId) -> TransferEvent<Vec<u8>> {
+		let details = BridgeTransferDetails {
+			bridge_transfer_id: transfer_id,
+			initiator: BridgeAddress(vec![1; 32]),
+			recipient: BridgeAddress(vec![2; 32]),
+			hash_lock: HashLock([3; 32]),
+			time_lock: TimeLock(100),
+			amount: Amount(10),
+			state: 1,
+		};
+		(BridgeContractEvent::Initiated(details), ChainId::ONE).into()
+	}
+
+	fn fail(runtime: &mut Runtime, action: &TransferAction) -> Option<TransferAction> {
+		runtime.process_action_exec_error(ActionExecError(
+			action.clone(),
+			BridgeContractError::CallError,
+		))
+	}
+
+	#[test]
+	fn test_exhausted_initiator_completion_keeps_transfer() -> Result<(), anyhow::Error> {
+		let mut runtime = runtime(None);
+		let transfer_id = BridgeTransferId::gen_unique_hash(&mut rand::thread_rng());
+		let lock_action = runtime.process_event(initiated_event(transfer_id))?;
+
+		let lock = LockDetails {
+			bridge_transfer_id: transfer_id,
+			initiator: BridgeAddress(vec![1; 32]),
+			recipient: BridgeAddress(vec![2; 32]),
+			hash_lock: HashLock([3; 32]),
+			time_lock: TimeLock(100),
+			amount: Amount(10),
+		};
+		runtime.process_event((BridgeContractEvent::Locked(lock), ChainId::TWO).into())?;
+		let complete_action = runtime.process_event(
+			(
+				BridgeContractEvent::CounterPartyCompleted(transfer_id, HashLockPreImage([4; 32])),
+				ChainId::TWO,
+			)
+				.into(),
+		)?;
+
+		// a lock retried after it landed does not refund the locked transfer
+		let mut next_action = None;
+		for _ in 0..6 {
+			next_action = fail(&mut runtime, &lock_action);
+		}
+		assert!(next_action.is_none());
+
+		// an initiator completion out of retries neither refunds nor panics
+		assert!(fail(&mut runtime, &complete_action).is_none());
+		let state = runtime.swap_state_map.get(&transfer_id).expect("transfer state");
+		assert_eq!(state.state, TransferStateType::SecretReceived);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_unchecked_lock_is_not_refunded() -> Result<(), anyhow::Error> {
+		let mut runtime = runtime(None);
+		let transfer_id = BridgeTransferId::gen_unique_hash(&mut rand::thread_rng());
+		let lock_action = runtime.process_event(initiated_event(transfer_id))?;
+
+		// the lock may have landed when the counterpart chain cannot tell
+		let mut next_action = None;
+		for _ in 0..6 {
+			next_action = runtime.process_action_exec_error(ActionExecError(
+				lock_action.clone(),
+				BridgeContractError::LockUnknown,
+			));
+		}
+		assert!(next_action.is_none());
+		let state = runtime.swap_state_map.get(&transfer_id).expect("transfer state");
+		assert_eq!(state.state, TransferStateType::Initialized);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_persist_and_restore_transfer_states() -> Result<(), anyhow::Error> {
+		let Some(client) = indexer_db_client()? else {
+			// needs a postgresql instance in BRIDGE_INDEXER_DATABASE_URL
+			return Ok(());
+		};
+		let mut runtime_before_stop = runtime(Some(client));
+		let transfer_id = BridgeTransferId::gen_unique_hash(&mut rand::thread_rng());
+		let lock_action = runtime_before_stop.process_event(initiated_event(transfer_id))?;
+
+		// an initialized transfer is locked again after a restart
+		let mut restarted = runtime(indexer_db_client()?);
+		let actions = restarted.restore();
+		let action = actions
+			.iter()
+			.find(|action| action.transfer_id == transfer_id)
+			.expect("restored lock action");
+		assert_eq!(action.chain, ChainId::TWO);
+		assert!(matches!(action.kind, TransferActionType::LockBridgeTransfer { .. }));
+		let state = restarted.swap_state_map.get(&transfer_id).expect("restored transfer state");
+		assert_eq!(state.state, TransferStateType::Initialized);
+		assert_eq!(state.amount, Amount(10));
+
+		// a transfer which fails to lock is refunded and not restored again
+		let mut refund_action = None;
+		for _ in 0..6 {
+			refund_action = fail(&mut restarted, &lock_action);
+		}
+		assert!(matches!(
+			refund_action.map(|action| action.kind),
+			Some(TransferActionType::RefundInitiator)
+		));
+		let mut restarted_again = runtime(indexer_db_client()?);
+		let actions = restarted_again.restore();
+		assert!(actions.iter().all(|action| action.transfer_id != transfer_id));
+		assert!(!restarted_again.swap_state_map.contains_key(&transfer_id));
+
+		Ok(())
 	}
 }
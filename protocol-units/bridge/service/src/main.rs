@@ -11,7 +11,7 @@ use bridge_service::{
 			client_framework::MovementClientFramework, event_monitoring::MovementMonitoring,
 		},
 	},
-	grpc::HealthCheckService,
+	grpc::{BridgeGrpcService, HealthCheckService},
 	rest::BridgeRest,
 };
 use godfig::{backend::config_file::ConfigFile, Godfig};
@@ -51,7 +51,22 @@ async fn main() -> Result<()> {
 	let two_stream =
 		MovementMonitoring::build(&bridge_config.movement, mvt_health_rx).await.unwrap();
 
-	let one_client_for_grpc = one_client.clone();
+	// Transfer state changes pushed by the relayer loop to the gRPC subscribers.
+	let (transfer_state_tx, _) = tokio::sync::broadcast::channel(1024);
+	let bridge_grpc_service = BridgeGrpcService::new(
+		one_client.clone(),
+		two_client.clone(),
+		&bridge_config.movement.grpc_authorized_keys,
+		transfer_state_tx.clone(),
+	)?;
+	// Start the state streams from the transfer states persisted by the relayer loop.
+	let bridge_grpc_service = match Client::from_env() {
+		Ok(client) => bridge_grpc_service.with_indexer_db_client(client),
+		Err(e) => {
+			tracing::warn!("Failed to create indexer db client for the gRPC service: {e:?}");
+			bridge_grpc_service
+		}
+	};
 
 	// Initialize the gRPC health check service
 	let health_service = HealthCheckService::default();
@@ -68,7 +83,7 @@ async fn main() -> Result<()> {
 	let grpc_jh = tokio::spawn(async move {
 		Server::builder()
 			.add_service(HealthServer::new(health_service))
-			.add_service(BridgeServer::new(one_client_for_grpc))
+			.add_service(BridgeServer::new(bridge_grpc_service))
 			.serve(grpc_addr)
 			.await
 	});
@@ -103,6 +118,7 @@ async fn main() -> Result<()> {
 			indexer_db_client,
 			eth_health_tx,
 			mvt_health_tx,
+			transfer_state_tx,
 		)
 		.await
 	});
@@ -47,6 +47,8 @@ pub enum BridgeContractError {
 	LockTransferError,
 	#[error("Failed to abort bridge transfer")]
 	AbortTransferError,
+	#[error("Failed to check whether the bridge transfer is locked")]
+	LockUnknown,
 	#[error("Address not set")]
 	AddressNotSet,
 	#[error("Error getting the signer")]
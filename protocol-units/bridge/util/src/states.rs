@@ -13,6 +13,16 @@ use std::fmt;
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TransferAddress(Vec<u8>);
 
+impl TransferAddress {
+	pub fn new(bytes: Vec<u8>) -> Self {
+		TransferAddress(bytes)
+	}
+
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.0
+	}
+}
+
 impl<A: Into<Vec<u8>>> From<BridgeAddress<A>> for TransferAddress {
 	fn from(addr: BridgeAddress<A>) -> Self {
 		TransferAddress(addr.0.into())
@@ -36,6 +46,13 @@ pub enum TransferStateType {
 	Refund,
 }
 
+impl TransferStateType {
+	/// Whether the transfer ends in this state, either completed or refunded.
+	pub fn is_final(&self) -> bool {
+		matches!(self, Self::Done | Self::Refund)
+	}
+}
+
 impl fmt::Display for TransferStateType {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		let kind = match self {
@@ -50,6 +67,22 @@ impl fmt::Display for TransferStateType {
 	}
 }
 
+impl std::str::FromStr for TransferStateType {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Initialized" => Ok(Self::Initialized),
+			"Locked" => Ok(Self::Locked),
+			"SecretReceived" => Ok(Self::SecretReceived),
+			"CompletedIntiator" => Ok(Self::CompletedIntiator),
+			"Done" => Ok(Self::Done),
+			"Refund" => Ok(Self::Refund),
+			_ => Err(format!("Unknown transfer state type: {s}")),
+		}
+	}
+}
+
 #[allow(dead_code)]
 pub struct TransferState {
 	pub state: TransferStateType,
@@ -192,6 +225,18 @@ impl TransferState {
 		(self, action_type)
 	}
 
+	/// The lock of the transfer on the counterpart chain, as requested when the transfer was initiated.
+	pub fn lock_action(&self) -> TransferAction {
+		let kind = TransferActionType::LockBridgeTransfer {
+			bridge_transfer_id: self.transfer_id,
+			hash_lock: self.hash_lock,
+			initiator: BridgeAddress(self.intiator_address.as_bytes().to_vec()),
+			recipient: BridgeAddress(self.counter_part_address.as_bytes().to_vec()),
+			amount: self.amount,
+		};
+		TransferAction { chain: self.init_chain.other(), transfer_id: self.transfer_id, kind }
+	}
+
 	pub fn transition_to_refund(&self) -> (TransferStateType, TransferActionType) {
 		(TransferStateType::Refund, TransferActionType::RefundInitiator)
 	}
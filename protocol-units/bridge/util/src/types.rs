@@ -48,6 +48,18 @@ impl fmt::Display for ChainId {
 	}
 }
 
+impl std::str::FromStr for ChainId {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"ONE" => Ok(ChainId::ONE),
+			"TWO" => Ok(ChainId::TWO),
+			_ => Err(format!("Unknown chain id: {s}")),
+		}
+	}
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 pub struct BridgeTransferId(pub BridgeHash);
 
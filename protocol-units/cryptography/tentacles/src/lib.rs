@@ -92,6 +92,8 @@ mod writer;
 #[cfg(any(test, feature = "mocks"))]
 pub mod mock;
 pub mod restore;
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb;
 
 use bytes32ext::Bytes32Ext;
 pub use iterator::JellyfishMerkleIterator;
@@ -0,0 +1,349 @@
+//! A persistent tree store backed by RocksDB.
+//!
+//! The store keeps the tree in four column families:
+//! - `nodes`: [`NodeKey`] to [`Node`], both borsh encoded.
+//! - `value_history`: key hash followed by the big endian version, to the borsh encoded
+//!   `Option<OwnedValue>` written at that version. The layout lets a reverse seek find the newest
+//!   value at or below a version.
+//! - `stale_node_indices`: big endian `stale_since_version` followed by the borsh encoded
+//!   [`NodeKey`], so that indices are iterated in the order they can be pruned.
+//! - `preimages`: key hash to key preimage.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use alloc::vec::Vec;
+use anyhow::{ensure, format_err, Result};
+use borsh::BorshDeserialize;
+use rocksdb::{
+    BoundColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB,
+};
+
+use crate::{
+    node_type::{LeafNode, Node, NodeKey},
+    storage::{HasPreimage, NodeBatch, StaleNodeIndex, TreeReader, TreeUpdateBatch, TreeWriter},
+    types::Version,
+    KeyHash, OwnedValue,
+};
+
+const NODES_CF: &str = "nodes";
+const VALUE_HISTORY_CF: &str = "value_history";
+const STALE_NODE_INDICES_CF: &str = "stale_node_indices";
+const PREIMAGES_CF: &str = "preimages";
+
+/// A tree store persisted in RocksDB.
+///
+/// Every batch is applied atomically, so a reader either sees all of the nodes of a version or
+/// none of them. Pruning only removes nodes that became stale at or below the least readable
+/// version, which are unreachable from the roots readers are allowed to use.
+pub struct RocksDbTreeStore {
+    db: DB,
+    least_readable_version: AtomicU64,
+}
+
+impl RocksDbTreeStore {
+    /// Opens the store at the given path, creating it if missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let column_families = [
+            NODES_CF,
+            VALUE_HISTORY_CF,
+            STALE_NODE_INDICES_CF,
+            PREIMAGES_CF,
+        ]
+        .into_iter()
+        .map(|name| ColumnFamilyDescriptor::new(name, Options::default()));
+        let db = DB::open_cf_descriptors(&options, path, column_families)
+            .map_err(|e| format_err!("Failed to open tree store: {e}"))?;
+
+        Ok(Self {
+            db,
+            least_readable_version: AtomicU64::new(0),
+        })
+    }
+
+    fn cf(&self, name: &str) -> Result<Arc<BoundColumnFamily>> {
+        self.db
+            .cf_handle(name)
+            .ok_or_else(|| format_err!("Missing column family {name}"))
+    }
+
+    fn value_history_key(key_hash: KeyHash, version: Version) -> Vec<u8> {
+        let mut key = Vec::with_capacity(40);
+        key.extend_from_slice(&key_hash.0);
+        key.extend_from_slice(&version.to_be_bytes());
+        key
+    }
+
+    fn stale_node_index_key(index: &StaleNodeIndex) -> Result<Vec<u8>> {
+        let mut key = index.stale_since_version.to_be_bytes().to_vec();
+        key.extend(borsh::to_vec(&index.node_key)?);
+        Ok(key)
+    }
+
+    fn put_node_batch(&self, write_batch: &mut WriteBatch, node_batch: &NodeBatch) -> Result<()> {
+        let nodes_cf = self.cf(NODES_CF)?;
+        for (node_key, node) in node_batch.nodes() {
+            write_batch.put_cf(&nodes_cf, borsh::to_vec(node_key)?, borsh::to_vec(node)?);
+        }
+        let value_history_cf = self.cf(VALUE_HISTORY_CF)?;
+        for ((version, key_hash), value) in node_batch.values() {
+            write_batch.put_cf(
+                &value_history_cf,
+                Self::value_history_key(*key_hash, *version),
+                borsh::to_vec(value)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Atomically writes the nodes, values and stale node indices of an update batch.
+    pub fn write_tree_update_batch(&self, batch: TreeUpdateBatch) -> Result<()> {
+        let mut write_batch = WriteBatch::default();
+        self.put_node_batch(&mut write_batch, &batch.node_batch)?;
+        let stale_node_indices_cf = self.cf(STALE_NODE_INDICES_CF)?;
+        for index in batch.stale_node_index_batch.iter() {
+            write_batch.put_cf(
+                &stale_node_indices_cf,
+                Self::stale_node_index_key(index)?,
+                [],
+            );
+        }
+        self.db.write(write_batch)?;
+        Ok(())
+    }
+
+    /// Records the preimage of a key hash.
+    pub fn put_key_preimage(&self, key_hash: KeyHash, preimage: &[u8]) -> Result<()> {
+        self.db
+            .put_cf(&self.cf(PREIMAGES_CF)?, key_hash.0, preimage)?;
+        Ok(())
+    }
+
+    /// Atomically records the preimages of several key hashes.
+    pub fn put_key_preimages<'a>(
+        &self,
+        preimages: impl IntoIterator<Item = (KeyHash, &'a [u8])>,
+    ) -> Result<()> {
+        let preimages_cf = self.cf(PREIMAGES_CF)?;
+        let mut write_batch = WriteBatch::default();
+        for (key_hash, preimage) in preimages {
+            write_batch.put_cf(&preimages_cf, key_hash.0, preimage);
+        }
+        self.db.write(write_batch)?;
+        Ok(())
+    }
+
+    /// The least version whose nodes are guaranteed to be kept by the pruner.
+    pub fn least_readable_version(&self) -> Version {
+        self.least_readable_version.load(Ordering::Acquire)
+    }
+
+    /// Deletes at most `limit` stale nodes retired at or before `least_readable_version`,
+    /// together with their indices, in a single atomic write.
+    ///
+    /// Returns the number of nodes deleted; fewer than `limit` means that nothing is left to
+    /// prune up to that version. Unlike `MockTreeStore::purge_stale_nodes`, the work is split so
+    /// that a large backlog does not hold up writers.
+    pub fn prune_stale_nodes(
+        &self,
+        least_readable_version: Version,
+        limit: usize,
+    ) -> Result<usize> {
+        self.least_readable_version
+            .fetch_max(least_readable_version, Ordering::AcqRel);
+
+        let nodes_cf = self.cf(NODES_CF)?;
+        let stale_node_indices_cf = self.cf(STALE_NODE_INDICES_CF)?;
+        let mut write_batch = WriteBatch::default();
+        let mut pruned = 0;
+        for entry in self
+            .db
+            .iterator_cf(&stale_node_indices_cf, IteratorMode::Start)
+            .take(limit)
+        {
+            let (key, _) = entry?;
+            ensure!(key.len() > 8, "Malformed stale node index.");
+            let (version, node_key) = key.split_at(8);
+            let stale_since_version = Version::from_be_bytes(version.try_into()?);
+            // Only records retired before or at `least_readable_version` can be purged in order
+            // to keep that version still readable.
+            if stale_since_version > least_readable_version {
+                break;
+            }
+            write_batch.delete_cf(&nodes_cf, node_key);
+            write_batch.delete_cf(&stale_node_indices_cf, &key);
+            pruned += 1;
+        }
+        self.db.write(write_batch)?;
+        Ok(pruned)
+    }
+
+    /// The number of nodes currently stored.
+    pub fn num_nodes(&self) -> Result<usize> {
+        Ok(self
+            .db
+            .iterator_cf(&self.cf(NODES_CF)?, IteratorMode::Start)
+            .count())
+    }
+
+    /// The number of stale node indices waiting to be pruned.
+    pub fn num_stale_node_indices(&self) -> Result<usize> {
+        Ok(self
+            .db
+            .iterator_cf(&self.cf(STALE_NODE_INDICES_CF)?, IteratorMode::Start)
+            .count())
+    }
+}
+
+impl TreeReader for RocksDbTreeStore {
+    fn get_node_option(&self, node_key: &NodeKey) -> Result<Option<Node>> {
+        match self
+            .db
+            .get_cf(&self.cf(NODES_CF)?, borsh::to_vec(node_key)?)?
+        {
+            Some(bytes) => Ok(Some(Node::try_from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Scans every node, so it should only be used while restoring a tree.
+    fn get_rightmost_leaf(&self) -> Result<Option<(NodeKey, LeafNode)>> {
+        let mut node_key_and_node: Option<(NodeKey, LeafNode)> = None;
+        for entry in self
+            .db
+            .iterator_cf(&self.cf(NODES_CF)?, IteratorMode::Start)
+        {
+            let (key, value) = entry?;
+            if let Node::Leaf(leaf_node) = Node::try_from_slice(&value)? {
+                if node_key_and_node.as_ref().map_or(true, |(_, rightmost)| {
+                    leaf_node.key_hash() > rightmost.key_hash()
+                }) {
+                    node_key_and_node.replace((NodeKey::try_from_slice(&key)?, leaf_node));
+                }
+            }
+        }
+        Ok(node_key_and_node)
+    }
+
+    fn get_value_option(
+        &self,
+        max_version: Version,
+        key_hash: KeyHash,
+    ) -> Result<Option<OwnedValue>> {
+        let from = Self::value_history_key(key_hash, max_version);
+        let mut iter = self.db.iterator_cf(
+            &self.cf(VALUE_HISTORY_CF)?,
+            IteratorMode::From(&from, Direction::Reverse),
+        );
+        match iter.next() {
+            Some(entry) => {
+                let (key, value) = entry?;
+                if key[..32] != key_hash.0 {
+                    return Ok(None);
+                }
+                Ok(Option::<OwnedValue>::try_from_slice(&value)?)
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl TreeWriter for RocksDbTreeStore {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> Result<()> {
+        let mut write_batch = WriteBatch::default();
+        self.put_node_batch(&mut write_batch, node_batch)?;
+        self.db.write(write_batch)?;
+        Ok(())
+    }
+}
+
+impl HasPreimage for RocksDbTreeStore {
+    fn preimage(&self, key_hash: KeyHash) -> Result<Option<Vec<u8>>> {
+        Ok(self.db.get_cf(&self.cf(PREIMAGES_CF)?, key_hash.0)?)
+    }
+}
+
+/// Prunes stale nodes of a [`RocksDbTreeStore`] on a background thread.
+///
+/// The pruner works towards the latest least readable version it was given, deleting at most
+/// `batch_size` nodes per write. The thread stops once the pruner is dropped.
+pub struct StaleNodePruner {
+    sender: Option<Sender<Version>>,
+    handle: Option<JoinHandle<Result<()>>>,
+}
+
+impl StaleNodePruner {
+    /// Spawns the pruning thread.
+    pub fn spawn(store: Arc<RocksDbTreeStore>, batch_size: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let handle = std::thread::Builder::new()
+            .name("tentacles-pruner".into())
+            .spawn(move || Self::run(store, receiver, batch_size.max(1)))
+            .expect("Failed to spawn the stale node pruner");
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        }
+    }
+
+    /// Lets the pruner delete nodes that went stale at or before `least_readable_version`.
+    pub fn set_least_readable_version(&self, least_readable_version: Version) -> Result<()> {
+        self.sender
+            .as_ref()
+            .ok_or_else(|| format_err!("Stale node pruner stopped"))?
+            .send(least_readable_version)
+            .map_err(|_| format_err!("Stale node pruner stopped"))
+    }
+
+    fn run(
+        store: Arc<RocksDbTreeStore>,
+        receiver: Receiver<Version>,
+        batch_size: usize,
+    ) -> Result<()> {
+        // wait for a target, then prune towards the newest one received
+        while let Ok(mut target) = receiver.recv() {
+            loop {
+                // a disconnected channel still lets the current target finish
+                while let Ok(version) = receiver.try_recv() {
+                    target = target.max(version);
+                }
+                let pruned = store.prune_stale_nodes(target, batch_size)?;
+                tracing::debug!(pruned, target, "pruned stale nodes");
+                if pruned < batch_size {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the pruner and waits for the current target to be reached.
+    pub fn stop(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        self.sender.take();
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .map_err(|_| format_err!("Stale node pruner panicked"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for StaleNodePruner {
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown() {
+            tracing::warn!("stale node pruner stopped with an error: {e}");
+        }
+    }
+}
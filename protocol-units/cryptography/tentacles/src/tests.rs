@@ -6,6 +6,8 @@ mod jellyfish_merkle;
 mod nibble_path;
 mod node_type;
 mod restore;
+#[cfg(feature = "rocksdb")]
+mod rocksdb;
 mod tree_cache;
 mod update_proof;
 mod vectors;
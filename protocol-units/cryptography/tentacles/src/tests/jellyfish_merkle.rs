@@ -5,6 +5,8 @@ use alloc::string::ToString;
 use alloc::vec::Vec;
 use alloc::{format, vec};
 
+#[cfg(feature = "rayon")]
+use proptest::prelude::*;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::SimpleHasher;
@@ -115,6 +117,14 @@ macro_rules! impl_jellyfish_tests_for_hasher {
                 fn proptest_get_leaf_count(keys in btree_set(any::<KeyHash>(), 1..1000).prop_map(|m| m.into_iter().collect())) {
                     super::test_get_leaf_count::<$hasher>(keys)
                 }
+
+                #[cfg(feature = "rayon")]
+                #[test]
+                fn proptest_parallel_batch_matches_sequential(
+                    (existing, value_sets) in (super::arb_value_sets(3, 50), super::arb_value_sets(5, 500))
+                ) {
+                    super::test_parallel_batch_matches_sequential::<$hasher>(existing, value_sets)
+                }
             }
 
         }
@@ -655,6 +665,60 @@ fn test_put_value_sets<H: SimpleHasher>() {
     }
 }
 
+/// Value sets over a small key space, so that later versions update earlier keys.
+#[cfg(feature = "rayon")]
+fn arb_value_sets(
+    max_versions: usize,
+    max_keys: usize,
+) -> impl Strategy<Value = Vec<Vec<(u16, Vec<u8>)>>> {
+    proptest::collection::vec(
+        proptest::collection::vec(
+            (
+                0..max_keys as u16,
+                proptest::collection::vec(any::<u8>(), 1..8),
+            ),
+            1..max_keys,
+        ),
+        0..max_versions,
+    )
+}
+
+#[cfg(feature = "rayon")]
+fn test_parallel_batch_matches_sequential<H: SimpleHasher + Send + Sync>(
+    existing: Vec<Vec<(u16, Vec<u8>)>>,
+    value_sets: Vec<Vec<(u16, Vec<u8>)>>,
+) {
+    let to_kvs = |sets: Vec<Vec<(u16, Vec<u8>)>>| {
+        sets.into_iter()
+            .map(|set| {
+                set.into_iter()
+                    .map(|(key, value)| (KeyHash::with::<H>(key.to_be_bytes()), value))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let db = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::<_, H>::new(&db);
+    let first_version = existing.len() as Version;
+    if !existing.is_empty() {
+        let (_roots, batch) = tree
+            .batch_put_value_sets(to_kvs(existing), None, 0 /* version */)
+            .unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+    }
+
+    let value_sets = to_kvs(value_sets);
+    let (root_hashes, batch) = tree
+        .batch_put_value_sets(value_sets.clone(), None, first_version)
+        .unwrap();
+    let (par_root_hashes, par_batch) = tree
+        .par_batch_put_value_sets(value_sets, None, first_version)
+        .unwrap();
+    assert_eq!(par_root_hashes, root_hashes);
+    assert_eq!(par_batch, batch);
+}
+
 fn many_keys_get_proof_and_verify_tree_root<H: SimpleHasher>(seed: &[u8], num_keys: usize) {
     assert!(seed.len() < 32);
     let mut actual_seed = [0u8; 32];
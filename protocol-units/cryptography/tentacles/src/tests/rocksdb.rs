@@ -0,0 +1,203 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sha2::Sha256;
+
+use crate::{
+    mock::MockTreeStore,
+    rocksdb::{RocksDbTreeStore, StaleNodePruner},
+    storage::{HasPreimage, TreeReader},
+    JellyfishMerkleTree, KeyHash, OwnedValue, Version,
+};
+
+fn random_batches(rng: &mut StdRng, count: usize) -> Vec<Vec<(KeyHash, Option<OwnedValue>)>> {
+    let keys: Vec<KeyHash> = (0..32).map(|_| KeyHash(rng.gen())).collect();
+    (0..count)
+        .map(|_| {
+            (0..8)
+                .map(|_| {
+                    let key = keys[rng.gen_range(0..keys.len())];
+                    let value = if rng.gen_bool(0.2) {
+                        None
+                    } else {
+                        Some(rng.gen::<[u8; 8]>().to_vec())
+                    };
+                    (key, value)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_rocksdb_matches_mock() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = RocksDbTreeStore::open(dir.path()).unwrap();
+    let mock = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::<_, Sha256>::new(&db);
+    let mock_tree = JellyfishMerkleTree::<_, Sha256>::new(&mock);
+
+    let mut rng = StdRng::from_seed([7; 32]);
+    let batches = random_batches(&mut rng, 10);
+    for (version, batch) in batches.iter().enumerate() {
+        let version = version as Version;
+        let (root, update) = tree.put_value_set(batch.clone(), version).unwrap();
+        let (mock_root, mock_update) = mock_tree.put_value_set(batch.clone(), version).unwrap();
+        assert_eq!(root, mock_root);
+        db.write_tree_update_batch(update).unwrap();
+        mock.write_tree_update_batch(mock_update).unwrap();
+    }
+
+    for version in 0..batches.len() as Version {
+        for (key, _) in batches.iter().flatten() {
+            assert_eq!(
+                tree.get(*key, version).unwrap(),
+                mock_tree.get(*key, version).unwrap()
+            );
+            assert_eq!(
+                db.get_value_option(version, *key).unwrap(),
+                mock.get_value_option(version, *key).unwrap()
+            );
+        }
+    }
+    assert_eq!(db.num_nodes().unwrap(), mock.num_nodes());
+}
+
+#[test]
+fn test_rocksdb_value_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = RocksDbTreeStore::open(dir.path()).unwrap();
+    let tree = JellyfishMerkleTree::<_, Sha256>::new(&db);
+
+    let key = KeyHash([1; 32]);
+    let neighbour = KeyHash([2; 32]);
+    let (_, batch) = tree
+        .put_value_set(vec![(neighbour, Some(b"neighbour".to_vec()))], 0)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+    let (_, batch) = tree
+        .put_value_set(vec![(key, Some(b"first".to_vec()))], 1)
+        .unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+    let (_, batch) = tree.put_value_set(vec![(key, None)], 3).unwrap();
+    db.write_tree_update_batch(batch).unwrap();
+
+    assert_eq!(db.get_value_option(0, key).unwrap(), None);
+    assert_eq!(
+        db.get_value_option(2, key).unwrap(),
+        Some(b"first".to_vec())
+    );
+    assert_eq!(db.get_value_option(3, key).unwrap(), None);
+    assert_eq!(
+        db.get_value_option(3, neighbour).unwrap(),
+        Some(b"neighbour".to_vec())
+    );
+    assert_eq!(db.get_value_option(3, KeyHash([0; 32])).unwrap(), None);
+}
+
+#[test]
+fn test_rocksdb_preimages() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = RocksDbTreeStore::open(dir.path()).unwrap();
+
+    db.put_key_preimage(KeyHash([1; 32]), b"one").unwrap();
+    db.put_key_preimages([(KeyHash([2; 32]), &b"two"[..])])
+        .unwrap();
+    assert_eq!(
+        db.preimage(KeyHash([1; 32])).unwrap(),
+        Some(b"one".to_vec())
+    );
+    assert_eq!(
+        db.preimage(KeyHash([2; 32])).unwrap(),
+        Some(b"two".to_vec())
+    );
+    assert_eq!(db.preimage(KeyHash([3; 32])).unwrap(), None);
+}
+
+#[test]
+fn test_rocksdb_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = KeyHash([5; 32]);
+    let root = {
+        let db = RocksDbTreeStore::open(dir.path()).unwrap();
+        let tree = JellyfishMerkleTree::<_, Sha256>::new(&db);
+        let (root, batch) = tree
+            .put_value_set(vec![(key, Some(b"value".to_vec()))], 0)
+            .unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+        root
+    };
+
+    let db = RocksDbTreeStore::open(dir.path()).unwrap();
+    let tree = JellyfishMerkleTree::<_, Sha256>::new(&db);
+    assert_eq!(tree.get_root_hash(0).unwrap(), root);
+    assert_eq!(tree.get(key, 0).unwrap(), Some(b"value".to_vec()));
+}
+
+#[test]
+fn test_rocksdb_prune_matches_mock() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = RocksDbTreeStore::open(dir.path()).unwrap();
+    let mock = MockTreeStore::default();
+    let tree = JellyfishMerkleTree::<_, Sha256>::new(&db);
+    let mock_tree = JellyfishMerkleTree::<_, Sha256>::new(&mock);
+
+    let mut rng = StdRng::from_seed([11; 32]);
+    let batches = random_batches(&mut rng, 8);
+    for (version, batch) in batches.iter().enumerate() {
+        let (_, update) = tree
+            .put_value_set(batch.clone(), version as Version)
+            .unwrap();
+        let (_, mock_update) = mock_tree
+            .put_value_set(batch.clone(), version as Version)
+            .unwrap();
+        db.write_tree_update_batch(update).unwrap();
+        mock.write_tree_update_batch(mock_update).unwrap();
+    }
+
+    for least_readable_version in 1..batches.len() as Version {
+        // a small batch size makes the pruning span several writes
+        while db.prune_stale_nodes(least_readable_version, 2).unwrap() == 2 {}
+        mock.purge_stale_nodes(least_readable_version).unwrap();
+        assert_eq!(db.num_nodes().unwrap(), mock.num_nodes());
+
+        let latest = batches.len() as Version - 1;
+        for version in least_readable_version..=latest {
+            assert_eq!(
+                tree.get_root_hash(version).unwrap(),
+                mock_tree.get_root_hash(version).unwrap()
+            );
+        }
+    }
+    assert_eq!(db.least_readable_version(), batches.len() as Version - 1);
+}
+
+#[test]
+fn test_stale_node_pruner() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(RocksDbTreeStore::open(dir.path()).unwrap());
+    let tree = JellyfishMerkleTree::<_, Sha256>::new(db.as_ref());
+
+    let key = KeyHash([9; 32]);
+    for version in 0..16 {
+        let (_, batch) = tree
+            .put_value_set(vec![(key, Some(version.to_be_bytes().to_vec()))], version)
+            .unwrap();
+        db.write_tree_update_batch(batch).unwrap();
+    }
+    assert_eq!(db.num_stale_node_indices().unwrap(), 15);
+
+    let pruner = StaleNodePruner::spawn(db.clone(), 4);
+    pruner.set_least_readable_version(10).unwrap();
+    pruner.stop().unwrap();
+
+    assert_eq!(db.num_stale_node_indices().unwrap(), 5);
+    assert_eq!(db.least_readable_version(), 10);
+    for version in 10..16 {
+        assert_eq!(
+            tree.get(key, version).unwrap(),
+            Some(version.to_be_bytes().to_vec())
+        );
+    }
+    assert!(tree.get_root_hash(9).is_err());
+}
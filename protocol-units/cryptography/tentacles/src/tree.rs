@@ -14,7 +14,7 @@ use crate::proof::{SparseMerkleLeafNode, SparseMerkleNode};
 use crate::{
     node_type::{Child, Children, InternalNode, LeafNode, Node, NodeKey, NodeType},
     storage::{TreeReader, TreeUpdateBatch},
-    tree_cache::TreeCache,
+    tree_cache::{NodeCache, TreeCache},
     types::{
         nibble::{
             nibble_path::{skip_common_prefix, NibbleIterator, NibblePath},
@@ -40,6 +40,8 @@ pub struct JellyfishMerkleTree<'a, R, H: SimpleHasher> {
 
 #[cfg(feature = "ics23")]
 pub mod ics23_impl;
+#[cfg(feature = "rayon")]
+mod parallel;
 
 impl<'a, R, H> JellyfishMerkleTree<'a, R, H>
 where
@@ -119,14 +121,14 @@ where
         Ok(tree_cache.into())
     }
 
-    fn batch_insert_at(
+    fn batch_insert_at<C: NodeCache>(
         &self,
         mut node_key: NodeKey,
         version: Version,
         kvs: &[(KeyHash, ValueHash)],
         depth: usize,
         hash_cache: &Option<&HashMap<NibblePath, [u8; 32]>>,
-        tree_cache: &mut TreeCache<R>,
+        tree_cache: &mut C,
     ) -> Result<(NodeKey, Node)> {
         assert!(!kvs.is_empty());
 
@@ -225,7 +227,7 @@ where
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn batch_create_subtree_with_existing_leaf(
+    fn batch_create_subtree_with_existing_leaf<C: NodeCache>(
         &self,
         node_key: NodeKey,
         version: Version,
@@ -233,7 +235,7 @@ where
         kvs: &[(KeyHash, ValueHash)],
         depth: usize,
         hash_cache: &Option<&HashMap<NibblePath, [u8; 32]>>,
-        tree_cache: &mut TreeCache<R>,
+        tree_cache: &mut C,
     ) -> Result<(NodeKey, Node)> {
         let existing_leaf_key = existing_leaf_node.key_hash();
 
@@ -296,14 +298,14 @@ where
         }
     }
 
-    fn batch_create_subtree(
+    fn batch_create_subtree<C: NodeCache>(
         &self,
         node_key: NodeKey,
         version: Version,
         kvs: &[(KeyHash, ValueHash)],
         depth: usize,
         hash_cache: &Option<&HashMap<NibblePath, [u8; 32]>>,
-        tree_cache: &mut TreeCache<R>,
+        tree_cache: &mut C,
     ) -> Result<(NodeKey, Node)> {
         if kvs.len() == 1 {
             let new_leaf_node = Node::Leaf(LeafNode::new(kvs[0].0, kvs[0].1));
@@ -0,0 +1,203 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use anyhow::{bail, Result};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    node_type::{Child, Children, InternalNode, Node, NodeKey},
+    storage::{TreeReader, TreeUpdateBatch},
+    tree_cache::{SubtreeCache, TreeCache},
+    types::{
+        nibble::{nibble_path::NibblePath, Nibble, NibbleRangeIterator},
+        Version,
+    },
+    Bytes32Ext, JellyfishMerkleTree, KeyHash, OwnedValue, RootHash, SimpleHasher, ValueHash,
+};
+
+impl<'a, R, H> JellyfishMerkleTree<'a, R, H>
+where
+    R: 'a + TreeReader + Sync,
+    H: SimpleHasher + Send + Sync,
+{
+    /// The parallel version of `batch_put_value_sets`.
+    ///
+    /// The updates of each version are partitioned by the top nibble of their key hashes, and the
+    /// up to 16 subtrees below the root are built concurrently. The root hashes and the
+    /// [`TreeUpdateBatch`] are identical to the ones produced by `batch_put_value_sets`.
+    pub fn par_batch_put_value_sets(
+        &self,
+        value_sets: Vec<Vec<(KeyHash, OwnedValue)>>,
+        node_hashes: Option<Vec<&HashMap<NibblePath, [u8; 32]>>>,
+        first_version: Version,
+    ) -> Result<(Vec<RootHash>, TreeUpdateBatch)> {
+        let mut tree_cache = TreeCache::new(self.reader, first_version)?;
+        let hash_sets: Vec<_> = match node_hashes {
+            Some(hashes) => hashes.into_iter().map(Some).collect(),
+            None => (0..value_sets.len()).map(|_| None).collect(),
+        };
+
+        for (idx, (value_set, hash_set)) in
+            itertools::zip_eq(value_sets.into_iter(), hash_sets.into_iter()).enumerate()
+        {
+            assert!(
+                !value_set.is_empty(),
+                "Transactions that output empty write set should not be included.",
+            );
+            let version = first_version + idx as u64;
+            let deduped_and_sorted_kvs = value_set
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .map(|(key, value)| {
+                    let value_hash = ValueHash::with::<H>(value.as_slice());
+                    tree_cache.put_value(version, key, Some(value));
+                    (key, value_hash)
+                })
+                .collect::<Vec<_>>();
+            let root_node_key = tree_cache.get_root_node_key().clone();
+            let new_root_node_key = self.par_batch_insert_at_root(
+                root_node_key,
+                version,
+                deduped_and_sorted_kvs.as_slice(),
+                &hash_set,
+                &mut tree_cache,
+            )?;
+            tree_cache.set_root_node_key(new_root_node_key);
+
+            // Freezes the current cache to make all contents in the current cache immutable.
+            tree_cache.freeze::<H>()?;
+        }
+
+        Ok(tree_cache.into())
+    }
+
+    /// Mirrors `batch_insert_at` at depth 0, building the children of the root in parallel.
+    fn par_batch_insert_at_root(
+        &self,
+        mut node_key: NodeKey,
+        version: Version,
+        kvs: &[(KeyHash, ValueHash)],
+        hash_cache: &Option<&HashMap<NibblePath, [u8; 32]>>,
+        tree_cache: &mut TreeCache<R>,
+    ) -> Result<NodeKey> {
+        assert!(!kvs.is_empty());
+
+        let node = tree_cache.get_node(&node_key)?;
+        let children = match node {
+            Node::Internal(internal_node) => {
+                // We always delete the existing internal node here because it will not be referenced anyway
+                // since this version.
+                tree_cache.delete_node(&node_key, false /* is_leaf */);
+
+                let children: Children = internal_node.clone().into();
+                self.par_batch_update_children(
+                    &node_key,
+                    children,
+                    |child_index| internal_node.child(child_index).map(|child| child.version),
+                    version,
+                    kvs,
+                    hash_cache,
+                    tree_cache,
+                )?
+            }
+            Node::Leaf(_) => {
+                // A single leaf has nothing to split, so the sequential path is as fast.
+                return Ok(self
+                    .batch_insert_at(node_key, version, kvs, 0, hash_cache, tree_cache)?
+                    .0);
+            }
+            Node::Null => {
+                if !node_key.nibble_path().is_empty() {
+                    bail!(
+                        "Null node exists for non-root node with node_key {:?}",
+                        node_key
+                    );
+                }
+
+                if node_key.version() == version {
+                    tree_cache.delete_node(&node_key, false /* is_leaf */);
+                }
+                if kvs.len() == 1 {
+                    return Ok(self
+                        .batch_create_subtree(
+                            NodeKey::new_empty_path(version),
+                            version,
+                            kvs,
+                            0,
+                            hash_cache,
+                            tree_cache,
+                        )?
+                        .0);
+                }
+                self.par_batch_update_children(
+                    &NodeKey::new_empty_path(version),
+                    Children::new(),
+                    |_| None,
+                    version,
+                    kvs,
+                    hash_cache,
+                    tree_cache,
+                )?
+            }
+        };
+
+        node_key.set_version(version);
+        tree_cache.put_node(node_key.clone(), InternalNode::new(children).into())?;
+        Ok(node_key)
+    }
+
+    /// Updates the children of the root touched by `kvs`, one subtree per top nibble, and applies
+    /// the changes to `tree_cache` in nibble order once all subtrees are built.
+    #[allow(clippy::too_many_arguments)]
+    fn par_batch_update_children(
+        &self,
+        node_key: &NodeKey,
+        mut children: Children,
+        existing_child_version: impl Fn(Nibble) -> Option<Version> + Sync,
+        version: Version,
+        kvs: &[(KeyHash, ValueHash)],
+        hash_cache: &Option<&HashMap<NibblePath, [u8; 32]>>,
+        tree_cache: &mut TreeCache<R>,
+    ) -> Result<Children> {
+        let ranges: Vec<_> = NibbleRangeIterator::new(kvs, 0).collect();
+        let parent: &TreeCache<R> = tree_cache;
+        let updates = ranges
+            .into_par_iter()
+            .map(|(left, right)| {
+                let child_index = kvs[left].0 .0.get_nibble(0);
+                let mut subtree_cache = SubtreeCache::new(parent);
+                let (new_child_node_key, new_child_node) = match existing_child_version(child_index)
+                {
+                    Some(child_version) => self.batch_insert_at(
+                        node_key.gen_child_node_key(child_version, child_index),
+                        version,
+                        &kvs[left..=right],
+                        1,
+                        hash_cache,
+                        &mut subtree_cache,
+                    )?,
+                    None => self.batch_create_subtree(
+                        node_key.gen_child_node_key(version, child_index),
+                        version,
+                        &kvs[left..=right],
+                        1,
+                        hash_cache,
+                        &mut subtree_cache,
+                    )?,
+                };
+                let child = Child::new(
+                    Self::get_hash(&new_child_node_key, &new_child_node, hash_cache),
+                    version,
+                    new_child_node.node_type(),
+                );
+                Ok((child_index, child, subtree_cache.finish()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (child_index, child, update) in updates {
+            update.apply(tree_cache)?;
+            children.insert(child_index, child);
+        }
+        Ok(children)
+    }
+}
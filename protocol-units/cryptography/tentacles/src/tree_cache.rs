@@ -325,6 +325,112 @@ where
     }
 }
 
+/// The node operations used while updating a tree, implemented by [`TreeCache`] and by the
+/// per-subtree caches of the parallel update path.
+pub(crate) trait NodeCache {
+    /// Gets a node with given node key.
+    fn get_node(&self, node_key: &NodeKey) -> Result<Node>;
+
+    /// Puts a node created by the current version.
+    fn put_node(&mut self, node_key: NodeKey, new_node: Node) -> Result<()>;
+
+    /// Marks a node as deleted by the current version.
+    fn delete_node(&mut self, old_node_key: &NodeKey, is_leaf: bool);
+}
+
+impl<'a, R> NodeCache for TreeCache<'a, R>
+where
+    R: 'a + TreeReader,
+{
+    fn get_node(&self, node_key: &NodeKey) -> Result<Node> {
+        self.get_node(node_key)
+    }
+
+    fn put_node(&mut self, node_key: NodeKey, new_node: Node) -> Result<()> {
+        self.put_node(node_key, new_node)
+    }
+
+    fn delete_node(&mut self, old_node_key: &NodeKey, is_leaf: bool) {
+        self.delete_node(old_node_key, is_leaf)
+    }
+}
+
+/// Records the changes to a single subtree on top of a shared, read-only parent cache, so that
+/// disjoint subtrees of the same version can be updated concurrently.
+#[cfg(feature = "rayon")]
+pub(crate) struct SubtreeCache<'c, P> {
+    parent: &'c P,
+    node_cache: HashMap<NodeKey, Node>,
+    stale_nodes: Vec<(NodeKey, bool)>,
+}
+
+/// The changes recorded by a [`SubtreeCache`], to be applied to the parent cache.
+#[cfg(feature = "rayon")]
+pub(crate) struct SubtreeUpdate {
+    node_cache: HashMap<NodeKey, Node>,
+    stale_nodes: Vec<(NodeKey, bool)>,
+}
+
+#[cfg(feature = "rayon")]
+impl<'c, P: NodeCache> SubtreeCache<'c, P> {
+    pub fn new(parent: &'c P) -> Self {
+        Self {
+            parent,
+            node_cache: HashMap::new(),
+            stale_nodes: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> SubtreeUpdate {
+        SubtreeUpdate {
+            node_cache: self.node_cache,
+            stale_nodes: self.stale_nodes,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<'c, P: NodeCache> NodeCache for SubtreeCache<'c, P> {
+    fn get_node(&self, node_key: &NodeKey) -> Result<Node> {
+        match self.node_cache.get(node_key) {
+            Some(node) => Ok(node.clone()),
+            None => self.parent.get_node(node_key),
+        }
+    }
+
+    fn put_node(&mut self, node_key: NodeKey, new_node: Node) -> Result<()> {
+        match self.node_cache.entry(node_key) {
+            Entry::Vacant(o) => {
+                o.insert(new_node);
+            }
+            Entry::Occupied(o) => bail!("Node with key {:?} already exists in NodeBatch", o.key()),
+        };
+        Ok(())
+    }
+
+    fn delete_node(&mut self, old_node_key: &NodeKey, is_leaf: bool) {
+        // Nodes created by this subtree cancel out; anything else is deleted from the parent
+        // once the update is applied.
+        if self.node_cache.remove(old_node_key).is_none() {
+            self.stale_nodes.push((old_node_key.clone(), is_leaf));
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl SubtreeUpdate {
+    /// Applies the update to the parent cache, as if it had been made there directly.
+    pub fn apply<P: NodeCache>(self, parent: &mut P) -> Result<()> {
+        for (node_key, is_leaf) in self.stale_nodes {
+            parent.delete_node(&node_key, is_leaf);
+        }
+        for (node_key, node) in self.node_cache {
+            parent.put_node(node_key, node)?;
+        }
+        Ok(())
+    }
+}
+
 impl<'a, R> TreeReader for TreeCache<'a, R>
 where
     R: 'a + TreeReader,
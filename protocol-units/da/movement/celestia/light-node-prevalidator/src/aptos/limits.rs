@@ -0,0 +1,153 @@
+use crate::{Error, Prevalidated, PrevalidatorOperations};
+use aptos_types::transaction::SignedTransaction as AptosTransaction;
+use movement_celestia_da_util::config::local::da_light_node::AptosTransactionLimits;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Rejects transactions which the chain could never execute, such as transactions for another chain,
+/// expired transactions and transactions which cannot pay for their gas.
+pub struct Validator {
+	limits: AptosTransactionLimits,
+}
+
+impl Validator {
+	pub fn new(limits: AptosTransactionLimits) -> Self {
+		Self { limits }
+	}
+
+	/// Checks the transaction against the limits at the given time in seconds since the epoch.
+	pub fn check(&self, transaction: &AptosTransaction, now_secs: u64) -> Result<(), Error> {
+		if transaction.chain_id().id() != self.limits.chain_id {
+			return Err(Error::Validation(format!(
+				"Transaction is for chain {}, not chain {}",
+				transaction.chain_id().id(),
+				self.limits.chain_id
+			)));
+		}
+
+		let expiration = transaction.expiration_timestamp_secs();
+		if expiration <= now_secs {
+			return Err(Error::Validation(format!("Transaction expired at {}", expiration)));
+		}
+		if expiration > now_secs.saturating_add(self.limits.max_expiration_secs) {
+			return Err(Error::Validation(format!(
+				"Transaction expires at {}, more than {} seconds in the future",
+				expiration, self.limits.max_expiration_secs
+			)));
+		}
+
+		if transaction.max_gas_amount() > self.limits.max_gas_amount {
+			return Err(Error::Validation(format!(
+				"Max gas amount {} is above the limit of {}",
+				transaction.max_gas_amount(),
+				self.limits.max_gas_amount
+			)));
+		}
+		let gas_unit_price = transaction.gas_unit_price();
+		if gas_unit_price < self.limits.min_gas_unit_price
+			|| gas_unit_price > self.limits.max_gas_unit_price
+		{
+			return Err(Error::Validation(format!(
+				"Gas unit price {} is outside of {}..={}",
+				gas_unit_price, self.limits.min_gas_unit_price, self.limits.max_gas_unit_price
+			)));
+		}
+
+		let size = bcs::serialized_size(transaction).map_err(|e| {
+			Error::Internal(format!("Failed to get the size of AptosTransaction: {}", e))
+		})? as u64;
+		if size > self.limits.max_transaction_size_bytes {
+			return Err(Error::Validation(format!(
+				"Transaction of {} bytes is above the limit of {} bytes",
+				size, self.limits.max_transaction_size_bytes
+			)));
+		}
+
+		Ok(())
+	}
+}
+
+#[tonic::async_trait]
+impl PrevalidatorOperations<AptosTransaction, AptosTransaction> for Validator {
+	/// Verifies that an AptosTransaction is within the limits
+	async fn prevalidate(
+		&self,
+		transaction: AptosTransaction,
+	) -> Result<Prevalidated<AptosTransaction>, Error> {
+		let now_secs = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_err(|e| Error::Internal(e.to_string()))?
+			.as_secs();
+		self.check(&transaction, now_secs)?;
+		Ok(Prevalidated::new(transaction))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use aptos_crypto::{
+		ed25519::{Ed25519PrivateKey, Ed25519Signature},
+		PrivateKey, Uniform,
+	};
+	use aptos_types::{
+		account_address::AccountAddress,
+		chain_id::ChainId,
+		transaction::{RawTransaction, Script, TransactionPayload},
+	};
+
+	fn limits() -> AptosTransactionLimits {
+		AptosTransactionLimits {
+			chain_id: 27,
+			max_expiration_secs: 600,
+			max_gas_amount: 5_000,
+			min_gas_unit_price: 100,
+			max_gas_unit_price: 1_000,
+			max_transaction_size_bytes: 1024,
+		}
+	}
+
+	fn transaction(
+		chain_id: u8,
+		expiration_timestamp_secs: u64,
+		max_gas_amount: u64,
+		gas_unit_price: u64,
+		script_size: usize,
+	) -> AptosTransaction {
+		let private_key = Ed25519PrivateKey::generate_for_testing();
+		let raw_transaction = RawTransaction::new(
+			AccountAddress::random(),
+			0,
+			TransactionPayload::Script(Script::new(vec![0; script_size], vec![], vec![])),
+			max_gas_amount,
+			gas_unit_price,
+			expiration_timestamp_secs,
+			ChainId::new(chain_id),
+		);
+		AptosTransaction::new(
+			raw_transaction,
+			private_key.public_key(),
+			Ed25519Signature::dummy_signature(),
+		)
+	}
+
+	#[test]
+	fn test_limits() {
+		let validator = Validator::new(limits());
+		let now = 1_000_000;
+
+		assert!(validator.check(&transaction(27, now + 60, 5_000, 100, 1), now).is_ok());
+
+		// another chain
+		assert!(validator.check(&transaction(1, now + 60, 5_000, 100, 1), now).is_err());
+		// expired, or expiring too far in the future
+		assert!(validator.check(&transaction(27, now, 5_000, 100, 1), now).is_err());
+		assert!(validator.check(&transaction(27, now + 601, 5_000, 100, 1), now).is_err());
+		// too much gas, or gas which is too cheap or too expensive
+		assert!(validator.check(&transaction(27, now + 60, 5_001, 100, 1), now).is_err());
+		assert!(validator.check(&transaction(27, now + 60, 5_000, 99, 1), now).is_err());
+		assert!(validator.check(&transaction(27, now + 60, 5_000, 1_001, 1), now).is_err());
+		// too large
+		assert!(validator.check(&transaction(27, now + 60, 5_000, 100, 1024), now).is_err());
+	}
+}
@@ -1,2 +1,5 @@
+pub mod limits;
+pub mod sequence_number;
+pub mod stack;
 pub mod transaction;
 pub mod whitelist;
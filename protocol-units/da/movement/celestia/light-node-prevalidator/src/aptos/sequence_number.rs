@@ -0,0 +1,118 @@
+use crate::{Error, Prevalidated, PrevalidatorOperations};
+use aptos_types::{
+	account_address::AccountAddress, transaction::SignedTransaction as AptosTransaction,
+};
+use movement_celestia_da_util::config::local::da_light_node::AptosSequenceNumbers;
+use serde::Deserialize;
+use std::time::Duration;
+use tracing::warn;
+
+/// The account resource as returned by the REST api of a full node.
+#[derive(Debug, Deserialize)]
+struct AccountData {
+	sequence_number: String,
+}
+
+/// Rejects transactions whose sequence numbers were already used, or are too far ahead of the account.
+///
+/// Sequence numbers are looked up on a full node. If it cannot be reached, transactions are let through,
+/// as the full node still rejects them on execution.
+pub struct Validator {
+	client: reqwest::Client,
+	rest_url: String,
+	max_sequence_number_gap: u64,
+}
+
+impl Validator {
+	pub fn try_new(config: &AptosSequenceNumbers) -> Result<Self, Error> {
+		let client = reqwest::Client::builder()
+			.timeout(Duration::from_millis(config.timeout_ms))
+			.build()
+			.map_err(|e| Error::Internal(format!("Failed to build REST client: {}", e)))?;
+		Ok(Self {
+			client,
+			rest_url: config.rest_url.trim_end_matches('/').to_string(),
+			max_sequence_number_gap: config.max_sequence_number_gap,
+		})
+	}
+
+	/// Gets the next sequence number of the account, which is 0 for accounts that do not exist yet.
+	pub async fn account_sequence_number(
+		&self,
+		address: AccountAddress,
+	) -> Result<u64, anyhow::Error> {
+		let response = self
+			.client
+			.get(format!("{}/v1/accounts/{}", self.rest_url, address.to_hex_literal()))
+			.send()
+			.await?;
+		if response.status() == reqwest::StatusCode::NOT_FOUND {
+			return Ok(0);
+		}
+		let account: AccountData = response.error_for_status()?.json().await?;
+		Ok(account.sequence_number.parse()?)
+	}
+
+	/// Checks the sequence number of a transaction against the next sequence number of its account.
+	pub fn check(&self, sequence_number: u64, account_sequence_number: u64) -> Result<(), Error> {
+		if sequence_number < account_sequence_number {
+			return Err(Error::Validation(format!(
+				"Sequence number {} is stale, the account is at {}",
+				sequence_number, account_sequence_number
+			)));
+		}
+		if sequence_number - account_sequence_number > self.max_sequence_number_gap {
+			return Err(Error::Validation(format!(
+				"Sequence number {} is more than {} ahead of the account at {}",
+				sequence_number, self.max_sequence_number_gap, account_sequence_number
+			)));
+		}
+		Ok(())
+	}
+}
+
+#[tonic::async_trait]
+impl PrevalidatorOperations<AptosTransaction, AptosTransaction> for Validator {
+	/// Verifies the sequence number of an AptosTransaction against its account
+	async fn prevalidate(
+		&self,
+		transaction: AptosTransaction,
+	) -> Result<Prevalidated<AptosTransaction>, Error> {
+		match self.account_sequence_number(transaction.sender()).await {
+			Ok(account_sequence_number) => {
+				self.check(transaction.sequence_number(), account_sequence_number)?
+			}
+			Err(e) => {
+				warn!(
+					sender = %transaction.sender(),
+					"failed to get the sequence number of the sender, letting the transaction through: {:?}",
+					e
+				);
+			}
+		}
+		Ok(Prevalidated::new(transaction))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[test]
+	fn test_sequence_numbers() -> Result<(), Error> {
+		let validator = Validator::try_new(&AptosSequenceNumbers {
+			rest_url: "http://localhost:30731/".to_string(),
+			max_sequence_number_gap: 10,
+			timeout_ms: 1000,
+		})?;
+		assert_eq!(validator.rest_url, "http://localhost:30731");
+
+		validator.check(5, 5)?;
+		validator.check(15, 5)?;
+		assert!(validator.check(4, 5).is_err());
+		assert!(validator.check(16, 5).is_err());
+
+		Ok(())
+	}
+}
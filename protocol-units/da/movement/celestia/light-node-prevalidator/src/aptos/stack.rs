@@ -0,0 +1,87 @@
+use crate::{
+	aptos::{
+		limits::Validator as LimitsValidator,
+		sequence_number::Validator as SequenceNumberValidator,
+		transaction::Validator as AptosTransactionValidator,
+		whitelist::whitelist::Validator as WhitelistValidator,
+	},
+	Error, Prevalidated, PrevalidatorOperations,
+};
+use aptos_types::{
+	account_address::AccountAddress, transaction::SignedTransaction as AptosTransaction,
+};
+use movement_celestia_da_util::config::local::da_light_node::AptosPrevalidator;
+use movement_types::transaction::Transaction;
+use std::collections::HashSet;
+use tracing::warn;
+
+/// A check of a signed AptosTransaction.
+pub type Check = Box<dyn PrevalidatorOperations<AptosTransaction, AptosTransaction> + Send + Sync>;
+
+/// Prevalidates a Transaction as a properly signed AptosTransaction, and then with each check in order.
+pub struct Validator {
+	checks: Vec<Check>,
+}
+
+impl Validator {
+	pub fn new(checks: Vec<Check>) -> Self {
+		Self { checks }
+	}
+
+	/// Builds the configured checks, in order.
+	///
+	/// The whitelist check is skipped when there is no whitelist.
+	pub fn try_from_prevalidators(
+		prevalidators: &[AptosPrevalidator],
+		whitelist: Option<HashSet<AccountAddress>>,
+	) -> Result<Self, Error> {
+		let mut checks: Vec<Check> = Vec::with_capacity(prevalidators.len());
+		for prevalidator in prevalidators {
+			match prevalidator {
+				AptosPrevalidator::Limits(limits) => {
+					checks.push(Box::new(LimitsValidator::new(limits.clone())))
+				}
+				AptosPrevalidator::Whitelist => match &whitelist {
+					Some(whitelist) => {
+						checks.push(Box::new(WhitelistValidator::new(whitelist.clone())))
+					}
+					None => warn!("No whitelist is configured, so senders are not checked."),
+				},
+				AptosPrevalidator::SequenceNumbers(sequence_numbers) => {
+					checks.push(Box::new(SequenceNumberValidator::try_new(sequence_numbers)?))
+				}
+			}
+		}
+		Ok(Self::new(checks))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.checks.is_empty()
+	}
+}
+
+#[tonic::async_trait]
+impl PrevalidatorOperations<Transaction, Transaction> for Validator {
+	/// Verifies a Transaction as a Valid Transaction
+	async fn prevalidate(
+		&self,
+		transaction: Transaction,
+	) -> Result<Prevalidated<Transaction>, Error> {
+		let application_priority = transaction.application_priority();
+		let sequence_number = transaction.sequence_number();
+
+		let mut aptos_transaction =
+			AptosTransactionValidator.prevalidate(transaction).await?.into_inner();
+		for check in &self.checks {
+			aptos_transaction = check.prevalidate(aptos_transaction).await?.into_inner();
+		}
+
+		Ok(Prevalidated(Transaction::new(
+			bcs::to_bytes(&aptos_transaction).map_err(|e| {
+				Error::Internal(format!("Failed to serialize AptosTransaction: {}", e))
+			})?,
+			application_priority,
+			sequence_number,
+		)))
+	}
+}
@@ -1,6 +1,7 @@
 pub mod celestia;
 pub mod permissioned_signers;
 pub mod signed;
+pub mod stack;
 
 pub use movement_da_light_node_proto::*;
 use thiserror::Error;
@@ -17,7 +17,7 @@ use ecdsa::{
 	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
 	SignatureSize,
 };
-use movement_celestia_da_util::ir_blob::IntermediateBlobRepresentation;
+use movement_celestia_da_util::{ir_blob::IntermediateBlobRepresentation, signers::SignerRegistry};
 use std::sync::Arc;
 
 /// A verifier of Celestia blobs for permissioned signers
@@ -58,6 +58,18 @@ where
 			known_signers: InKnownSignersVerifier::new(known_signers_sec1_bytes),
 		}
 	}
+
+	/// Creates a verifier for signers which are known at the heights in the registry.
+	pub fn with_registry(
+		celestia_client: Arc<Client>,
+		celestia_namespace: Namespace,
+		known_signers: SignerRegistry,
+	) -> Self {
+		Self {
+			celestia: CelestiaVerifier::new(celestia_client, celestia_namespace),
+			known_signers: InKnownSignersVerifier::with_registry(known_signers),
+		}
+	}
 }
 
 #[tonic::async_trait]
@@ -11,8 +11,7 @@ use ecdsa::{
 	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
 	SignatureSize,
 };
-use movement_celestia_da_util::ir_blob::IntermediateBlobRepresentation;
-use std::collections::HashSet;
+use movement_celestia_da_util::{ir_blob::IntermediateBlobRepresentation, signers::SignerRegistry};
 use tracing::info;
 
 /// A verifier that checks the signature of the inner blob.
@@ -62,7 +61,7 @@ where
 	}
 }
 
-/// Verifies that the signer of the inner blob is in the known signers set.
+/// Verifies that the signer of the inner blob was a known signer at the height of the blob.
 /// This is built around an inner signer because we should always check the signature first. That is, this composition prevents unsafe usage.
 #[derive(Clone)]
 pub struct InKnownSignersVerifier<C>
@@ -74,8 +73,8 @@ where
 	FieldBytesSize<C>: ModulusSize,
 {
 	pub inner_verifier: Verifier<C>,
-	/// The set of known signers in sec1 bytes hex format.
-	pub known_signers_sec1_bytes_hex: HashSet<String>,
+	/// The known signers in sec1 bytes hex format, with the heights they sign for.
+	pub known_signers: SignerRegistry,
 }
 
 impl<C> InKnownSignersVerifier<C>
@@ -86,18 +85,17 @@ where
 	AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
 	FieldBytesSize<C>: ModulusSize,
 {
+	/// Creates a verifier for signers which are known at all heights.
 	pub fn new<T>(known_signers_sec1_bytes_hex: T) -> Self
 	where
 		T: IntoIterator,
 		T::Item: Into<String>,
 	{
-		Self {
-			inner_verifier: Verifier::new(),
-			known_signers_sec1_bytes_hex: known_signers_sec1_bytes_hex
-				.into_iter()
-				.map(Into::into)
-				.collect(),
-		}
+		Self::with_registry(SignerRegistry::permanent(known_signers_sec1_bytes_hex))
+	}
+
+	pub fn with_registry(known_signers: SignerRegistry) -> Self {
+		Self { inner_verifier: Verifier::new(), known_signers }
 	}
 }
 
@@ -119,8 +117,11 @@ where
 		let ir_blob = self.inner_verifier.verify(blob, height).await?;
 		info!("Verified inner blob");
 		let signer = ir_blob.inner().signer_hex();
-		if !self.known_signers_sec1_bytes_hex.contains(&signer) {
-			return Err(Error::Validation("signer not in known signers".to_string()));
+		if !self.known_signers.is_active_at(&signer, height) {
+			return Err(Error::Validation(format!(
+				"signer not in known signers at height {}",
+				height
+			)));
 		}
 
 		Ok(ir_blob)
@@ -130,4 +131,43 @@ where
 #[cfg(test)]
 pub mod tests {
 	// TODO: we need to recreate the signed verifier tests
+
+	use super::*;
+	use ecdsa::SigningKey;
+	use k256::Secp256k1;
+	use movement_celestia_da_util::{ir_blob::InnerSignedBlobV1Data, signers::SignerKey};
+
+	fn signed_blob(
+		signing_key: &SigningKey<Secp256k1>,
+	) -> Result<IntermediateBlobRepresentation, anyhow::Error> {
+		Ok(InnerSignedBlobV1Data::new(vec![1, 2, 3], 1).try_to_sign(signing_key)?.into())
+	}
+
+	#[tokio::test]
+	async fn test_known_signers_are_scoped_to_heights() -> Result<(), anyhow::Error> {
+		let old_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let new_key = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let verifier =
+			InKnownSignersVerifier::<Secp256k1>::with_registry(SignerRegistry::new(vec![
+				SignerKey {
+					public_key_hex: hex::encode(old_key.verifying_key().to_sec1_bytes()),
+					activation_height: 0,
+					retirement_height: Some(100),
+				},
+				SignerKey {
+					public_key_hex: hex::encode(new_key.verifying_key().to_sec1_bytes()),
+					activation_height: 100,
+					retirement_height: None,
+				},
+			]));
+
+		// blobs from before the rotation still verify on replay
+		verifier.verify(signed_blob(&old_key)?, 99).await?;
+		verifier.verify(signed_blob(&new_key)?, 100).await?;
+
+		assert!(verifier.verify(signed_blob(&old_key)?, 100).await.is_err());
+		assert!(verifier.verify(signed_blob(&new_key)?, 99).await.is_err());
+
+		Ok(())
+	}
 }
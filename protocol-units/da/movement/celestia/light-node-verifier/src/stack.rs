@@ -0,0 +1,275 @@
+use crate::{
+	celestia::{pessimistic::Verifier as PessimisticVerifier, Verifier as CelestiaVerifier},
+	permissioned_signers::Verifier as PermissionedSignersVerifier,
+	signed::{InKnownSignersVerifier, Verifier as SignedVerifier},
+	Error, Verified, VerifierOperations,
+};
+use celestia_rpc::Client;
+use celestia_types::{nmt::Namespace, Blob as CelestiaBlob};
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	SignatureSize,
+};
+use movement_celestia_da_util::{
+	config::local::da_light_node::DaVerification, ir_blob::IntermediateBlobRepresentation,
+	signers::SignerRegistry,
+};
+use std::marker::PhantomData;
+use std::sync::{
+	atomic::{AtomicU64, Ordering},
+	Arc,
+};
+use tracing::warn;
+
+/// Accepts every blob as it is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cowboy;
+
+#[tonic::async_trait]
+impl<A> VerifierOperations<A, A> for Cowboy
+where
+	A: Send + Sync + 'static,
+{
+	async fn verify(&self, blob: A, _height: u64) -> Result<Verified<A>, Error> {
+		Ok(Verified::new(blob))
+	}
+}
+
+/// Verifies with the first verifier, and then the outcome of the first with the second.
+pub struct Chain<F, S, B> {
+	pub first: F,
+	pub second: S,
+	_intermediate: PhantomData<fn() -> B>,
+}
+
+impl<F, S, B> Chain<F, S, B> {
+	pub fn new(first: F, second: S) -> Self {
+		Self { first, second, _intermediate: PhantomData }
+	}
+}
+
+#[tonic::async_trait]
+impl<A, B, D, F, S> VerifierOperations<A, D> for Chain<F, S, B>
+where
+	A: Send + Sync + 'static,
+	B: Send + Sync + 'static,
+	D: Send + Sync + 'static,
+	F: VerifierOperations<A, B> + Send + Sync,
+	S: VerifierOperations<B, D> + Send + Sync,
+{
+	async fn verify(&self, blob: A, height: u64) -> Result<Verified<D>, Error> {
+		let verified = self.first.verify(blob, height).await?;
+		self.second.verify(verified.into_inner(), height).await
+	}
+}
+
+/// The number of blobs rejected by a verifier, by kind of error.
+#[derive(Debug, Default)]
+pub struct Rejections {
+	validation: AtomicU64,
+	internal: AtomicU64,
+}
+
+impl Rejections {
+	/// Blobs rejected as invalid.
+	pub fn validation(&self) -> u64 {
+		self.validation.load(Ordering::Relaxed)
+	}
+
+	/// Blobs rejected because they could not be checked.
+	pub fn internal(&self) -> u64 {
+		self.internal.load(Ordering::Relaxed)
+	}
+}
+
+/// Wraps a verifier, counting and logging the blobs it rejects along with the reason.
+pub struct Counted<A, B> {
+	inner: Box<dyn VerifierOperations<A, B> + Send + Sync>,
+	rejections: Arc<Rejections>,
+}
+
+impl<A, B> Counted<A, B>
+where
+	A: Send + Sync + 'static,
+	B: Send + Sync + 'static,
+{
+	pub fn new(inner: Box<dyn VerifierOperations<A, B> + Send + Sync>) -> Self {
+		Self { inner, rejections: Arc::new(Rejections::default()) }
+	}
+
+	pub fn rejections(&self) -> Arc<Rejections> {
+		self.rejections.clone()
+	}
+}
+
+#[tonic::async_trait]
+impl<A, B> VerifierOperations<A, B> for Counted<A, B>
+where
+	A: Send + Sync + 'static,
+	B: Send + Sync + 'static,
+{
+	async fn verify(&self, blob: A, height: u64) -> Result<Verified<B>, Error> {
+		let error = match self.inner.verify(blob, height).await {
+			Ok(verified) => return Ok(verified),
+			Err(error) => error,
+		};
+		match &error {
+			Error::Validation(_) => self.rejections.validation.fetch_add(1, Ordering::Relaxed),
+			Error::Internal(_) => self.rejections.internal.fetch_add(1, Ordering::Relaxed),
+		};
+		warn!(
+			height,
+			reason = %error,
+			validation_rejections = self.rejections.validation(),
+			internal_rejections = self.rejections.internal(),
+			"rejected blob"
+		);
+		Err(error)
+	}
+}
+
+/// Builds the verifier of Celestia blobs for the verification mode.
+pub fn celestia_verifier<C>(
+	verification: DaVerification,
+	client: Arc<Client>,
+	namespace: Namespace,
+	known_signers: SignerRegistry,
+) -> Counted<CelestiaBlob, IntermediateBlobRepresentation>
+where
+	C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+	Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+	SignatureSize<C>: ArrayLength<u8>,
+	AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+	FieldBytesSize<C>: ModulusSize,
+{
+	let verifier: Box<
+		dyn VerifierOperations<CelestiaBlob, IntermediateBlobRepresentation> + Send + Sync,
+	> =
+		match verification {
+			DaVerification::Cowboy => Box::new(CelestiaVerifier::new(client, namespace)),
+			DaVerification::Signed => Box::new(Chain::<_, _, IntermediateBlobRepresentation>::new(
+				CelestiaVerifier::new(client, namespace),
+				SignedVerifier::<C>::new(),
+			)),
+			DaVerification::KnownSigners => Box::new(
+				PermissionedSignersVerifier::<C>::with_registry(client, namespace, known_signers),
+			),
+			DaVerification::PessimisticKnownSigners => {
+				Box::new(Chain::<_, _, IntermediateBlobRepresentation>::new(
+					PessimisticVerifier::new(client, namespace),
+					InKnownSignersVerifier::<C>::with_registry(known_signers),
+				))
+			}
+		};
+	Counted::new(verifier)
+}
+
+/// Builds the verifier of blobs which are stored as intermediate blob representations for the verification mode.
+///
+/// Pessimistic verification needs the data availability headers of Celestia, so it is not available here.
+pub fn ir_verifier<C>(
+	verification: DaVerification,
+	known_signers: SignerRegistry,
+) -> Result<Counted<IntermediateBlobRepresentation, IntermediateBlobRepresentation>, anyhow::Error>
+where
+	C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+	Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+	SignatureSize<C>: ArrayLength<u8>,
+	AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+	FieldBytesSize<C>: ModulusSize,
+{
+	let verifier: Box<
+		dyn VerifierOperations<IntermediateBlobRepresentation, IntermediateBlobRepresentation>
+			+ Send
+			+ Sync,
+	> = match verification {
+		DaVerification::Cowboy => Box::new(Cowboy),
+		DaVerification::Signed => Box::new(SignedVerifier::<C>::new()),
+		DaVerification::KnownSigners => {
+			Box::new(InKnownSignersVerifier::<C>::with_registry(known_signers))
+		}
+		DaVerification::PessimisticKnownSigners => {
+			anyhow::bail!("Pessimistic verification is only available with the Celestia backend.")
+		}
+	};
+	Ok(Counted::new(verifier))
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use ecdsa::SigningKey;
+	use k256::Secp256k1;
+	use movement_celestia_da_util::ir_blob::InnerSignedBlobV1Data;
+
+	fn signed_blob(
+		signing_key: &SigningKey<Secp256k1>,
+	) -> Result<IntermediateBlobRepresentation, anyhow::Error> {
+		Ok(InnerSignedBlobV1Data::new(vec![1, 2, 3], 1).try_to_sign(signing_key)?.into())
+	}
+
+	#[tokio::test]
+	async fn test_ir_verification_modes() -> Result<(), anyhow::Error> {
+		let known_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let other_key = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let known_signers =
+			SignerRegistry::permanent(vec![hex::encode(known_key.verifying_key().to_sec1_bytes())]);
+
+		let known_signers_verifier =
+			ir_verifier::<Secp256k1>(DaVerification::KnownSigners, known_signers.clone())?;
+		known_signers_verifier.verify(signed_blob(&known_key)?, 1).await?;
+		assert!(known_signers_verifier.verify(signed_blob(&other_key)?, 1).await.is_err());
+
+		let signed_verifier =
+			ir_verifier::<Secp256k1>(DaVerification::Signed, known_signers.clone())?;
+		signed_verifier.verify(signed_blob(&other_key)?, 1).await?;
+
+		assert!(ir_verifier::<Secp256k1>(DaVerification::PessimisticKnownSigners, known_signers)
+			.is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_rejections_are_counted() -> Result<(), anyhow::Error> {
+		let known_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let other_key = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let verifier = ir_verifier::<Secp256k1>(
+			DaVerification::KnownSigners,
+			SignerRegistry::permanent(vec![hex::encode(known_key.verifying_key().to_sec1_bytes())]),
+		)?;
+		let rejections = verifier.rejections();
+
+		verifier.verify(signed_blob(&known_key)?, 1).await?;
+		assert_eq!(rejections.validation(), 0);
+
+		assert!(verifier.verify(signed_blob(&other_key)?, 1).await.is_err());
+		assert!(verifier.verify(signed_blob(&other_key)?, 2).await.is_err());
+		assert_eq!(rejections.validation(), 2);
+		assert_eq!(rejections.internal(), 0);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_chain() -> Result<(), anyhow::Error> {
+		let key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let chain = Chain::<_, _, IntermediateBlobRepresentation>::new(
+			Cowboy,
+			SignedVerifier::<Secp256k1>::new(),
+		);
+		let verified = chain.verify(signed_blob(&key)?, 1).await?;
+		assert_eq!(verified.inner().blob(), &[1, 2, 3]);
+
+		Ok(())
+	}
+}
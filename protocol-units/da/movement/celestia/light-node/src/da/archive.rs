@@ -0,0 +1,423 @@
+use crate::da::{DaOperations, HeightStream};
+use movement_celestia_da_util::ir_blob::IntermediateBlobRepresentation;
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+mod column_families {
+	pub const BLOBS: &str = "blobs";
+	pub const HEIGHTS: &str = "heights";
+}
+use column_families::*;
+
+/// The longest wait between attempts to backfill after errors from the wrapped backend.
+const MAX_BACKFILL_BACKOFF: Duration = Duration::from_secs(300);
+
+/// A DA backend which keeps the verified blobs read from another backend in a local RocksDB archive.
+///
+/// Archived heights are served locally without asking or re-verifying with the wrapped backend.
+/// A height is only archived once it is at or below the head, so the archive never holds a partial height.
+#[derive(Clone)]
+pub struct Da {
+	archive: Arc<DB>,
+	/// The backend blobs are read from and submitted to.
+	inner: Arc<Box<dyn DaOperations>>,
+	/// How many heights below the head to keep, or all heights if `None`.
+	retention_heights: Option<u64>,
+	/// The lowest height archived when all heights are kept.
+	floor_height: u64,
+}
+
+impl Da {
+	pub fn open(
+		path: impl AsRef<Path>,
+		inner: Box<dyn DaOperations>,
+		retention_heights: Option<u64>,
+		floor_height: u64,
+	) -> Result<Self, anyhow::Error> {
+		let mut options = Options::default();
+		options.create_if_missing(true);
+		options.create_missing_column_families(true);
+
+		let blobs = ColumnFamilyDescriptor::new(BLOBS, Options::default());
+		let heights = ColumnFamilyDescriptor::new(HEIGHTS, Options::default());
+
+		let db = DB::open_cf_descriptors(&options, path, vec![blobs, heights])
+			.map_err(|e| anyhow::anyhow!("Failed to open blob archive: {:?}", e))?;
+
+		Ok(Self { archive: Arc::new(db), inner: Arc::new(inner), retention_heights, floor_height })
+	}
+
+	/// Blob keys are the big-endian height, the big-endian index of the blob at that height and the blob id,
+	/// such that keys sort by height and keep the order of the blobs within a height.
+	fn blob_key(height: u64, index: u32, blob_id: &[u8]) -> Vec<u8> {
+		let mut key = Vec::with_capacity(12 + blob_id.len());
+		key.extend_from_slice(&height.to_be_bytes());
+		key.extend_from_slice(&index.to_be_bytes());
+		key.extend_from_slice(blob_id);
+		key
+	}
+
+	/// The lowest height kept in the archive for the given head.
+	pub fn first_retained_height(&self, head_height: u64) -> u64 {
+		match self.retention_heights {
+			Some(retention_heights) => head_height.saturating_sub(retention_heights),
+			None => self.floor_height,
+		}
+	}
+
+	/// Gets the archived blobs at a height, or `None` if the height has not been archived.
+	async fn get_archived_blobs(
+		&self,
+		height: u64,
+	) -> Result<Option<Vec<IntermediateBlobRepresentation>>, anyhow::Error> {
+		let db = self.archive.clone();
+		tokio::task::spawn_blocking(move || {
+			let heights_cf =
+				db.cf_handle(HEIGHTS).ok_or(anyhow::anyhow!("No heights column family"))?;
+			if db
+				.get_cf(&heights_cf, height.to_be_bytes())
+				.map_err(|e| anyhow::anyhow!("Failed to get archived height: {:?}", e))?
+				.is_none()
+			{
+				return Ok(None);
+			}
+
+			let blobs_cf = db.cf_handle(BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let prefix = height.to_be_bytes();
+			let mut blobs = Vec::new();
+			for item in db.iterator_cf(&blobs_cf, IteratorMode::From(&prefix, Direction::Forward)) {
+				let (key, value) =
+					item.map_err(|e| anyhow::anyhow!("Failed to iterate archived blobs: {:?}", e))?;
+				if !key.starts_with(&prefix) {
+					break;
+				}
+				let blob = bcs::from_bytes(&value)
+					.map_err(|e| anyhow::anyhow!("Failed to deserialize archived blob: {:?}", e))?;
+				blobs.push(blob);
+			}
+			Ok::<_, anyhow::Error>(Some(blobs))
+		})
+		.await?
+	}
+
+	/// Archives the verified blobs of a height, together with the marker that the height is complete.
+	async fn archive_blobs(
+		&self,
+		height: u64,
+		blobs: &[IntermediateBlobRepresentation],
+	) -> Result<(), anyhow::Error> {
+		let db = self.archive.clone();
+		let blob_count = blobs.len() as u32;
+		let mut values = Vec::with_capacity(blobs.len());
+		for (index, blob) in blobs.iter().enumerate() {
+			let value = bcs::to_bytes(blob)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize blob: {:?}", e))?;
+			values.push((Self::blob_key(height, index as u32, blob.id()), value));
+		}
+
+		tokio::task::spawn_blocking(move || {
+			let blobs_cf = db.cf_handle(BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let heights_cf =
+				db.cf_handle(HEIGHTS).ok_or(anyhow::anyhow!("No heights column family"))?;
+
+			let mut batch = WriteBatch::default();
+			for (key, value) in values {
+				batch.put_cf(&blobs_cf, key, value);
+			}
+			batch.put_cf(&heights_cf, height.to_be_bytes(), blob_count.to_be_bytes());
+
+			db.write(batch).map_err(|e| anyhow::anyhow!("Failed to archive blobs: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Whether a height has been archived.
+	pub async fn is_archived(&self, height: u64) -> Result<bool, anyhow::Error> {
+		let db = self.archive.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db.cf_handle(HEIGHTS).ok_or(anyhow::anyhow!("No heights column family"))?;
+			let value = db
+				.get_cf(&cf, height.to_be_bytes())
+				.map_err(|e| anyhow::anyhow!("Failed to get archived height: {:?}", e))?;
+			Ok(value.is_some())
+		})
+		.await?
+	}
+
+	/// Drops the heights which fall out of the retention window.
+	pub async fn prune(&self, head_height: u64) -> Result<(), anyhow::Error> {
+		// without a retention window nothing is pruned, not even below the floor height
+		if self.retention_heights.is_none() {
+			return Ok(());
+		}
+		let first_retained_height = self.first_retained_height(head_height);
+		if first_retained_height == 0 {
+			return Ok(());
+		}
+
+		let db = self.archive.clone();
+		tokio::task::spawn_blocking(move || {
+			let blobs_cf = db.cf_handle(BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let heights_cf =
+				db.cf_handle(HEIGHTS).ok_or(anyhow::anyhow!("No heights column family"))?;
+			let from = 0u64.to_be_bytes();
+			let to = first_retained_height.to_be_bytes();
+
+			let mut batch = WriteBatch::default();
+			batch.delete_range_cf(&heights_cf, from, to);
+			batch.delete_range_cf(&blobs_cf, from, to);
+			db.write(batch)
+				.map_err(|e| anyhow::anyhow!("Failed to prune blob archive: {:?}", e))
+		})
+		.await?
+	}
+
+	/// Archives every height from `start_height` to `end_height` which is not archived yet.
+	///
+	/// Returns the number of heights archived.
+	pub async fn backfill(&self, start_height: u64, end_height: u64) -> Result<u64, anyhow::Error> {
+		let mut archived = 0;
+		for height in start_height..=end_height {
+			if self.is_archived(height).await? {
+				continue;
+			}
+			let blobs = self.inner.get_ir_blobs_at_height(height).await?;
+			self.archive_blobs(height, &blobs).await?;
+			archived += 1;
+		}
+		Ok(archived)
+	}
+
+	/// Fills and prunes the archive up to the head of the wrapped backend once.
+	async fn backfill_to_head(&self) -> Result<(), anyhow::Error> {
+		let head_height = self.inner.get_head_height().await?;
+		self.prune(head_height).await?;
+
+		let start_height = self.first_retained_height(head_height).max(1);
+		let archived = self.backfill(start_height, head_height).await?;
+		if archived > 0 {
+			info!("backfilled {} heights into the blob archive up to {}", archived, head_height);
+		}
+		Ok(())
+	}
+
+	/// Keeps the archive filled and pruned up to the head of the wrapped backend.
+	///
+	/// With a retention window the whole window is filled, otherwise all heights from the floor height are.
+	/// Errors from the wrapped backend are retried with a backoff, as archived heights are kept between attempts.
+	pub async fn run_backfill(&self, interval: Duration) -> Result<(), anyhow::Error> {
+		let mut backoff = interval;
+		loop {
+			match self.backfill_to_head().await {
+				Ok(()) => {
+					backoff = interval;
+					tokio::time::sleep(interval).await;
+				}
+				Err(e) => {
+					warn!("failed to backfill the blob archive, retrying in {:?}: {}", backoff, e);
+					tokio::time::sleep(backoff).await;
+					backoff = (backoff * 2).min(MAX_BACKFILL_BACKOFF);
+				}
+			}
+		}
+	}
+}
+
+#[tonic::async_trait]
+impl DaOperations for Da {
+	async fn submit_blobs(
+		&self,
+		blobs: Vec<IntermediateBlobRepresentation>,
+	) -> Result<u64, anyhow::Error> {
+		// submitted blobs are archived when their height is read
+		self.inner.submit_blobs(blobs).await
+	}
+
+	async fn get_ir_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error> {
+		if let Some(blobs) = self.get_archived_blobs(height).await? {
+			debug!("serving height {} from the blob archive", height);
+			return Ok(blobs);
+		}
+
+		// heights whose blobs could not all be verified fail here, so they are never archived incomplete
+		let blobs = self.inner.get_ir_blobs_at_height(height).await?;
+		let head_height = self.inner.get_head_height().await?;
+		if height <= head_height && height >= self.first_retained_height(head_height) {
+			self.archive_blobs(height, &blobs).await?;
+		}
+
+		Ok(blobs)
+	}
+
+	async fn get_head_height(&self) -> Result<u64, anyhow::Error> {
+		self.inner.get_head_height().await
+	}
+
+	async fn get_height_timestamp(&self, height: u64) -> Result<u64, anyhow::Error> {
+		self.inner.get_height_timestamp(height).await
+	}
+
+	async fn stream_heights(&self) -> Result<HeightStream, anyhow::Error> {
+		self.inner.stream_heights().await
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use crate::da::digest_store;
+	use ecdsa::SigningKey;
+	use k256::Secp256k1;
+	use movement_celestia_da_light_node_verifier::signed::InKnownSignersVerifier;
+	use movement_celestia_da_util::ir_blob::InnerSignedBlobV1Data;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	/// Counts the reads which reach the wrapped backend.
+	struct CountingDa {
+		inner: digest_store::Da,
+		reads: Arc<AtomicUsize>,
+	}
+
+	#[tonic::async_trait]
+	impl DaOperations for CountingDa {
+		async fn submit_blobs(
+			&self,
+			blobs: Vec<IntermediateBlobRepresentation>,
+		) -> Result<u64, anyhow::Error> {
+			self.inner.submit_blobs(blobs).await
+		}
+
+		async fn get_ir_blobs_at_height(
+			&self,
+			height: u64,
+		) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error> {
+			self.reads.fetch_add(1, Ordering::SeqCst);
+			self.inner.get_ir_blobs_at_height(height).await
+		}
+
+		async fn get_head_height(&self) -> Result<u64, anyhow::Error> {
+			self.inner.get_head_height().await
+		}
+
+		async fn get_height_timestamp(&self, height: u64) -> Result<u64, anyhow::Error> {
+			self.inner.get_height_timestamp(height).await
+		}
+
+		async fn stream_heights(&self) -> Result<HeightStream, anyhow::Error> {
+			self.inner.stream_heights().await
+		}
+	}
+
+	fn make_da(
+		path: &Path,
+		signing_key: &SigningKey<Secp256k1>,
+		retention_heights: Option<u64>,
+		floor_height: u64,
+	) -> Result<(Da, Arc<AtomicUsize>), anyhow::Error> {
+		let signer = hex::encode(signing_key.verifying_key().to_sec1_bytes());
+		let inner = digest_store::Da::open(
+			path.join("digest-store"),
+			Arc::new(Box::new(InKnownSignersVerifier::<Secp256k1>::new(vec![signer]))),
+		)?;
+		let reads = Arc::new(AtomicUsize::new(0));
+		let da = Da::open(
+			path.join("archive"),
+			Box::new(CountingDa { inner, reads: reads.clone() }),
+			retention_heights,
+			floor_height,
+		)?;
+		Ok((da, reads))
+	}
+
+	async fn submit(
+		da: &Da,
+		signing_key: &SigningKey<Secp256k1>,
+		data: Vec<u8>,
+	) -> Result<u64, anyhow::Error> {
+		let blob = InnerSignedBlobV1Data::new(data, 1).try_to_sign(signing_key)?;
+		da.submit_blobs(vec![blob.into()]).await
+	}
+
+	#[tokio::test]
+	async fn test_archived_heights_are_served_locally() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let signing_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let (da, reads) = make_da(dir.path(), &signing_key, None, 1)?;
+
+		let height = submit(&da, &signing_key, vec![1, 2, 3]).await?;
+		assert_eq!(da.get_ir_blobs_at_height(height).await?[0].blob(), &[1, 2, 3]);
+		assert_eq!(da.get_ir_blobs_at_height(height).await?[0].blob(), &[1, 2, 3]);
+		assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+		// heights above the head are not archived, as they may still be written
+		assert!(da.get_ir_blobs_at_height(height + 1).await?.is_empty());
+		assert!(!da.is_archived(height + 1).await?);
+		let next_height = submit(&da, &signing_key, vec![4]).await?;
+		assert_eq!(next_height, height + 1);
+		assert_eq!(da.get_ir_blobs_at_height(next_height).await?[0].blob(), &[4]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_backfill_and_retention() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let signing_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let (da, reads) = make_da(dir.path(), &signing_key, Some(2), 1)?;
+
+		let mut heights = Vec::new();
+		for i in 0..5 {
+			heights.push(submit(&da, &signing_key, vec![i]).await?);
+		}
+		let head_height = da.get_head_height().await?;
+		assert_eq!(head_height, *heights.last().unwrap());
+
+		// fill the retention window
+		let start_height = da.first_retained_height(head_height);
+		assert_eq!(da.backfill(start_height, head_height).await?, 3);
+		assert_eq!(da.backfill(start_height, head_height).await?, 0);
+		let reads_after_backfill = reads.load(Ordering::SeqCst);
+		assert_eq!(da.get_ir_blobs_at_height(head_height).await?[0].blob(), &[4]);
+		assert_eq!(reads.load(Ordering::SeqCst), reads_after_backfill);
+
+		// heights below the window are not archived when read
+		assert_eq!(da.get_ir_blobs_at_height(heights[0]).await?[0].blob(), &[0]);
+		assert!(!da.is_archived(heights[0]).await?);
+
+		// moving the head drops heights out of the window
+		submit(&da, &signing_key, vec![5]).await?;
+		da.prune(da.get_head_height().await?).await?;
+		assert!(!da.is_archived(start_height).await?);
+		assert!(da.is_archived(head_height).await?);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_backfill_from_the_floor_height() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let signing_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let (da, _) = make_da(dir.path(), &signing_key, None, 3)?;
+
+		let mut heights = Vec::new();
+		for i in 0..5 {
+			heights.push(submit(&da, &signing_key, vec![i]).await?);
+		}
+
+		// all heights from the floor are filled, even though none was archived before
+		da.backfill_to_head().await?;
+		assert!(!da.is_archived(heights[1]).await?);
+		for height in &heights[2..] {
+			assert!(da.is_archived(*height).await?);
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,563 @@
+use crate::da::{DaOperations, HeightStream};
+use celestia_rpc::{BlobClient, Client, HeaderClient};
+use celestia_types::{nmt::Namespace, Blob as CelestiaBlob, TxConfig};
+use movement_celestia_da_light_node_verifier::{Error as VerifierError, VerifierOperations};
+use movement_celestia_da_util::config::local::da_light_node::CelestiaSubmission;
+use movement_celestia_da_util::ir_blob::{
+	celestia::CelestiaIntermediateBlobRepresentation, IntermediateBlobRepresentation,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::MissedTickBehavior;
+use tokio_stream::StreamExt;
+use tracing::{error, info, warn};
+
+/// The gas charged for a PayForBlobs transaction regardless of its blobs.
+const PFB_GAS_FIXED_COST: u64 = 75_000;
+/// The gas charged per byte of blob shares.
+const GAS_PER_BLOB_BYTE: u64 = 8;
+/// The gas charged per byte of transaction, applied to the blob info of each blob.
+const TX_SIZE_COST_PER_BYTE: u64 = 10;
+const BYTES_PER_BLOB_INFO: u64 = 70;
+const SHARE_SIZE: u64 = 512;
+/// The blob bytes held by the first share of a blob and by each continuation share.
+const FIRST_SHARE_CAPACITY: u64 = 478;
+const CONTINUATION_SHARE_CAPACITY: u64 = 482;
+
+/// Estimates the gas a PayForBlobs transaction for the blob sizes uses, as celestia-app does.
+pub fn estimate_gas(blob_sizes: impl IntoIterator<Item = usize>) -> u64 {
+	let mut gas = PFB_GAS_FIXED_COST;
+	for size in blob_sizes {
+		let size = size as u64;
+		let shares = if size <= FIRST_SHARE_CAPACITY {
+			1
+		} else {
+			1 + (size - FIRST_SHARE_CAPACITY).div_ceil(CONTINUATION_SHARE_CAPACITY)
+		};
+		gas +=
+			shares * SHARE_SIZE * GAS_PER_BLOB_BYTE + TX_SIZE_COST_PER_BYTE * BYTES_PER_BLOB_INFO;
+	}
+	gas
+}
+
+/// Picks gas prices for Celestia submissions and decides whether failed submissions are retried.
+///
+/// Gas prices are in millionths of a utia, as in [CelestiaSubmission].
+#[derive(Debug)]
+pub struct SubmissionPolicy {
+	config: CelestiaSubmission,
+	/// The heights and gas prices of recently accepted submissions, in order of height.
+	accepted: Mutex<VecDeque<(u64, u64)>>,
+	/// The heights of recent blocks and the gas prices paid by their transactions, in order of height.
+	network: Mutex<VecDeque<(u64, Vec<u64>)>>,
+	retry_queue: Arc<Semaphore>,
+}
+
+impl SubmissionPolicy {
+	pub fn new(config: CelestiaSubmission) -> Self {
+		let retry_queue = Arc::new(Semaphore::new(config.retry_queue_size));
+		Self {
+			config,
+			accepted: Mutex::new(VecDeque::new()),
+			network: Mutex::new(VecDeque::new()),
+			retry_queue,
+		}
+	}
+
+	pub fn config(&self) -> &CelestiaSubmission {
+		&self.config
+	}
+
+	/// The gas price to first attempt a submission with.
+	///
+	/// This is the median gas price paid in the recent blocks of the network,
+	/// or the median gas price of this node's submissions accepted within the window if no blocks were read,
+	/// kept between the minimum gas price and the fee cap.
+	pub fn estimate_gas_price(&self) -> u64 {
+		let network = self.network.lock().expect("submission policy lock poisoned");
+		let mut prices: Vec<u64> = network.iter().flat_map(|(_, prices)| prices.clone()).collect();
+		drop(network);
+		if prices.is_empty() {
+			let accepted = self.accepted.lock().expect("submission policy lock poisoned");
+			prices = accepted.iter().map(|(_, price)| *price).collect();
+		}
+		prices.sort_unstable();
+		let estimate = prices.get(prices.len() / 2).copied().unwrap_or(self.config.min_gas_price);
+		estimate.clamp(
+			self.config.min_gas_price,
+			self.config.max_gas_price.max(self.config.min_gas_price),
+		)
+	}
+
+	/// The first height of the network to read gas prices from, given the latest height.
+	pub fn next_network_height(&self, latest_height: u64) -> u64 {
+		let window_start = (latest_height + 1).saturating_sub(self.config.gas_price_window_heights);
+		let network = self.network.lock().expect("submission policy lock poisoned");
+		match network.back() {
+			Some((height, _)) => (height + 1).max(window_start),
+			None => window_start,
+		}
+	}
+
+	/// Records the gas prices paid by the transactions of a block of the network.
+	pub fn record_network_gas_prices(&self, height: u64, gas_prices: Vec<u64>) {
+		let mut network = self.network.lock().expect("submission policy lock poisoned");
+		network.push_back((height, gas_prices));
+		let latest = network.iter().map(|(height, _)| *height).max().unwrap_or(height);
+		network.retain(|(height, _)| height + self.config.gas_price_window_heights > latest);
+	}
+
+	/// Records a submission accepted at a height.
+	///
+	/// A submission accepted on its first attempt may have overpaid, so it counts at one bump below its gas price.
+	/// This lets the estimate fall back once congestion clears.
+	pub fn record_accepted(&self, height: u64, gas_price: u64, first_attempt: bool) {
+		let gas_price = if first_attempt {
+			gas_price * 100 / (100 + self.config.gas_price_bump_percent)
+		} else {
+			gas_price
+		};
+		let mut accepted = self.accepted.lock().expect("submission policy lock poisoned");
+		accepted.push_back((height, gas_price));
+		let latest = accepted.iter().map(|(height, _)| *height).max().unwrap_or(height);
+		accepted.retain(|(height, _)| height + self.config.gas_price_window_heights > latest);
+	}
+
+	/// The gas price to retry with after an attempt at the given gas price, unless it would exceed the fee cap.
+	pub fn bumped_gas_price(&self, gas_price: u64) -> Option<u64> {
+		let bumped = gas_price
+			.saturating_mul(100 + self.config.gas_price_bump_percent)
+			.div_ceil(100)
+			.max(gas_price + 1);
+		if bumped > self.config.max_gas_price {
+			return None;
+		}
+		Some(bumped)
+	}
+
+	/// Whether a submission error is worth retrying at a higher gas price.
+	pub fn is_retryable(error: &str) -> bool {
+		let error = error.to_lowercase();
+		error.contains("insufficient fee")
+			|| error.contains("timed out")
+			|| error.contains("timeout")
+			|| error.contains("deadline exceeded")
+	}
+
+	/// Takes a place in the retry queue, which is held until the submission completes.
+	///
+	/// Waits for a place for up to the submission timeout, so that retries are throttled rather than dropped.
+	pub async fn enter_retry_queue(&self) -> Option<OwnedSemaphorePermit> {
+		let timeout = Duration::from_millis(self.config.submission_timeout_ms);
+		tokio::time::timeout(timeout, self.retry_queue.clone().acquire_owned())
+			.await
+			.ok()?
+			.ok()
+	}
+}
+
+/// Reads the gas prices paid in recent blocks from the REST API of a celestia-app node.
+#[derive(Debug, Clone)]
+pub struct GasPriceApi {
+	client: reqwest::Client,
+	url: String,
+}
+
+impl GasPriceApi {
+	pub fn try_new(url: String, timeout: Duration) -> Result<Self, anyhow::Error> {
+		let client = reqwest::Client::builder().timeout(timeout).build()?;
+		Ok(Self { client, url: url.trim_end_matches('/').to_string() })
+	}
+
+	async fn get(&self, path: &str) -> Result<serde_json::Value, anyhow::Error> {
+		let url = format!("{}{}", self.url, path);
+		Ok(self.client.get(url).send().await?.error_for_status()?.json().await?)
+	}
+
+	/// The height of the latest block.
+	pub async fn latest_height(&self) -> Result<u64, anyhow::Error> {
+		let response = self.get("/cosmos/base/tendermint/v1beta1/blocks/latest").await?;
+		let height = response["block"]["header"]["height"]
+			.as_str()
+			.ok_or(anyhow::anyhow!("No height in the latest block"))?;
+		Ok(height.parse()?)
+	}
+
+	/// The gas prices paid by the transactions of the block at a height.
+	pub async fn gas_prices_at_height(&self, height: u64) -> Result<Vec<u64>, anyhow::Error> {
+		let response = self.get(&format!("/cosmos/tx/v1beta1/txs/block/{}", height)).await?;
+		Ok(parse_gas_prices(&response))
+	}
+}
+
+/// The gas prices, in millionths of a utia, of the transactions of a `GetBlockWithTxs` response which pay in utia.
+fn parse_gas_prices(response: &serde_json::Value) -> Vec<u64> {
+	let txs = response["txs"].as_array().map(Vec::as_slice).unwrap_or_default();
+	txs.iter()
+		.filter_map(|tx| {
+			let fee = &tx["auth_info"]["fee"];
+			let gas_limit: u64 = fee["gas_limit"].as_str()?.parse().ok()?;
+			let amount: u64 =
+				fee["amount"].as_array()?.iter().find(|coin| coin["denom"] == "utia")?["amount"]
+					.as_str()?
+					.parse()
+					.ok()?;
+			if gas_limit == 0 {
+				return None;
+			}
+			Some((amount as u128 * 1_000_000 / gas_limit as u128) as u64)
+		})
+		.collect()
+}
+
+/// A DA backend which submits blobs to and reads blobs from a Celestia node.
+#[derive(Clone)]
+pub struct Da {
+	/// The Celestia RPC client
+	pub default_client: Arc<Client>,
+	/// The namespace of the Celestia Blobs
+	pub celestia_namespace: Namespace,
+	/// The verifier for Celestia Blobs
+	pub verifier: Arc<
+		Box<dyn VerifierOperations<CelestiaBlob, IntermediateBlobRepresentation> + Send + Sync>,
+	>,
+	/// The policy for gas prices and retries of submissions
+	pub submission: Arc<SubmissionPolicy>,
+	/// Where the gas prices of recent blocks are read from, if anywhere
+	pub gas_price_api: Option<GasPriceApi>,
+}
+
+impl Da {
+	pub fn try_new(
+		default_client: Arc<Client>,
+		celestia_namespace: Namespace,
+		verifier: Arc<
+			Box<dyn VerifierOperations<CelestiaBlob, IntermediateBlobRepresentation> + Send + Sync>,
+		>,
+		submission: CelestiaSubmission,
+	) -> Result<Self, anyhow::Error> {
+		let gas_price_api = match &submission.gas_price_api_url {
+			Some(url) => Some(GasPriceApi::try_new(
+				url.clone(),
+				Duration::from_millis(submission.submission_timeout_ms),
+			)?),
+			None => None,
+		};
+		Ok(Self {
+			default_client,
+			celestia_namespace,
+			verifier,
+			submission: Arc::new(SubmissionPolicy::new(submission)),
+			gas_price_api,
+		})
+	}
+
+	/// Reads the gas prices of the blocks which entered the estimation window since the last read.
+	pub async fn refresh_network_gas_prices(&self) -> Result<(), anyhow::Error> {
+		let gas_price_api = match &self.gas_price_api {
+			Some(gas_price_api) => gas_price_api,
+			None => return Ok(()),
+		};
+		let latest_height = gas_price_api.latest_height().await?;
+		for height in self.submission.next_network_height(latest_height)..=latest_height {
+			let gas_prices = gas_price_api.gas_prices_at_height(height).await?;
+			self.submission.record_network_gas_prices(height, gas_prices);
+		}
+		Ok(())
+	}
+
+	/// Reads the gas prices of recent blocks every interval, so submissions never wait for them.
+	///
+	/// Failed reads are logged and retried later, while submissions use the last estimate.
+	pub async fn run_gas_price_refresh(&self, interval: Duration) -> Result<(), anyhow::Error> {
+		if self.gas_price_api.is_none() {
+			return Ok(());
+		}
+		let mut interval = tokio::time::interval(interval);
+		interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		loop {
+			interval.tick().await;
+			if let Err(e) = self.refresh_network_gas_prices().await {
+				warn!(error = %e, "failed to read the gas prices of recent blocks");
+			}
+		}
+	}
+
+	/// Creates a CelestiaBlob from an IntermediateBlobRepresentation in the configured namespace.
+	pub fn create_new_celestia_blob(
+		&self,
+		ir_blob: IntermediateBlobRepresentation,
+	) -> Result<CelestiaBlob, anyhow::Error> {
+		CelestiaIntermediateBlobRepresentation(ir_blob, self.celestia_namespace.clone()).try_into()
+	}
+
+	/// Submits Celestia blobs to the Celestia node.
+	///
+	/// Rejections for insufficient fees and timeouts are retried at bumped gas prices up to the fee cap.
+	/// A timed out attempt may still be included, in which case the blobs are posted twice.
+	pub async fn submit_celestia_blobs(
+		&self,
+		blobs: &[CelestiaBlob],
+	) -> Result<u64, anyhow::Error> {
+		let config = self.submission.config();
+		let started = Instant::now();
+		let estimated_gas = estimate_gas(blobs.iter().map(|blob| blob.data.len()));
+		let mut gas_price = self.submission.estimate_gas_price();
+		let mut retries = 0;
+		let mut retry_permit = None;
+
+		loop {
+			let mut tx_config = TxConfig::default();
+			tx_config.with_gas_price(gas_price as f64 / 1_000_000.0);
+
+			let result = match tokio::time::timeout(
+				Duration::from_millis(config.submission_timeout_ms),
+				self.default_client.blob_submit(blobs, tx_config),
+			)
+			.await
+			{
+				Ok(Ok(height)) => Ok(height),
+				Ok(Err(e)) => Err(e.to_string()),
+				Err(_) => Err("submission timed out".to_string()),
+			};
+
+			let error = match result {
+				Ok(height) => {
+					self.submission.record_accepted(height, gas_price, retries == 0);
+					info!(
+						target: "movement_timing",
+						height,
+						blob_count = blobs.len(),
+						latency_ms = started.elapsed().as_millis() as u64,
+						retries,
+						gas_price,
+						estimated_fee_utia = estimated_gas * gas_price / 1_000_000,
+						"celestia_blobs_submitted"
+					);
+					return Ok(height);
+				}
+				Err(e) => e,
+			};
+
+			let next_gas_price =
+				if SubmissionPolicy::is_retryable(&error) && retries < config.max_retries {
+					self.submission.bumped_gas_price(gas_price)
+				} else {
+					None
+				};
+			if retry_permit.is_none() && next_gas_price.is_some() {
+				retry_permit = self.submission.enter_retry_queue().await;
+				if retry_permit.is_none() {
+					warn!("the Celestia submission retry queue is full");
+				}
+			}
+			let next_gas_price = match (next_gas_price, &retry_permit) {
+				(Some(next_gas_price), Some(_)) => next_gas_price,
+				_ => {
+					error!(error = %error, retries, gas_price, "failed to submit the blobs");
+					info!(
+						target: "movement_timing",
+						blob_count = blobs.len(),
+						latency_ms = started.elapsed().as_millis() as u64,
+						retries,
+						gas_price,
+						"celestia_blobs_submission_failed"
+					);
+					anyhow::bail!("Failed submitting the blob: {}", error);
+				}
+			};
+
+			warn!(error = %error, gas_price, next_gas_price, "retrying the blob submission");
+			retries += 1;
+			gas_price = next_gas_price;
+		}
+	}
+}
+
+#[tonic::async_trait]
+impl DaOperations for Da {
+	async fn submit_blobs(
+		&self,
+		blobs: Vec<IntermediateBlobRepresentation>,
+	) -> Result<u64, anyhow::Error> {
+		let mut celestia_blobs = Vec::with_capacity(blobs.len());
+		for blob in blobs {
+			celestia_blobs.push(self.create_new_celestia_blob(blob)?);
+		}
+		self.submit_celestia_blobs(&celestia_blobs).await
+	}
+
+	async fn get_ir_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error> {
+		let height = if height == 0 { 1 } else { height };
+		match self.default_client.blob_get_all(height, &[self.celestia_namespace]).await {
+			Err(e) => {
+				error!(error = %e, "failed to get blobs at height {height}");
+				anyhow::bail!(e);
+			}
+			Ok(blobs) => {
+				let blobs = blobs.unwrap_or_default();
+
+				let mut verified_blobs = Vec::new();
+				for blob in blobs {
+					match self.verifier.verify(blob, height).await {
+						Ok(verified_blob) => {
+							let blob = verified_blob.into_inner();
+							info!("verified blob at height {}: {}", height, hex::encode(blob.id()));
+							verified_blobs.push(blob);
+						}
+						// rejected blobs are counted and logged by the verifier
+						Err(VerifierError::Validation(_)) => {}
+						// the blob may be valid, so the height is not served without it
+						Err(e @ VerifierError::Internal(_)) => {
+							anyhow::bail!("failed to verify a blob at height {}: {}", height, e)
+						}
+					}
+				}
+
+				Ok(verified_blobs)
+			}
+		}
+	}
+
+	async fn get_head_height(&self) -> Result<u64, anyhow::Error> {
+		let head = self.default_client.header_network_head().await?;
+		Ok(head.height().into())
+	}
+
+	async fn get_height_timestamp(&self, height: u64) -> Result<u64, anyhow::Error> {
+		let header = self.default_client.header_get_by_height(height).await?;
+		Ok((header.time().unix_timestamp_nanos() / 1_000) as u64)
+	}
+
+	async fn stream_heights(&self) -> Result<HeightStream, anyhow::Error> {
+		let mut subscription = self.default_client.header_subscribe().await?;
+
+		let stream = async_stream::try_stream! {
+			while let Some(header_res) = subscription.next().await {
+				let header = header_res?;
+				info!("Stream got header: {:?}", header.height());
+				yield header.height().into();
+			}
+		};
+
+		Ok(Box::pin(stream) as HeightStream)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	fn config() -> CelestiaSubmission {
+		CelestiaSubmission {
+			min_gas_price: 2000,
+			max_gas_price: 10000,
+			gas_price_bump_percent: 50,
+			gas_price_window_heights: 10,
+			gas_price_api_url: None,
+			max_retries: 5,
+			retry_queue_size: 1,
+			submission_timeout_ms: 1000,
+		}
+	}
+
+	#[test]
+	fn test_gas_price_bumps_up_to_the_cap() {
+		let policy = SubmissionPolicy::new(config());
+		assert_eq!(policy.estimate_gas_price(), 2000);
+		assert_eq!(policy.bumped_gas_price(2000), Some(3000));
+		assert_eq!(policy.bumped_gas_price(6000), Some(9000));
+		assert_eq!(policy.bumped_gas_price(9000), None);
+	}
+
+	#[test]
+	fn test_gas_price_estimate_follows_accepted_submissions() {
+		let policy = SubmissionPolicy::new(config());
+		policy.record_accepted(1, 4500, false);
+		policy.record_accepted(2, 4500, false);
+		policy.record_accepted(3, 2000, false);
+		assert_eq!(policy.estimate_gas_price(), 4500);
+
+		// accepted on the first attempt, so counted one bump lower
+		policy.record_accepted(12, 4500, true);
+		policy.record_accepted(13, 4500, true);
+		assert_eq!(policy.estimate_gas_price(), 3000);
+
+		// never below the minimum
+		policy.record_accepted(30, 2000, true);
+		assert_eq!(policy.estimate_gas_price(), 2000);
+	}
+
+	#[test]
+	fn test_retryable_errors() {
+		assert!(SubmissionPolicy::is_retryable(
+			"insufficient fees; got: 100utia required: 200utia: insufficient fee"
+		));
+		assert!(SubmissionPolicy::is_retryable("submission timed out"));
+		assert!(!SubmissionPolicy::is_retryable("blob size exceeds the maximum"));
+	}
+
+	#[test]
+	fn test_gas_price_estimate_prefers_network_gas_prices() {
+		let policy = SubmissionPolicy::new(config());
+		policy.record_accepted(1, 9000, false);
+		assert_eq!(policy.next_network_height(20), 11);
+
+		policy.record_network_gas_prices(19, vec![2500, 4000]);
+		policy.record_network_gas_prices(20, vec![3000]);
+		assert_eq!(policy.estimate_gas_price(), 3000);
+		assert_eq!(policy.next_network_height(22), 21);
+
+		// blocks out of the window no longer count
+		policy.record_network_gas_prices(30, vec![]);
+		policy.record_network_gas_prices(31, vec![5000]);
+		assert_eq!(policy.estimate_gas_price(), 5000);
+	}
+
+	#[test]
+	fn test_parse_gas_prices() {
+		let tx = |denom: &str, amount: &str, gas_limit: &str| {
+			serde_json::json!({
+				"auth_info": {
+					"fee": { "amount": [{ "denom": denom, "amount": amount }], "gas_limit": gas_limit }
+				}
+			})
+		};
+		let response = serde_json::json!({
+			"txs": [tx("utia", "200", "100000"), tx("other", "200", "100000"), tx("utia", "200", "0")]
+		});
+		assert_eq!(parse_gas_prices(&response), vec![2000]);
+		assert!(parse_gas_prices(&serde_json::json!({})).is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_retry_queue_is_bounded() -> Result<(), anyhow::Error> {
+		let policy = Arc::new(SubmissionPolicy::new(config()));
+		let permit = policy.enter_retry_queue().await;
+		assert!(permit.is_some());
+		// a full queue is waited on for up to the submission timeout
+		assert!(policy.enter_retry_queue().await.is_none());
+
+		let waiting = tokio::spawn({
+			let policy = policy.clone();
+			async move { policy.enter_retry_queue().await.is_some() }
+		});
+		drop(permit);
+		assert!(waiting.await?);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_estimate_gas() {
+		assert_eq!(estimate_gas(vec![]), 75_000);
+		assert_eq!(estimate_gas(vec![478]), 75_000 + 4096 + 700);
+		assert_eq!(estimate_gas(vec![479]), 75_000 + 2 * 4096 + 700);
+	}
+}
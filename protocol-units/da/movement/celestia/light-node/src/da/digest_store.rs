@@ -0,0 +1,332 @@
+use crate::da::{DaOperations, HeightStream};
+use movement_celestia_da_light_node_verifier::{Error as VerifierError, VerifierOperations};
+use movement_celestia_da_util::ir_blob::IntermediateBlobRepresentation;
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{watch, Mutex};
+use tracing::info;
+
+mod column_families {
+	pub const BLOBS: &str = "blobs";
+	pub const HEAD: &str = "head";
+}
+use column_families::*;
+
+/// The key under which the head height is stored.
+const HEAD_HEIGHT_KEY: &str = "head_height";
+
+/// The prefix of the keys under which the submission time of each height is stored.
+const HEIGHT_TIMESTAMP_PREFIX: &[u8] = b"height_timestamp";
+
+/// Height 1 is reserved as an empty genesis height, mirroring Celestia, so the first submission lands at height 2.
+const GENESIS_HEIGHT: u64 = 1;
+
+/// A DA backend which keeps blobs in a local RocksDB store and assigns heights itself.
+///
+/// Every submission is written at the next height, so heights are dense and each height holds exactly the blobs of one submission.
+/// This allows running the light node without any Celestia processes.
+#[derive(Clone)]
+pub struct Da {
+	inner: Arc<DB>,
+	/// Serializes height assignment across submissions.
+	submission_lock: Arc<Mutex<()>>,
+	/// Publishes the head height to subscribers.
+	head_sender: Arc<watch::Sender<u64>>,
+	/// The verifier for the stored blobs
+	pub verifier: Arc<
+		Box<
+			dyn VerifierOperations<IntermediateBlobRepresentation, IntermediateBlobRepresentation>
+				+ Send
+				+ Sync,
+		>,
+	>,
+}
+
+impl Da {
+	pub fn open(
+		path: impl AsRef<Path>,
+		verifier: Arc<
+			Box<
+				dyn VerifierOperations<
+						IntermediateBlobRepresentation,
+						IntermediateBlobRepresentation,
+					> + Send
+					+ Sync,
+			>,
+		>,
+	) -> Result<Self, anyhow::Error> {
+		let mut options = Options::default();
+		options.create_if_missing(true);
+		options.create_missing_column_families(true);
+
+		let blobs = ColumnFamilyDescriptor::new(BLOBS, Options::default());
+		let head = ColumnFamilyDescriptor::new(HEAD, Options::default());
+
+		let db = DB::open_cf_descriptors(&options, path, vec![blobs, head])
+			.map_err(|e| anyhow::anyhow!("Failed to open digest store: {:?}", e))?;
+
+		let head_height = Self::read_head_height(&db)?;
+		let (head_sender, _) = watch::channel(head_height);
+
+		Ok(Self {
+			inner: Arc::new(db),
+			submission_lock: Arc::new(Mutex::new(())),
+			head_sender: Arc::new(head_sender),
+			verifier,
+		})
+	}
+
+	fn read_head_height(db: &DB) -> Result<u64, anyhow::Error> {
+		let cf = db.cf_handle(HEAD).ok_or(anyhow::anyhow!("No head column family"))?;
+		let height = db
+			.get_cf(&cf, HEAD_HEIGHT_KEY)
+			.map_err(|e| anyhow::anyhow!("Failed to get head height: {:?}", e))?;
+		match height {
+			Some(height) => serde_json::from_slice(&height)
+				.map_err(|e| anyhow::anyhow!("Failed to deserialize head height: {:?}", e)),
+			None => Ok(GENESIS_HEIGHT),
+		}
+	}
+
+	/// Blob keys are the big-endian height followed by the big-endian index of the blob at that height, such that keys sort by height.
+	fn blob_key(height: u64, index: u32) -> Vec<u8> {
+		let mut key = Vec::with_capacity(12);
+		key.extend_from_slice(&height.to_be_bytes());
+		key.extend_from_slice(&index.to_be_bytes());
+		key
+	}
+
+	fn height_timestamp_key(height: u64) -> Vec<u8> {
+		let mut key = HEIGHT_TIMESTAMP_PREFIX.to_vec();
+		key.extend_from_slice(&height.to_be_bytes());
+		key
+	}
+
+	/// Gets the unverified blobs stored at a given height.
+	async fn get_stored_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db.cf_handle(BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let prefix = height.to_be_bytes();
+			let mut blobs = Vec::new();
+			for item in db.iterator_cf(&cf, IteratorMode::From(&prefix, Direction::Forward)) {
+				let (key, value) =
+					item.map_err(|e| anyhow::anyhow!("Failed to iterate blobs: {:?}", e))?;
+				if !key.starts_with(&prefix) {
+					break;
+				}
+				let blob = bcs::from_bytes(&value)
+					.map_err(|e| anyhow::anyhow!("Failed to deserialize blob: {:?}", e))?;
+				blobs.push(blob);
+			}
+			Ok::<_, anyhow::Error>(blobs)
+		})
+		.await?
+	}
+}
+
+#[tonic::async_trait]
+impl DaOperations for Da {
+	async fn submit_blobs(
+		&self,
+		blobs: Vec<IntermediateBlobRepresentation>,
+	) -> Result<u64, anyhow::Error> {
+		let _guard = self.submission_lock.lock().await;
+		let height = *self.head_sender.borrow() + 1;
+
+		// the store is the DA, so the time of a height is the time it was submitted at
+		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
+
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let blobs_cf = db.cf_handle(BLOBS).ok_or(anyhow::anyhow!("No blobs column family"))?;
+			let head_cf = db.cf_handle(HEAD).ok_or(anyhow::anyhow!("No head column family"))?;
+
+			// write the blobs and the new head atomically
+			let mut batch = WriteBatch::default();
+			for (index, blob) in blobs.iter().enumerate() {
+				let value = bcs::to_bytes(blob)
+					.map_err(|e| anyhow::anyhow!("Failed to serialize blob: {:?}", e))?;
+				batch.put_cf(&blobs_cf, Self::blob_key(height, index as u32), value);
+			}
+			let head_height = serde_json::to_string(&height)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize head height: {:?}", e))?;
+			batch.put_cf(&head_cf, HEAD_HEIGHT_KEY, head_height);
+			let timestamp = serde_json::to_string(&timestamp)
+				.map_err(|e| anyhow::anyhow!("Failed to serialize height timestamp: {:?}", e))?;
+			batch.put_cf(&head_cf, Self::height_timestamp_key(height), timestamp);
+
+			db.write(batch).map_err(|e| anyhow::anyhow!("Failed to write blobs: {:?}", e))
+		})
+		.await??;
+
+		self.head_sender.send_replace(height);
+		info!("digest store submitted blobs at height {}", height);
+
+		Ok(height)
+	}
+
+	async fn get_ir_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error> {
+		let blobs = self.get_stored_blobs_at_height(height).await?;
+
+		let mut verified_blobs = Vec::new();
+		for blob in blobs {
+			match self.verifier.verify(blob, height).await {
+				Ok(verified_blob) => {
+					let blob = verified_blob.into_inner();
+					info!("verified blob at height {}: {}", height, hex::encode(blob.id()));
+					verified_blobs.push(blob);
+				}
+				// rejected blobs are counted and logged by the verifier
+				Err(VerifierError::Validation(_)) => {}
+				// the blob may be valid, so the height is not served without it
+				Err(e @ VerifierError::Internal(_)) => {
+					anyhow::bail!("failed to verify a blob at height {}: {}", height, e)
+				}
+			}
+		}
+
+		Ok(verified_blobs)
+	}
+
+	async fn get_head_height(&self) -> Result<u64, anyhow::Error> {
+		Ok(*self.head_sender.borrow())
+	}
+
+	async fn get_height_timestamp(&self, height: u64) -> Result<u64, anyhow::Error> {
+		let db = self.inner.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf = db.cf_handle(HEAD).ok_or(anyhow::anyhow!("No head column family"))?;
+			let timestamp = db
+				.get_cf(&cf, Self::height_timestamp_key(height))
+				.map_err(|e| anyhow::anyhow!("Failed to get height timestamp: {:?}", e))?
+				.ok_or(anyhow::anyhow!("No timestamp recorded for height {}", height))?;
+			serde_json::from_slice(&timestamp)
+				.map_err(|e| anyhow::anyhow!("Failed to deserialize height timestamp: {:?}", e))
+		})
+		.await?
+	}
+
+	async fn stream_heights(&self) -> Result<HeightStream, anyhow::Error> {
+		let mut receiver = self.head_sender.subscribe();
+
+		let stream = async_stream::try_stream! {
+			// yield the current head first so that subscribers can back fetch without waiting for a new submission
+			let mut last_height = *receiver.borrow_and_update();
+			yield last_height;
+			while receiver.changed().await.is_ok() {
+				let head_height = *receiver.borrow_and_update();
+				// the watch channel may coalesce updates, so yield every height in between
+				for height in last_height + 1..=head_height {
+					yield height;
+				}
+				last_height = head_height;
+			}
+		};
+
+		Ok(Box::pin(stream) as HeightStream)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use ecdsa::SigningKey;
+	use k256::Secp256k1;
+	use movement_celestia_da_light_node_verifier::signed::InKnownSignersVerifier;
+	use movement_celestia_da_light_node_verifier::Verified;
+	use movement_celestia_da_util::ir_blob::InnerSignedBlobV1Data;
+	use tokio_stream::StreamExt;
+
+	fn make_da(path: &Path, signing_key: &SigningKey<Secp256k1>) -> Result<Da, anyhow::Error> {
+		let signer = hex::encode(signing_key.verifying_key().to_sec1_bytes());
+		Da::open(path, Arc::new(Box::new(InKnownSignersVerifier::<Secp256k1>::new(vec![signer]))))
+	}
+
+	#[tokio::test]
+	async fn test_digest_store_assigns_heights() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let signing_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let da = make_da(dir.path(), &signing_key)?;
+		assert_eq!(da.get_head_height().await?, GENESIS_HEIGHT);
+
+		let mut heights = da.stream_heights().await?;
+		assert_eq!(heights.next().await.transpose()?, Some(GENESIS_HEIGHT));
+
+		let before = chrono::Utc::now().timestamp_micros() as u64;
+		let blob = InnerSignedBlobV1Data::new(vec![1, 2, 3], 1).try_to_sign(&signing_key)?;
+		let height = da.submit_blobs(vec![blob.into()]).await?;
+		assert_eq!(height, GENESIS_HEIGHT + 1);
+		assert_eq!(heights.next().await.transpose()?, Some(height));
+
+		// the height is timed by the store, not by the signed blob
+		let timestamp = da.get_height_timestamp(height).await?;
+		assert!(timestamp >= before && timestamp <= chrono::Utc::now().timestamp_micros() as u64);
+		assert!(da.get_height_timestamp(height + 1).await.is_err());
+
+		let blobs = da.get_ir_blobs_at_height(height).await?;
+		assert_eq!(blobs.len(), 1);
+		assert_eq!(blobs[0].blob(), &[1, 2, 3]);
+		assert!(da.get_ir_blobs_at_height(height + 1).await?.is_empty());
+
+		// the head survives reopening the store
+		drop(heights);
+		drop(da);
+		let da = make_da(dir.path(), &signing_key)?;
+		assert_eq!(da.get_head_height().await?, height);
+		assert_eq!(da.get_height_timestamp(height).await?, timestamp);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_digest_store_drops_unknown_signers() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let signing_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let other_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let da = make_da(dir.path(), &signing_key)?;
+
+		let blob = InnerSignedBlobV1Data::new(vec![1, 2, 3], 1).try_to_sign(&other_key)?;
+		let height = da.submit_blobs(vec![blob.into()]).await?;
+		assert!(da.get_ir_blobs_at_height(height).await?.is_empty());
+
+		Ok(())
+	}
+
+	/// Fails every verification as an internal error, as a verifier which cannot reach its DA does.
+	struct UnavailableVerifier;
+
+	#[tonic::async_trait]
+	impl VerifierOperations<IntermediateBlobRepresentation, IntermediateBlobRepresentation>
+		for UnavailableVerifier
+	{
+		async fn verify(
+			&self,
+			_blob: IntermediateBlobRepresentation,
+			_height: u64,
+		) -> Result<Verified<IntermediateBlobRepresentation>, VerifierError> {
+			Err(VerifierError::Internal("the DA is unavailable".to_string()))
+		}
+	}
+
+	#[tokio::test]
+	async fn test_digest_store_fails_reads_on_internal_errors() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let signing_key = SigningKey::<Secp256k1>::random(&mut rand::rngs::OsRng);
+		let da = Da::open(dir.path(), Arc::new(Box::new(UnavailableVerifier)))?;
+
+		let blob = InnerSignedBlobV1Data::new(vec![1, 2, 3], 1).try_to_sign(&signing_key)?;
+		let height = da.submit_blobs(vec![blob.into()]).await?;
+		assert!(da.get_ir_blobs_at_height(height).await.is_err());
+
+		Ok(())
+	}
+}
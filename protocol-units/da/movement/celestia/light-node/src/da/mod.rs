@@ -0,0 +1,39 @@
+pub mod archive;
+pub mod celestia;
+pub mod digest_store;
+
+use movement_celestia_da_util::ir_blob::IntermediateBlobRepresentation;
+use std::pin::Pin;
+use tokio_stream::Stream;
+
+/// A stream of DA heights as they become available.
+pub type HeightStream = Pin<Box<dyn Stream<Item = Result<u64, anyhow::Error>> + Send>>;
+
+/// The operations a DA backend must provide for the light node to sit on it.
+///
+/// Implementations are responsible for verifying the blobs they return, such that callers only ever see blobs accepted by the backend's verifier.
+#[tonic::async_trait]
+pub trait DaOperations: Send + Sync {
+	/// Submits the blobs to the DA and returns the height at which they were included.
+	async fn submit_blobs(
+		&self,
+		blobs: Vec<IntermediateBlobRepresentation>,
+	) -> Result<u64, anyhow::Error>;
+
+	/// Gets the verified blobs at a given height.
+	async fn get_ir_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error>;
+
+	/// Gets the current head height of the DA.
+	async fn get_head_height(&self) -> Result<u64, anyhow::Error>;
+
+	/// Gets the time of the DA at a given height in microseconds, as agreed by the DA rather than by the blob signers.
+	async fn get_height_timestamp(&self, height: u64) -> Result<u64, anyhow::Error>;
+
+	/// Streams heights as they become available on the DA.
+	///
+	/// Backends may yield their current head first.
+	async fn stream_heights(&self) -> Result<HeightStream, anyhow::Error>;
+}
@@ -1 +1,2 @@
+pub mod da;
 pub mod v1;
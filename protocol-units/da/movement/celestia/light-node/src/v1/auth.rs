@@ -0,0 +1,331 @@
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	SignatureSize,
+};
+use movement_celestia_da_util::config::local::da_light_node::{
+	DaServiceAuth, DaServiceClient, DaServiceTls,
+};
+use movement_da_light_node_client::auth::{BearerToken, AUTHORIZATION_METADATA_KEY};
+pub use movement_da_light_node_client::auth::{BATCH_WRITE_METHOD, STREAM_WRITE_BLOB_METHOD};
+use sha2::{Digest, Sha256};
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+use tracing::info;
+
+/// Reads the TLS configuration of the light node service.
+pub fn try_server_tls_config(tls: &DaServiceTls) -> Result<ServerTlsConfig, anyhow::Error> {
+	let certificate = std::fs::read(&tls.certificate_path)?;
+	let key = std::fs::read(&tls.key_path)?;
+	let mut config = ServerTlsConfig::new().identity(Identity::from_pem(certificate, key));
+	if let Some(client_ca_certificate_path) = &tls.client_ca_certificate_path {
+		let client_ca_certificate = std::fs::read(client_ca_certificate_path)?;
+		config = config.client_ca_root(Certificate::from_pem(client_ca_certificate));
+	}
+	Ok(config)
+}
+
+/// A token bucket which refills at a rate per second and holds up to one second's worth.
+#[derive(Debug)]
+struct RateLimiter {
+	per_second: u64,
+	available: f64,
+	refilled_at: Instant,
+}
+
+impl RateLimiter {
+	fn new(per_second: u64, now: Instant) -> Self {
+		Self { per_second, available: per_second as f64, refilled_at: now }
+	}
+
+	fn refill(&mut self, now: Instant) {
+		let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+		self.available =
+			(self.available + elapsed * self.per_second as f64).min(self.per_second as f64);
+		self.refilled_at = now;
+	}
+
+	/// Whether the amount can be taken. An amount larger than the bucket can never be taken,
+	/// so a single request cannot exceed the rate.
+	fn can_take(&self, amount: u64) -> bool {
+		amount <= self.per_second && self.available >= amount as f64
+	}
+
+	fn take(&mut self, amount: u64) {
+		self.available -= amount as f64;
+	}
+}
+
+#[derive(Debug)]
+struct Quota {
+	blobs: Option<RateLimiter>,
+	bytes: Option<RateLimiter>,
+}
+
+/// A client which may write to the light node service.
+#[derive(Debug)]
+pub struct AuthorizedClient {
+	name: String,
+	token_public_key: Option<Vec<u8>>,
+	certificate_sha256: Option<Vec<u8>>,
+	quota: Mutex<Quota>,
+}
+
+impl AuthorizedClient {
+	fn try_new(client: &DaServiceClient, now: Instant) -> Result<Self, anyhow::Error> {
+		let token_public_key =
+			client.token_public_key_hex.as_deref().map(hex::decode).transpose()?;
+		let certificate_sha256 =
+			client.certificate_sha256_hex.as_deref().map(hex::decode).transpose()?;
+		if token_public_key.is_none() && certificate_sha256.is_none() {
+			anyhow::bail!(
+				"Client {} has neither a token public key nor a certificate fingerprint, so it can never authenticate.",
+				client.name
+			);
+		}
+		Ok(Self {
+			name: client.name.clone(),
+			token_public_key,
+			certificate_sha256,
+			quota: Mutex::new(Quota {
+				blobs: client.blobs_per_second.map(|rate| RateLimiter::new(rate, now)),
+				bytes: client.bytes_per_second.map(|rate| RateLimiter::new(rate, now)),
+			}),
+		})
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// Takes the blobs from the quota of the client, or fails with `ResourceExhausted` if it is used up.
+	pub fn try_consume(&self, blobs: u64, bytes: u64) -> Result<(), tonic::Status> {
+		self.try_consume_at(blobs, bytes, Instant::now())
+	}
+
+	fn try_consume_at(&self, blobs: u64, bytes: u64, now: Instant) -> Result<(), tonic::Status> {
+		let mut guard = self.quota.lock().expect("quota lock poisoned");
+		let quota = &mut *guard;
+		for (limiter, amount) in [(&mut quota.blobs, blobs), (&mut quota.bytes, bytes)] {
+			if let Some(limiter) = limiter {
+				limiter.refill(now);
+				if !limiter.can_take(amount) {
+					info!(client = %self.name, blobs, bytes, "rejecting write over quota");
+					return Err(tonic::Status::resource_exhausted(format!(
+						"Client {} is over its write quota",
+						self.name
+					)));
+				}
+			}
+		}
+		for (limiter, amount) in [(&mut quota.blobs, blobs), (&mut quota.bytes, bytes)] {
+			if let Some(limiter) = limiter {
+				limiter.take(amount);
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Authenticates the clients writing to the light node service, by their mTLS certificate or a bearer token.
+#[derive(Debug)]
+pub struct Authenticator<C> {
+	clients: Vec<Arc<AuthorizedClient>>,
+	token_max_age_ms: u64,
+	_curve: PhantomData<fn() -> C>,
+}
+
+impl<C> Authenticator<C>
+where
+	C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+	Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+	SignatureSize<C>: ArrayLength<u8>,
+	AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+	FieldBytesSize<C>: ModulusSize,
+{
+	/// Creates the authenticator for the configured clients, or none if writes are not authenticated.
+	pub fn try_new(auth: &DaServiceAuth) -> Result<Option<Self>, anyhow::Error> {
+		if auth.clients.is_empty() {
+			return Ok(None);
+		}
+		let now = Instant::now();
+		let clients = auth
+			.clients
+			.iter()
+			.map(|client| AuthorizedClient::try_new(client, now).map(Arc::new))
+			.collect::<Result<Vec<_>, _>>()?;
+		Ok(Some(Self { clients, token_max_age_ms: auth.token_max_age_ms, _curve: PhantomData }))
+	}
+
+	/// Identifies the client making a request to the method, by its certificate if it presents a known one
+	/// and by its bearer token over the method and the protobuf encoded body of the request otherwise.
+	pub fn authenticate<T>(
+		&self,
+		method: &str,
+		body: &[u8],
+		request: &tonic::Request<T>,
+	) -> Result<Arc<AuthorizedClient>, tonic::Status> {
+		if let Some(certificate) = request.peer_certs().as_ref().and_then(|certs| certs.first()) {
+			let fingerprint = Sha256::digest(certificate.as_ref()).to_vec();
+			if let Some(client) = self
+				.clients
+				.iter()
+				.find(|client| client.certificate_sha256.as_ref() == Some(&fingerprint))
+			{
+				return Ok(client.clone());
+			}
+		}
+
+		let authorization = request
+			.metadata()
+			.get(AUTHORIZATION_METADATA_KEY)
+			.ok_or(tonic::Status::unauthenticated("No known client certificate or bearer token"))?
+			.to_str()
+			.map_err(|_| tonic::Status::unauthenticated("Malformed authorization"))?;
+		let now_ms = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_err(|e| tonic::Status::internal(e.to_string()))?
+			.as_millis() as u64;
+		self.authenticate_token(authorization, method, body, now_ms)
+	}
+
+	fn authenticate_token(
+		&self,
+		authorization: &str,
+		method: &str,
+		body: &[u8],
+		now_ms: u64,
+	) -> Result<Arc<AuthorizedClient>, tonic::Status> {
+		let token = BearerToken::try_from_header_value(authorization)
+			.map_err(|e| tonic::Status::unauthenticated(e.to_string()))?;
+		let client = self
+			.clients
+			.iter()
+			.find(|client| client.name == token.client_name)
+			.ok_or(tonic::Status::unauthenticated("Unknown client"))?;
+		let public_key = client
+			.token_public_key
+			.as_ref()
+			.ok_or(tonic::Status::unauthenticated("Client does not use bearer tokens"))?;
+		if token.timestamp_ms.abs_diff(now_ms) > self.token_max_age_ms {
+			return Err(tonic::Status::unauthenticated("Bearer token has expired"));
+		}
+		token
+			.try_verify::<C>(public_key, method, body)
+			.map_err(|e| tonic::Status::unauthenticated(e.to_string()))?;
+		Ok(client.clone())
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use ecdsa::SigningKey;
+	use k256::Secp256k1;
+	use movement_da_light_node_client::auth::STREAM_READ_FROM_HEIGHT_METHOD;
+	use std::time::Duration;
+
+	fn auth(signing_key: &SigningKey<Secp256k1>) -> DaServiceAuth {
+		DaServiceAuth {
+			tls: None,
+			clients: vec![DaServiceClient {
+				name: "full-node".to_string(),
+				token_public_key_hex: Some(hex::encode(
+					signing_key.verifying_key().to_sec1_bytes(),
+				)),
+				certificate_sha256_hex: None,
+				blobs_per_second: Some(10),
+				bytes_per_second: Some(1000),
+			}],
+			token_max_age_ms: 1000,
+		}
+	}
+
+	#[test]
+	fn test_bearer_authentication() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let authenticator =
+			Authenticator::<Secp256k1>::try_new(&auth(&signing_key))?.expect("clients configured");
+
+		let token =
+			BearerToken::try_sign("full-node", 10_000, BATCH_WRITE_METHOD, &[1], &signing_key)?;
+		let client = authenticator.authenticate_token(
+			&token.to_header_value(),
+			BATCH_WRITE_METHOD,
+			&[1],
+			10_500,
+		)?;
+		assert_eq!(client.name(), "full-node");
+
+		// too old
+		let status = authenticator
+			.authenticate_token(&token.to_header_value(), BATCH_WRITE_METHOD, &[1], 12_000)
+			.unwrap_err();
+		assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+		// replayed with another body or method
+		let header = token.to_header_value();
+		assert!(authenticator
+			.authenticate_token(&header, BATCH_WRITE_METHOD, &[2], 10_000)
+			.is_err());
+		assert!(authenticator
+			.authenticate_token(&header, STREAM_READ_FROM_HEIGHT_METHOD, &[1], 10_000)
+			.is_err());
+
+		// signed by another key
+		let other_key = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let token =
+			BearerToken::try_sign("full-node", 10_000, BATCH_WRITE_METHOD, &[1], &other_key)?;
+		assert!(authenticator
+			.authenticate_token(&token.to_header_value(), BATCH_WRITE_METHOD, &[1], 10_000)
+			.is_err());
+
+		// unknown client
+		let token =
+			BearerToken::try_sign("other-node", 10_000, BATCH_WRITE_METHOD, &[1], &signing_key)?;
+		assert!(authenticator
+			.authenticate_token(&token.to_header_value(), BATCH_WRITE_METHOD, &[1], 10_000)
+			.is_err());
+
+		// no credentials at all
+		let request = tonic::Request::new(());
+		let status = authenticator.authenticate(BATCH_WRITE_METHOD, &[], &request).unwrap_err();
+		assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_quota() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let now = Instant::now();
+		let client = AuthorizedClient::try_new(&auth(&signing_key).clients[0], now)?;
+
+		client.try_consume_at(5, 500, now)?;
+		client.try_consume_at(5, 400, now)?;
+		let status = client.try_consume_at(1, 1, now).unwrap_err();
+		assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+		// a rejected write takes nothing from the quota
+		let now = now + Duration::from_millis(100);
+		assert!(client.try_consume_at(1, 300, now).is_err());
+		client.try_consume_at(1, 100, now)?;
+
+		// a write larger than the quota is rejected even with a full bucket
+		let now = now + Duration::from_secs(1);
+		assert!(client.try_consume_at(1, 3000, now).is_err());
+		client.try_consume_at(1, 1000, now)?;
+
+		Ok(())
+	}
+}
@@ -0,0 +1,21 @@
+use aptos_types::transaction::SignedTransaction;
+use memseq::{Transaction, TransactionInspector};
+
+/// Reads the senders and gas unit prices of the Aptos transactions written to the light node.
+pub struct AptosTransactionInspector;
+
+impl AptosTransactionInspector {
+	fn decode(transaction: &Transaction) -> Option<SignedTransaction> {
+		bcs::from_bytes(transaction.data()).ok()
+	}
+}
+
+impl TransactionInspector for AptosTransactionInspector {
+	fn sender(&self, transaction: &Transaction) -> Option<Vec<u8>> {
+		Self::decode(transaction).map(|transaction| transaction.sender().to_vec())
+	}
+
+	fn gas_unit_price(&self, transaction: &Transaction) -> Option<u64> {
+		Self::decode(transaction).map(|transaction| transaction.gas_unit_price())
+	}
+}
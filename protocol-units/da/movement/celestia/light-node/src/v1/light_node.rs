@@ -2,7 +2,7 @@ use movement_celestia_da_util::config::Config;
 use movement_da_light_node_proto::light_node_service_server::{
 	LightNodeService, LightNodeServiceServer,
 };
-use tonic::transport::Server;
+use tonic::transport::{Server, ServerTlsConfig};
 use tracing::info;
 
 pub trait LightNodeV1Operations: LightNodeService + Send + Sync + Sized + Clone {
@@ -15,6 +15,9 @@ pub trait LightNodeV1Operations: LightNodeService + Send + Sync + Sized + Clone
 	/// Tries to get the service address
 	fn try_service_address(&self) -> Result<String, anyhow::Error>;
 
+	/// Tries to get the TLS configuration of the service, if it uses TLS
+	fn try_tls_config(&self) -> Result<Option<ServerTlsConfig>, anyhow::Error>;
+
 	/// Runs the server
 	async fn run_server(&self) -> Result<(), anyhow::Error> {
 		let reflection = tonic_reflection::server::Builder::configure()
@@ -23,7 +26,12 @@ pub trait LightNodeV1Operations: LightNodeService + Send + Sync + Sized + Clone
 
 		let address = self.try_service_address()?;
 		info!("Server listening on: {}", address);
-		Server::builder()
+		let mut server = Server::builder();
+		if let Some(tls_config) = self.try_tls_config()? {
+			info!("Server using TLS.");
+			server = server.tls_config(tls_config)?;
+		}
+		server
 			.max_frame_size(1024 * 1024 * 16 - 1)
 			.accept_http1(true)
 			.add_service(LightNodeServiceServer::new(self.clone()))
@@ -1,4 +1,8 @@
+pub mod auth;
+#[cfg(feature = "sequencer")]
+pub mod inspector;
 pub mod passthrough;
+pub mod signer;
 #[cfg(feature = "sequencer")]
 pub mod sequencer;
 
@@ -1,23 +1,28 @@
 use movement_celestia_da_util::ir_blob::IntermediateBlobRepresentation;
+use std::collections::BTreeSet;
 use std::fmt::{self, Debug, Formatter};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_stream::{Stream, StreamExt};
-use tracing::{debug, error, info};
-
-use celestia_rpc::{BlobClient, Client, HeaderClient};
-use celestia_types::{nmt::Namespace, Blob as CelestiaBlob, TxConfig};
+use tonic::transport::ServerTlsConfig;
+use tracing::{debug, info, warn};
 
 // FIXME: glob imports are bad style
-use movement_celestia_da_light_node_verifier::{
-	permissioned_signers::Verifier, VerifierOperations,
-};
+use movement_celestia_da_light_node_verifier::stack::{self, Rejections};
 use movement_celestia_da_util::{
-	config::Config,
-	ir_blob::{celestia::CelestiaIntermediateBlobRepresentation, InnerSignedBlobV1Data},
+	config::{local::da_light_node::DaBackend, Config},
+	ir_blob::{BlobChunk, InnerSignedBlobV1Data, InnerSignedBlobV2},
+	signers::{SignerKey, SignerRegistry, SignerSchedule},
 };
 use movement_da_light_node_proto::light_node_service_server::LightNodeService;
 use movement_da_light_node_proto::*;
+use movement_types::block::Block;
+use prost::Message;
+use sequencing_util::{ProposerSchedule, SlotClock};
 
+use crate::da::{self, DaOperations};
+use crate::v1::auth::{self, Authenticator, AuthorizedClient};
+use crate::v1::signer::DaSigner;
 use crate::v1::LightNodeV1Operations;
 use ecdsa::{
 	elliptic_curve::{
@@ -29,9 +34,15 @@ use ecdsa::{
 		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
 	},
 	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
-	SignatureSize, SigningKey,
+	SignatureSize,
 };
 
+/// How often the counts of rejected blobs are logged.
+const REJECTIONS_REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the gas prices of recent Celestia blocks are read, about once per block.
+const GAS_PRICE_REFRESH_INTERVAL: Duration = Duration::from_secs(6);
+
 #[derive(Clone)]
 pub struct LightNodeV1<C>
 where
@@ -42,12 +53,19 @@ where
 	FieldBytesSize<C>: ModulusSize,
 {
 	pub config: Config,
-	pub celestia_namespace: Namespace,
-	pub default_client: Arc<Client>,
-	pub verifier: Arc<
-		Box<dyn VerifierOperations<CelestiaBlob, IntermediateBlobRepresentation> + Send + Sync>,
-	>,
-	pub signing_key: SigningKey<C>,
+	pub da: Arc<Box<dyn DaOperations>>,
+	/// The signer of the blobs this node posts.
+	pub signer: Arc<DaSigner>,
+	/// The rotation of block production between the known signers, if any.
+	pub schedule: Option<Arc<SignerSchedule>>,
+	/// The local blob archive in front of the DA, if enabled.
+	pub archive: Option<da::archive::Da>,
+	/// The authentication of writing clients, if writes are authenticated.
+	pub auth: Option<Arc<Authenticator<C>>>,
+	/// The blobs rejected by the verifier of the DA backend.
+	pub rejections: Arc<Rejections>,
+	/// The Celestia backend, whose gas price estimate is refreshed in the background.
+	pub celestia: Option<da::celestia::Da>,
 }
 
 impl<C> Debug for LightNodeV1<C>
@@ -61,6 +79,7 @@ where
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
 		f.debug_struct("LightNodeV1")
 			.field("celestia_namespace", &self.config.celestia_namespace())
+			.field("rejections", &self.rejections)
 			.finish()
 	}
 }
@@ -75,24 +94,92 @@ where
 {
 	/// Tries to create a new LightNodeV1 instance from the toml config file.
 	async fn try_from_config(config: Config) -> Result<Self, anyhow::Error> {
-		let client = Arc::new(config.connect_celestia().await?);
-
-		let signing_key_str = config.da_signing_key();
-		let hex_bytes = hex::decode(signing_key_str)?;
+		movement_celestia_da_util::config::try_check_env_defaults()?;
+		let signer = Arc::new(DaSigner::try_from_config(&config).await?);
+
+		let registry = config.try_da_signer_registry::<C>()?;
+		let schedule = try_proposer_schedule(&config, &registry)?.map(Arc::new);
+
+		let mut known_signers = registry;
+		// a node signing with a key it does not accept would reject its own blobs
+		let signer_hex = hex::encode(signer.public_key_sec1());
+		if !known_signers.keys().iter().any(|key| key.public_key_hex == signer_hex) {
+			if !config.da_trust_own_signer() {
+				anyhow::bail!(
+					"The DA signing key {} is not one of the known DA signers. Add it to the signers, or set MOVEMENT_DA_TRUST_OWN_SIGNER to trust it at all heights.",
+					signer_hex
+				);
+			}
+			warn!(
+				"Trusting the DA signing key {} at all heights, as it is not a known signer.",
+				signer_hex
+			);
+			known_signers.extend([SignerKey::permanent(signer_hex)]);
+		}
+		info!("Verifying blobs against {} known signer keys.", known_signers.keys().len());
+
+		// keep the rejection counters and the Celestia backend, which the boxed DA no longer exposes
+		let (da, rejections, celestia): (Box<dyn DaOperations>, _, _) = match config.da_backend() {
+			DaBackend::Celestia => {
+				let client = Arc::new(config.connect_celestia().await?);
+				let verifier = stack::celestia_verifier::<C>(
+					config.da_verification(),
+					client.clone(),
+					config.celestia_namespace(),
+					known_signers,
+				);
+				let rejections = verifier.rejections();
+				let da = da::celestia::Da::try_new(
+					client,
+					config.celestia_namespace(),
+					Arc::new(Box::new(verifier)),
+					config.celestia_submission(),
+				)?;
+				(Box::new(da.clone()), rejections, Some(da))
+			}
+			DaBackend::DigestStore { digest_store_path } => {
+				let digest_store_path = digest_store_path.ok_or(anyhow::anyhow!(
+					"Failed to get digest store path from config. This is required for the digest store backend."
+				))?;
+				info!("Using the digest store at {} as the DA backend.", digest_store_path);
+				let verifier = stack::ir_verifier::<C>(config.da_verification(), known_signers)?;
+				let rejections = verifier.rejections();
+				let da =
+					da::digest_store::Da::open(digest_store_path, Arc::new(Box::new(verifier)))?;
+				(Box::new(da), rejections, None)
+			}
+		};
+		info!("Verifying blobs read from the DA with {:?}.", config.da_verification());
+
+		// serve historical heights from the local archive instead of the DA
+		let (da, archive): (Box<dyn DaOperations>, _) = match config.da_archive() {
+			Some(archive_config) => {
+				info!("Using the blob archive at {}.", archive_config.archive_path);
+				let archive = da::archive::Da::open(
+					archive_config.archive_path,
+					da,
+					archive_config.retention_heights,
+					archive_config.floor_height,
+				)?;
+				(Box::new(archive.clone()), Some(archive))
+			}
+			None => (da, None),
+		};
 
-		let signing_key = SigningKey::from_bytes(hex_bytes.as_slice().try_into()?)
-			.map_err(|e| anyhow::anyhow!("Failed to create signing key: {}", e))?;
+		let auth = match config.movement_da_light_node_auth() {
+			Some(service_auth) => Authenticator::try_new(&service_auth)?.map(Arc::new),
+			None => None,
+		};
 
 		Ok(Self {
 			config: config.clone(),
-			celestia_namespace: config.celestia_namespace(),
-			default_client: client.clone(),
-			verifier: Arc::new(Box::new(Verifier::<C>::new(
-				client,
-				config.celestia_namespace(),
-				config.da_signers_sec1_keys(),
-			))),
-			signing_key,
+			da: Arc::new(da),
+			signer,
+			schedule,
+			archive,
+			auth,
+			rejections,
+			celestia,
 		})
 	}
 
@@ -100,12 +187,209 @@ where
 		Ok(self.config.movement_da_light_node_service())
 	}
 
+	fn try_tls_config(&self) -> Result<Option<ServerTlsConfig>, anyhow::Error> {
+		match self
+			.config
+			.movement_da_light_node_auth()
+			.and_then(|service_auth| service_auth.tls)
+		{
+			Some(tls) => Ok(Some(auth::try_server_tls_config(&tls)?)),
+			None => Ok(None),
+		}
+	}
+
 	/// Runs background tasks for the LightNodeV1 instance.
 	async fn run_background_tasks(&self) -> Result<(), anyhow::Error> {
+		let backfill = async {
+			if let (Some(archive), Some(archive_config)) = (&self.archive, self.config.da_archive())
+			{
+				archive
+					.run_backfill(Duration::from_millis(archive_config.backfill_interval_ms))
+					.await?;
+			}
+			Ok::<_, anyhow::Error>(())
+		};
+		let gas_prices = async {
+			if let Some(celestia) = &self.celestia {
+				celestia.run_gas_price_refresh(GAS_PRICE_REFRESH_INTERVAL).await?;
+			}
+			Ok::<_, anyhow::Error>(())
+		};
+		futures::try_join!(backfill, gas_prices, self.report_rejections())?;
+
 		Ok(())
 	}
 }
 
+/// Builds the schedule over the known DA signers when block production rotates between them.
+///
+/// Rotation only applies to sequenced blocks, so pass through nodes never filter blobs.
+fn try_proposer_schedule(
+	config: &Config,
+	registry: &SignerRegistry,
+) -> Result<Option<SignerSchedule>, anyhow::Error> {
+	if !cfg!(feature = "sequencer") {
+		return Ok(None);
+	}
+
+	let schedule = SignerSchedule::try_from_rotation(registry, &config.proposer_rotation())?;
+	if let Some(schedule) = &schedule {
+		info!(
+			"Rotating block production between {} scheduled signer keys with {:?}",
+			registry.keys().len(),
+			schedule.clock()
+		);
+	}
+	Ok(schedule)
+}
+
+/// How many DA heights the chunks of a blob may span.
+///
+/// The sequencer posts one chunk at a time, so at worst each chunk lands at its own height.
+const CHUNK_WINDOW_HEIGHTS: u64 = 64;
+
+/// The most bytes of chunks which are held for chunked blobs that are not complete yet.
+const MAX_PENDING_CHUNK_BYTES: usize = 64 * 1024 * 1024;
+
+/// The chunks of a chunked blob seen so far, with the blob of its first chunk and the height it landed at.
+struct PendingChunks {
+	first: Blob,
+	first_height: u64,
+	chunks: Vec<BlobChunk>,
+}
+
+impl PendingChunks {
+	fn is_complete(&self) -> bool {
+		let indices = self.chunks.iter().map(|chunk| chunk.index).collect::<BTreeSet<_>>();
+		indices.len() == self.chunks[0].total as usize
+	}
+
+	fn size(&self) -> usize {
+		self.chunks.iter().map(|chunk| chunk.data.len()).sum()
+	}
+}
+
+/// Reassembles chunked blobs from the blobs of consecutive heights.
+///
+/// Each chunk is posted in its own transaction, so the chunks of a blob can land at different heights.
+/// A reassembled blob is placed at the height and position of its last chunk.
+/// Sets which do not complete within [CHUNK_WINDOW_HEIGHTS] of their first chunk are dropped,
+/// and chunks which would hold more than [MAX_PENDING_CHUNK_BYTES] are dropped.
+#[derive(Default)]
+pub(crate) struct ChunkReassembler {
+	pending: Vec<PendingChunks>,
+	pending_bytes: usize,
+}
+
+impl ChunkReassembler {
+	/// Takes the blobs of the next height, returning its whole blobs and the chunked blobs which completed at it.
+	pub(crate) fn push_height(&mut self, blobs: Vec<Blob>, height: u64) -> Vec<Blob> {
+		self.evict_before(height);
+
+		let mut whole_blobs = Vec::with_capacity(blobs.len());
+		for blob in blobs {
+			let chunk = match BlobChunk::try_from_bytes(&blob.data) {
+				None => {
+					whole_blobs.push(blob);
+					continue;
+				}
+				Some(Ok(chunk)) => chunk,
+				Some(Err(e)) => {
+					info!(
+						"Dropping blob {} at height {}: {}",
+						hex::encode(&blob.blob_id),
+						height,
+						e
+					);
+					continue;
+				}
+			};
+			if self.pending_bytes + chunk.data.len() > MAX_PENDING_CHUNK_BYTES {
+				info!(
+					"Dropping chunk of blob {} at height {}: too many pending chunks",
+					hex::encode(&chunk.blob_id),
+					height
+				);
+				continue;
+			}
+			self.pending_bytes += chunk.data.len();
+
+			// chunks only combine with chunks of the same signer
+			let position = self.pending.iter().position(|pending| {
+				pending.first.signer == blob.signer && pending.chunks[0].blob_id == chunk.blob_id
+			});
+			let position = match position {
+				Some(position) => {
+					self.pending[position].chunks.push(chunk);
+					position
+				}
+				None => {
+					let first = Blob { data: Vec::new(), ..blob };
+					self.pending.push(PendingChunks {
+						first,
+						first_height: height,
+						chunks: vec![chunk],
+					});
+					self.pending.len() - 1
+				}
+			};
+
+			if self.pending[position].is_complete() {
+				let pending = self.pending.remove(position);
+				self.pending_bytes -= pending.size();
+				if let Some(blob) = Self::reassemble(pending, height) {
+					whole_blobs.push(blob);
+				}
+			}
+		}
+
+		whole_blobs
+	}
+
+	/// Drops the sets whose first chunk is out of the window for the height.
+	fn evict_before(&mut self, height: u64) {
+		let mut evicted_bytes = 0;
+		self.pending.retain(|pending| {
+			if height.saturating_sub(pending.first_height) < CHUNK_WINDOW_HEIGHTS {
+				return true;
+			}
+			info!(
+				"Dropping chunked blob {} from height {}: incomplete at height {}",
+				hex::encode(&pending.chunks[0].blob_id),
+				pending.first_height,
+				height
+			);
+			evicted_bytes += pending.size();
+			false
+		});
+		self.pending_bytes -= evicted_bytes;
+	}
+
+	fn reassemble(pending: PendingChunks, height: u64) -> Option<Blob> {
+		let blob_id = pending.chunks[0].blob_id.clone();
+		let data = match BlobChunk::reassemble(pending.chunks) {
+			Ok(data) => data,
+			Err(e) => {
+				info!("Dropping chunked blob at height {}: {}", height, e);
+				return None;
+			}
+		};
+		// only blocks are chunked, and a chunked block must hash to the id its chunks claim
+		let matches_id = bcs::from_bytes::<Block>(&data)
+			.map(|block| block.id().as_bytes().as_slice() == blob_id.as_slice())
+			.unwrap_or(false);
+		if !matches_id {
+			info!(
+				"Dropping chunked blob at height {}: chunks do not form block {}",
+				height,
+				hex::encode(&blob_id)
+			);
+			return None;
+		}
+		Some(Blob { data, blob_id, height, ..pending.first })
+	}
+}
+
 impl<C> LightNodeV1<C>
 where
 	C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
@@ -114,50 +398,83 @@ where
 	AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
 	FieldBytesSize<C>: ModulusSize,
 {
+	/// Logs the blobs the verifier has rejected whenever the counts change.
+	async fn report_rejections(&self) -> Result<(), anyhow::Error> {
+		let mut interval = tokio::time::interval(REJECTIONS_REPORT_INTERVAL);
+		let mut reported = (0, 0);
+		loop {
+			interval.tick().await;
+			let counts = (self.rejections.validation(), self.rejections.internal());
+			if counts != reported {
+				info!(
+					validation = counts.0,
+					internal = counts.1,
+					"The DA verifier has rejected {} blobs in total.",
+					counts.0 + counts.1
+				);
+				reported = counts;
+			}
+		}
+	}
+
 	/// Creates a new signed blob instance with the provided data.
-	pub fn create_new_celestia_blob(&self, data: Vec<u8>) -> Result<CelestiaBlob, anyhow::Error> {
+	pub async fn create_new_ir_blob(
+		&self,
+		data: Vec<u8>,
+	) -> Result<IntermediateBlobRepresentation, anyhow::Error> {
 		// mark the timestamp as now in milliseconds
 		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
 
 		// sign the blob data and the timestamp
-		let data = InnerSignedBlobV1Data::new(data, timestamp).try_to_sign(&self.signing_key)?;
+		let data = InnerSignedBlobV1Data::new(data, timestamp)
+			.try_to_sign_with(self.signer.as_ref())
+			.await?;
 
-		// create the celestia blob
-		CelestiaIntermediateBlobRepresentation(data.into(), self.celestia_namespace.clone())
-			.try_into()
+		Ok(InnerSignedBlobV2::try_new_with(data, self.config.da_blob_codec(), self.signer.as_ref())
+			.await?
+			.into())
 	}
 
-	/// Submits a CelestiaBlob to the Celestia node.
-	pub async fn submit_celestia_blob(&self, blob: CelestiaBlob) -> Result<u64, anyhow::Error> {
-		let config = TxConfig::default();
-		// config.with_gas(2);
-		let height = self.default_client.blob_submit(&[blob], config).await.map_err(|e| {
-			error!(error = %e, "failed to submit the blob");
-			anyhow::anyhow!("Failed submitting the blob: {}", e)
-		})?;
+	/// Identifies the client writing with the request to the method, if writes are authenticated.
+	pub fn authenticate_writer<T>(
+		&self,
+		method: &str,
+		body: &[u8],
+		request: &tonic::Request<T>,
+	) -> Result<Option<Arc<AuthorizedClient>>, tonic::Status> {
+		match &self.auth {
+			Some(auth) => Ok(Some(auth.authenticate(method, body, request)?)),
+			None => Ok(None),
+		}
+	}
 
-		Ok(height)
+	/// Authenticates a write of blobs and takes them from the quota of the writing client.
+	pub fn authorize_write(
+		&self,
+		request: &tonic::Request<BatchWriteRequest>,
+	) -> Result<(), tonic::Status> {
+		let body = request.get_ref().encode_to_vec();
+		if let Some(client) = self.authenticate_writer(auth::BATCH_WRITE_METHOD, &body, request)? {
+			let blobs = &request.get_ref().blobs;
+			let bytes = blobs.iter().map(|blob| blob.data.len() as u64).sum();
+			client.try_consume(blobs.len() as u64, bytes)?;
+		}
+		Ok(())
 	}
 
-	/// Submits Celestia blobs to the Celestia node.
-	pub async fn submit_celestia_blobs(
+	/// Submits blobs to the DA.
+	pub async fn submit_ir_blobs(
 		&self,
-		blobs: &[CelestiaBlob],
+		blobs: Vec<IntermediateBlobRepresentation>,
 	) -> Result<u64, anyhow::Error> {
-		let height =
-			self.default_client.blob_submit(blobs, TxConfig::default()).await.map_err(|e| {
-				error!(error = %e, "failed to submit the blobs");
-				anyhow::anyhow!("Failed submitting the blob: {}", e)
-			})?;
-
-		Ok(height)
+		self.da.submit_blobs(blobs).await
 	}
 
-	/// Submits a blob to the Celestia node.
+	/// Submits a blob to the DA.
 	pub async fn submit_blob(&self, data: Vec<u8>) -> Result<Blob, anyhow::Error> {
-		let celestia_blob = self.create_new_celestia_blob(data)?;
-		let height = self.submit_celestia_blob(celestia_blob.clone()).await?;
-		Ok(Self::celestia_blob_to_blob(celestia_blob, height)?)
+		let ir_blob = self.create_new_ir_blob(data).await?;
+		let height = self.submit_ir_blobs(vec![ir_blob.clone()]).await?;
+		Self::ir_blob_to_blob(ir_blob, height)
 	}
 
 	/// Gets the blobs at a given height.
@@ -165,44 +482,125 @@ where
 		&self,
 		height: u64,
 	) -> Result<Vec<IntermediateBlobRepresentation>, anyhow::Error> {
-		let height = if height == 0 { 1 } else { height };
-		match self.default_client.blob_get_all(height, &[self.celestia_namespace]).await {
-			Err(e) => {
-				error!(error = %e, "failed to get blobs at height {height}");
-				anyhow::bail!(e);
-			}
-			Ok(blobs) => {
-				let blobs = blobs.unwrap_or_default();
+		self.da.get_ir_blobs_at_height(height).await
+	}
 
-				let mut verified_blobs = Vec::new();
-				for blob in blobs {
-					match self.verifier.verify(blob, height).await {
-						Ok(verified_blob) => {
-							let blob = verified_blob.into_inner();
-							info!("verified blob at height {}: {}", height, hex::encode(blob.id()));
-							verified_blobs.push(blob);
-						}
-						Err(e) => {
-							error!(error = %e, "failed to verify blob");
-						}
-					}
+	/// Gets the proposer slot of a DA height.
+	///
+	/// Time slots are judged by the time of the DA at the height, as the blob timestamps are chosen by their signers.
+	pub(crate) async fn slot_at_height(
+		&self,
+		schedule: &ProposerSchedule,
+		height: u64,
+	) -> Result<u64, anyhow::Error> {
+		let da_time = match schedule.clock() {
+			SlotClock::Time { .. } => self.da.get_height_timestamp(height).await?,
+			SlotClock::DaHeight { .. } => 0,
+		};
+		Ok(schedule.slot(height, da_time))
+	}
+
+	/// Whether the signer, in its sec1 encoding, is the proposer of the slot of a DA height.
+	///
+	/// Only the signers active at the height take turns, so the turns change along with the signer registry.
+	pub(crate) async fn is_proposer_at_height(
+		&self,
+		schedule: &SignerSchedule,
+		signer: &[u8],
+		height: u64,
+	) -> Result<bool, anyhow::Error> {
+		let schedule = match schedule.at_height(height) {
+			Some(schedule) => schedule,
+			None => return Ok(false),
+		};
+		let slot = self.slot_at_height(&schedule, height).await?;
+		Ok(schedule.is_proposer_for_slot(signer, slot))
+	}
+
+	/// Gets the verified blobs at a height as they were posted, without reassembling chunks.
+	async fn get_posted_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
+		self.get_ir_blobs_at_height(height)
+			.await?
+			.into_iter()
+			.map(|ir_blob| Self::ir_blob_to_blob(ir_blob, height))
+			.collect()
+	}
+
+	/// Drops the blobs whose signer is not the proposer of the slot of the height they are placed at.
+	async fn filter_scheduled(
+		&self,
+		blobs: Vec<Blob>,
+		height: u64,
+	) -> Result<Vec<Blob>, anyhow::Error> {
+		// followers only accept blocks from the proposer of the slot they landed in
+		let schedule = match (&self.schedule, blobs.is_empty()) {
+			(Some(schedule), false) => schedule,
+			_ => return Ok(blobs),
+		};
+		// only the signers active at the height take turns
+		let schedule = schedule.at_height(height);
+		let slot = match &schedule {
+			Some(schedule) => self.slot_at_height(schedule, height).await?,
+			None => 0,
+		};
+		Ok(blobs
+			.into_iter()
+			.filter(|blob| {
+				let is_proposer = schedule
+					.as_ref()
+					.map_or(false, |schedule| schedule.is_proposer_for_slot(&blob.signer, slot));
+				if !is_proposer {
+					info!(
+						"Dropping blob {} at height {}: signer {} is not the proposer for slot {}",
+						hex::encode(&blob.blob_id),
+						height,
+						hex::encode(&blob.signer),
+						slot
+					);
 				}
+				is_proposer
+			})
+			.collect())
+	}
 
-				Ok(verified_blobs)
+	/// Feeds the heights of the chunk window before a height to the reassembler,
+	/// so that chunked blobs which started before it can complete.
+	async fn prime_reassembler(&self, reassembler: &mut ChunkReassembler, height: u64) {
+		for earlier in height.saturating_sub(CHUNK_WINDOW_HEIGHTS - 1)..height {
+			match self.get_posted_blobs_at_height(earlier).await {
+				Ok(blobs) => {
+					reassembler.push_height(blobs, earlier);
+				}
+				Err(e) => warn!("Not reassembling chunks from height {}: {}", earlier, e),
 			}
 		}
 	}
 
+	/// Gets the blobs placed at the next height of a reassembler.
+	async fn next_blobs_at_height(
+		&self,
+		reassembler: &mut ChunkReassembler,
+		height: u64,
+	) -> Result<Vec<Blob>, anyhow::Error> {
+		let blobs = self.get_posted_blobs_at_height(height).await?;
+		let blobs = reassembler.push_height(blobs, height);
+		self.filter_scheduled(blobs, height).await
+	}
+
+	/// Gets the blobs placed at a height, where a chunked blob is placed at the height of its last chunk.
 	#[tracing::instrument(target = "movement_timing", level = "info", skip(self))]
-	async fn get_blobs_at_height(&self, height: u64) -> Result<Vec<Blob>, anyhow::Error> {
-		let ir_blobs = self.get_ir_blobs_at_height(height).await?;
-		let mut blobs = Vec::new();
-		for ir_blob in ir_blobs {
-			let blob = Self::ir_blob_to_blob(ir_blob, height)?;
-			// todo: update logging here
-			blobs.push(blob);
+	pub(crate) async fn get_blobs_at_height(
+		&self,
+		height: u64,
+	) -> Result<Vec<Blob>, anyhow::Error> {
+		let blobs = self.get_posted_blobs_at_height(height).await?;
+		let mut reassembler = ChunkReassembler::default();
+		// only chunks at this height can complete a chunked blob here
+		if blobs.iter().any(|blob| BlobChunk::try_from_bytes(&blob.data).is_some()) {
+			self.prime_reassembler(&mut reassembler, height).await;
 		}
-		Ok(blobs)
+		let blobs = reassembler.push_height(blobs, height);
+		self.filter_scheduled(blobs, height).await
 	}
 
 	/// Streams blobs until it can't get another one in the loop
@@ -219,12 +617,14 @@ where
 		let me = Arc::new(self.clone());
 
 		let stream = async_stream::try_stream! {
+			let mut reassembler = ChunkReassembler::default();
+			me.prime_reassembler(&mut reassembler, start_height).await;
 			loop {
 				if height > end_height {
 					break;
 				}
 
-				let blobs = me.get_blobs_at_height(height).await?;
+				let blobs = me.next_blobs_at_height(&mut reassembler, height).await?;
 				for blob in blobs {
 					yield blob;
 				}
@@ -237,7 +637,7 @@ where
 	}
 
 	/// Streams the latest blobs that can subscribed to.
-	async fn stream_blobs_from_height_on(
+	pub(crate) async fn stream_blobs_from_height_on(
 		&self,
 		start_height: Option<u64>,
 	) -> Result<
@@ -246,39 +646,37 @@ where
 	> {
 		let start_height = start_height.unwrap_or_else(|| u64::MAX);
 		let me = Arc::new(self.clone());
-		let mut subscription = me.default_client.header_subscribe().await?;
+		let mut subscription = me.da.stream_heights().await?;
 
 		let stream = async_stream::try_stream! {
-			let mut first_flag = true;
-			while let Some(header_res) = subscription.next().await {
-
-				let header = header_res?;
-				let height = header.height().into();
-
-				info!("Stream got header: {:?}", header.height());
-
-				// back fetch the blobs
-				if first_flag && (height > start_height) {
-
-					let mut blob_stream = me.stream_blobs_in_range(start_height, Some(height)).await?;
-
-					while let Some(blob) = blob_stream.next().await {
+			// heights are read in order, so that chunks landing at consecutive heights are reassembled
+			let mut reassembler = ChunkReassembler::default();
+			let mut next_height = None;
+			while let Some(height) = subscription.next().await {
+
+				let height = height?;
+
+				info!("Stream got height: {:?}", height);
+
+				// back fetch the blobs from the start height
+				let from = match next_height {
+					Some(next_height) => next_height,
+					None => {
+						let from = start_height.min(height);
+						me.prime_reassembler(&mut reassembler, from).await;
+						from
+					}
+				};
+				for at in from..=height {
+					let blobs = me.next_blobs_at_height(&mut reassembler, at).await?;
+					for blob in blobs {
 
 						debug!("Stream got blob: {:?}", blob);
 
-						yield blob?;
+						yield blob;
 					}
-
-				}
-				first_flag = false;
-
-				let blobs = me.get_blobs_at_height(height).await?;
-				for blob in blobs {
-
-					debug!("Stream got blob: {:?}", blob);
-
-					yield blob;
 				}
+				next_height = Some(from.max(height + 1));
 			}
 		};
 
@@ -300,19 +698,6 @@ where
 		})
 	}
 
-	pub fn celestia_blob_to_blob(blob: CelestiaBlob, height: u64) -> Result<Blob, anyhow::Error> {
-		let ir_blob: IntermediateBlobRepresentation = blob.try_into()?;
-
-		Ok(Blob {
-			data: ir_blob.blob().to_vec(),
-			signature: ir_blob.signature().to_vec(),
-			timestamp: ir_blob.timestamp(),
-			signer: ir_blob.signer().to_vec(),
-			blob_id: ir_blob.id().to_vec(),
-			height,
-		})
-	}
-
 	pub fn blob_to_blob_write_response(blob: Blob) -> Result<BlobResponse, anyhow::Error> {
 		Ok(BlobResponse { blob_type: Some(blob_response::BlobType::PassedThroughBlob(blob)) })
 	}
@@ -411,6 +796,8 @@ where
 		&self,
 		request: tonic::Request<tonic::Streaming<StreamWriteBlobRequest>>,
 	) -> std::result::Result<tonic::Response<Self::StreamWriteBlobStream>, tonic::Status> {
+		// the blobs are streamed after the request is authenticated, so the token is over an empty body
+		let client = self.authenticate_writer(auth::STREAM_WRITE_BLOB_METHOD, &[], &request)?;
 		let mut stream = request.into_inner();
 		let me = Arc::new(self.clone());
 
@@ -419,6 +806,9 @@ where
 			while let Some(request) = stream.next().await {
 				let request = request?;
 				let blob_data = request.blob.ok_or(tonic::Status::invalid_argument("No blob in request"))?.data;
+				if let Some(client) = &client {
+					client.try_consume(1, blob_data.len() as u64)?;
+				}
 
 				let blob = me.submit_blob(blob_data).await.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
@@ -497,6 +887,7 @@ where
 		&self,
 		request: tonic::Request<BatchWriteRequest>,
 	) -> std::result::Result<tonic::Response<BatchWriteResponse>, tonic::Status> {
+		self.authorize_write(&request)?;
 		let blobs = request.into_inner().blobs;
 		let mut responses = Vec::with_capacity(blobs.len());
 		for data in blobs {
@@ -515,6 +906,141 @@ where
 			);
 		}
 
-		Ok(tonic::Response::new(BatchWriteResponse { blobs: blob_responses }))
+		Ok(tonic::Response::new(BatchWriteResponse { blobs: blob_responses, rejected: vec![] }))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use movement_types::{
+		block::{BlockMetadata, Id},
+		transaction::Transaction,
+	};
+
+	fn blob(data: Vec<u8>, signer: Vec<u8>) -> Blob {
+		Blob { data, signature: vec![], timestamp: 0, signer, blob_id: vec![], height: 1 }
+	}
+
+	fn chunked_block(signer: &[u8]) -> Result<(Block, Vec<Blob>), anyhow::Error> {
+		let transactions = (0..8).map(|i| Transaction::new(vec![i; 64], 0, i as u64));
+		let block = Block::new(
+			BlockMetadata::new(0, signer.to_vec(), 1),
+			Id::genesis_block(),
+			BTreeSet::from_iter(transactions),
+		);
+		let chunks = BlobChunk::split(block.id().to_vec(), &bcs::to_bytes(&block)?, 128);
+		let blobs = chunks
+			.iter()
+			.map(|chunk| Ok(blob(chunk.try_to_bytes()?, signer.to_vec())))
+			.collect::<Result<Vec<_>, anyhow::Error>>()?;
+		Ok((block, blobs))
+	}
+
+	fn reassemble(blobs: Vec<Blob>, height: u64) -> Vec<Blob> {
+		ChunkReassembler::default().push_height(blobs, height)
+	}
+
+	#[test]
+	fn test_reassembles_chunks_in_place() -> Result<(), anyhow::Error> {
+		let (block, chunk_blobs) = chunked_block(&[1])?;
+		assert!(chunk_blobs.len() > 2);
+
+		let mut blobs = vec![blob(vec![1], vec![1])];
+		blobs.extend(chunk_blobs.into_iter().rev());
+		blobs.push(blob(vec![2], vec![1]));
+
+		let reassembled = reassemble(blobs, 1);
+		assert_eq!(reassembled.len(), 3);
+		assert_eq!(reassembled[0].data, vec![1]);
+		assert_eq!(bcs::from_bytes::<Block>(&reassembled[1].data)?, block);
+		assert_eq!(reassembled[1].blob_id, block.id().to_vec());
+		assert_eq!(reassembled[2].data, vec![2]);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_reassembles_chunks_across_heights() -> Result<(), anyhow::Error> {
+		let (block, chunk_blobs) = chunked_block(&[1])?;
+		let mut reassembler = ChunkReassembler::default();
+
+		// the block is placed at the height of its last chunk, after the blobs before it
+		let last_height = chunk_blobs.len() as u64;
+		for (height, chunk_blob) in (1..).zip(chunk_blobs) {
+			let blobs = reassembler.push_height(vec![blob(vec![3], vec![1]), chunk_blob], height);
+			if height < last_height {
+				assert_eq!(blobs.len(), 1);
+				continue;
+			}
+			assert_eq!(blobs.len(), 2);
+			assert_eq!(bcs::from_bytes::<Block>(&blobs[1].data)?, block);
+			assert_eq!(blobs[1].height, last_height);
+		}
+		assert!(reassembler.pending.is_empty());
+		assert_eq!(reassembler.pending_bytes, 0);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_evicts_chunks_out_of_the_window() -> Result<(), anyhow::Error> {
+		let (_, mut chunk_blobs) = chunked_block(&[1])?;
+		let last = chunk_blobs.pop().unwrap();
+		let mut reassembler = ChunkReassembler::default();
+
+		assert!(reassembler.push_height(chunk_blobs, 1).is_empty());
+		assert!(reassembler.pending_bytes > 0);
+		assert!(reassembler.push_height(vec![last], 1 + CHUNK_WINDOW_HEIGHTS).is_empty());
+		// the last chunk starts a new set, which holds only its own bytes
+		assert_eq!(reassembler.pending.len(), 1);
+		assert_eq!(reassembler.pending[0].first_height, 1 + CHUNK_WINDOW_HEIGHTS);
+
+		// chunks beyond the pending limit are dropped
+		let mut reassembler = ChunkReassembler::default();
+		reassembler.pending_bytes = MAX_PENDING_CHUNK_BYTES;
+		let (_, chunk_blobs) = chunked_block(&[1])?;
+		assert!(reassembler.push_height(chunk_blobs, 1).is_empty());
+		assert!(reassembler.pending.is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_rejects_incomplete_and_conflicting_chunks() -> Result<(), anyhow::Error> {
+		// incomplete
+		let (_, mut chunk_blobs) = chunked_block(&[1])?;
+		chunk_blobs.pop();
+		assert!(reassemble(chunk_blobs, 1).is_empty());
+
+		// conflicting
+		let (_, chunk_blobs) = chunked_block(&[1])?;
+		let mut chunk = BlobChunk::try_from_bytes(&chunk_blobs[0].data).unwrap()?;
+		chunk.data[0] ^= 1;
+		let mut conflicting = vec![blob(chunk.try_to_bytes()?, vec![1])];
+		conflicting.extend(chunk_blobs);
+		assert!(reassemble(conflicting, 1).is_empty());
+
+		// consistent chunks which do not form the claimed block
+		let (_, chunk_blobs) = chunked_block(&[1])?;
+		let mut forged = Vec::new();
+		for chunk_blob in chunk_blobs {
+			let mut chunk = BlobChunk::try_from_bytes(&chunk_blob.data).unwrap()?;
+			if chunk.index == 0 {
+				chunk.data[0] ^= 1;
+			}
+			forged.push(blob(chunk.try_to_bytes()?, vec![1]));
+		}
+		assert!(reassemble(forged, 1).is_empty());
+
+		// chunks from another signer do not complete a set
+		let (_, mut chunk_blobs) = chunked_block(&[1])?;
+		if let Some(last) = chunk_blobs.last_mut() {
+			last.signer = vec![2];
+		}
+		assert!(reassemble(chunk_blobs, 1).is_empty());
+
+		Ok(())
 	}
 }
@@ -12,38 +12,58 @@ use ecdsa::{
 	SignatureSize,
 };
 use movement_celestia_da_light_node_prevalidator::{
-	aptos::whitelist::Validator, PrevalidatorOperations,
+	aptos::stack::Validator, PrevalidatorOperations,
 };
 use std::boxed::Box;
 use std::fmt::Debug;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::{atomic::AtomicU64, Arc};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use tokio::{
 	sync::mpsc::{Receiver, Sender},
 	time::timeout,
 };
-use tokio_stream::Stream;
-use tracing::{debug, info};
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::ServerTlsConfig;
+use tracing::{debug, info, warn};
 
-use celestia_rpc::HeaderClient;
 use memseq::{Sequencer, Transaction};
 use movement_algs::grouping_heuristic::{
 	apply::ToApply, binpacking::FirstFitBinpacking, drop_success::DropSuccess, skip::SkipFor,
 	splitting::Splitting, GroupingHeuristicStack, GroupingOutcome,
 };
-use movement_celestia_da_util::config::Config;
+use movement_celestia_da_util::{
+	config::Config,
+	ir_blob::{BlobChunk, MAX_BLOB_DATA_SIZE},
+	signers::SignerSchedule,
+};
 use movement_da_light_node_proto as grpc;
 use movement_da_light_node_proto::blob_response::BlobType;
 use movement_da_light_node_proto::light_node_service_server::LightNodeService;
-use movement_types::block::Block;
+use movement_types::{block::Block, intent::SequencedIntent, transaction};
+use sequencing_util::SlotClock;
 
-use crate::v1::{passthrough::LightNodeV1 as LightNodeV1PassThrough, LightNodeV1Operations};
+use crate::v1::{
+	inspector::AptosTransactionInspector, passthrough::LightNodeV1 as LightNodeV1PassThrough,
+	LightNodeV1Operations,
+};
 
 const LOGGING_UID: AtomicU64 = AtomicU64::new(0);
 
+/// The largest blob the sequencer posts, below the Celestia blob limit.
+const MAX_BLOB_SIZE: usize = 1_700_000;
+
+/// How many DA heights below the head are searched for the last posted block on startup.
+const PARENT_LOOKBACK_HEIGHTS: u64 = 1_000;
+
+/// How long the startup search for the last posted block may take before the chain starts over.
+const PARENT_LOOKBACK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long to wait before following the DA again after the parent sync failed.
+const PARENT_SYNC_RETRY: Duration = Duration::from_secs(1);
+
 #[derive(Clone)]
 pub struct LightNodeV1<C>
 where
@@ -89,19 +109,39 @@ where
 		info!("Memseq path: {:?}", memseq_path);
 		let (max_block_size, build_time) = pass_through.config.try_block_building_parameters()?;
 
-		let memseq = Arc::new(memseq::Memseq::try_move_rocks(
-			PathBuf::from(memseq_path),
-			max_block_size,
-			build_time,
-		)?);
+		let memseq = Arc::new(
+			memseq::Memseq::try_move_rocks(PathBuf::from(memseq_path), max_block_size, build_time)?
+				.with_proposer(pass_through.signer.public_key_sec1())
+				.with_block_policy(config.block_policy())
+				.with_inspector(Arc::new(AptosTransactionInspector)),
+		);
 		info!("Initialized Memseq with Move Rocks for LightNodeV1 in sequencer mode.");
 
+		if let Some(schedule) = &pass_through.schedule {
+			if !schedule.contains(memseq.proposer()) {
+				anyhow::bail!("The signing key of this node is not one of the known DA signers, so it can never propose a block.");
+			}
+			if let SlotClock::Time { slot_ms } = schedule.clock() {
+				// a block is built and then published within up to twice the building time
+				if slot_ms <= build_time * 4 {
+					anyhow::bail!(
+						"Proposer slots of {}ms are too short for a block building time of {}ms.",
+						slot_ms,
+						build_time
+					);
+				}
+			}
+		}
+
 		// prevalidator
-		let whitelisted_accounts = config.whitelisted_accounts()?;
-		let prevalidator = match whitelisted_accounts {
-			Some(whitelisted_accounts) => Some(Arc::new(Validator::new(whitelisted_accounts))),
-			None => None,
-		};
+		let prevalidator = Validator::try_from_prevalidators(
+			&config.da_prevalidators(),
+			config.whitelisted_accounts()?,
+		)
+		.map_err(|e| anyhow::anyhow!("Failed to build the prevalidator: {}", e))?;
+		info!("Prevalidating written transactions with {:?}.", config.da_prevalidators());
+		let prevalidator =
+			if prevalidator.is_empty() { None } else { Some(Arc::new(prevalidator)) };
 
 		Ok(Self { pass_through, memseq, prevalidator })
 	}
@@ -110,8 +150,12 @@ where
 		self.pass_through.try_service_address()
 	}
 
+	fn try_tls_config(&self) -> Result<Option<ServerTlsConfig>, anyhow::Error> {
+		self.pass_through.try_tls_config()
+	}
+
 	async fn run_background_tasks(&self) -> Result<(), anyhow::Error> {
-		self.run_block_proposer().await?;
+		futures::try_join!(self.run_block_proposer(), self.pass_through.run_background_tasks())?;
 
 		Ok(())
 	}
@@ -128,6 +172,11 @@ where
 	async fn tick_build_blocks(&self, sender: Sender<Block>) -> Result<(), anyhow::Error> {
 		let memseq = self.memseq.clone();
 
+		// only build blocks in our own slots
+		if let Some(schedule) = &self.pass_through.schedule {
+			self.wait_for_slot(schedule).await?;
+		}
+
 		// this has an internal timeout based on its building time
 		// so in the worst case scenario we will roughly double the internal timeout
 		let uid = LOGGING_UID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -147,21 +196,209 @@ where
 		}
 	}
 
+	/// Waits until this node is the proposer of the current slot,
+	/// with enough of the slot left for the next block to land in it.
+	async fn wait_for_slot(&self, schedule: &SignerSchedule) -> Result<(), anyhow::Error> {
+		let proposer = self.memseq.proposer();
+		let building_time_ms = self.memseq.building_time_ms();
+		loop {
+			// the next blob lands at the earliest on the height after the head
+			let head = self.pass_through.da.get_head_height().await?;
+			let wait = match (schedule.at_height(head + 1), schedule.clock()) {
+				// no signer is active at the height, so wait for the registry to move on
+				(None, SlotClock::Time { slot_ms }) => Duration::from_millis(slot_ms),
+				(None, SlotClock::DaHeight { .. }) => Duration::from_millis(building_time_ms),
+				(Some(schedule), SlotClock::Time { slot_ms }) => {
+					let now = chrono::Utc::now().timestamp_micros() as u64;
+					let slot = schedule.slot(0, now);
+					// the blob lands at a DA height timed up to twice the building time from now
+					let published = now + building_time_ms * 2 * 1000;
+					if schedule.is_proposer_for_slot(proposer, slot)
+						&& schedule.slot(0, published) == slot
+					{
+						return Ok(());
+					}
+					// this node may not be active at the height, in which case the signers can change by the next slot
+					let next_start = match schedule.next_slot_for(proposer, slot + 1) {
+						Some(next_slot) => schedule
+							.slot_start_micros(next_slot)
+							.ok_or(anyhow::anyhow!("Slot has no start time"))?,
+						None => now + slot_ms * 1000,
+					};
+					Duration::from_micros(next_start.saturating_sub(now))
+				}
+				(Some(schedule), SlotClock::DaHeight { .. }) => {
+					if schedule.is_proposer_for_slot(proposer, schedule.slot(head + 1, 0)) {
+						return Ok(());
+					}
+					Duration::from_millis(building_time_ms)
+				}
+			};
+			debug!(target: "movement_timing", wait_ms = wait.as_millis() as u64, "waiting_for_proposer_slot");
+			tokio::time::sleep(wait).await;
+		}
+	}
+
+	/// Follows the blocks of the other proposers, so that the next block built here extends them.
+	async fn run_parent_sync(&self) -> Result<(), anyhow::Error> {
+		if self.pass_through.schedule.is_none() {
+			return Ok(());
+		}
+
+		// a DA error only interrupts following, so log it and subscribe again
+		loop {
+			if let Err(e) = self.follow_parent_blocks().await {
+				warn!("Parent sync failed, resubscribing: {:?}", e);
+			}
+			tokio::time::sleep(PARENT_SYNC_RETRY).await;
+		}
+	}
+
+	/// Observes the blocks of the other proposers from the DA head on, until the stream ends or fails.
+	async fn follow_parent_blocks(&self) -> Result<(), anyhow::Error> {
+		let head = self.pass_through.da.get_head_height().await?;
+		let mut blobs = self.pass_through.stream_blobs_from_height_on(Some(head)).await?;
+		while let Some(blob) = blobs.next().await {
+			let blob = blob?;
+			if blob.signer.as_slice() == self.memseq.proposer() {
+				continue;
+			}
+			let block: Block = match bcs::from_bytes(&blob.data) {
+				Ok(block) => block,
+				Err(e) => {
+					debug!("Skipping blob at height {} that is not a block: {:?}", blob.height, e);
+					continue;
+				}
+			};
+			if self.memseq.observe_block(&block).await {
+				info!(
+					block_id = %block.id(),
+					height = blob.height,
+					"extending block from proposer {}",
+					hex::encode(&blob.signer)
+				);
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Restores the parent of the next block from the last block posted to the DA,
+	/// so that the chain continues where it was left before a restart.
+	///
+	/// Only the last [PARENT_LOOKBACK_HEIGHTS] heights are searched, for at most
+	/// [PARENT_LOOKBACK_TIMEOUT]. Heights that cannot be read are skipped, and without a block
+	/// the chain starts over; the parent sync still extends blocks of other proposers later.
+	async fn restore_parent_block(&self) {
+		let head = match self.pass_through.da.get_head_height().await {
+			Ok(head) => head,
+			Err(e) => {
+				warn!("Failed to read the DA head, starting without a parent block: {:?}", e);
+				return;
+			}
+		};
+		let lowest = head.saturating_sub(PARENT_LOOKBACK_HEIGHTS);
+		let start = Instant::now();
+		for height in (lowest..=head).rev() {
+			if start.elapsed() > PARENT_LOOKBACK_TIMEOUT {
+				warn!(
+					"No block found down to DA height {} within {:?}, starting a new chain.",
+					height, PARENT_LOOKBACK_TIMEOUT
+				);
+				return;
+			}
+			let blobs = match self.pass_through.get_blobs_at_height(height).await {
+				Ok(blobs) => blobs,
+				Err(e) => {
+					warn!(
+						"Skipping DA height {} while restoring the parent block: {:?}",
+						height, e
+					);
+					continue;
+				}
+			};
+			let mut found = false;
+			for blob in blobs {
+				let block: Block = match bcs::from_bytes(&blob.data) {
+					Ok(block) => block,
+					Err(_) => continue,
+				};
+				if block.metadata().height().is_some() {
+					found = true;
+					self.memseq.observe_block(&block).await;
+				}
+			}
+			if found {
+				info!(
+					block_id = %self.memseq.parent_block().await,
+					height,
+					"restored parent block at height {}",
+					self.memseq.parent_height()
+				);
+				return;
+			}
+		}
+
+		info!("No block found in the last {} DA heights, starting a new chain.", head - lowest + 1);
+	}
+
 	async fn submit_blocks(&self, blocks: &Vec<block::WrappedBlock>) -> Result<(), anyhow::Error> {
 		for block in blocks {
 			info!(target: "movement_timing", block_id = %block.block.id(), "inner_submitting_block");
 		}
-		// get references to celestia blobs in the wrapped blocks
-		let block_blobs = blocks
-			.iter()
-			.map(|wrapped_block| &wrapped_block.blob)
-			.cloned() // hopefully, the compiler optimizes this out
-			.collect::<Vec<_>>();
-		// use deref on the wrapped block to get the blob
-		self.pass_through.submit_celestia_blobs(&block_blobs).await?;
+		// sign the wrapped blocks as blobs
+		let mut block_blobs = Vec::with_capacity(blocks.len());
+		for wrapped_block in blocks {
+			let block_bytes = bcs::to_bytes(&wrapped_block.block)?;
+			block_blobs.push(self.pass_through.create_new_ir_blob(block_bytes).await?);
+		}
+		let height = self.pass_through.submit_ir_blobs(block_blobs).await?;
 		for block in blocks {
 			info!(target: "movement_timing", block_id = %block.block.id(), "inner_submitted_block");
 		}
+		let blocks = blocks.iter().map(|wrapped_block| &wrapped_block.block).collect::<Vec<_>>();
+		self.requeue_if_slot_missed(&blocks, height).await;
+		Ok(())
+	}
+
+	/// Puts the transactions of blocks back into the mempool when the blocks landed outside of this node's slot,
+	/// as the followers drop them there.
+	///
+	/// The blocks are posted already, so failures are only logged rather than failing the submission,
+	/// which would post the blocks again.
+	async fn requeue_if_slot_missed(&self, blocks: &[&Block], height: u64) {
+		if let Err(e) = self.try_requeue_if_slot_missed(blocks, height).await {
+			warn!(height, "failed to check the proposer slot of posted blocks: {:?}", e);
+		}
+	}
+
+	async fn try_requeue_if_slot_missed(
+		&self,
+		blocks: &[&Block],
+		height: u64,
+	) -> Result<(), anyhow::Error> {
+		let schedule = match &self.pass_through.schedule {
+			Some(schedule) => schedule,
+			None => return Ok(()),
+		};
+		if self
+			.pass_through
+			.is_proposer_at_height(schedule, self.memseq.proposer(), height)
+			.await?
+		{
+			return Ok(());
+		}
+
+		// unwind the latest block first, so that the parent goes back past all of them
+		for block in blocks.iter().rev() {
+			warn!(
+				block_id = %block.id(),
+				height,
+				"block landed outside of the proposer slot, requeuing its transactions"
+			);
+			self.memseq.unwind_block(block).await;
+			self.memseq.publish_many(block.transactions().cloned().collect()).await?;
+		}
 		Ok(())
 	}
 
@@ -170,27 +407,66 @@ where
 			info!(target: "movement_timing", block_id = %block.id(), "submitting_block");
 		}
 
-		// wrap the blocks in a struct that can be split and compressed
+		// wrap the blocks in a struct that can be split and weighed
 		// spawn blocking because the compression is blocking and could be slow
-		let pass_through = self.pass_through.clone();
+		let codec = self.pass_through.config.da_blob_codec();
 		let blocks = tokio::task::spawn_blocking(move || {
 			let mut wrapped_blocks = Vec::new();
 			for block in blocks {
-				let block_bytes = bcs::to_bytes(&block)?;
-				let celestia_blob = pass_through.create_new_celestia_blob(block_bytes)?;
-				let wrapped_block = block::WrappedBlock::new(block, celestia_blob);
+				let wrapped_block = block::WrappedBlock::try_new(block, codec)?;
 				wrapped_blocks.push(wrapped_block);
 			}
 			Ok::<Vec<WrappedBlock>, anyhow::Error>(wrapped_blocks)
 		})
 		.await??;
 
+		// blocks which cannot fit in a blob, or which followers would refuse to decompress, are posted in chunks,
+		// in order with the other blocks
+		let mut packable_blocks = Vec::new();
+		for wrapped_block in blocks {
+			if wrapped_block.weight > MAX_BLOB_SIZE || wrapped_block.size > MAX_BLOB_DATA_SIZE {
+				self.submit_with_binpacking(std::mem::take(&mut packable_blocks)).await?;
+				self.submit_block_in_chunks(&wrapped_block.block).await?;
+			} else {
+				packable_blocks.push(wrapped_block);
+			}
+		}
+		self.submit_with_binpacking(packable_blocks).await
+	}
+
+	/// Submits a block which is too large for a blob as ordered chunks.
+	///
+	/// Each chunk is posted in its own transaction, as a PayForBlobs transaction is bounded like a single blob.
+	/// The chunks may therefore land at consecutive heights, and followers place the block at the height of its
+	/// last chunk, so the slot is checked there.
+	async fn submit_block_in_chunks(&self, block: &Block) -> Result<(), anyhow::Error> {
+		let block_bytes = bcs::to_bytes(block)?;
+		let chunks = BlobChunk::split(block.id().to_vec(), &block_bytes, MAX_BLOB_SIZE);
+		info!(target: "movement_timing", block_id = %block.id(), chunk_count = chunks.len(), "submitting_block_in_chunks");
+
+		let mut height = 0;
+		for chunk in chunks {
+			let chunk_blob = self.pass_through.create_new_ir_blob(chunk.try_to_bytes()?).await?;
+			height = self.pass_through.submit_ir_blobs(vec![chunk_blob]).await?;
+		}
+
+		info!(target: "movement_timing", block_id = %block.id(), height, "submitted_block_in_chunks");
+		self.requeue_if_slot_missed(&[block], height).await;
+		Ok(())
+	}
+
+	/// Packs the blocks into as few blobs as possible, splitting blocks whose submission fails.
+	async fn submit_with_binpacking(&self, blocks: Vec<WrappedBlock>) -> Result<(), anyhow::Error> {
+		if blocks.is_empty() {
+			return Ok(());
+		}
+
 		let mut heuristic: GroupingHeuristicStack<block::WrappedBlock> =
 			GroupingHeuristicStack::new(vec![
 				DropSuccess::boxed(),
 				ToApply::boxed(),
 				SkipFor::boxed(1, Splitting::boxed(2)),
-				FirstFitBinpacking::boxed(1_700_000),
+				FirstFitBinpacking::boxed(MAX_BLOB_SIZE),
 			]);
 
 		let start_distribution = GroupingOutcome::new_apply_distribution(blocks);
@@ -236,7 +512,7 @@ where
 		receiver: &mut Receiver<Block>,
 	) -> Result<Vec<Block>, anyhow::Error> {
 		let half_building_time = self.memseq.building_time_ms();
-		let start = std::time::Instant::now();
+		let start = Instant::now();
 		let mut blocks = Vec::new();
 		loop {
 			let remaining = match half_building_time.checked_sub(start.elapsed().as_millis() as u64)
@@ -320,6 +596,7 @@ where
 	}
 
 	pub async fn run_block_proposer(&self) -> Result<(), anyhow::Error> {
+		self.restore_parent_block().await;
 		let (sender, mut receiver) = tokio::sync::mpsc::channel(2 ^ 10);
 
 		loop {
@@ -327,6 +604,7 @@ where
 				self.run_block_builder(sender.clone()),
 				self.run_block_publisher(&mut receiver),
 				self.run_gc(),
+				self.run_parent_sync(),
 			) {
 				Ok(_) => {
 					info!("block proposer completed");
@@ -355,19 +633,26 @@ where
 		Ok(grpc::BlobResponse { blob_type: Some(sequenced_block) })
 	}
 
-	pub fn make_sequenced_blob_intent(
-		data: Vec<u8>,
-		height: u64,
+	/// Signs an intent committing to include the transaction in the next block before the deadline.
+	pub async fn make_sequenced_blob_intent(
+		&self,
+		transaction_id: transaction::Id,
 	) -> Result<grpc::BlobResponse, anyhow::Error> {
+		let timestamp = chrono::Utc::now().timestamp_micros() as u64;
+		let deadline = timestamp + self.pass_through.config.intent_deadline_ms() * 1000;
+		let target_block_height = self.memseq.parent_height() + 1;
+		let signed_intent = SequencedIntent::new(transaction_id, target_block_height, deadline)
+			.try_to_sign_with(self.pass_through.signer.as_ref())
+			.await?;
+
 		Ok(grpc::BlobResponse {
 			blob_type: Some(BlobType::SequencedBlobIntent(grpc::Blob {
-				data,
-				blob_id: vec![],
-				height,
-				// todo: at some point it would be good to sign these intents, as they can then be used as pre-confirmations against which we can slash
-				signature: vec![],
-				signer: vec![],
-				timestamp: 0,
+				data: signed_intent.intent.to_bytes()?,
+				blob_id: transaction_id.to_vec(),
+				height: target_block_height,
+				signature: signed_intent.signature,
+				signer: signed_intent.signer,
+				timestamp,
 			})),
 		})
 	}
@@ -450,22 +735,17 @@ where
 		&self,
 		request: tonic::Request<grpc::BatchWriteRequest>,
 	) -> std::result::Result<tonic::Response<grpc::BatchWriteResponse>, tonic::Status> {
+		self.pass_through.authorize_write(&request)?;
 		let blobs_for_submission = request.into_inner().blobs;
-		let height: u64 = self
-			.pass_through
-			.default_client
-			.header_network_head()
-			.await
-			.map_err(|e| tonic::Status::internal(e.to_string()))?
-			.height()
-			.into();
 
 		// make transactions from the blobs
 		let mut transactions = Vec::new();
 		let mut intents = Vec::new();
+		let mut rejected = Vec::new();
 		for blob in blobs_for_submission {
 			let transaction: Transaction = serde_json::from_slice(&blob.data)
 				.map_err(|e| tonic::Status::internal(e.to_string()))?;
+			let transaction_id = transaction.id();
 
 			match &self.prevalidator {
 				Some(prevalidator) => {
@@ -474,7 +754,8 @@ where
 						Ok(prevalidated) => {
 							transactions.push(prevalidated.into_inner());
 							intents.push(
-								Self::make_sequenced_blob_intent(blob.data, height)
+								self.make_sequenced_blob_intent(transaction_id)
+									.await
 									.map_err(|e| tonic::Status::internal(e.to_string()))?,
 							);
 						}
@@ -483,11 +764,15 @@ where
 								movement_celestia_da_light_node_prevalidator::Error::Validation(
 									_,
 								) => {
-									// discard the transaction
-									info!(
+									// discard the transaction, telling the writer why
+									warn!(
 										"discarding transaction due to prevalidation error {:?}",
 										e
 									);
+									rejected.push(grpc::RejectedTransaction {
+										transaction_id: transaction_id.to_vec(),
+										reason: e.to_string(),
+									});
 								}
 								movement_celestia_da_light_node_prevalidator::Error::Internal(
 									e,
@@ -498,7 +783,14 @@ where
 						}
 					}
 				}
-				None => transactions.push(transaction),
+				None => {
+					transactions.push(transaction);
+					intents.push(
+						self.make_sequenced_blob_intent(transaction_id)
+							.await
+							.map_err(|e| tonic::Status::internal(e.to_string()))?,
+					);
+				}
 			}
 		}
 
@@ -509,51 +801,52 @@ where
 			.await
 			.map_err(|e| tonic::Status::internal(e.to_string()))?;
 
-		Ok(tonic::Response::new(grpc::BatchWriteResponse { blobs: intents }))
+		Ok(tonic::Response::new(grpc::BatchWriteResponse { blobs: intents, rejected }))
 	}
 }
 
 pub mod block {
 
-	use celestia_types::{consts::appconsts::AppVersion, nmt::Namespace, Blob};
 	use movement_algs::grouping_heuristic::{binpacking::BinpackingWeighted, splitting::Splitable};
+	use movement_celestia_da_util::ir_blob::BlobCodec;
 	use movement_types::block::Block;
 
 	/// A wrapped block that can be used with the binpacking heuristic
 	#[derive(Debug, Clone, PartialEq, Eq)]
 	pub struct WrappedBlock {
 		pub block: Block,
-		pub blob: Blob,
+		/// The size of the block's blob payload once the codec compressed it, which is what is posted to the DA.
+		pub weight: usize,
+		/// The serialized size of the block, which is what followers decompress.
+		pub size: usize,
+		/// The codec the block's blob is posted with.
+		pub codec: BlobCodec,
 	}
 
 	impl WrappedBlock {
-		/// Create a new wrapped block from a blob and block
-		pub fn new(block: Block, blob: Blob) -> Self {
-			Self { block, blob }
+		/// Create a new wrapped block from a block, its weight, its size and its codec
+		pub fn new(block: Block, weight: usize, size: usize, codec: BlobCodec) -> Self {
+			Self { block, weight, size, codec }
 		}
 
-		/// Create a new wrapped block from a block and a namespace
-		pub fn try_new(block: Block, namespace: Namespace) -> Result<Self, anyhow::Error> {
+		/// Create a new wrapped block from a block, weighing it by its payload size under the codec
+		pub fn try_new(block: Block, codec: BlobCodec) -> Result<Self, anyhow::Error> {
 			// first serialize the block
 			let block_bytes = bcs::to_bytes(&block)?;
 
-			// then compress the block bytes
-			let compressed_block_bytes = zstd::encode_all(block_bytes.as_slice(), 0)?;
-
-			// then create a blob from the compressed block bytes
-			let blob = Blob::new(namespace, compressed_block_bytes, AppVersion::V2)?;
+			// then compress the block bytes as they will be posted
+			let weight = codec.compress(block_bytes.as_slice())?.len();
 
-			Ok(Self { block, blob })
+			Ok(Self { block, weight, size: block_bytes.len(), codec })
 		}
 	}
 
 	impl Splitable for WrappedBlock {
 		fn split(self, factor: usize) -> Result<Vec<Self>, anyhow::Error> {
-			let namespace = self.blob.namespace;
 			let split_blocks = self.block.split(factor)?;
 			let mut wrapped_blocks = Vec::new();
 			for block in split_blocks {
-				let wrapped_block = WrappedBlock::try_new(block, namespace)?;
+				let wrapped_block = WrappedBlock::try_new(block, self.codec)?;
 				wrapped_blocks.push(wrapped_block);
 			}
 			Ok(wrapped_blocks)
@@ -562,7 +855,7 @@ pub mod block {
 
 	impl BinpackingWeighted for WrappedBlock {
 		fn weight(&self) -> usize {
-			self.blob.data.len()
+			self.weight
 		}
 	}
 
@@ -587,7 +880,7 @@ pub mod block {
 				block::Id::test(),
 				transactions.into_iter().collect(),
 			);
-			let wrapped_block = WrappedBlock::try_new(block, Namespace::new(0, &[0])?)?;
+			let wrapped_block = WrappedBlock::try_new(block, BlobCodec::Zstd)?;
 			let original_block = wrapped_block.clone();
 			let split_blocks = wrapped_block.split(2)?;
 			assert_eq!(split_blocks.len(), 2);
@@ -617,5 +910,27 @@ pub mod block {
 
 			Ok(())
 		}
+
+		#[test]
+		fn test_block_splits_have_distinct_metadata() -> Result<(), anyhow::Error> {
+			let transactions = (0..4).map(|i| Transaction::new(vec![i; 32], 0, i as u64));
+			let block = Block::new(
+				block::BlockMetadata::new(1_000, vec![1; 33], 7),
+				block::Id::test(),
+				transactions.collect(),
+			);
+
+			let split_blocks = WrappedBlock::try_new(block, BlobCodec::Zstd)?.split(2)?;
+			let metadata: Vec<_> =
+				split_blocks.iter().map(|wrapped| wrapped.block.metadata().clone()).collect();
+			assert_eq!(metadata[0].height(), Some(7));
+			assert_eq!(metadata[1].height(), Some(7));
+			assert_eq!(metadata[0].offset(), 0);
+			assert_eq!(metadata[1].offset(), 2);
+			assert_eq!(metadata[0].timestamp(), Some(1_000));
+			assert_eq!(metadata[1].timestamp(), Some(1_002));
+
+			Ok(())
+		}
 	}
 }
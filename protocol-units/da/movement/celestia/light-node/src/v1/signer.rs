@@ -0,0 +1,78 @@
+use movement_celestia_da_util::config::{local::da_light_node::DaSigningBackend, Config};
+use movement_signer::{
+	cryptography::{secp256k1, secp256k1::Secp256k1},
+	local::LocalSigner,
+	SignerError, Signing,
+};
+use movement_signer_aws_kms::hsm::AwsKms;
+use movement_signer_hashicorp_vault::kv::HashiCorpVaultKv;
+use tracing::info;
+
+enum Backend {
+	Local(LocalSigner),
+	AwsKms(AwsKms<Secp256k1>),
+}
+
+impl Signing<Secp256k1> for Backend {
+	async fn sign(&self, message: &[u8]) -> Result<secp256k1::Signature, SignerError> {
+		match self {
+			Backend::Local(signer) => signer.sign(message).await,
+			Backend::AwsKms(signer) => signer.sign(message).await,
+		}
+	}
+
+	async fn public_key(&self) -> Result<secp256k1::PublicKey, SignerError> {
+		match self {
+			Backend::Local(signer) => signer.public_key().await,
+			Backend::AwsKms(signer) => signer.public_key().await,
+		}
+	}
+}
+
+/// The signer of the blobs and intents the light node posts to the DA.
+///
+/// The public key is fetched once at startup, so a remote signer is only called to sign.
+pub struct DaSigner {
+	backend: Backend,
+	public_key: secp256k1::PublicKey,
+}
+
+impl DaSigner {
+	/// Connects to the signing backend selected in the config.
+	pub async fn try_from_config(config: &Config) -> Result<Self, anyhow::Error> {
+		let backend = match config.da_signing_backend() {
+			DaSigningBackend::Local => {
+				Backend::Local(LocalSigner::try_from_hex(&config.da_signing_key())?)
+			}
+			DaSigningBackend::AwsKms { key_id } => {
+				info!("Signing blobs with the AWS KMS key {}.", key_id);
+				Backend::AwsKms(AwsKms::try_from_key_id(key_id).await?)
+			}
+			DaSigningBackend::HashiCorpVault { mount_name, secret_path, field } => {
+				info!("Signing blobs with the key in the HashiCorp Vault secret {}.", secret_path);
+				let private_key_hex = HashiCorpVaultKv::try_from_env(mount_name)?
+					.read_field(&secret_path, &field)
+					.await?;
+				Backend::Local(LocalSigner::try_from_hex(&private_key_hex)?)
+			}
+		};
+		let public_key = backend.public_key().await?;
+
+		Ok(Self { backend, public_key })
+	}
+
+	/// The sec1 encoded public key of the signer.
+	pub fn public_key_sec1(&self) -> Vec<u8> {
+		self.public_key.as_bytes().to_vec()
+	}
+}
+
+impl Signing<Secp256k1> for DaSigner {
+	async fn sign(&self, message: &[u8]) -> Result<secp256k1::Signature, SignerError> {
+		self.backend.sign(message).await
+	}
+
+	async fn public_key(&self) -> Result<secp256k1::PublicKey, SignerError> {
+		Ok(self.public_key)
+	}
+}
@@ -0,0 +1,44 @@
+use crate::common;
+use dot_movement::DotMovement;
+use movement_celestia_da_util::config::local::{da_light_node::DaBackend, Config};
+use tracing::info;
+
+pub fn initialize_digest_store_config(
+	dot_movement: DotMovement,
+	mut config: Config,
+) -> Result<Config, anyhow::Error> {
+	// use the dot movement path to set up the digest store path
+	let dot_movement_path = dot_movement.get_path();
+
+	// use the chain id from the celestia config to set up the digest store path
+	let chain_id = config.appd.celestia_chain_id.clone();
+
+	let path = dot_movement_path
+		.join("digest-store")
+		.join(chain_id)
+		.join(".digest-store")
+		.to_str()
+		.ok_or(anyhow::anyhow!("Failed to convert path to string."))?
+		.to_string();
+	config.da_light_node.da_backend = DaBackend::DigestStore { digest_store_path: Some(path) };
+
+	Ok(config)
+}
+
+pub async fn make_dirs(config: Config) -> Result<Config, anyhow::Error> {
+	// make the digest store directory
+	if let DaBackend::DigestStore { digest_store_path: Some(path) } =
+		&config.da_light_node.da_backend
+	{
+		info!("Creating Digest Store Path: {}", path.as_str());
+		common::file::make_parent_dirs(path.as_str()).await?;
+	}
+
+	// make the memseq database directory
+	if let Some(database_path) = &config.memseq.sequencer_database_path {
+		info!("Creating MemSeq Database Path: {}", database_path.as_str());
+		common::file::make_parent_dirs(database_path.as_str()).await?;
+	}
+
+	Ok(config)
+}
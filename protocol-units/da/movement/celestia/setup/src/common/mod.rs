@@ -1,3 +1,4 @@
 pub mod celestia;
+pub mod digest_store;
 pub mod file;
 pub mod memseq;
@@ -8,6 +8,8 @@ pub async fn setup(
 	dot_movement: dot_movement::DotMovement,
 	mut config: CelestiaDaLightNodeConfig,
 ) -> Result<CelestiaDaLightNodeConfig, anyhow::Error> {
+	movement_celestia_da_util::config::try_check_env_defaults()?;
+
 	let inner_config = match config.celestia_da_light_node_config {
 		movement_celestia_da_util::config::Config::Local(config) => {
 			let local = local::Local::new();
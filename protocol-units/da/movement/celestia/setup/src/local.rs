@@ -2,7 +2,7 @@ use crate::common;
 use anyhow::Context;
 use commander::run_command;
 use dot_movement::DotMovement;
-use movement_celestia_da_util::config::local::Config;
+use movement_celestia_da_util::config::local::{da_light_node::DaBackend, Config};
 use tokio::fs;
 use tracing::info;
 
@@ -188,6 +188,20 @@ impl Local {
 		Ok(())
 	}
 
+	async fn setup_digest_store(
+		&self,
+		dot_movement: DotMovement,
+		config: Config,
+	) -> Result<Config, anyhow::Error> {
+		info!("Setting up the Digest Store.");
+		let config = common::memseq::initialize_memseq_config(dot_movement.clone(), config)?;
+		let config = common::digest_store::initialize_digest_store_config(dot_movement, config)?;
+		let config = common::digest_store::make_dirs(config).await?;
+		info!("Setup config for Memseq and the Digest Store: {:?}", config);
+
+		Ok(config)
+	}
+
 	pub async fn setup(
 		&self,
 		dot_movement: DotMovement,
@@ -199,8 +213,16 @@ impl Local {
 			return Ok(config);
 		}
 
-		info!("Setting up Celestia for M1 DA Light Node.");
-		let mut config = self.setup_celestia(dot_movement, config).await?;
+		let mut config = match config.da_light_node.da_backend {
+			DaBackend::Celestia => {
+				info!("Setting up Celestia for M1 DA Light Node.");
+				self.setup_celestia(dot_movement, config).await?
+			}
+			DaBackend::DigestStore { .. } => {
+				info!("Setting up the Digest Store for M1 DA Light Node.");
+				self.setup_digest_store(dot_movement, config).await?
+			}
+		};
 
 		info!("M1 DA Light Node setup complete.");
 
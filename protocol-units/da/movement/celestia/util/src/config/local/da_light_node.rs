@@ -5,15 +5,115 @@ use crate::config::common::{
 	default_movement_da_light_node_connection_port, default_movement_da_light_node_http1,
 	default_movement_da_light_node_listen_hostname, default_movement_da_light_node_listen_port,
 };
+use crate::ir_blob::BlobCodec;
+use crate::signers::SignerKey;
 use ecdsa::SigningKey;
+use godfig::env_default;
 use k256::Secp256k1;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct DaSigners {
+	/// The key blobs are signed with, which is only set with [DaSigningBackend::Local].
+	#[serde(default)]
 	pub private_key_hex: String,
+	/// Where the key blobs are signed with is kept.
+	#[serde(default = "default_da_signing_backend")]
+	pub signing_backend: DaSigningBackend,
+	/// Keys which are valid at all heights, unless they are also scheduled.
 	pub public_keys_hex: HashSet<String>,
+	/// Keys which are only valid for a range of heights, such as rotated keys.
+	#[serde(default)]
+	pub scheduled_keys: Vec<SignerKey>,
+	/// A signed file of further scheduled keys, if any.
+	#[serde(default = "default_da_signer_rotation_file")]
+	pub rotation_file: Option<DaSignerRotationFile>,
+	/// Whether the light node trusts its own key at all heights when it is not one of the signers above.
+	///
+	/// Otherwise the light node refuses to start with a key it would not accept blobs from.
+	#[serde(default = "default_da_trust_own_signer")]
+	pub trust_own_signer: bool,
+}
+
+/// Where the light node's secp256k1 DA signing key is kept.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DaSigningBackend {
+	/// The private key in the config.
+	Local,
+	/// A key in AWS KMS, with the AWS credentials and region from the environment.
+	AwsKms { key_id: String },
+	/// A hex encoded private key in a KV secret of HashiCorp Vault, with the address and token from the environment.
+	///
+	/// The transit engine of Vault has no secp256k1 keys, so the key is read at startup and blobs are signed in process.
+	HashiCorpVault { mount_name: String, secret_path: String, field: String },
+}
+
+/// The default signing backend, read from `MOVEMENT_DA_SIGNING_BACKEND` as `local`, `aws-kms` or `hashicorp-vault`.
+///
+/// AWS KMS uses the key in `MOVEMENT_DA_SIGNING_AWS_KMS_KEY_ID`.
+/// HashiCorp Vault uses the `MOVEMENT_DA_SIGNING_VAULT_FIELD` field, `private_key` by default,
+/// of the secret at `MOVEMENT_DA_SIGNING_VAULT_SECRET_PATH` under `MOVEMENT_DA_SIGNING_VAULT_MOUNT`, `secret` by default.
+///
+/// Unknown or incomplete backends are rejected by [try_default_da_signing_backend], which setup and startup check.
+pub fn default_da_signing_backend() -> DaSigningBackend {
+	try_default_da_signing_backend().unwrap_or(DaSigningBackend::Local)
+}
+
+/// Reads the signing backend from `MOVEMENT_DA_SIGNING_BACKEND`,
+/// failing on an unknown backend or a remote backend without its key.
+pub fn try_default_da_signing_backend() -> Result<DaSigningBackend, anyhow::Error> {
+	let val = match std::env::var("MOVEMENT_DA_SIGNING_BACKEND") {
+		Ok(val) => val,
+		Err(_) => return Ok(DaSigningBackend::Local),
+	};
+	match val.as_str() {
+		"local" => Ok(DaSigningBackend::Local),
+		"aws-kms" => Ok(DaSigningBackend::AwsKms {
+			key_id: std::env::var("MOVEMENT_DA_SIGNING_AWS_KMS_KEY_ID").map_err(|_| {
+				anyhow::anyhow!(
+					"MOVEMENT_DA_SIGNING_AWS_KMS_KEY_ID must be set for AWS KMS signing"
+				)
+			})?,
+		}),
+		"hashicorp-vault" => Ok(DaSigningBackend::HashiCorpVault {
+			mount_name: std::env::var("MOVEMENT_DA_SIGNING_VAULT_MOUNT")
+				.unwrap_or_else(|_| "secret".to_string()),
+			secret_path: std::env::var("MOVEMENT_DA_SIGNING_VAULT_SECRET_PATH").map_err(|_| {
+				anyhow::anyhow!(
+					"MOVEMENT_DA_SIGNING_VAULT_SECRET_PATH must be set for HashiCorp Vault signing"
+				)
+			})?,
+			field: std::env::var("MOVEMENT_DA_SIGNING_VAULT_FIELD")
+				.unwrap_or_else(|_| "private_key".to_string()),
+		}),
+		_ => Err(anyhow::anyhow!(
+			"Unknown MOVEMENT_DA_SIGNING_BACKEND {:?}, expected local, aws-kms or hashicorp-vault",
+			val
+		)),
+	}
+}
+
+env_default!(default_da_trust_own_signer, "MOVEMENT_DA_TRUST_OWN_SIGNER", bool, false);
+
+/// A rotation file of scheduled signer keys, which is only trusted if signed by a rotation authority.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaSignerRotationFile {
+	/// The path to the JSON encoded [crate::signers::SignedSignerRotation].
+	pub path: String,
+	/// The hex encoded sec1 keys of the rotation authorities.
+	pub authority_public_keys_hex: HashSet<String>,
+}
+
+/// The default rotation file, which is set by `MOVEMENT_DA_SIGNER_ROTATION_FILE`
+/// with the comma separated authorities in `MOVEMENT_DA_SIGNER_ROTATION_AUTHORITIES`.
+pub fn default_da_signer_rotation_file() -> Option<DaSignerRotationFile> {
+	let path = std::env::var("MOVEMENT_DA_SIGNER_ROTATION_FILE").ok()?;
+	let authority_public_keys_hex = match std::env::var("MOVEMENT_DA_SIGNER_ROTATION_AUTHORITIES") {
+		Ok(val) => val.split(',').map(|s| s.to_string()).collect(),
+		Err(_) => HashSet::new(),
+	};
+	Some(DaSignerRotationFile { path, authority_public_keys_hex })
 }
 
 /// The default da signing private key
@@ -44,21 +144,491 @@ pub fn default_da_signers_sec1_keys() -> HashSet<String> {
 	}
 }
 
-pub fn default_da_signers() -> DaSigners {
-	let da_signer = default_da_signing_private_key();
+/// The backend the light node posts blobs to and reads blobs from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DaBackend {
+	/// Blobs are submitted to and read from a Celestia node.
+	Celestia,
+	/// Blobs are kept in a local RocksDB digest store which assigns heights itself.
+	/// This requires no Celestia processes and is intended for development and testing.
+	DigestStore {
+		/// The path to the digest store database.
+		/// This does not have a default because if it is needed, a default is generally not appropriate.
+		digest_store_path: Option<String>,
+	},
+}
+
+/// The default DA backend, read from `MOVEMENT_DA_BACKEND` as `celestia` or `digest-store`.
+///
+/// Unknown backends are rejected by [try_default_da_backend], which setup and startup check.
+pub fn default_da_backend() -> DaBackend {
+	try_default_da_backend().unwrap_or(DaBackend::Celestia)
+}
+
+/// Reads the DA backend from `MOVEMENT_DA_BACKEND`, failing on an unknown backend.
+pub fn try_default_da_backend() -> Result<DaBackend, anyhow::Error> {
+	let val = match std::env::var("MOVEMENT_DA_BACKEND") {
+		Ok(val) => val,
+		Err(_) => return Ok(DaBackend::Celestia),
+	};
+	match val.as_str() {
+		"celestia" => Ok(DaBackend::Celestia),
+		"digest-store" => Ok(DaBackend::DigestStore { digest_store_path: None }),
+		_ => Err(anyhow::anyhow!(
+			"Unknown DA backend {:?} in MOVEMENT_DA_BACKEND, expected celestia or digest-store",
+			val
+		)),
+	}
+}
+
+/// How the light node verifies the blobs it reads from the DA.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DaVerification {
+	/// Blobs are only decoded.
+	Cowboy,
+	/// Blobs must be signed, by anyone.
+	Signed,
+	/// Blobs must be signed by one of the known DA signers.
+	KnownSigners,
+	/// Blobs must be signed by one of the known DA signers, and their inclusion in the namespace is
+	/// checked against the data availability header. This is only possible with the Celestia backend.
+	PessimisticKnownSigners,
+}
+
+/// The default verification, read from `MOVEMENT_DA_VERIFICATION` as `cowboy`, `signed`,
+/// `known-signers` or `pessimistic-known-signers`.
+pub fn default_da_verification() -> DaVerification {
+	match std::env::var("MOVEMENT_DA_VERIFICATION") {
+		Ok(val) => match val.as_str() {
+			"cowboy" => DaVerification::Cowboy,
+			"signed" => DaVerification::Signed,
+			"pessimistic-known-signers" => DaVerification::PessimisticKnownSigners,
+			_ => DaVerification::KnownSigners,
+		},
+		Err(_) => DaVerification::KnownSigners,
+	}
+}
+
+/// A check of the Aptos transactions written to the light node, before they are sequenced.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AptosPrevalidator {
+	/// Checks the chain id, expiration, gas and size of transactions.
+	Limits(AptosTransactionLimits),
+	/// Checks that senders are in the whitelist of the access control config.
+	Whitelist,
+	/// Checks sequence numbers against the accounts on a full node.
+	SequenceNumbers(AptosSequenceNumbers),
+}
+
+/// The limits which transactions are checked against, following the Aptos defaults.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AptosTransactionLimits {
+	/// The id of the chain transactions must be signed for.
+	#[serde(default = "default_aptos_prevalidation_chain_id")]
+	pub chain_id: u8,
+	/// The most seconds into the future a transaction may expire at.
+	#[serde(default = "default_aptos_prevalidation_max_expiration_secs")]
+	pub max_expiration_secs: u64,
+	/// The most units of gas a transaction may pay for.
+	#[serde(default = "default_aptos_prevalidation_max_gas_amount")]
+	pub max_gas_amount: u64,
+	/// The lowest gas unit price, below which transactions cannot pay for their execution.
+	#[serde(default = "default_aptos_prevalidation_min_gas_unit_price")]
+	pub min_gas_unit_price: u64,
+	/// The highest gas unit price.
+	#[serde(default = "default_aptos_prevalidation_max_gas_unit_price")]
+	pub max_gas_unit_price: u64,
+	/// The largest bcs encoded signed transaction.
+	#[serde(default = "default_aptos_prevalidation_max_transaction_size_bytes")]
+	pub max_transaction_size_bytes: u64,
+}
+
+env_default!(default_aptos_prevalidation_chain_id, "MAPTOS_CHAIN_ID", u8, 27);
+
+env_default!(
+	default_aptos_prevalidation_max_expiration_secs,
+	"MOVEMENT_DA_PREVALIDATION_MAX_EXPIRATION_SECS",
+	u64,
+	3600
+);
+
+env_default!(
+	default_aptos_prevalidation_max_gas_amount,
+	"MOVEMENT_DA_PREVALIDATION_MAX_GAS_AMOUNT",
+	u64,
+	2_000_000
+);
+
+env_default!(
+	default_aptos_prevalidation_min_gas_unit_price,
+	"MOVEMENT_DA_PREVALIDATION_MIN_GAS_UNIT_PRICE",
+	u64,
+	100
+);
+
+env_default!(
+	default_aptos_prevalidation_max_gas_unit_price,
+	"MOVEMENT_DA_PREVALIDATION_MAX_GAS_UNIT_PRICE",
+	u64,
+	10_000_000_000
+);
+
+env_default!(
+	default_aptos_prevalidation_max_transaction_size_bytes,
+	"MOVEMENT_DA_PREVALIDATION_MAX_TRANSACTION_SIZE_BYTES",
+	u64,
+	64 * 1024
+);
+
+pub fn default_aptos_transaction_limits() -> AptosTransactionLimits {
+	AptosTransactionLimits {
+		chain_id: default_aptos_prevalidation_chain_id(),
+		max_expiration_secs: default_aptos_prevalidation_max_expiration_secs(),
+		max_gas_amount: default_aptos_prevalidation_max_gas_amount(),
+		min_gas_unit_price: default_aptos_prevalidation_min_gas_unit_price(),
+		max_gas_unit_price: default_aptos_prevalidation_max_gas_unit_price(),
+		max_transaction_size_bytes: default_aptos_prevalidation_max_transaction_size_bytes(),
+	}
+}
+
+/// The full node which sequence numbers are checked against.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AptosSequenceNumbers {
+	/// The base url of the REST api of the full node, e.g. `http://0.0.0.0:30731`.
+	#[serde(default = "default_aptos_prevalidation_rest_url")]
+	pub rest_url: String,
+	/// How far ahead of the account's sequence number a transaction may be.
+	#[serde(default = "default_aptos_prevalidation_max_sequence_number_gap")]
+	pub max_sequence_number_gap: u64,
+	/// How long to wait for the full node before letting a transaction through unchecked.
+	#[serde(default = "default_aptos_prevalidation_rest_timeout_ms")]
+	pub timeout_ms: u64,
+}
+
+env_default!(
+	default_aptos_prevalidation_rest_url,
+	"MOVEMENT_DA_PREVALIDATION_REST_URL",
+	String,
+	"http://0.0.0.0:30731".to_string()
+);
+
+env_default!(
+	default_aptos_prevalidation_max_sequence_number_gap,
+	"MOVEMENT_DA_PREVALIDATION_MAX_SEQUENCE_NUMBER_GAP",
+	u64,
+	100
+);
+
+env_default!(
+	default_aptos_prevalidation_rest_timeout_ms,
+	"MOVEMENT_DA_PREVALIDATION_REST_TIMEOUT_MS",
+	u64,
+	1000
+);
+
+pub fn default_aptos_sequence_numbers() -> AptosSequenceNumbers {
+	AptosSequenceNumbers {
+		rest_url: default_aptos_prevalidation_rest_url(),
+		max_sequence_number_gap: default_aptos_prevalidation_max_sequence_number_gap(),
+		timeout_ms: default_aptos_prevalidation_rest_timeout_ms(),
+	}
+}
+
+/// The default prevalidators, read from `MOVEMENT_DA_PREVALIDATORS` as a comma separated list
+/// of `limits`, `whitelist` and `sequence-numbers`, in the order they are applied.
+///
+/// Only the whitelist applies by default, which checks nothing unless a whitelist is configured.
+/// The limits check the chain id from `MAPTOS_CHAIN_ID`, so they are only applied when asked for.
+/// Unknown names are rejected by [try_default_da_prevalidators], which setup and startup check.
+pub fn default_da_prevalidators() -> Vec<AptosPrevalidator> {
+	try_default_da_prevalidators().unwrap_or_else(|_| vec![AptosPrevalidator::Whitelist])
+}
+
+/// Reads the prevalidators from `MOVEMENT_DA_PREVALIDATORS`, failing on an unknown name.
+///
+/// An empty list applies no prevalidators.
+pub fn try_default_da_prevalidators() -> Result<Vec<AptosPrevalidator>, anyhow::Error> {
+	let prevalidators =
+		std::env::var("MOVEMENT_DA_PREVALIDATORS").unwrap_or_else(|_| "whitelist".to_string());
+	prevalidators
+		.split(',')
+		.map(str::trim)
+		.filter(|name| !name.is_empty())
+		.map(|name| match name {
+			"limits" => Ok(AptosPrevalidator::Limits(default_aptos_transaction_limits())),
+			"whitelist" => Ok(AptosPrevalidator::Whitelist),
+			"sequence-numbers" => {
+				Ok(AptosPrevalidator::SequenceNumbers(default_aptos_sequence_numbers()))
+			}
+			_ => Err(anyhow::anyhow!(
+				"Unknown prevalidator {:?} in MOVEMENT_DA_PREVALIDATORS, expected limits, whitelist or sequence-numbers",
+				name
+			)),
+		})
+		.collect()
+}
+
+/// A local archive of the verified blobs read from the DA, which serves reads of historical heights.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaArchive {
+	/// The path to the archive database.
+	pub archive_path: String,
+	/// How many heights below the head to keep, or all heights if unset.
+	pub retention_heights: Option<u64>,
+	/// The lowest height archived when all heights are kept.
+	#[serde(default = "default_da_archive_floor_height")]
+	pub floor_height: u64,
+	/// How often the backfill task fills gaps in the archive.
+	#[serde(default = "default_da_archive_backfill_interval_ms")]
+	pub backfill_interval_ms: u64,
+}
 
-	// always trust yourself
+env_default!(default_da_archive_floor_height, "MOVEMENT_DA_ARCHIVE_FLOOR_HEIGHT", u64, 1);
+
+env_default!(
+	default_da_archive_backfill_interval_ms,
+	"MOVEMENT_DA_ARCHIVE_BACKFILL_INTERVAL_MS",
+	u64,
+	10000
+);
+
+/// The default blob archive, which is enabled by setting `MOVEMENT_DA_ARCHIVE_PATH`.
+///
+/// An invalid retention is rejected by [try_default_da_archive], which setup and startup check.
+pub fn default_da_archive() -> Option<DaArchive> {
+	try_default_da_archive().unwrap_or(None)
+}
+
+/// Reads the blob archive from `MOVEMENT_DA_ARCHIVE_PATH` and `MOVEMENT_DA_ARCHIVE_RETENTION_HEIGHTS`,
+/// failing on a retention which is not a number of heights.
+pub fn try_default_da_archive() -> Result<Option<DaArchive>, anyhow::Error> {
+	let archive_path = match std::env::var("MOVEMENT_DA_ARCHIVE_PATH") {
+		Ok(archive_path) => archive_path,
+		Err(_) => return Ok(None),
+	};
+	let retention_heights = match std::env::var("MOVEMENT_DA_ARCHIVE_RETENTION_HEIGHTS") {
+		Ok(val) => Some(val.parse().map_err(|e| {
+			anyhow::anyhow!("Invalid MOVEMENT_DA_ARCHIVE_RETENTION_HEIGHTS {:?}: {}", val, e)
+		})?),
+		Err(_) => None,
+	};
+	Ok(Some(DaArchive {
+		archive_path,
+		retention_heights,
+		floor_height: default_da_archive_floor_height(),
+		backfill_interval_ms: default_da_archive_backfill_interval_ms(),
+	}))
+}
+
+/// How blobs are submitted to Celestia.
+///
+/// Gas prices are given in millionths of a utia per unit of gas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CelestiaSubmission {
+	/// The lowest gas price to submit with.
+	#[serde(default = "default_celestia_min_gas_price")]
+	pub min_gas_price: u64,
+	/// The fee cap: a submission is abandoned rather than retried above this gas price.
+	#[serde(default = "default_celestia_max_gas_price")]
+	pub max_gas_price: u64,
+	/// By how many percent the gas price is raised when a submission is rejected for its fee or times out.
+	#[serde(default = "default_celestia_gas_price_bump_percent")]
+	pub gas_price_bump_percent: u64,
+	/// How many heights back gas prices are used for estimation.
+	#[serde(default = "default_celestia_gas_price_window_heights")]
+	pub gas_price_window_heights: u64,
+	/// The REST API of a celestia-app node, which the gas prices paid in recent blocks are read from.
+	/// If unset, or if the API fails, only the gas prices of this node's accepted submissions are used.
+	#[serde(default = "default_celestia_gas_price_api_url")]
+	pub gas_price_api_url: Option<String>,
+	/// How many times a submission is retried before giving up.
+	#[serde(default = "default_celestia_submission_max_retries")]
+	pub max_retries: u32,
+	/// How many submissions may be retrying at once.
+	/// Further failing submissions wait for a place for up to the submission timeout, and are not retried otherwise.
+	#[serde(default = "default_celestia_submission_retry_queue_size")]
+	pub retry_queue_size: usize,
+	/// How long to wait for a single submission attempt.
+	#[serde(default = "default_celestia_submission_timeout_ms")]
+	pub submission_timeout_ms: u64,
+}
+
+env_default!(default_celestia_min_gas_price, "CELESTIA_MIN_GAS_PRICE", u64, 2000);
+
+env_default!(default_celestia_max_gas_price, "CELESTIA_MAX_GAS_PRICE", u64, 200000);
+
+env_default!(default_celestia_gas_price_bump_percent, "CELESTIA_GAS_PRICE_BUMP_PERCENT", u64, 50);
+
+env_default!(
+	default_celestia_gas_price_window_heights,
+	"CELESTIA_GAS_PRICE_WINDOW_HEIGHTS",
+	u64,
+	20
+);
+
+env_default!(default_celestia_gas_price_api_url, "CELESTIA_GAS_PRICE_API_URL", String);
+
+env_default!(default_celestia_submission_max_retries, "CELESTIA_SUBMISSION_MAX_RETRIES", u32, 5);
+
+env_default!(
+	default_celestia_submission_retry_queue_size,
+	"CELESTIA_SUBMISSION_RETRY_QUEUE_SIZE",
+	usize,
+	16
+);
+
+env_default!(default_celestia_submission_timeout_ms, "CELESTIA_SUBMISSION_TIMEOUT_MS", u64, 60000);
+
+pub fn default_celestia_submission() -> CelestiaSubmission {
+	CelestiaSubmission {
+		min_gas_price: default_celestia_min_gas_price(),
+		max_gas_price: default_celestia_max_gas_price(),
+		gas_price_bump_percent: default_celestia_gas_price_bump_percent(),
+		gas_price_window_heights: default_celestia_gas_price_window_heights(),
+		gas_price_api_url: default_celestia_gas_price_api_url(),
+		max_retries: default_celestia_submission_max_retries(),
+		retry_queue_size: default_celestia_submission_retry_queue_size(),
+		submission_timeout_ms: default_celestia_submission_timeout_ms(),
+	}
+}
+
+/// TLS for the light node service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaServiceTls {
+	/// The path to the PEM encoded certificate of the service.
+	pub certificate_path: String,
+	/// The path to the PEM encoded key of the service.
+	pub key_path: String,
+	/// The path to the PEM encoded authority client certificates must be signed by.
+	/// If set, clients must present a certificate.
+	pub client_ca_certificate_path: Option<String>,
+}
+
+/// A client which may write to the light node service.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaServiceClient {
+	/// The name the client signs its bearer tokens as.
+	pub name: String,
+	/// The hex encoded sec1 public key the client signs bearer tokens with, if it uses them.
+	pub token_public_key_hex: Option<String>,
+	/// The hex encoded SHA-256 fingerprint of the DER certificate the client presents over mTLS, if it does.
+	pub certificate_sha256_hex: Option<String>,
+	/// How many blobs per second the client may write, or unlimited if unset.
+	pub blobs_per_second: Option<u64>,
+	/// How many bytes per second the client may write, or unlimited if unset.
+	pub bytes_per_second: Option<u64>,
+}
+
+/// Who may write to the light node service and how much.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaServiceAuth {
+	/// TLS for the service, if any.
+	pub tls: Option<DaServiceTls>,
+	/// The clients which may write. If empty, writes are not authenticated. Reads never are.
+	#[serde(default)]
+	pub clients: Vec<DaServiceClient>,
+	/// How old a bearer token may be.
+	#[serde(default = "default_da_service_token_max_age_ms")]
+	pub token_max_age_ms: u64,
+}
+
+env_default!(
+	default_da_service_token_max_age_ms,
+	"MOVEMENT_DA_LIGHT_NODE_TOKEN_MAX_AGE_MS",
+	u64,
+	30000
+);
+
+/// The default service authentication, which is off.
+/// The clients do not fit into environment variables, so authentication is set up in the config file.
+pub fn default_da_service_auth() -> Option<DaServiceAuth> {
+	None
+}
+
+/// TLS for connecting to the light node service as a client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaClientTls {
+	/// The path to the PEM encoded authority which signed the certificate of the service.
+	pub ca_certificate_path: String,
+	/// The path to the PEM encoded certificate to present to the service, if it requires mTLS.
+	pub certificate_path: Option<String>,
+	/// The path to the PEM encoded key of the certificate presented to the service.
+	pub key_path: Option<String>,
+	/// The name the certificate of the service is checked against, if not its hostname.
+	pub domain_name: Option<String>,
+}
+
+/// How to authenticate to the light node service as a client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaServiceCredentials {
+	/// The name to sign bearer tokens as.
+	pub client_name: String,
+	/// The hex encoded key to sign bearer tokens with, if the service expects them.
+	pub signing_key_hex: Option<String>,
+	/// TLS for the connection, if any.
+	pub tls: Option<DaClientTls>,
+}
+
+/// The default client credentials, which are bearer credentials set by
+/// `MOVEMENT_DA_LIGHT_NODE_CLIENT_NAME` and `MOVEMENT_DA_LIGHT_NODE_CLIENT_SIGNING_KEY`, if any.
+pub fn default_da_service_credentials() -> Option<DaServiceCredentials> {
+	let client_name = std::env::var("MOVEMENT_DA_LIGHT_NODE_CLIENT_NAME").ok()?;
+	let signing_key_hex = std::env::var("MOVEMENT_DA_LIGHT_NODE_CLIENT_SIGNING_KEY").ok();
+	Some(DaServiceCredentials { client_name, signing_key_hex, tls: None })
+}
+
+/// The default codec for posted blobs, read from `MOVEMENT_DA_BLOB_CODEC` as `none`, `zstd` or `lz4`.
+///
+/// Unknown values are rejected by [try_default_da_blob_codec], which setup and startup check.
+pub fn default_da_blob_codec() -> BlobCodec {
+	try_default_da_blob_codec().unwrap_or(BlobCodec::Zstd)
+}
+
+/// Reads the codec for posted blobs from `MOVEMENT_DA_BLOB_CODEC`, failing on an unknown value.
+pub fn try_default_da_blob_codec() -> Result<BlobCodec, anyhow::Error> {
+	match std::env::var("MOVEMENT_DA_BLOB_CODEC") {
+		Ok(val) => match val.as_str() {
+			"none" => Ok(BlobCodec::None),
+			"zstd" => Ok(BlobCodec::Zstd),
+			"lz4" => Ok(BlobCodec::Lz4),
+			_ => Err(anyhow::anyhow!(
+				"Unknown MOVEMENT_DA_BLOB_CODEC {:?}, expected none, zstd or lz4",
+				val
+			)),
+		},
+		Err(_) => Ok(BlobCodec::Zstd),
+	}
+}
+
+pub fn default_da_signers() -> DaSigners {
+	let signing_backend = default_da_signing_backend();
 	let mut trusted_signers = HashSet::new();
-	let sec1_hex = hex::encode(da_signer.verifying_key().to_sec1_bytes().to_vec());
-	trusted_signers.insert(sec1_hex);
+
+	// a remote key never sits in the config, it is trusted once the light node has fetched its public key
+	let private_key_hex = match signing_backend {
+		DaSigningBackend::Local => {
+			let da_signer = default_da_signing_private_key();
+
+			// always trust yourself
+			let sec1_hex = hex::encode(da_signer.verifying_key().to_sec1_bytes().to_vec());
+			trusted_signers.insert(sec1_hex);
+
+			hex::encode(da_signer.to_bytes().as_slice())
+		}
+		_ => String::new(),
+	};
 
 	// add the other specified signers
 	let additional_signers = default_da_signers_sec1_keys();
 	trusted_signers.extend(additional_signers);
 
 	DaSigners {
-		private_key_hex: hex::encode(da_signer.to_bytes().as_slice()),
+		private_key_hex,
+		signing_backend,
 		public_keys_hex: trusted_signers,
+		scheduled_keys: Vec::new(),
+		rotation_file: default_da_signer_rotation_file(),
+		trust_own_signer: default_da_trust_own_signer(),
 	}
 }
 
@@ -140,6 +710,38 @@ pub struct Config {
 	/// The DA signers
 	#[serde(default = "default_da_signers")]
 	pub da_signers: DaSigners,
+
+	/// The DA backend
+	#[serde(default = "default_da_backend")]
+	pub da_backend: DaBackend,
+
+	/// How blobs read from the DA are verified
+	#[serde(default = "default_da_verification")]
+	pub da_verification: DaVerification,
+
+	/// The codec for the payload of posted blobs
+	#[serde(default = "default_da_blob_codec")]
+	pub da_blob_codec: BlobCodec,
+
+	/// The local blob archive, if any
+	#[serde(default = "default_da_archive")]
+	pub da_archive: Option<DaArchive>,
+
+	/// How blobs are submitted to Celestia
+	#[serde(default = "default_celestia_submission")]
+	pub celestia_submission: CelestiaSubmission,
+
+	/// The checks of transactions written to the light node, in the order they are applied
+	#[serde(default = "default_da_prevalidators")]
+	pub da_prevalidators: Vec<AptosPrevalidator>,
+
+	/// Who may write to the movement-celestia-da-light-node service
+	#[serde(default = "default_da_service_auth")]
+	pub movement_da_light_node_auth: Option<DaServiceAuth>,
+
+	/// How to authenticate to the movement-celestia-da-light-node service as a client
+	#[serde(default = "default_da_service_credentials")]
+	pub movement_da_light_node_credentials: Option<DaServiceCredentials>,
 }
 
 impl Default for Config {
@@ -161,6 +763,14 @@ impl Default for Config {
 			),
 			movement_da_light_node_http1: default_movement_da_light_node_http1(),
 			da_signers: default_da_signers(),
+			da_backend: default_da_backend(),
+			da_verification: default_da_verification(),
+			da_blob_codec: default_da_blob_codec(),
+			da_archive: default_da_archive(),
+			celestia_submission: default_celestia_submission(),
+			da_prevalidators: default_da_prevalidators(),
+			movement_da_light_node_auth: default_da_service_auth(),
+			movement_da_light_node_credentials: default_da_service_credentials(),
 		}
 	}
 }
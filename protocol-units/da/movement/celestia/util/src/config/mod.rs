@@ -1,7 +1,21 @@
+use crate::signers::{SignedSignerRotation, SignerRegistry};
 use anyhow::Context;
 use aptos_types::account_address::AccountAddress;
 use celestia_rpc::Client;
 use celestia_types::nmt::Namespace;
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	SignatureSize,
+};
+use memseq_util::{BlockPolicy, ProposerRotation};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -28,6 +42,18 @@ impl Default for Config {
 	}
 }
 
+/// Checks the environment which the config defaults are read from,
+/// so that a mistyped value fails setup and startup instead of falling back to a default.
+pub fn try_check_env_defaults() -> Result<(), anyhow::Error> {
+	memseq_util::try_default_memseq_proposer_rotation()?;
+	local::da_light_node::try_default_da_backend()?;
+	local::da_light_node::try_default_da_blob_codec()?;
+	local::da_light_node::try_default_da_archive()?;
+	local::da_light_node::try_default_da_signing_backend()?;
+	local::da_light_node::try_default_da_prevalidators()?;
+	Ok(())
+}
+
 impl Config {
 	/// Connects to a Celestia node using the config
 	pub async fn connect_celestia(&self) -> Result<Client, anyhow::Error> {
@@ -218,6 +244,24 @@ impl Config {
 		}
 	}
 
+	/// Gets where the DA signing key is kept
+	pub fn da_signing_backend(&self) -> local::da_light_node::DaSigningBackend {
+		match self {
+			Config::Local(local) => local.da_light_node.da_signers.signing_backend.clone(),
+			Config::Arabica(local) => local.da_light_node.da_signers.signing_backend.clone(),
+			Config::Mocha(local) => local.da_light_node.da_signers.signing_backend.clone(),
+		}
+	}
+
+	/// Gets whether the light node trusts its own DA signing key when it is not a known signer
+	pub fn da_trust_own_signer(&self) -> bool {
+		match self {
+			Config::Local(local) => local.da_light_node.da_signers.trust_own_signer,
+			Config::Arabica(local) => local.da_light_node.da_signers.trust_own_signer,
+			Config::Mocha(local) => local.da_light_node.da_signers.trust_own_signer,
+		}
+	}
+
 	/// Gets the da signers sec1 keys
 	pub fn da_signers_sec1_keys(&self) -> HashSet<String> {
 		match self {
@@ -227,6 +271,56 @@ impl Config {
 		}
 	}
 
+	/// Gets the registry of the DA signer keys, loading the rotation file if there is one
+	pub fn try_da_signer_registry<C>(&self) -> Result<SignerRegistry, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let da_signers = match self {
+			Config::Local(local) => &local.da_light_node.da_signers,
+			Config::Arabica(local) => &local.da_light_node.da_signers,
+			Config::Mocha(local) => &local.da_light_node.da_signers,
+		};
+
+		let mut scheduled_keys = da_signers.scheduled_keys.clone();
+		if let Some(rotation_file) = &da_signers.rotation_file {
+			scheduled_keys.extend(
+				SignedSignerRotation::try_load_keys::<C>(
+					&rotation_file.path,
+					&rotation_file.authority_public_keys_hex,
+				)
+				.context("Failed to load the DA signer rotation file")?,
+			);
+		}
+
+		// a scheduled key is only valid for its heights, even if it is also listed as permanent
+		let scheduled: HashSet<&String> =
+			scheduled_keys.iter().map(|key| &key.public_key_hex).collect();
+		let permanent_keys: Vec<String> = da_signers
+			.public_keys_hex
+			.iter()
+			.filter(|key| !scheduled.contains(key))
+			.cloned()
+			.collect();
+
+		let mut registry = SignerRegistry::permanent(permanent_keys);
+		registry.extend(scheduled_keys);
+		Ok(registry)
+	}
+
+	/// Gets the DA backend
+	pub fn da_backend(&self) -> local::da_light_node::DaBackend {
+		match self {
+			Config::Local(local) => local.da_light_node.da_backend.clone(),
+			Config::Arabica(local) => local.da_light_node.da_backend.clone(),
+			Config::Mocha(local) => local.da_light_node.da_backend.clone(),
+		}
+	}
+
 	pub fn try_block_building_parameters(&self) -> Result<(u32, u64), anyhow::Error> {
 		match self {
 			Config::Local(local) => {
@@ -241,6 +335,100 @@ impl Config {
 		}
 	}
 
+	/// Gets the local blob archive configuration, if the archive is enabled
+	pub fn da_archive(&self) -> Option<local::da_light_node::DaArchive> {
+		match self {
+			Config::Local(local) => local.da_light_node.da_archive.clone(),
+			Config::Arabica(local) => local.da_light_node.da_archive.clone(),
+			Config::Mocha(local) => local.da_light_node.da_archive.clone(),
+		}
+	}
+
+	/// Gets who may write to the light node service
+	pub fn movement_da_light_node_auth(&self) -> Option<local::da_light_node::DaServiceAuth> {
+		match self {
+			Config::Local(local) => local.da_light_node.movement_da_light_node_auth.clone(),
+			Config::Arabica(local) => local.da_light_node.movement_da_light_node_auth.clone(),
+			Config::Mocha(local) => local.da_light_node.movement_da_light_node_auth.clone(),
+		}
+	}
+
+	/// Gets how to authenticate to the light node service as a client
+	pub fn movement_da_light_node_credentials(
+		&self,
+	) -> Option<local::da_light_node::DaServiceCredentials> {
+		match self {
+			Config::Local(local) => local.da_light_node.movement_da_light_node_credentials.clone(),
+			Config::Arabica(local) => {
+				local.da_light_node.movement_da_light_node_credentials.clone()
+			}
+			Config::Mocha(local) => local.da_light_node.movement_da_light_node_credentials.clone(),
+		}
+	}
+
+	/// Gets how blobs are submitted to Celestia
+	pub fn celestia_submission(&self) -> local::da_light_node::CelestiaSubmission {
+		match self {
+			Config::Local(local) => local.da_light_node.celestia_submission.clone(),
+			Config::Arabica(local) => local.da_light_node.celestia_submission.clone(),
+			Config::Mocha(local) => local.da_light_node.celestia_submission.clone(),
+		}
+	}
+
+	/// Gets how blobs read from the DA are verified
+	pub fn da_verification(&self) -> local::da_light_node::DaVerification {
+		match self {
+			Config::Local(local) => local.da_light_node.da_verification,
+			Config::Arabica(local) => local.da_light_node.da_verification,
+			Config::Mocha(local) => local.da_light_node.da_verification,
+		}
+	}
+
+	/// Gets the codec for the payload of posted blobs
+	pub fn da_blob_codec(&self) -> crate::ir_blob::BlobCodec {
+		match self {
+			Config::Local(local) => local.da_light_node.da_blob_codec,
+			Config::Arabica(local) => local.da_light_node.da_blob_codec,
+			Config::Mocha(local) => local.da_light_node.da_blob_codec,
+		}
+	}
+
+	/// Gets how block production rotates between the DA signers
+	pub fn proposer_rotation(&self) -> ProposerRotation {
+		match self {
+			Config::Local(local) => local.memseq.memseq_proposer_rotation.clone(),
+			Config::Arabica(local) => local.memseq.memseq_proposer_rotation.clone(),
+			Config::Mocha(local) => local.memseq.memseq_proposer_rotation.clone(),
+		}
+	}
+
+	/// Gets how long after accepting a transaction the sequencer commits to including it in a block
+	pub fn intent_deadline_ms(&self) -> u64 {
+		match self {
+			Config::Local(local) => local.memseq.memseq_intent_deadline_ms,
+			Config::Arabica(local) => local.memseq.memseq_intent_deadline_ms,
+			Config::Mocha(local) => local.memseq.memseq_intent_deadline_ms,
+		}
+	}
+
+	/// Gets how the sequencer picks the transactions of a block
+	pub fn block_policy(&self) -> BlockPolicy {
+		match self {
+			Config::Local(local) => local.memseq.memseq_block_policy.clone(),
+			Config::Arabica(local) => local.memseq.memseq_block_policy.clone(),
+			Config::Mocha(local) => local.memseq.memseq_block_policy.clone(),
+		}
+	}
+
+	/// Gets the checks of written transactions
+	pub fn da_prevalidators(&self) -> Vec<local::da_light_node::AptosPrevalidator> {
+		match self {
+			Config::Local(local) => local.da_light_node.da_prevalidators.clone(),
+			Config::Arabica(local) => local.da_light_node.da_prevalidators.clone(),
+			Config::Mocha(local) => local.da_light_node.da_prevalidators.clone(),
+		}
+	}
+
 	pub fn whitelisted_accounts(&self) -> Result<Option<HashSet<AccountAddress>>, anyhow::Error> {
 		match self {
 			Config::Local(local) => local.access_control.whitelisted_accounts(),
@@ -11,7 +11,12 @@ use ecdsa::{
 	signature::{digest::Digest, DigestVerifier},
 	SignatureSize, SigningKey, VerifyingKey,
 };
+use movement_signer::{cryptography::secp256k1, Signing};
 use serde::{Deserialize, Serialize};
+use std::io::Read;
+
+/// The most bytes the data of a blob may decompress to, which bounds the size of a posted block.
+pub const MAX_BLOB_DATA_SIZE: usize = 32 * 1024 * 1024;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InnerSignedBlobV1Data {
@@ -87,6 +92,29 @@ impl InnerSignedBlobV1Data {
 			id,
 		})
 	}
+
+	/// Signs with a possibly remote signer, such as a KMS.
+	///
+	/// The signer hashes the message with SHA-256, so the blob verifies as one signed with a local secp256k1 key.
+	pub async fn try_to_sign_with<S>(self, signer: &S) -> Result<InnerSignedBlobV1, anyhow::Error>
+	where
+		S: Signing<secp256k1::Secp256k1>,
+	{
+		let id = self.compute_id::<k256::Secp256k1>();
+		let mut message = self.blob.clone();
+		message.extend_from_slice(&self.timestamp.to_be_bytes());
+		message.extend_from_slice(id.as_slice());
+
+		let signature = signer.sign(&message).await?;
+		let public_key = signer.public_key().await?;
+
+		Ok(InnerSignedBlobV1 {
+			data: self,
+			signature: signature.as_bytes().to_vec(),
+			signer: public_key.as_bytes().to_vec(),
+			id,
+		})
+	}
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -121,9 +149,251 @@ impl InnerSignedBlobV1 {
 	}
 }
 
+/// The compression applied to the payload of a [InnerSignedBlobV2].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobCodec {
+	None,
+	Zstd,
+	Lz4,
+}
+
+impl BlobCodec {
+	pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+		match self {
+			BlobCodec::None => Ok(data.to_vec()),
+			BlobCodec::Zstd => Ok(zstd::encode_all(data, 0)?),
+			BlobCodec::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+		}
+	}
+
+	/// Decompresses the data, failing as soon as it decompresses to more than `max_size` bytes.
+	pub fn decompress(&self, data: &[u8], max_size: usize) -> Result<Vec<u8>, anyhow::Error> {
+		let decompressed = match self {
+			BlobCodec::None => data.to_vec(),
+			BlobCodec::Zstd => {
+				let mut decompressed = Vec::new();
+				zstd::stream::read::Decoder::new(data)?
+					.take(max_size as u64 + 1)
+					.read_to_end(&mut decompressed)?;
+				decompressed
+			}
+			BlobCodec::Lz4 => {
+				// the size is prepended as little endian u32, so it is checked before anything is allocated
+				let size = match data {
+					[a, b, c, d, ..] => u32::from_le_bytes([*a, *b, *c, *d]) as usize,
+					_ => anyhow::bail!("Blob data is too short to be lz4 compressed"),
+				};
+				if size > max_size {
+					anyhow::bail!("Blob data decompresses to more than {} bytes", max_size);
+				}
+				lz4_flex::decompress_size_prepended(data)?
+			}
+		};
+		if decompressed.len() > max_size {
+			anyhow::bail!("Blob data decompresses to more than {} bytes", max_size);
+		}
+		Ok(decompressed)
+	}
+}
+
+/// A signed blob whose payload is compressed on the wire.
+///
+/// The signature and id are those of the uncompressed [InnerSignedBlobV1], so clients can check the data they are served.
+/// The compressed payload is signed as well, by the same signer, so that it is checked before it is decompressed.
+/// Payload signatures are secp256k1 signatures, like those of all DA signers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "CompressedSignedBlobV2", into = "CompressedSignedBlobV2")]
+pub struct InnerSignedBlobV2 {
+	pub codec: BlobCodec,
+	pub inner: InnerSignedBlobV1,
+	payload: Vec<u8>,
+	payload_signature: Vec<u8>,
+}
+
+impl InnerSignedBlobV2 {
+	/// Compresses the data of a signed blob and signs the compressed payload.
+	pub fn try_new(
+		inner: InnerSignedBlobV1,
+		codec: BlobCodec,
+		signing_key: &SigningKey<k256::Secp256k1>,
+	) -> Result<Self, anyhow::Error> {
+		let payload = codec.compress(inner.data.blob.as_slice())?;
+		let prehash = Self::payload_digest(&payload, &inner.id).finalize();
+		let (signature, _recovery_id) = signing_key.sign_prehash_recoverable(prehash.as_slice())?;
+		Ok(Self { codec, inner, payload, payload_signature: signature.to_vec() })
+	}
+
+	/// Compresses the data of a signed blob and signs the compressed payload with a possibly remote signer.
+	pub async fn try_new_with<S>(
+		inner: InnerSignedBlobV1,
+		codec: BlobCodec,
+		signer: &S,
+	) -> Result<Self, anyhow::Error>
+	where
+		S: Signing<secp256k1::Secp256k1>,
+	{
+		let payload = codec.compress(inner.data.blob.as_slice())?;
+		let mut message = payload.clone();
+		message.extend_from_slice(inner.id.as_slice());
+		let signature = signer.sign(&message).await?;
+		Ok(Self { codec, inner, payload, payload_signature: signature.as_bytes().to_vec() })
+	}
+
+	/// The digest signed for a payload, which binds it to the id of the blob.
+	fn payload_digest(payload: &[u8], id: &Id) -> <k256::Secp256k1 as DigestPrimitive>::Digest {
+		let mut hasher = <k256::Secp256k1 as DigestPrimitive>::Digest::new();
+		hasher.update(payload);
+		hasher.update(id.as_slice());
+		hasher
+	}
+
+	/// Checks the signature over a compressed payload, before it is decompressed.
+	fn verify_payload(blob: &CompressedSignedBlobV2) -> Result<(), anyhow::Error> {
+		let verifying_key =
+			VerifyingKey::<k256::Secp256k1>::from_sec1_bytes(blob.signer.as_slice())?;
+		let signature =
+			ecdsa::Signature::<k256::Secp256k1>::from_slice(blob.payload_signature.as_slice())?;
+
+		match verifying_key.verify_digest(Self::payload_digest(&blob.payload, &blob.id), &signature)
+		{
+			Ok(_) => Ok(()),
+			Err(_) => Err(anyhow::anyhow!("Failed to verify payload signature")),
+		}
+	}
+}
+
+/// The serialized form of [InnerSignedBlobV2].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CompressedSignedBlobV2 {
+	codec: BlobCodec,
+	payload: Vec<u8>,
+	payload_signature: Vec<u8>,
+	timestamp: u64,
+	signature: Vec<u8>,
+	signer: Vec<u8>,
+	id: Id,
+}
+
+impl From<InnerSignedBlobV2> for CompressedSignedBlobV2 {
+	fn from(blob: InnerSignedBlobV2) -> Self {
+		let InnerSignedBlobV2 { codec, inner, payload, payload_signature } = blob;
+		Self {
+			codec,
+			payload,
+			payload_signature,
+			timestamp: inner.data.timestamp,
+			signature: inner.signature,
+			signer: inner.signer,
+			id: inner.id,
+		}
+	}
+}
+
+impl TryFrom<CompressedSignedBlobV2> for InnerSignedBlobV2 {
+	type Error = anyhow::Error;
+
+	fn try_from(blob: CompressedSignedBlobV2) -> Result<Self, Self::Error> {
+		Self::verify_payload(&blob)?;
+		let data = InnerSignedBlobV1Data::new(
+			blob.codec.decompress(&blob.payload, MAX_BLOB_DATA_SIZE)?,
+			blob.timestamp,
+		);
+		Ok(Self {
+			codec: blob.codec,
+			inner: InnerSignedBlobV1 {
+				data,
+				signature: blob.signature,
+				signer: blob.signer,
+				id: blob.id,
+			},
+			payload: blob.payload,
+			payload_signature: blob.payload_signature,
+		})
+	}
+}
+
+/// An ordered part of a blob which is too large to be posted as a single blob.
+///
+/// Each chunk is posted as the data of its own signed blob, prefixed with [BlobChunk::MAGIC].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobChunk {
+	/// The id of the chunked blob, e.g. the id of the block it encodes.
+	pub blob_id: Vec<u8>,
+	pub index: u32,
+	pub total: u32,
+	pub data: Vec<u8>,
+}
+
+impl BlobChunk {
+	/// Marks blob data as a chunk. No bcs encoded block can start with these bytes.
+	pub const MAGIC: &'static [u8; 8] = b"mvmtchnk";
+
+	/// Splits the data into chunks of at most `max_chunk_size` bytes.
+	pub fn split(blob_id: Vec<u8>, data: &[u8], max_chunk_size: usize) -> Vec<Self> {
+		let parts: Vec<&[u8]> = data.chunks(max_chunk_size.max(1)).collect();
+		let total = parts.len() as u32;
+		parts
+			.into_iter()
+			.enumerate()
+			.map(|(index, part)| Self {
+				blob_id: blob_id.clone(),
+				index: index as u32,
+				total,
+				data: part.to_vec(),
+			})
+			.collect()
+	}
+
+	pub fn try_to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
+		let mut bytes = Self::MAGIC.to_vec();
+		bcs::serialize_into(&mut bytes, self)?;
+		Ok(bytes)
+	}
+
+	/// Decodes blob data as a chunk, or returns `None` if the data is not a chunk.
+	pub fn try_from_bytes(bytes: &[u8]) -> Option<Result<Self, anyhow::Error>> {
+		let encoded = bytes.strip_prefix(Self::MAGIC.as_slice())?;
+		Some(bcs::from_bytes(encoded).map_err(|e| anyhow::anyhow!("Invalid blob chunk: {}", e)))
+	}
+
+	/// Reassembles the data from a complete set of chunks of the same blob, in any order.
+	///
+	/// Fails on missing chunks, and on chunks which disagree on the blob or on the contents of an index.
+	pub fn reassemble(mut chunks: Vec<Self>) -> Result<Vec<u8>, anyhow::Error> {
+		let first = chunks.first().ok_or(anyhow::anyhow!("No chunks to reassemble"))?;
+		let (blob_id, total) = (first.blob_id.clone(), first.total);
+		if chunks.iter().any(|chunk| chunk.blob_id != blob_id || chunk.total != total) {
+			anyhow::bail!("Chunks of different blobs cannot be reassembled");
+		}
+
+		chunks.sort_by_key(|chunk| chunk.index);
+		// posting the same chunk twice is harmless, posting different contents for it is not
+		chunks.dedup();
+		if chunks.windows(2).any(|pair| pair[0].index == pair[1].index) {
+			anyhow::bail!("Conflicting chunks for blob {}", hex::encode(&blob_id));
+		}
+		if chunks.len() != total as usize
+			|| chunks
+				.iter()
+				.enumerate()
+				.any(|(position, chunk)| chunk.index != position as u32)
+		{
+			anyhow::bail!(
+				"Incomplete chunks for blob {}: got {} of {}",
+				hex::encode(&blob_id),
+				chunks.len(),
+				total
+			);
+		}
+
+		Ok(chunks.into_iter().flat_map(|chunk| chunk.data).collect())
+	}
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum IntermediateBlobRepresentation {
 	SignedV1(InnerSignedBlobV1),
+	SignedV2(InnerSignedBlobV2),
 }
 
 impl From<InnerSignedBlobV1> for IntermediateBlobRepresentation {
@@ -132,29 +402,43 @@ impl From<InnerSignedBlobV1> for IntermediateBlobRepresentation {
 	}
 }
 
+impl From<InnerSignedBlobV2> for IntermediateBlobRepresentation {
+	fn from(inner: InnerSignedBlobV2) -> Self {
+		IntermediateBlobRepresentation::SignedV2(inner)
+	}
+}
+
 impl IntermediateBlobRepresentation {
-	pub fn blob(&self) -> &[u8] {
+	/// The signed blob, which is the same for all formats.
+	fn signed(&self) -> &InnerSignedBlobV1 {
 		match self {
-			IntermediateBlobRepresentation::SignedV1(inner) => inner.data.blob.as_slice(),
+			IntermediateBlobRepresentation::SignedV1(inner) => inner,
+			IntermediateBlobRepresentation::SignedV2(inner) => &inner.inner,
 		}
 	}
 
-	pub fn signature(&self) -> &[u8] {
+	/// The codec the blob payload is compressed with.
+	pub fn codec(&self) -> BlobCodec {
 		match self {
-			IntermediateBlobRepresentation::SignedV1(inner) => inner.signature.as_slice(),
+			IntermediateBlobRepresentation::SignedV1(_) => BlobCodec::None,
+			IntermediateBlobRepresentation::SignedV2(inner) => inner.codec,
 		}
 	}
 
+	pub fn blob(&self) -> &[u8] {
+		self.signed().data.blob.as_slice()
+	}
+
+	pub fn signature(&self) -> &[u8] {
+		self.signed().signature.as_slice()
+	}
+
 	pub fn timestamp(&self) -> u64 {
-		match self {
-			IntermediateBlobRepresentation::SignedV1(inner) => inner.data.timestamp,
-		}
+		self.signed().data.timestamp
 	}
 
 	pub fn signer(&self) -> &[u8] {
-		match self {
-			IntermediateBlobRepresentation::SignedV1(inner) => inner.signer.as_slice(),
-		}
+		self.signed().signer.as_slice()
 	}
 
 	pub fn signer_hex(&self) -> String {
@@ -162,9 +446,7 @@ impl IntermediateBlobRepresentation {
 	}
 
 	pub fn id(&self) -> &[u8] {
-		match self {
-			IntermediateBlobRepresentation::SignedV1(inner) => inner.id.as_slice(),
-		}
+		self.signed().id.as_slice()
 	}
 
 	pub fn verify_signature<C>(&self) -> Result<(), anyhow::Error>
@@ -175,9 +457,7 @@ impl IntermediateBlobRepresentation {
 		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
 		FieldBytesSize<C>: ModulusSize,
 	{
-		match self {
-			IntermediateBlobRepresentation::SignedV1(inner) => inner.try_verify::<C>(),
-		}
+		self.signed().try_verify::<C>()
 	}
 }
 
@@ -199,6 +479,138 @@ pub mod test {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn test_remote_signature_matches_local() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<k256::Secp256k1>::random(&mut rand::thread_rng());
+		let signer = movement_signer::local::LocalSigner::new(signing_key.clone());
+
+		let blob = InnerSignedBlobV1Data::new(vec![1, 2, 3], 123);
+		let local = blob.clone().try_to_sign(&signing_key)?;
+		let remote = blob.try_to_sign_with(&signer).await?;
+		remote.try_verify::<k256::Secp256k1>()?;
+
+		assert_eq!(remote.signer, local.signer);
+		assert_eq!(remote.id.as_slice(), local.id.as_slice());
+		assert_eq!(remote.signature, local.signature);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_v2_round_trip_with_each_codec() -> Result<(), anyhow::Error> {
+		let data = vec![7; 4096];
+		let blob = InnerSignedBlobV1Data::new(data.clone(), 123);
+		let signing_key = SigningKey::<k256::Secp256k1>::random(&mut rand::thread_rng());
+		let signed_blob = blob.try_to_sign(&signing_key)?;
+
+		for codec in [BlobCodec::None, BlobCodec::Zstd, BlobCodec::Lz4] {
+			let ir_blob: IntermediateBlobRepresentation =
+				InnerSignedBlobV2::try_new(signed_blob.clone(), codec, &signing_key)?.into();
+			let bytes = bcs::to_bytes(&ir_blob)?;
+			if codec != BlobCodec::None {
+				assert!(bytes.len() < data.len());
+			}
+
+			let decoded: IntermediateBlobRepresentation = bcs::from_bytes(&bytes)?;
+			assert_eq!(decoded.codec(), codec);
+			assert_eq!(decoded.blob(), data.as_slice());
+			assert_eq!(decoded.id(), signed_blob.id.as_slice());
+			decoded.verify_signature::<k256::Secp256k1>()?;
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_decompression_is_bounded() -> Result<(), anyhow::Error> {
+		let data = vec![0; 4096];
+		for codec in [BlobCodec::None, BlobCodec::Zstd, BlobCodec::Lz4] {
+			let compressed = codec.compress(&data)?;
+			assert_eq!(codec.decompress(&compressed, data.len())?, data);
+			assert!(codec.decompress(&compressed, data.len() - 1).is_err());
+		}
+
+		// a forged lz4 size is rejected before anything is allocated
+		let mut forged = BlobCodec::Lz4.compress(&data)?;
+		forged[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+		assert!(BlobCodec::Lz4.decompress(&forged, MAX_BLOB_DATA_SIZE).is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_v2_payload_signature() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<k256::Secp256k1>::random(&mut rand::thread_rng());
+		let signer = movement_signer::local::LocalSigner::new(signing_key.clone());
+		let signed_blob =
+			InnerSignedBlobV1Data::new(vec![7; 4096], 123).try_to_sign(&signing_key)?;
+
+		// a remote signer signs the payload as a local key does
+		let local = InnerSignedBlobV2::try_new(signed_blob.clone(), BlobCodec::Zstd, &signing_key)?;
+		let remote =
+			InnerSignedBlobV2::try_new_with(signed_blob.clone(), BlobCodec::Zstd, &signer).await?;
+		assert_eq!(remote.payload_signature, local.payload_signature);
+		let ir_blob: IntermediateBlobRepresentation = remote.into();
+		let decoded: IntermediateBlobRepresentation = bcs::from_bytes(&bcs::to_bytes(&ir_blob)?)?;
+		assert_eq!(decoded.blob(), signed_blob.data.blob.as_slice());
+
+		// a payload which was not signed is never decompressed
+		let mut tampered = CompressedSignedBlobV2::from(local);
+		tampered.payload = BlobCodec::Zstd.compress(&[0; 4096])?;
+		assert!(InnerSignedBlobV2::try_from(tampered).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_blob_chunks_reassemble() -> Result<(), anyhow::Error> {
+		let data: Vec<u8> = (0..=255).collect();
+		let mut chunks = BlobChunk::split(vec![1], &data, 100);
+		assert_eq!(chunks.len(), 3);
+		assert!(chunks.iter().all(|chunk| chunk.total == 3));
+
+		let bytes = chunks[1].try_to_bytes()?;
+		assert_eq!(BlobChunk::try_from_bytes(&bytes).transpose()?, Some(chunks[1].clone()));
+		assert!(BlobChunk::try_from_bytes(&data).is_none());
+
+		// order and duplicates do not matter
+		chunks.reverse();
+		chunks.push(chunks[0].clone());
+		assert_eq!(BlobChunk::reassemble(chunks.clone())?, data);
+
+		// missing chunks
+		assert!(BlobChunk::reassemble(chunks[1..3].to_vec()).is_err());
+
+		// conflicting chunks
+		let mut conflicting = chunks.clone();
+		conflicting[0].data[0] ^= 1;
+		assert!(BlobChunk::reassemble(conflicting).is_err());
+
+		// chunks of another blob
+		let mut mixed = chunks;
+		mixed[0].blob_id = vec![2];
+		assert!(BlobChunk::reassemble(mixed).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_v1_encoding_is_unchanged() -> Result<(), anyhow::Error> {
+		let blob = InnerSignedBlobV1Data::new(vec![1, 2, 3], 123);
+		let signing_key = SigningKey::<k256::Secp256k1>::random(&mut rand::thread_rng());
+		let ir_blob: IntermediateBlobRepresentation = blob.try_to_sign(&signing_key)?.into();
+
+		let bytes = bcs::to_bytes(&ir_blob)?;
+		// the variant tag of the first format
+		assert_eq!(bytes[0], 0);
+		let decoded: IntermediateBlobRepresentation = bcs::from_bytes(&bytes)?;
+		assert_eq!(decoded.codec(), BlobCodec::None);
+		assert_eq!(decoded.blob(), &[1, 2, 3]);
+		decoded.verify_signature::<k256::Secp256k1>()?;
+
+		Ok(())
+	}
 }
 
 pub mod celestia {
@@ -207,14 +619,24 @@ pub mod celestia {
 	use anyhow::Context;
 	use celestia_types::{consts::appconsts::AppVersion, nmt::Namespace, Blob as CelestiaBlob};
 
+	/// The magic number which starts every zstd frame.
+	const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
 	impl TryFrom<CelestiaBlob> for IntermediateBlobRepresentation {
 		type Error = anyhow::Error;
 
-		// todo: it would be nice to have this be self describing over the compression and serialization format
+		/// Reads both the legacy zstd framed blobs and the self describing V2 blobs.
+		///
+		/// V2 blobs are posted as plain bcs, whose leading variant tag can never be the start of a zstd frame.
 		fn try_from(blob: CelestiaBlob) -> Result<Self, Self::Error> {
-			// decompress blob.data with zstd
-			let decompressed =
-				zstd::decode_all(blob.data.as_slice()).context("failed to decompress blob")?;
+			if !blob.data.starts_with(&ZSTD_MAGIC) {
+				return bcs::from_bytes(blob.data.as_slice()).context("failed to deserialize blob");
+			}
+
+			// decompress blob.data with zstd, where the blob is framed with the signature around the data
+			let decompressed = super::BlobCodec::Zstd
+				.decompress(blob.data.as_slice(), super::MAX_BLOB_DATA_SIZE + 1024)
+				.context("failed to decompress blob")?;
 
 			// deserialize the decompressed data with bcs
 			let blob =
@@ -240,9 +662,14 @@ pub mod celestia {
 			// Serialize the inner blob with bcs
 			let serialized_blob = bcs::to_bytes(&ir_blob).context("failed to serialize blob")?;
 
-			// Compress the serialized data with zstd
-			let compressed_blob = zstd::encode_all(serialized_blob.as_slice(), 0)
-				.context("failed to compress blob")?;
+			// V2 blobs carry their own compression, legacy blobs are compressed as a whole with zstd
+			let compressed_blob = match ir_blob {
+				IntermediateBlobRepresentation::SignedV1(_) => {
+					zstd::encode_all(serialized_blob.as_slice(), 0)
+						.context("failed to compress blob")?
+				}
+				IntermediateBlobRepresentation::SignedV2(_) => serialized_blob,
+			};
 
 			// Construct the final CelestiaBlob by assigning the compressed data
 			// and associating it with the provided namespace
@@ -250,4 +677,36 @@ pub mod celestia {
 				.map_err(|e| anyhow::anyhow!(e))?)
 		}
 	}
+
+	#[cfg(test)]
+	pub mod test {
+
+		use super::*;
+		use crate::ir_blob::{BlobCodec, InnerSignedBlobV1Data, InnerSignedBlobV2};
+		use ecdsa::SigningKey;
+
+		#[test]
+		fn test_reads_v1_and_v2_celestia_blobs() -> Result<(), anyhow::Error> {
+			let signing_key = SigningKey::<k256::Secp256k1>::random(&mut rand::thread_rng());
+			let signed_blob =
+				InnerSignedBlobV1Data::new(vec![5; 1024], 123).try_to_sign(&signing_key)?;
+
+			let v1: IntermediateBlobRepresentation = signed_blob.clone().into();
+			let v2: IntermediateBlobRepresentation =
+				InnerSignedBlobV2::try_new(signed_blob, BlobCodec::Lz4, &signing_key)?.into();
+
+			for ir_blob in [v1, v2] {
+				let codec = ir_blob.codec();
+				let namespace = Namespace::new_v0(b"movement")?;
+				let celestia_blob: CelestiaBlob =
+					CelestiaIntermediateBlobRepresentation(ir_blob, namespace).try_into()?;
+				let decoded = IntermediateBlobRepresentation::try_from(celestia_blob)?;
+				assert_eq!(decoded.codec(), codec);
+				assert_eq!(decoded.blob(), &[5; 1024]);
+				decoded.verify_signature::<k256::Secp256k1>()?;
+			}
+
+			Ok(())
+		}
+	}
 }
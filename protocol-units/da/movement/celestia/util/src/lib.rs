@@ -1,3 +1,4 @@
 pub mod config;
 pub use config::*;
 pub mod ir_blob;
+pub mod signers;
@@ -0,0 +1,344 @@
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	signature::{digest::Digest, DigestVerifier},
+	SignatureSize, SigningKey, VerifyingKey,
+};
+use memseq_util::ProposerRotation;
+use sequencing_util::{ProposerSchedule, SlotClock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// A DA signing key and the DA heights it signs blobs for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SignerKey {
+	/// The hex encoded sec1 public key.
+	pub public_key_hex: String,
+	/// The first height at which blobs signed with the key are accepted.
+	pub activation_height: u64,
+	/// The first height at which blobs signed with the key are no longer accepted, if the key is retired.
+	pub retirement_height: Option<u64>,
+}
+
+impl SignerKey {
+	/// A key which signs for all heights.
+	pub fn permanent(public_key_hex: String) -> Self {
+		Self { public_key_hex, activation_height: 0, retirement_height: None }
+	}
+
+	pub fn is_active_at(&self, height: u64) -> bool {
+		height >= self.activation_height
+			&& self
+				.retirement_height
+				.map_or(true, |retirement_height| height < retirement_height)
+	}
+}
+
+/// The DA signing keys over the history of the chain.
+///
+/// Blobs are checked against the keys that were active at their height,
+/// so blobs signed with a retired key still verify when the chain is replayed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SignerRegistry {
+	keys: Vec<SignerKey>,
+}
+
+impl SignerRegistry {
+	pub fn new(keys: impl IntoIterator<Item = SignerKey>) -> Self {
+		let mut registry = Self::default();
+		registry.extend(keys);
+		registry
+	}
+
+	/// A registry of keys which sign for all heights.
+	pub fn permanent<T>(public_keys_hex: T) -> Self
+	where
+		T: IntoIterator,
+		T::Item: Into<String>,
+	{
+		Self::new(public_keys_hex.into_iter().map(|key| SignerKey::permanent(key.into())))
+	}
+
+	pub fn extend(&mut self, keys: impl IntoIterator<Item = SignerKey>) {
+		for key in keys {
+			if !self.keys.contains(&key) {
+				self.keys.push(key);
+			}
+		}
+	}
+
+	pub fn keys(&self) -> &[SignerKey] {
+		&self.keys
+	}
+
+	/// Whether the key signs blobs at the height.
+	pub fn is_active_at(&self, public_key_hex: &str, height: u64) -> bool {
+		self.keys
+			.iter()
+			.any(|key| key.public_key_hex == public_key_hex && key.is_active_at(height))
+	}
+
+	/// The keys which sign blobs at the height.
+	pub fn active_at(&self, height: u64) -> HashSet<String> {
+		self.keys
+			.iter()
+			.filter(|key| key.is_active_at(height))
+			.map(|key| key.public_key_hex.clone())
+			.collect()
+	}
+}
+
+/// The rotation of block production between the DA signers, following the heights each signer is active at.
+///
+/// The signer registry is read at startup, so a new rotation file only takes effect after a restart.
+/// The rotations already in the registry take effect at their activation and retirement heights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerSchedule {
+	/// The signer keys along with their sec1 encoding.
+	signers: Vec<(SignerKey, Vec<u8>)>,
+	clock: SlotClock,
+}
+
+impl SignerSchedule {
+	pub fn try_new(registry: &SignerRegistry, clock: SlotClock) -> Result<Self, anyhow::Error> {
+		let signers = registry
+			.keys()
+			.iter()
+			.map(|key| Ok((key.clone(), hex::decode(&key.public_key_hex)?)))
+			.collect::<Result<Vec<_>, anyhow::Error>>()?;
+		// checks the clock and that there is a proposer at all
+		ProposerSchedule::try_new(signers.iter().map(|(_, sec1)| sec1.clone()), clock)?;
+		Ok(Self { signers, clock })
+	}
+
+	/// The schedule for a proposer rotation, if block production rotates at all.
+	pub fn try_from_rotation(
+		registry: &SignerRegistry,
+		rotation: &ProposerRotation,
+	) -> Result<Option<Self>, anyhow::Error> {
+		let clock = match rotation {
+			ProposerRotation::Disabled => return Ok(None),
+			ProposerRotation::DaHeight { slot_heights } => {
+				SlotClock::DaHeight { slot_heights: *slot_heights }
+			}
+			ProposerRotation::Time { slot_ms } => SlotClock::Time { slot_ms: *slot_ms },
+		};
+		Ok(Some(Self::try_new(registry, clock)?))
+	}
+
+	pub fn clock(&self) -> SlotClock {
+		self.clock
+	}
+
+	/// Whether the key, in its sec1 encoding, is one of the signers at any height.
+	pub fn contains(&self, signer: &[u8]) -> bool {
+		self.signers.iter().any(|(_, sec1)| sec1.as_slice() == signer)
+	}
+
+	/// The schedule between the signers active at the height, if any signer is.
+	pub fn at_height(&self, height: u64) -> Option<ProposerSchedule> {
+		let proposers: Vec<Vec<u8>> = self
+			.signers
+			.iter()
+			.filter(|(key, _)| key.is_active_at(height))
+			.map(|(_, sec1)| sec1.clone())
+			.collect();
+		if proposers.is_empty() {
+			return None;
+		}
+		ProposerSchedule::try_new(proposers, self.clock).ok()
+	}
+}
+
+/// A set of signer keys endorsed by a rotation authority, as distributed in a rotation file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedSignerRotation {
+	pub keys: Vec<SignerKey>,
+	pub signature: Vec<u8>,
+	/// The sec1 encoded public key of the rotation authority.
+	pub signer: Vec<u8>,
+}
+
+impl SignedSignerRotation {
+	/// Signs the keys with the key of a rotation authority.
+	pub fn try_sign<C>(
+		keys: Vec<SignerKey>,
+		signing_key: &SigningKey<C>,
+	) -> Result<Self, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let prehash = C::Digest::new_with_prefix(bcs::to_bytes(&keys)?).finalize();
+		let (signature, _recovery_id) = signing_key.sign_prehash_recoverable(prehash.as_slice())?;
+
+		Ok(Self {
+			keys,
+			signature: signature.to_vec(),
+			signer: signing_key.verifying_key().to_sec1_bytes().to_vec(),
+		})
+	}
+
+	/// Checks that the keys were signed by one of the rotation authorities, given as hex encoded sec1 keys.
+	pub fn try_verify<C>(&self, authorities_hex: &HashSet<String>) -> Result<(), anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		if !authorities_hex.contains(&hex::encode(&self.signer)) {
+			anyhow::bail!("Signer rotation is not signed by a rotation authority");
+		}
+
+		let digest = C::Digest::new_with_prefix(bcs::to_bytes(&self.keys)?);
+		let verifying_key = VerifyingKey::<C>::from_sec1_bytes(self.signer.as_slice())?;
+		let signature = ecdsa::Signature::<C>::from_slice(self.signature.as_slice())?;
+
+		match verifying_key.verify_digest(digest, &signature) {
+			Ok(_) => Ok(()),
+			Err(_) => Err(anyhow::anyhow!("Failed to verify signer rotation signature")),
+		}
+	}
+
+	/// Reads a rotation file and returns its keys if it is signed by one of the rotation authorities.
+	pub fn try_load_keys<C>(
+		path: impl AsRef<Path>,
+		authorities_hex: &HashSet<String>,
+	) -> Result<Vec<SignerKey>, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let rotation: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+		rotation.try_verify::<C>(authorities_hex)?;
+		Ok(rotation.keys)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use k256::Secp256k1;
+
+	#[test]
+	fn test_keys_are_scoped_to_heights() {
+		let registry = SignerRegistry::new(vec![
+			SignerKey {
+				public_key_hex: "old".to_string(),
+				activation_height: 0,
+				retirement_height: Some(100),
+			},
+			SignerKey {
+				public_key_hex: "new".to_string(),
+				activation_height: 100,
+				retirement_height: None,
+			},
+		]);
+
+		assert!(registry.is_active_at("old", 0));
+		assert!(registry.is_active_at("old", 99));
+		assert!(!registry.is_active_at("old", 100));
+		assert!(!registry.is_active_at("new", 99));
+		assert!(registry.is_active_at("new", 100));
+		assert!(registry.is_active_at("new", u64::MAX));
+		assert!(!registry.is_active_at("other", 50));
+		assert_eq!(registry.active_at(100), HashSet::from(["new".to_string()]));
+
+		let registry = SignerRegistry::permanent(vec!["key"]);
+		assert!(registry.is_active_at("key", 0));
+		assert!(registry.is_active_at("key", u64::MAX));
+	}
+
+	#[test]
+	fn test_signed_rotation() -> Result<(), anyhow::Error> {
+		let authority = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let authorities = HashSet::from([hex::encode(authority.verifying_key().to_sec1_bytes())]);
+		let keys = vec![SignerKey {
+			public_key_hex: "new".to_string(),
+			activation_height: 100,
+			retirement_height: None,
+		}];
+
+		let rotation = SignedSignerRotation::try_sign(keys.clone(), &authority)?;
+		rotation.try_verify::<Secp256k1>(&authorities)?;
+
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("rotation.json");
+		std::fs::write(&path, serde_json::to_vec(&rotation)?)?;
+		assert_eq!(SignedSignerRotation::try_load_keys::<Secp256k1>(&path, &authorities)?, keys);
+
+		// the keys cannot be changed after signing
+		let mut forged = rotation.clone();
+		forged.keys[0].activation_height = 0;
+		assert!(forged.try_verify::<Secp256k1>(&authorities).is_err());
+
+		// nor can anyone but an authority sign them
+		let other = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let rotation = SignedSignerRotation::try_sign(keys, &other)?;
+		assert!(rotation.try_verify::<Secp256k1>(&authorities).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_schedule_follows_the_signer_registry() -> Result<(), anyhow::Error> {
+		let registry = SignerRegistry::new(vec![
+			SignerKey::permanent("01".to_string()),
+			SignerKey {
+				public_key_hex: "02".to_string(),
+				activation_height: 0,
+				retirement_height: Some(100),
+			},
+			SignerKey {
+				public_key_hex: "03".to_string(),
+				activation_height: 100,
+				retirement_height: None,
+			},
+		]);
+		let schedule = SignerSchedule::try_new(&registry, SlotClock::DaHeight { slot_heights: 1 })?;
+		assert!(schedule.contains(&[3]));
+		assert!(!schedule.contains(&[4]));
+
+		// the retired key stops taking turns once its successor takes over
+		let before = schedule.at_height(99).expect("signers are active");
+		assert_eq!(before.proposers(), &[vec![1], vec![2]]);
+		let after = schedule.at_height(100).expect("signers are active");
+		assert_eq!(after.proposers(), &[vec![1], vec![3]]);
+		assert!(after.is_proposer_for_slot(&[3], after.slot(101, 0)));
+
+		// no one proposes at heights without an active signer
+		let registry = SignerRegistry::new(vec![SignerKey {
+			public_key_hex: "01".to_string(),
+			activation_height: 10,
+			retirement_height: None,
+		}]);
+		let schedule = SignerSchedule::try_new(&registry, SlotClock::Time { slot_ms: 1 })?;
+		assert!(schedule.at_height(9).is_none());
+		assert!(schedule.at_height(10).is_some());
+
+		assert!(SignerSchedule::try_new(
+			&SignerRegistry::default(),
+			SlotClock::Time { slot_ms: 1 }
+		)
+		.is_err());
+
+		Ok(())
+	}
+}
@@ -0,0 +1,319 @@
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	signature::{digest::Digest, DigestVerifier},
+	SignatureSize, SigningKey, VerifyingKey,
+};
+use k256::Secp256k1;
+use movement_celestia_da_util::config::local::da_light_node::DaServiceCredentials;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tonic::metadata::AsciiMetadataValue;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
+
+/// The metadata key bearer tokens are sent under.
+pub const AUTHORIZATION_METADATA_KEY: &str = "authorization";
+
+/// The name bearer tokens for `BatchWrite` requests are signed for.
+pub const BATCH_WRITE_METHOD: &str = "BatchWrite";
+
+/// The name bearer tokens for `StreamReadFromHeight` requests are signed for.
+pub const STREAM_READ_FROM_HEIGHT_METHOD: &str = "StreamReadFromHeight";
+
+/// The name bearer tokens for `ReadAtHeight` requests are signed for.
+pub const READ_AT_HEIGHT_METHOD: &str = "ReadAtHeight";
+
+/// The name bearer tokens for `StreamWriteBlob` requests are signed for, over an empty body.
+pub const STREAM_WRITE_BLOB_METHOD: &str = "StreamWriteBlob";
+
+/// Separates bearer token signatures from signatures over other data made with the same key.
+const TOKEN_DOMAIN: &[u8] = b"movement-da-light-node-bearer-token";
+
+/// A token proving that a client held the key of its identity at a point in time,
+/// and that it made a request to a method with a body.
+///
+/// It is sent as `Bearer <client name>.<timestamp ms>.<hex signature>`.
+/// The light node only accepts recent tokens, and clients sign a new token for every request.
+/// The method and the protobuf encoded body are signed but not sent, the light node takes them from the request,
+/// so a token cannot be replayed with another request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BearerToken {
+	pub client_name: String,
+	/// The time the token was signed at, in milliseconds since the epoch.
+	pub timestamp_ms: u64,
+	pub signature: Vec<u8>,
+}
+
+impl BearerToken {
+	fn digest<D: Digest>(client_name: &str, timestamp_ms: u64, method: &str, body: &[u8]) -> D {
+		D::new_with_prefix(TOKEN_DOMAIN)
+			.chain_update(client_name.as_bytes())
+			.chain_update([0])
+			.chain_update(timestamp_ms.to_be_bytes())
+			.chain_update(method.as_bytes())
+			.chain_update([0])
+			.chain_update(body)
+	}
+
+	/// Signs a token for the client's request to the method with the body at the given time.
+	pub fn try_sign<C>(
+		client_name: &str,
+		timestamp_ms: u64,
+		method: &str,
+		body: &[u8],
+		signing_key: &SigningKey<C>,
+	) -> Result<Self, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		if client_name.is_empty() || client_name.contains('.') {
+			anyhow::bail!("Client names must be non-empty and must not contain a '.'");
+		}
+		let prehash = Self::digest::<C::Digest>(client_name, timestamp_ms, method, body).finalize();
+		let (signature, _recovery_id) = signing_key.sign_prehash_recoverable(prehash.as_slice())?;
+
+		Ok(Self {
+			client_name: client_name.to_string(),
+			timestamp_ms,
+			signature: signature.to_vec(),
+		})
+	}
+
+	/// Checks that the token was signed by the holder of the sec1 encoded public key,
+	/// for a request to the method with the body.
+	pub fn try_verify<C>(
+		&self,
+		public_key: &[u8],
+		method: &str,
+		body: &[u8],
+	) -> Result<(), anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let digest = Self::digest::<C::Digest>(&self.client_name, self.timestamp_ms, method, body);
+		let verifying_key = VerifyingKey::<C>::from_sec1_bytes(public_key)?;
+		let signature = ecdsa::Signature::<C>::from_slice(self.signature.as_slice())?;
+
+		match verifying_key.verify_digest(digest, &signature) {
+			Ok(_) => Ok(()),
+			Err(_) => Err(anyhow::anyhow!("Failed to verify bearer token signature")),
+		}
+	}
+
+	pub fn to_header_value(&self) -> String {
+		format!(
+			"Bearer {}.{}.{}",
+			self.client_name,
+			self.timestamp_ms,
+			hex::encode(&self.signature)
+		)
+	}
+
+	pub fn try_from_header_value(value: &str) -> Result<Self, anyhow::Error> {
+		let token = value
+			.strip_prefix("Bearer ")
+			.ok_or(anyhow::anyhow!("Authorization is not a bearer token"))?;
+		let mut parts = token.split('.');
+		match (parts.next(), parts.next(), parts.next(), parts.next()) {
+			(Some(client_name), Some(timestamp_ms), Some(signature), None)
+				if !client_name.is_empty() =>
+			{
+				Ok(Self {
+					client_name: client_name.to_string(),
+					timestamp_ms: timestamp_ms.parse()?,
+					signature: hex::decode(signature)?,
+				})
+			}
+			_ => anyhow::bail!("Malformed bearer token"),
+		}
+	}
+}
+
+/// The identity a client authenticates to the light node with using bearer tokens.
+#[derive(Debug, Clone)]
+pub struct BearerCredentials {
+	pub client_name: String,
+	pub signing_key: SigningKey<Secp256k1>,
+}
+
+impl BearerCredentials {
+	pub fn new(client_name: String, signing_key: SigningKey<Secp256k1>) -> Self {
+		Self { client_name, signing_key }
+	}
+
+	pub fn try_from_hex(client_name: String, signing_key_hex: &str) -> Result<Self, anyhow::Error> {
+		let signing_key = SigningKey::from_slice(&hex::decode(signing_key_hex)?)
+			.map_err(|e| anyhow::anyhow!("Failed to create signing key: {}", e))?;
+		Ok(Self::new(client_name, signing_key))
+	}
+
+	/// Signs a fresh token for the method and the body of the request into its metadata.
+	pub fn authorize<T: prost::Message>(
+		&self,
+		method: &str,
+		request: &mut tonic::Request<T>,
+	) -> Result<(), tonic::Status> {
+		let timestamp_ms = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map_err(|e| tonic::Status::internal(e.to_string()))?
+			.as_millis() as u64;
+		let body = request.get_ref().encode_to_vec();
+		let token = BearerToken::try_sign(
+			&self.client_name,
+			timestamp_ms,
+			method,
+			&body,
+			&self.signing_key,
+		)
+		.map_err(|e| tonic::Status::internal(e.to_string()))?;
+		let value: AsciiMetadataValue = token
+			.to_header_value()
+			.parse()
+			.map_err(|_| tonic::Status::internal("Bearer token is not valid metadata"))?;
+		request.metadata_mut().insert(AUTHORIZATION_METADATA_KEY, value);
+		Ok(())
+	}
+}
+
+/// TLS for the connection to the light node.
+#[derive(Debug, Clone)]
+pub struct ClientTls {
+	/// The PEM encoded certificate of the authority which signed the light node's certificate.
+	pub ca_certificate_pem: Vec<u8>,
+	/// The PEM encoded certificate and key presented to a light node which requires mTLS.
+	pub identity_pem: Option<(Vec<u8>, Vec<u8>)>,
+	/// The name the light node's certificate is checked against, if not the host connected to.
+	pub domain_name: Option<String>,
+}
+
+impl ClientTls {
+	/// Reads the PEM files making up the TLS configuration.
+	pub fn try_from_files(
+		ca_certificate_path: &str,
+		identity_paths: Option<(&str, &str)>,
+		domain_name: Option<String>,
+	) -> Result<Self, anyhow::Error> {
+		let ca_certificate_pem = std::fs::read(ca_certificate_path)?;
+		let identity_pem = match identity_paths {
+			Some((certificate_path, key_path)) => {
+				Some((std::fs::read(certificate_path)?, std::fs::read(key_path)?))
+			}
+			None => None,
+		};
+		Ok(Self { ca_certificate_pem, identity_pem, domain_name })
+	}
+
+	pub fn to_tls_config(&self) -> ClientTlsConfig {
+		let mut config =
+			ClientTlsConfig::new().ca_certificate(Certificate::from_pem(&self.ca_certificate_pem));
+		if let Some((certificate, key)) = &self.identity_pem {
+			config = config.identity(Identity::from_pem(certificate, key));
+		}
+		if let Some(domain_name) = &self.domain_name {
+			config = config.domain_name(domain_name.clone());
+		}
+		config
+	}
+}
+
+/// How a client authenticates to the light node.
+#[derive(Debug, Clone, Default)]
+pub struct ClientOptions {
+	/// Sign a bearer token into every request.
+	pub bearer: Option<BearerCredentials>,
+	/// Connect over TLS. This is only supported over http2.
+	pub tls: Option<ClientTls>,
+}
+
+impl ClientOptions {
+	pub fn with_bearer(mut self, bearer: BearerCredentials) -> Self {
+		self.bearer = Some(bearer);
+		self
+	}
+
+	pub fn with_tls(mut self, tls: ClientTls) -> Self {
+		self.tls = Some(tls);
+		self
+	}
+
+	/// Builds the options from the configured credentials, reading their key and certificates.
+	pub fn try_from_credentials(credentials: &DaServiceCredentials) -> Result<Self, anyhow::Error> {
+		let mut options = Self::default();
+		if let Some(signing_key_hex) = &credentials.signing_key_hex {
+			options = options.with_bearer(BearerCredentials::try_from_hex(
+				credentials.client_name.clone(),
+				signing_key_hex,
+			)?);
+		}
+		if let Some(tls) = &credentials.tls {
+			let identity_paths = match (&tls.certificate_path, &tls.key_path) {
+				(Some(certificate_path), Some(key_path)) => {
+					Some((certificate_path.as_str(), key_path.as_str()))
+				}
+				(None, None) => None,
+				_ => anyhow::bail!("A client certificate needs both a certificate and a key path"),
+			};
+			options = options.with_tls(ClientTls::try_from_files(
+				&tls.ca_certificate_path,
+				identity_paths,
+				tls.domain_name.clone(),
+			)?);
+		}
+		Ok(options)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[test]
+	fn test_bearer_token_round_trip() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let public_key = signing_key.verifying_key().to_sec1_bytes().to_vec();
+
+		let token = BearerToken::try_sign("full-node-1", 1_000, "BatchWrite", &[1], &signing_key)?;
+		let parsed = BearerToken::try_from_header_value(&token.to_header_value())?;
+		assert_eq!(parsed, token);
+		parsed.try_verify::<Secp256k1>(&public_key, "BatchWrite", &[1])?;
+
+		// the signature does not carry over to another method
+		assert!(parsed
+			.try_verify::<Secp256k1>(&public_key, "StreamReadFromHeight", &[1])
+			.is_err());
+
+		// nor to another body
+		assert!(parsed.try_verify::<Secp256k1>(&public_key, "BatchWrite", &[2]).is_err());
+
+		// nor to another time
+		let mut forged = parsed.clone();
+		forged.timestamp_ms += 1;
+		assert!(forged.try_verify::<Secp256k1>(&public_key, "BatchWrite", &[1]).is_err());
+
+		// nor to another client
+		let mut forged = parsed;
+		forged.client_name = "full-node-2".to_string();
+		assert!(forged.try_verify::<Secp256k1>(&public_key, "BatchWrite", &[1]).is_err());
+
+		assert!(BearerToken::try_sign("full.node", 1_000, "BatchWrite", &[], &signing_key).is_err());
+		assert!(BearerToken::try_from_header_value("Basic abc").is_err());
+		assert!(BearerToken::try_from_header_value("Bearer a.1.00.00").is_err());
+
+		Ok(())
+	}
+}
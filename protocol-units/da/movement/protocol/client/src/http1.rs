@@ -1,3 +1,4 @@
+use crate::auth::BearerCredentials;
 use hyper_util::client::legacy::connect::HttpConnector;
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
@@ -17,6 +18,7 @@ pub struct Http1 {
 			>,
 		>,
 	>,
+	bearer: Option<BearerCredentials>,
 }
 
 impl Http1 {
@@ -28,7 +30,18 @@ impl Http1 {
 
 		let client = LightNodeServiceClient::with_origin(svc, connection_string.try_into()?);
 
-		Ok(Http1 { client })
+		Ok(Http1 { client, bearer: None })
+	}
+
+	/// Authenticates requests with bearer tokens signed with the credentials.
+	pub fn with_bearer(mut self, bearer: BearerCredentials) -> Self {
+		self.bearer = Some(bearer);
+		self
+	}
+
+	/// Returns the credentials requests are authenticated with, if any.
+	pub fn bearer(&self) -> Option<&BearerCredentials> {
+		self.bearer.as_ref()
 	}
 
 	/// Returns a reference to the client.
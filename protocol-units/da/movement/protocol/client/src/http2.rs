@@ -1,15 +1,41 @@
+use crate::auth::{BearerCredentials, ClientTls};
 use movement_da_light_node_proto::light_node_service_client::LightNodeServiceClient;
+use tonic::transport::Channel;
 
 #[derive(Debug, Clone)]
 pub struct Http2 {
 	client: LightNodeServiceClient<tonic::transport::Channel>,
+	bearer: Option<BearerCredentials>,
 }
 
 impl Http2 {
 	/// Connects to a light node service using the given connection string.
 	pub async fn connect(connection_string: &str) -> Result<Self, anyhow::Error> {
 		let client = LightNodeServiceClient::connect(connection_string.to_string()).await?;
-		Ok(Http2 { client })
+		Ok(Http2 { client, bearer: None })
+	}
+
+	/// Connects to a light node service over TLS using the given connection string.
+	pub async fn connect_with_tls(
+		connection_string: &str,
+		tls: &ClientTls,
+	) -> Result<Self, anyhow::Error> {
+		let channel = Channel::from_shared(connection_string.to_string())?
+			.tls_config(tls.to_tls_config())?
+			.connect()
+			.await?;
+		Ok(Http2 { client: LightNodeServiceClient::new(channel), bearer: None })
+	}
+
+	/// Authenticates requests with bearer tokens signed with the credentials.
+	pub fn with_bearer(mut self, bearer: BearerCredentials) -> Self {
+		self.bearer = Some(bearer);
+		self
+	}
+
+	/// Returns the credentials requests are authenticated with, if any.
+	pub fn bearer(&self) -> Option<&BearerCredentials> {
+		self.bearer.as_ref()
 	}
 
 	/// Returns a reference to the client.
@@ -0,0 +1,629 @@
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	SignatureSize,
+};
+use movement_celestia_da_util::ir_blob::{InnerSignedBlobV1, InnerSignedBlobV1Data};
+use movement_celestia_da_util::signers::{SignerRegistry, SignerSchedule};
+use movement_da_light_node_proto::{blob_response::BlobType, Blob, BlobResponse};
+use movement_types::{
+	block::Block,
+	intent::{SequencedIntent, SignedIntent},
+};
+use sequencing_util::SlotClock;
+use serde::{Deserialize, Serialize};
+
+/// Verifies a sequenced blob intent returned by the light node on write.
+///
+/// Returns the signed intent, which the caller should check to be signed by a known sequencer.
+pub fn verify_intent<C>(response: &BlobResponse) -> Result<SignedIntent, anyhow::Error>
+where
+	C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+	Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+	SignatureSize<C>: ArrayLength<u8>,
+	AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+	FieldBytesSize<C>: ModulusSize,
+{
+	let blob = match &response.blob_type {
+		Some(BlobType::SequencedBlobIntent(blob)) => blob,
+		_ => anyhow::bail!("Blob response is not a sequenced blob intent"),
+	};
+
+	let intent = SequencedIntent::try_from_bytes(&blob.data)?;
+	if blob.blob_id != intent.transaction_id.to_vec() {
+		anyhow::bail!("Intent blob id does not match the transaction id of the intent");
+	}
+	if blob.height != intent.target_block_height {
+		anyhow::bail!("Intent blob height does not match the target block height of the intent");
+	}
+
+	let signed_intent =
+		SignedIntent { intent, signature: blob.signature.clone(), signer: blob.signer.clone() };
+	signed_intent.try_verify::<C>()?;
+	Ok(signed_intent)
+}
+
+/// A block as it was posted to the DA, with the signature of the DA signer which posted it.
+///
+/// Only blocks posted in a single blob can be proven, as the chunks of a chunked block are not served to clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBlockBlob {
+	/// The bcs encoded block, as signed.
+	pub data: Vec<u8>,
+	pub timestamp: u64,
+	pub blob_id: Vec<u8>,
+	pub signature: Vec<u8>,
+	/// The sec1 encoded public key of the DA signer.
+	pub signer: Vec<u8>,
+	/// The DA height the blob was posted at.
+	pub da_height: u64,
+}
+
+impl From<Blob> for SignedBlockBlob {
+	fn from(blob: Blob) -> Self {
+		Self {
+			data: blob.data,
+			timestamp: blob.timestamp,
+			blob_id: blob.blob_id,
+			signature: blob.signature,
+			signer: blob.signer,
+			da_height: blob.height,
+		}
+	}
+}
+
+impl SignedBlockBlob {
+	/// Checks that the blob was signed by a DA signer active at its DA height and decodes the block it carries.
+	pub fn try_verify<C>(&self, signers: &SignerRegistry) -> Result<Block, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		if !signers.is_active_at(&hex::encode(&self.signer), self.da_height) {
+			anyhow::bail!(
+				"Blob {} is not signed by a DA signer active at DA height {}",
+				hex::encode(&self.blob_id),
+				self.da_height
+			);
+		}
+		InnerSignedBlobV1 {
+			data: InnerSignedBlobV1Data::new(self.data.clone(), self.timestamp),
+			signature: self.signature.clone(),
+			signer: self.signer.clone(),
+			id: self.blob_id.clone().into(),
+		}
+		.try_verify::<C>()?;
+		Ok(bcs::from_bytes(&self.data)?)
+	}
+}
+
+/// Evidence that a sequencer signed an intent and did not honor it.
+///
+/// The blobs carry a contiguous part of the chain in DA order, starting at or below the target block height,
+/// none of which before the deadline include the transaction, and ending with a block past the deadline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashableProof {
+	pub intent: SignedIntent,
+	pub blobs: Vec<SignedBlockBlob>,
+}
+
+impl SlashableProof {
+	/// Checks the proof against the DA signers and their proposer schedule, without any other access to the chain.
+	///
+	/// With a schedule every block must have been posted in the slot of its signer,
+	/// as blocks posted outside of it are dropped by the nodes.
+	pub fn try_verify<C>(
+		&self,
+		signers: &SignerRegistry,
+		schedule: Option<&SignerSchedule>,
+	) -> Result<(), anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let intent_signer = hex::encode(&self.intent.signer);
+		if !signers.keys().iter().any(|key| key.public_key_hex == intent_signer) {
+			anyhow::bail!("Intent is not signed by a known DA signer");
+		}
+		self.intent.try_verify::<C>()?;
+		let intent = &self.intent.intent;
+
+		let blocks = self
+			.blobs
+			.iter()
+			.map(|blob| blob.try_verify::<C>(signers))
+			.collect::<Result<Vec<_>, _>>()?;
+		if let Some(schedule) = schedule {
+			for blob in &self.blobs {
+				check_scheduled(schedule, blob)?;
+			}
+		}
+
+		let first = blocks.first().ok_or(anyhow::anyhow!("Proof contains no blocks"))?;
+		if block_height(first)? > intent.target_block_height {
+			anyhow::bail!("Proof blocks start after the target block height");
+		}
+		if self.blobs.windows(2).any(|pair| pair[1].da_height < pair[0].da_height) {
+			anyhow::bail!("Proof blobs are not in DA order");
+		}
+		check_contiguous(&blocks, intent.target_block_height)?;
+
+		for block in &blocks {
+			if block_timestamp(block)? <= intent.deadline && includes(block, intent) {
+				anyhow::bail!("Transaction was included before the deadline");
+			}
+		}
+
+		let last = blocks.last().ok_or(anyhow::anyhow!("Proof contains no blocks"))?;
+		if block_timestamp(last)? <= intent.deadline {
+			anyhow::bail!("Proof blocks do not reach past the deadline");
+		}
+
+		Ok(())
+	}
+}
+
+/// Checks that a blob was posted by the proposer of the slot of its DA height.
+///
+/// Slots measured in time depend on the time of the DA at the height, which proofs do not carry,
+/// so no proof is accepted under such a schedule.
+fn check_scheduled(schedule: &SignerSchedule, blob: &SignedBlockBlob) -> Result<(), anyhow::Error> {
+	let proposers = schedule
+		.at_height(blob.da_height)
+		.ok_or(anyhow::anyhow!("No DA signer is active at DA height {}", blob.da_height))?;
+	let slot = match proposers.clock() {
+		SlotClock::DaHeight { .. } => proposers.slot(blob.da_height, 0),
+		SlotClock::Time { .. } => {
+			anyhow::bail!("Proofs cannot be checked against proposer slots measured in time")
+		}
+	};
+	if !proposers.is_proposer_for_slot(&blob.signer, slot) {
+		anyhow::bail!(
+			"Blob {} was posted outside the slot of its signer",
+			hex::encode(&blob.blob_id)
+		);
+	}
+	Ok(())
+}
+
+/// Checks that no block is left out between the first and the last of the blocks.
+///
+/// The parts of a split block follow each other by their offsets, and must all be present from the target height on.
+/// A block extends either the block the parts before it were split from, or the last of those parts.
+fn check_contiguous(blocks: &[Block], target_block_height: u64) -> Result<(), anyhow::Error> {
+	let mut heights = Vec::<&[Block]>::new();
+	for (index, block) in blocks.iter().enumerate() {
+		let height = block_height(block)?;
+		match heights.last_mut() {
+			Some(parts) if block_height(&parts[0])? == height => {
+				let previous = &parts[parts.len() - 1];
+				if block.parent() != previous.parent()
+					|| block.metadata().offset()
+						!= previous.metadata().offset() + previous.transactions().len() as u64
+				{
+					anyhow::bail!(
+						"Proof blocks leave out a part of the block at height {}",
+						height
+					);
+				}
+				*parts = &blocks[index - parts.len()..=index];
+			}
+			_ => {
+				if height >= target_block_height && block.metadata().offset() != 0 {
+					anyhow::bail!(
+						"Proof blocks leave out a part of the block at height {}",
+						height
+					);
+				}
+				if let Some(parts) = heights.last() {
+					let previous = &parts[parts.len() - 1];
+					let extends =
+						block.parent() == unsplit(parts).id() || block.parent() == previous.id();
+					if height != block_height(previous)? + 1 || !extends {
+						anyhow::bail!("Proof blocks are not contiguous");
+					}
+				}
+				heights.push(&blocks[index..=index]);
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Rebuilds the block that parts were split from.
+fn unsplit(parts: &[Block]) -> Block {
+	let first = &parts[0];
+	let transactions = parts.iter().flat_map(|part| part.transactions().cloned()).collect();
+	Block::new(first.metadata().unsplit(), first.parent(), transactions)
+}
+
+/// Follows the chain of blocks and checks that signed intents are honored.
+///
+/// An intent can only be found broken once a block past its deadline is observed,
+/// so a stalled chain holds back proofs until it makes progress.
+/// Intents which the observed blocks cannot prove broken, e.g., after a gap in the chain, are given up on.
+#[derive(Debug, Clone)]
+pub struct IntentChecker {
+	/// The DA signers, with the heights they sign blobs for.
+	signers: SignerRegistry,
+	/// The proposer schedule of the DA signers, if block production rotates between them.
+	schedule: Option<SignerSchedule>,
+	pending: Vec<SignedIntent>,
+	/// The latest observed blocks with the blobs which carried them, in DA order.
+	blocks: Vec<(Block, SignedBlockBlob)>,
+	/// How many blocks to keep beyond those needed by pending intents, for intents added late.
+	retained_blocks: usize,
+}
+
+impl IntentChecker {
+	pub fn new(
+		signers: SignerRegistry,
+		schedule: Option<SignerSchedule>,
+		retained_blocks: usize,
+	) -> Self {
+		Self { signers, schedule, pending: Vec::new(), blocks: Vec::new(), retained_blocks }
+	}
+
+	/// Adds an intent, which should have been verified with [verify_intent].
+	pub fn add_intent(&mut self, intent: SignedIntent) {
+		self.pending.push(intent);
+	}
+
+	/// The intents whose transactions have not been seen in a block yet.
+	pub fn pending(&self) -> &[SignedIntent] {
+		&self.pending
+	}
+
+	/// Observes the next blob of the chain and returns proofs for the intents it shows to be broken.
+	///
+	/// Blobs must be observed in DA order. Blobs which are not signed by a DA signer active at their DA height
+	/// are rejected, and blocks without a height are ignored.
+	pub fn observe_blob<C>(
+		&mut self,
+		blob: SignedBlockBlob,
+	) -> Result<Vec<SlashableProof>, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let block = blob.try_verify::<C>(&self.signers)?;
+		if block.metadata().height().is_none() || block.metadata().timestamp().is_none() {
+			return Ok(Vec::new());
+		}
+		self.blocks.push((block, blob));
+
+		let mut proofs = Vec::new();
+		let blocks = &self.blocks;
+		let signers = &self.signers;
+		let schedule = self.schedule.as_ref();
+		self.pending.retain(|signed_intent| {
+			let intent = &signed_intent.intent;
+			let start = blocks
+				.iter()
+				.position(|(block, _)| {
+					block_height(block).unwrap_or(0) >= intent.target_block_height
+				})
+				.unwrap_or(blocks.len() - 1);
+			let relevant = &blocks[start..];
+
+			let honored = relevant.iter().any(|(block, _)| {
+				block_timestamp(block).unwrap_or(u64::MAX) <= intent.deadline
+					&& includes(block, intent)
+			});
+			if honored {
+				return false;
+			}
+
+			let past_deadline = relevant
+				.last()
+				.map(|(block, _)| block_timestamp(block).unwrap_or(0) > intent.deadline)
+				.unwrap_or(false);
+			if past_deadline {
+				let proof = SlashableProof {
+					intent: signed_intent.clone(),
+					blobs: relevant.iter().map(|(_, blob)| blob.clone()).collect(),
+				};
+				if proof.try_verify::<C>(signers, schedule).is_ok() {
+					proofs.push(proof);
+				}
+				return false;
+			}
+
+			true
+		});
+
+		self.prune_blocks();
+		Ok(proofs)
+	}
+
+	/// Drops the blocks which are neither needed by pending intents nor among the retained ones.
+	fn prune_blocks(&mut self) {
+		let min_target = self.pending.iter().map(|intent| intent.intent.target_block_height).min();
+		let keep_from = self.blocks.len().saturating_sub(self.retained_blocks.max(1));
+		let needed_from = match min_target {
+			Some(target) => self
+				.blocks
+				.iter()
+				.position(|(block, _)| block_height(block).unwrap_or(0) >= target)
+				.unwrap_or(self.blocks.len() - 1),
+			None => keep_from,
+		};
+		self.blocks.drain(..keep_from.min(needed_from));
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use ecdsa::SigningKey;
+	use k256::Secp256k1;
+	use movement_celestia_da_util::signers::SignerKey;
+	use movement_types::{
+		block::{BlockMetadata, Id},
+		transaction::Transaction,
+	};
+	use std::collections::BTreeSet;
+
+	fn signing_key() -> Result<SigningKey<Secp256k1>, anyhow::Error> {
+		Ok(SigningKey::<Secp256k1>::from_slice(&[7; 32])?)
+	}
+
+	fn signer_hex() -> Result<String, anyhow::Error> {
+		Ok(hex::encode(signing_key()?.verifying_key().to_sec1_bytes()))
+	}
+
+	fn known_signers() -> Result<SignerRegistry, anyhow::Error> {
+		Ok(SignerRegistry::permanent(vec![signer_hex()?]))
+	}
+
+	fn signed_intent(
+		transaction: &Transaction,
+		target_block_height: u64,
+		deadline: u64,
+	) -> Result<SignedIntent, anyhow::Error> {
+		SequencedIntent::new(transaction.id(), target_block_height, deadline)
+			.try_to_sign(&signing_key()?)
+	}
+
+	fn signed_blob(block: &Block, da_height: u64) -> Result<SignedBlockBlob, anyhow::Error> {
+		let signed =
+			InnerSignedBlobV1Data::new(bcs::to_bytes(block)?, 0).try_to_sign(&signing_key()?)?;
+		Ok(SignedBlockBlob {
+			data: signed.data.blob,
+			timestamp: signed.data.timestamp,
+			blob_id: signed.id.into_vec(),
+			signature: signed.signature,
+			signer: signed.signer,
+			da_height,
+		})
+	}
+
+	fn chain(
+		timestamps: &[u64],
+		included_at: Option<usize>,
+		transaction: &Transaction,
+	) -> Result<Vec<SignedBlockBlob>, anyhow::Error> {
+		let mut parent = Id::genesis_block();
+		let mut blobs = Vec::new();
+		for (index, timestamp) in timestamps.iter().enumerate() {
+			let mut transactions =
+				BTreeSet::from_iter(vec![Transaction::new(vec![index as u8], 0, 0)]);
+			if included_at == Some(index) {
+				transactions.insert(transaction.clone());
+			}
+			let block = Block::new(
+				BlockMetadata::new(*timestamp, vec![1], index as u64 + 1),
+				parent,
+				transactions,
+			);
+			parent = block.id();
+			blobs.push(signed_blob(&block, index as u64 + 2)?);
+		}
+		Ok(blobs)
+	}
+
+	#[test]
+	fn test_verify_intent() -> Result<(), anyhow::Error> {
+		let transaction = Transaction::new(vec![42], 0, 0);
+		let signed = signed_intent(&transaction, 2, 100)?;
+		let mut blob = Blob {
+			data: signed.intent.to_bytes()?,
+			blob_id: transaction.id().to_vec(),
+			height: 2,
+			signature: signed.signature.clone(),
+			signer: signed.signer.clone(),
+			timestamp: 0,
+		};
+		let response =
+			BlobResponse { blob_type: Some(BlobType::SequencedBlobIntent(blob.clone())) };
+		assert_eq!(verify_intent::<Secp256k1>(&response)?, signed);
+
+		blob.height = 3;
+		let response =
+			BlobResponse { blob_type: Some(BlobType::SequencedBlobIntent(blob.clone())) };
+		assert!(verify_intent::<Secp256k1>(&response).is_err());
+
+		let response = BlobResponse { blob_type: Some(BlobType::PassedThroughBlob(blob)) };
+		assert!(verify_intent::<Secp256k1>(&response).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_honored_intent() -> Result<(), anyhow::Error> {
+		let transaction = Transaction::new(vec![42], 0, 0);
+		let mut checker = IntentChecker::new(known_signers()?, None, 8);
+		checker.add_intent(signed_intent(&transaction, 2, 100)?);
+
+		for blob in chain(&[10, 50, 150], Some(1), &transaction)? {
+			assert!(checker.observe_blob::<Secp256k1>(blob)?.is_empty());
+		}
+		assert!(checker.pending().is_empty());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_broken_intent() -> Result<(), anyhow::Error> {
+		let transaction = Transaction::new(vec![42], 0, 0);
+		let known_signers = known_signers()?;
+		let mut checker = IntentChecker::new(known_signers.clone(), None, 8);
+		checker.add_intent(signed_intent(&transaction, 2, 100)?);
+
+		// included, but only after the deadline
+		let blobs = chain(&[10, 50, 90, 150], Some(3), &transaction)?;
+		let mut proofs = Vec::new();
+		for blob in blobs.clone() {
+			proofs.extend(checker.observe_blob::<Secp256k1>(blob)?);
+		}
+		assert!(checker.pending().is_empty());
+		assert_eq!(proofs.len(), 1);
+		assert_eq!(proofs[0].blobs, blobs[1..].to_vec());
+		proofs[0].try_verify::<Secp256k1>(&known_signers, None)?;
+
+		// a proof is only accepted from known DA signers
+		assert!(proofs[0].try_verify::<Secp256k1>(&SignerRegistry::default(), None).is_err());
+
+		// a proof is only accepted from DA signers active at the heights of its blobs
+		let retired = SignerRegistry::new(vec![SignerKey {
+			public_key_hex: signer_hex()?,
+			activation_height: 0,
+			retirement_height: Some(4),
+		}]);
+		assert!(proofs[0].try_verify::<Secp256k1>(&retired, None).is_err());
+
+		// a proof which leaves out a block is rejected
+		let mut proof = proofs[0].clone();
+		proof.blobs.remove(1);
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, None).is_err());
+
+		// a proof which does not reach past the deadline is rejected
+		let mut proof = proofs[0].clone();
+		proof.blobs.pop();
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, None).is_err());
+
+		// a proof with a block which was not posted by the signer is rejected
+		let mut proof = proofs[0].clone();
+		let forged = Block::new(
+			BlockMetadata::new(150, vec![1], 4),
+			bcs::from_bytes::<Block>(&proof.blobs[1].data)?.id(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![3], 0, 0)]),
+		);
+		proof.blobs[2].data = bcs::to_bytes(&forged)?;
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, None).is_err());
+
+		// a proof whose blobs are not in DA order is rejected
+		let mut proof = proofs[0].clone();
+		proof.blobs[2].da_height = 0;
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, None).is_err());
+
+		// a proof for a transaction that was included in time is rejected
+		let proof = SlashableProof {
+			intent: proofs[0].intent.clone(),
+			blobs: chain(&[10, 50, 150], Some(1), &transaction)?,
+		};
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, None).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_broken_intent_across_split_block() -> Result<(), anyhow::Error> {
+		let transaction = Transaction::new(vec![42], 0, 0);
+		let known_signers = known_signers()?;
+
+		// the block at the target height is posted in two parts, followed by a block past the deadline
+		let metadata = BlockMetadata::new(10, vec![1], 1);
+		let first_part = Block::new(
+			metadata.split_at(0),
+			Id::genesis_block(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![1], 0, 0)]),
+		);
+		let second_part = Block::new(
+			metadata.split_at(1),
+			Id::genesis_block(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![2], 0, 0)]),
+		);
+		let whole = Block::new(
+			metadata,
+			Id::genesis_block(),
+			BTreeSet::from_iter(vec![
+				Transaction::new(vec![1], 0, 0),
+				Transaction::new(vec![2], 0, 0),
+			]),
+		);
+		let next = Block::new(
+			BlockMetadata::new(150, vec![1], 2),
+			whole.id(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![3], 0, 0)]),
+		);
+		let blobs = vec![
+			signed_blob(&first_part, 2)?,
+			signed_blob(&second_part, 3)?,
+			signed_blob(&next, 4)?,
+		];
+
+		let mut checker = IntentChecker::new(known_signers.clone(), None, 8);
+		checker.add_intent(signed_intent(&transaction, 1, 100)?);
+		let mut proofs = Vec::new();
+		for blob in blobs.clone() {
+			proofs.extend(checker.observe_blob::<Secp256k1>(blob)?);
+		}
+		assert_eq!(proofs.len(), 1);
+		proofs[0].try_verify::<Secp256k1>(&known_signers, None)?;
+
+		// leaving out a part breaks the link to the next block
+		let mut proof = proofs[0].clone();
+		proof.blobs.remove(1);
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, None).is_err());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_proof_follows_the_proposer_schedule() -> Result<(), anyhow::Error> {
+		let transaction = Transaction::new(vec![42], 0, 0);
+		let blobs = chain(&[10, 50, 90, 150], Some(3), &transaction)?;
+		let proof = SlashableProof {
+			intent: signed_intent(&transaction, 2, 100)?,
+			blobs: blobs[1..].to_vec(),
+		};
+
+		// a sole proposer owns every slot
+		let known_signers = known_signers()?;
+		let schedule =
+			SignerSchedule::try_new(&known_signers, SlotClock::DaHeight { slot_heights: 1 })?;
+		proof.try_verify::<Secp256k1>(&known_signers, Some(&schedule))?;
+
+		// taking turns with another signer, some of the blocks were posted outside of the slots of the signer
+		let other = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let mut rotating = known_signers.clone();
+		rotating.extend([SignerKey::permanent(hex::encode(other.verifying_key().to_sec1_bytes()))]);
+		let schedule = SignerSchedule::try_new(&rotating, SlotClock::DaHeight { slot_heights: 1 })?;
+		proof.try_verify::<Secp256k1>(&rotating, None)?;
+		assert!(proof.try_verify::<Secp256k1>(&rotating, Some(&schedule)).is_err());
+
+		// slots measured in time cannot be checked from the proof
+		let schedule = SignerSchedule::try_new(&known_signers, SlotClock::Time { slot_ms: 1 })?;
+		assert!(proof.try_verify::<Secp256k1>(&known_signers, Some(&schedule)).is_err());
+
+		Ok(())
+	}
+}
@@ -1,5 +1,10 @@
+pub mod auth;
 pub mod http1;
 pub mod http2;
+pub mod intent;
+
+pub use auth::{BearerCredentials, BearerToken, ClientOptions, ClientTls};
+pub use intent::{verify_intent, IntentChecker, SignedBlockBlob, SlashableProof};
 
 /// An enum wrapping MovementDaLightNodeClients over complex types.
 ///
@@ -23,6 +28,53 @@ impl MovementDaLightNodeClient {
 		Ok(Self::Http2(http2::Http2::connect(connection_string).await?))
 	}
 
+	/// Creates an http1 connection to the light node service which authenticates with the options.
+	pub fn try_http1_with_options(
+		connection_string: &str,
+		options: ClientOptions,
+	) -> Result<Self, anyhow::Error> {
+		if options.tls.is_some() {
+			anyhow::bail!("TLS is only supported for http2 connections to the light node service");
+		}
+		let mut client = http1::Http1::try_new(connection_string)?;
+		if let Some(bearer) = options.bearer {
+			client = client.with_bearer(bearer);
+		}
+		Ok(Self::Http1(client))
+	}
+
+	/// Creates an http2 connection to the light node service which authenticates with the options.
+	pub async fn try_http2_with_options(
+		connection_string: &str,
+		options: ClientOptions,
+	) -> Result<Self, anyhow::Error> {
+		let mut client = match &options.tls {
+			Some(tls) => http2::Http2::connect_with_tls(connection_string, tls).await?,
+			None => http2::Http2::connect(connection_string).await?,
+		};
+		if let Some(bearer) = options.bearer {
+			client = client.with_bearer(bearer);
+		}
+		Ok(Self::Http2(client))
+	}
+
+	/// Wraps a message for the method into a request, authenticated if the client has credentials.
+	fn request<T: prost::Message>(
+		&self,
+		method: &str,
+		message: T,
+	) -> Result<tonic::Request<T>, tonic::Status> {
+		let mut request = tonic::Request::new(message);
+		let bearer = match self {
+			Self::Http1(client) => client.bearer(),
+			Self::Http2(client) => client.bearer(),
+		};
+		if let Some(bearer) = bearer {
+			bearer.authorize(method, &mut request)?;
+		}
+		Ok(request)
+	}
+
 	/// Stream reads from a given height.
 	pub async fn stream_read_from_height(
 		&mut self,
@@ -31,6 +83,7 @@ impl MovementDaLightNodeClient {
 		tonic::Streaming<movement_da_light_node_proto::StreamReadFromHeightResponse>,
 		tonic::Status,
 	> {
+		let request = self.request(auth::STREAM_READ_FROM_HEIGHT_METHOD, request)?;
 		match self {
 			Self::Http1(client) => {
 				let response = client.client_mut().stream_read_from_height(request).await?;
@@ -43,11 +96,30 @@ impl MovementDaLightNodeClient {
 		}
 	}
 
+	/// Reads the blobs at a given height.
+	pub async fn read_at_height(
+		&mut self,
+		request: movement_da_light_node_proto::ReadAtHeightRequest,
+	) -> Result<movement_da_light_node_proto::ReadAtHeightResponse, tonic::Status> {
+		let request = self.request(auth::READ_AT_HEIGHT_METHOD, request)?;
+		match self {
+			Self::Http1(client) => {
+				let response = client.client_mut().read_at_height(request).await?;
+				Ok(response.into_inner())
+			}
+			Self::Http2(client) => {
+				let response = client.client_mut().read_at_height(request).await?;
+				Ok(response.into_inner())
+			}
+		}
+	}
+
 	/// Writes a batch of transactions to the light node
 	pub async fn batch_write(
 		&mut self,
 		request: movement_da_light_node_proto::BatchWriteRequest,
 	) -> Result<movement_da_light_node_proto::BatchWriteResponse, tonic::Status> {
+		let request = self.request(auth::BATCH_WRITE_METHOD, request)?;
 		match self {
 			Self::Http1(client) => {
 				let response = client.client_mut().batch_write(request).await?;
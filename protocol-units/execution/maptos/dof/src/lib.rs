@@ -60,6 +60,9 @@ pub trait DynOptFinExecutor {
 		block_height: u64,
 	) -> Result<BlockCommitment, anyhow::Error>;
 
+	/// Gets the first and the last ledger version of the block at the given height.
+	fn get_block_version_range(&self, block_height: u64) -> Result<(u64, u64), anyhow::Error>;
+
 	/// Revert the chain to the specified height
 	async fn revert_block_head_to(&self, block_height: u64) -> Result<(), anyhow::Error>;
 
@@ -107,6 +107,10 @@ impl DynOptFinExecutor for Executor {
 		self.executor.get_commitment_for_version(version)
 	}
 
+	fn get_block_version_range(&self, block_height: u64) -> Result<(u64, u64), anyhow::Error> {
+		self.executor.get_block_version_range(block_height)
+	}
+
 	async fn revert_block_head_to(&self, block_height: u64) -> Result<(), anyhow::Error> {
 		if let Some(final_height) = self.finality_view.finalized_block_height() {
 			if block_height < final_height {
@@ -0,0 +1,197 @@
+//! Forwarding of transactions submitted to a read-only node.
+
+use super::Error;
+
+use maptos_execution_util::config::follower::{Config as FollowerConfig, ForwardTarget};
+
+use aptos_api_types::{AptosError, AptosErrorCode};
+use aptos_mempool::SubmissionStatus;
+use aptos_storage_interface::DbReader;
+use aptos_types::mempool_status::{MempoolStatus, MempoolStatusCode};
+use aptos_types::transaction::SignedTransaction;
+use aptos_types::vm_status::StatusCode;
+use aptos_vm_validator::vm_validator::{TransactionValidation, VMValidator};
+use movement_da_light_node_client::{ClientOptions, MovementDaLightNodeClient};
+use movement_da_light_node_proto::{BatchWriteRequest, BlobWrite};
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::{debug, warn};
+
+/// The content type accepted by the Aptos REST API for BCS-encoded transactions.
+const BCS_SIGNED_TRANSACTION: &str = "application/x.aptos.signed_transaction+bcs";
+
+/// Sends transactions on to the node that can actually include them in a block.
+#[derive(Clone)]
+pub enum Forwarder {
+	/// Forwards to the REST API of a leader full node.
+	Rest { client: reqwest::Client, url: String },
+	/// Validates against the local ledger and writes to the DA light node.
+	DaLightNode {
+		connection_string: String,
+		http1: bool,
+		options: ClientOptions,
+		client: Arc<OnceCell<MovementDaLightNodeClient>>,
+		db_reader: Arc<dyn DbReader>,
+	},
+}
+
+impl Forwarder {
+	/// Builds the forwarder for the configured target, if any.
+	pub fn try_from_config(
+		config: &FollowerConfig,
+		db_reader: Arc<dyn DbReader>,
+	) -> Result<Option<Self>, anyhow::Error> {
+		let timeout = Duration::from_millis(config.forward_timeout_ms);
+		match &config.forward_target {
+			ForwardTarget::None => Ok(None),
+			ForwardTarget::Rest { url } => Ok(Some(Self::Rest {
+				client: reqwest::Client::builder().timeout(timeout).build()?,
+				url: format!("{}/v1/transactions", url.trim_end_matches('/')),
+			})),
+			ForwardTarget::DaLightNode { connection_string, http1, credentials } => {
+				let options = match credentials {
+					Some(credentials) => ClientOptions::try_from_credentials(credentials)?,
+					None => ClientOptions::default(),
+				};
+				Ok(Some(Self::DaLightNode {
+					connection_string: connection_string.clone(),
+					http1: *http1,
+					options,
+					client: Arc::new(OnceCell::new()),
+					db_reader,
+				}))
+			}
+		}
+	}
+
+	/// Forwards the transaction and reports the status given by the target.
+	pub async fn forward(&self, transaction: SignedTransaction) -> Result<SubmissionStatus, Error> {
+		match self {
+			Self::Rest { client, url } => Self::forward_rest(client, url, transaction).await,
+			Self::DaLightNode { connection_string, http1, options, client, db_reader } => {
+				let client = client
+					.get_or_try_init(|| async {
+						if *http1 {
+							MovementDaLightNodeClient::try_http1_with_options(
+								connection_string,
+								options.clone(),
+							)
+						} else {
+							MovementDaLightNodeClient::try_http2_with_options(
+								connection_string,
+								options.clone(),
+							)
+							.await
+						}
+					})
+					.await?;
+				Self::forward_da_light_node(client.clone(), db_reader.clone(), transaction).await
+			}
+		}
+	}
+
+	async fn forward_rest(
+		client: &reqwest::Client,
+		url: &str,
+		transaction: SignedTransaction,
+	) -> Result<SubmissionStatus, Error> {
+		let body = bcs::to_bytes(&transaction).map_err(|e| anyhow::anyhow!(e))?;
+		let response = match client
+			.post(url)
+			.header(reqwest::header::CONTENT_TYPE, BCS_SIGNED_TRANSACTION)
+			.body(body)
+			.send()
+			.await
+		{
+			Ok(response) => response,
+			Err(e) => {
+				warn!("failed to forward transaction to {}: {:?}", url, e);
+				return Ok(unavailable(format!("failed to reach the leader: {}", e)));
+			}
+		};
+
+		let status = response.status();
+		if status.is_success() {
+			return Ok((MempoolStatus::new(MempoolStatusCode::Accepted), None));
+		}
+
+		let bytes = response.bytes().await.unwrap_or_default();
+		match serde_json::from_slice::<AptosError>(&bytes) {
+			Ok(error) => Ok(submission_status_from_rest_error(error)),
+			Err(_) => Ok(unavailable(format!("leader responded with {}", status))),
+		}
+	}
+
+	async fn forward_da_light_node(
+		mut client: MovementDaLightNodeClient,
+		db_reader: Arc<dyn DbReader>,
+		transaction: SignedTransaction,
+	) -> Result<SubmissionStatus, Error> {
+		// The DA does not validate transactions, so run the same VM checks as the leader
+		// would before the write. This also gives us the application priority.
+		let vm_validator = VMValidator::new(db_reader);
+		let tx_result = vm_validator.validate_transaction(transaction.clone())?;
+		if let Some(vm_status) = tx_result.status() {
+			debug!("Forwarded transaction not accepted: {:?}", vm_status);
+			return Ok((MempoolStatus::new(MempoolStatusCode::VmError), Some(vm_status)));
+		}
+		let application_priority = u64::MAX - tx_result.score();
+
+		let movement_transaction = movement_types::transaction::Transaction::new(
+			bcs::to_bytes(&transaction).map_err(|e| anyhow::anyhow!(e))?,
+			application_priority,
+			transaction.sequence_number(),
+		);
+		let data = serde_json::to_vec(&movement_transaction).map_err(|e| anyhow::anyhow!(e))?;
+		match client.batch_write(BatchWriteRequest { blobs: vec![BlobWrite { data }] }).await {
+			Ok(_) => Ok((MempoolStatus::new(MempoolStatusCode::Accepted), None)),
+			Err(e) => {
+				warn!("failed to forward transaction to the DA light node: {:?}", e);
+				Ok(unavailable(format!("failed to write to the DA light node: {}", e.message())))
+			}
+		}
+	}
+}
+
+/// The status reported when the node has nowhere to send the transaction.
+pub(crate) fn unavailable(message: String) -> SubmissionStatus {
+	(MempoolStatus::new(MempoolStatusCode::UnknownStatus).with_message(message), None)
+}
+
+/// Maps the error body of a rejected REST submission back onto a mempool status.
+fn submission_status_from_rest_error(error: AptosError) -> SubmissionStatus {
+	let code = match error.error_code {
+		AptosErrorCode::MempoolIsFull => MempoolStatusCode::MempoolIsFull,
+		AptosErrorCode::SequenceNumberTooOld => MempoolStatusCode::InvalidSeqNumber,
+		AptosErrorCode::InvalidTransactionUpdate => MempoolStatusCode::InvalidUpdate,
+		AptosErrorCode::VmError => MempoolStatusCode::VmError,
+		_ => MempoolStatusCode::UnknownStatus,
+	};
+	let vm_status = error.vm_error_code.and_then(|code| StatusCode::try_from(code).ok());
+	(MempoolStatus::new(code).with_message(error.message), vm_status)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_rest_error_maps_to_mempool_status() {
+		let error = AptosError::new_with_error_code("full", AptosErrorCode::MempoolIsFull);
+		let (status, vm_status) = submission_status_from_rest_error(error);
+		assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+		assert_eq!(status.message, "full");
+		assert_eq!(vm_status, None);
+
+		let error = AptosError::new_with_vm_status(
+			"too old",
+			AptosErrorCode::VmError,
+			StatusCode::SEQUENCE_NUMBER_TOO_OLD,
+		);
+		let (status, vm_status) = submission_status_from_rest_error(error);
+		assert_eq!(status.code, MempoolStatusCode::VmError);
+		assert_eq!(vm_status, Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD));
+	}
+}
@@ -1,11 +1,13 @@
 mod task;
 
+mod forward;
 mod read_only;
 mod transaction_pipe;
 
 mod error;
 
 pub use error::Error;
+use forward::Forwarder;
 use read_only::NullMempool;
 pub use task::BackgroundTask;
 pub use transaction_pipe::TransactionPipe;
@@ -1,26 +1,64 @@
+use super::forward::{unavailable, Forwarder};
 use super::Error;
 
 use aptos_mempool::MempoolClientRequest;
 
 use futures::channel::mpsc::Receiver;
 use futures::StreamExt;
-use tracing::debug;
+use tracing::{debug, info_span, Instrument};
 
+/// The mempool stub of a read-only node.
+///
+/// Submitted transactions are handed to the configured [`Forwarder`] when there is one,
+/// and rejected otherwise.
 pub struct NullMempool {
 	// The receiver for the mempool client.
 	mempool_client_receiver: Receiver<MempoolClientRequest>,
+	// Where submitted transactions are sent, if anywhere.
+	forwarder: Option<Forwarder>,
 }
 
 impl NullMempool {
-	pub fn new(mempool_client_receiver: Receiver<MempoolClientRequest>) -> Self {
-		Self { mempool_client_receiver }
+	pub fn new(
+		mempool_client_receiver: Receiver<MempoolClientRequest>,
+		forwarder: Option<Forwarder>,
+	) -> Self {
+		Self { mempool_client_receiver, forwarder }
 	}
 
 	pub async fn run(mut self) -> Result<(), Error> {
 		while let Some(request) = self.mempool_client_receiver.next().await {
 			match request {
-				MempoolClientRequest::SubmitTransaction(_, _) => {
-					panic!("SubmitTransaction received in read-only mode");
+				MempoolClientRequest::SubmitTransaction(transaction, callback) => {
+					let Some(forwarder) = self.forwarder.clone() else {
+						callback
+							.send(Ok(unavailable(
+								"transaction submission is disabled on this read-only node"
+									.to_string(),
+							)))
+							.unwrap_or_else(|_| {
+								debug!("SubmitTransaction request canceled");
+							});
+						continue;
+					};
+					let span = info_span!(
+						target: "movement_timing",
+						"forward_transaction",
+						tx_hash = %transaction.committed_hash(),
+						sender = %transaction.sender(),
+						sequence_number = transaction.sequence_number(),
+					);
+					// forward in the background so a slow leader does not stall the API
+					tokio::spawn(
+						async move {
+							let result =
+								forwarder.forward(transaction).await.map_err(anyhow::Error::from);
+							callback.send(result).unwrap_or_else(|_| {
+								debug!("SubmitTransaction request canceled");
+							});
+						}
+						.instrument(span),
+					);
 				}
 				MempoolClientRequest::GetTransactionByHash(_hash, sender) => {
 					sender.send(None).unwrap_or_else(|_| {
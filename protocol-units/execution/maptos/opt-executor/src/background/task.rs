@@ -1,5 +1,6 @@
-use super::{Error, NullMempool, TransactionPipe};
+use super::{Error, Forwarder, NullMempool, TransactionPipe};
 
+use maptos_execution_util::config::follower::Config as FollowerConfig;
 use maptos_execution_util::config::mempool::Config as MempoolConfig;
 
 use aptos_config::config::NodeConfig;
@@ -51,10 +52,17 @@ impl BackgroundTask {
 		})
 	}
 
+	/// Constructs the stub task for a read-only node, forwarding submitted
+	/// transactions if the follower configuration names a target.
 	pub(crate) fn read_only(
 		mempool_client_receiver: futures_mpsc::Receiver<MempoolClientRequest>,
-	) -> Self {
-		Self { inner: BackgroundInner::ReadOnly(NullMempool::new(mempool_client_receiver)) }
+		db_reader: Arc<dyn DbReader>,
+		follower_config: &FollowerConfig,
+	) -> Result<Self, anyhow::Error> {
+		let forwarder = Forwarder::try_from_config(follower_config, db_reader)?;
+		Ok(Self {
+			inner: BackgroundInner::ReadOnly(NullMempool::new(mempool_client_receiver, forwarder)),
+		})
 	}
 
 	/// Runs the background task.
@@ -115,6 +115,13 @@ impl Executor {
 		Ok(BlockCommitment::new(height.into(), Id::new(*block_id.clone()), commitment))
 	}
 
+	/// Gets the first and the last version of the block at the given height.
+	pub fn get_block_version_range(&self, height: u64) -> Result<(u64, u64), anyhow::Error> {
+		let (block_start_version, block_end_version, _block_event) =
+			self.db().reader.get_block_info_by_height(height)?;
+		Ok((block_start_version, block_end_version))
+	}
+
 	pub fn get_commitment_for_version(
 		&self,
 		version: u64,
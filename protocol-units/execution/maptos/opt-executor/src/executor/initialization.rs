@@ -39,7 +39,9 @@ impl Executor {
 
 		// read-only settings
 		if maptos_config.chain.maptos_read_only {
-			node_config.api.transaction_submission_enabled = false;
+			// submissions stay open when they can be forwarded to a leader
+			node_config.api.transaction_submission_enabled =
+				maptos_config.follower.forwards_transactions();
 			node_config.api.encode_submission_enabled = false;
 			node_config.api.transaction_simulation_enabled = false;
 			node_config.api.gas_estimation.enabled = false;
@@ -140,7 +142,7 @@ impl Executor {
 
 	/// Creates an instance of [`Context`] and the background [`TransactionPipe`]
 	/// task to process transactions. If the configuration is for a read-only node,
-	/// `None` is returned instead of the transaction pipe task.
+	/// a stub task that forwards or rejects submissions is returned instead.
 	/// The `Context` must be kept around for as long as the `TransactionPipe`
 	/// task needs to be running.
 	pub fn background(
@@ -155,7 +157,11 @@ impl Executor {
 			futures_mpsc::channel::<MempoolClientRequest>(EXECUTOR_CHANNEL_SIZE);
 
 		let background_task = if maptos_config.chain.maptos_read_only {
-			BackgroundTask::read_only(mempool_client_receiver)
+			BackgroundTask::read_only(
+				mempool_client_receiver,
+				self.db().reader.clone(),
+				&self.config.follower,
+			)?
 		} else {
 			BackgroundTask::transaction_pipe(
 				mempool_client_receiver,
@@ -191,3 +191,10 @@ env_default!(default_sequence_number_ttl_ms, "MAPTOS_SEQUENCE_NUMBER_TTL_MS", u6
 env_default!(default_gc_slot_duration_ms, "MAPTOS_GC_SLOT_DURATION_MS", u64, 1000 * 2);
 
 env_default!(default_ingress_account_whitelist, "MAPTOS_INGRESS_ACCOUNT_WHITELIST", String);
+
+env_default!(
+	default_maptos_follower_forward_timeout_ms,
+	"MAPTOS_FOLLOWER_FORWARD_TIMEOUT_MS",
+	u64,
+	1000 * 10
+);
@@ -0,0 +1,73 @@
+//! Configuration for transaction ingress on read-only (follower) nodes.
+
+use super::common::default_maptos_follower_forward_timeout_ms;
+
+use movement_celestia_da_util::config::local::da_light_node::{
+	default_da_service_credentials, DaServiceCredentials,
+};
+use serde::{Deserialize, Serialize};
+
+/// Where a read-only node sends the transactions submitted to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ForwardTarget {
+	/// Submissions are rejected.
+	None,
+	/// Submissions are forwarded to the REST API of a leader full node,
+	/// e.g. `http://leader:30731`.
+	Rest { url: String },
+	/// Submissions are validated locally and written to the DA light node
+	/// with `BatchWrite`, authenticated with the credentials if any.
+	DaLightNode {
+		connection_string: String,
+		http1: bool,
+		credentials: Option<DaServiceCredentials>,
+	},
+}
+
+/// Picks the forward target from the environment.
+///
+/// `MAPTOS_FOLLOWER_FORWARD_REST_URL` takes precedence over
+/// `MAPTOS_FOLLOWER_FORWARD_DA_LIGHT_NODE`. Writes to the light node use the
+/// light node client credentials of the environment.
+pub fn default_forward_target() -> ForwardTarget {
+	if let Ok(url) = std::env::var("MAPTOS_FOLLOWER_FORWARD_REST_URL") {
+		return ForwardTarget::Rest { url };
+	}
+	match std::env::var("MAPTOS_FOLLOWER_FORWARD_DA_LIGHT_NODE") {
+		Ok(connection_string) => ForwardTarget::DaLightNode {
+			http1: std::env::var("MAPTOS_FOLLOWER_FORWARD_DA_LIGHT_NODE_HTTP1")
+				.map(|val| val == "true")
+				.unwrap_or(false),
+			connection_string,
+			credentials: default_da_service_credentials(),
+		},
+		Err(_) => ForwardTarget::None,
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+	/// Where submitted transactions go when the node is read-only
+	#[serde(default = "default_forward_target")]
+	pub forward_target: ForwardTarget,
+
+	/// How long to wait on the forward target before giving up on a submission
+	#[serde(default = "default_maptos_follower_forward_timeout_ms")]
+	pub forward_timeout_ms: u64,
+}
+
+impl Config {
+	/// Whether the node accepts submissions while read-only.
+	pub fn forwards_transactions(&self) -> bool {
+		self.forward_target != ForwardTarget::None
+	}
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			forward_target: default_forward_target(),
+			forward_timeout_ms: default_maptos_follower_forward_timeout_ms(),
+		}
+	}
+}
@@ -3,6 +3,7 @@ pub mod client;
 pub mod common;
 pub mod faucet;
 pub mod fin;
+pub mod follower;
 pub mod indexer;
 pub mod indexer_processor;
 pub mod load_shedding;
@@ -47,6 +48,10 @@ pub struct Config {
 	/// Access control
 	#[serde(default)]
 	pub access_control: aptos_account_whitelist::config::Config,
+
+	/// Transaction ingress on read-only nodes
+	#[serde(default)]
+	pub follower: follower::Config,
 }
 
 impl Default for Config {
@@ -61,6 +66,7 @@ impl Default for Config {
 			load_shedding: load_shedding::Config::default(),
 			mempool: mempool::Config::default(),
 			access_control: aptos_account_whitelist::config::Config::default(),
+			follower: follower::Config::default(),
 		}
 	}
 }
@@ -323,6 +323,59 @@ impl MempoolTransactionOperations for RocksdbMempool {
 		.await?
 	}
 
+	async fn peek_mempool_transactions(
+		&self,
+		n: usize,
+	) -> Result<Vec<MempoolTransaction>, anyhow::Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+			let mut mempool_transactions = Vec::with_capacity(n);
+			for res in db.iterator_cf(&cf_handle, IteratorMode::Start).take(n) {
+				let (_key, value) = res?;
+				mempool_transactions.push(bcs::from_bytes(&value)?);
+			}
+
+			Ok(mempool_transactions)
+		})
+		.await?
+	}
+
+	async fn remove_mempool_transactions(
+		&self,
+		transaction_ids: Vec<transaction::Id>,
+	) -> Result<(), anyhow::Error> {
+		let db = self.db.clone();
+		tokio::task::spawn_blocking(move || {
+			let cf_handle = db
+				.cf_handle(cf::MEMPOOL_TRANSACTIONS)
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+			let lookups_cf_handle = db
+				.cf_handle(cf::TRANSACTION_LOOKUPS)
+				.ok_or_else(|| Error::msg("CF handle not found"))?;
+
+			// Remove the transactions and their lookup table entries
+			// atomically in a single write batch.
+			// https://github.com/movementlabsxyz/movement/issues/322
+
+			let mut batch = WriteBatch::default();
+			for transaction_id in transaction_ids {
+				if let Some(key) = Self::internal_get_mempool_transaction_key(&db, transaction_id)?
+				{
+					batch.delete_cf(&cf_handle, key);
+					batch.delete_cf(&lookups_cf_handle, transaction_id.to_vec());
+				}
+			}
+			db.write(batch)?;
+
+			Ok(())
+		})
+		.await?
+	}
+
 	async fn gc_mempool_transactions(
 		&self,
 		timestamp_threshold: u64,
@@ -491,6 +544,38 @@ pub mod tests {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_peek_and_remove_mempool_transactions() -> Result<(), Error> {
+		let temp_dir = tempdir().unwrap();
+		let path = temp_dir.path().to_str().unwrap();
+		let mempool = RocksdbMempool::try_new(path)?;
+
+		let transaction1 = MempoolTransaction::at_time(Transaction::new(vec![1], 0, 0), 2);
+		let transaction2 = MempoolTransaction::at_time(Transaction::new(vec![2], 0, 0), 64);
+		let transaction3 = MempoolTransaction::at_time(Transaction::new(vec![3], 0, 0), 128);
+		mempool
+			.add_mempool_transactions(vec![
+				transaction3.clone(),
+				transaction1.clone(),
+				transaction2.clone(),
+			])
+			.await?;
+
+		// peeking leaves the transactions in place
+		let transactions = mempool.peek_mempool_transactions(2).await?;
+		assert_eq!(transactions, vec![transaction1.clone(), transaction2.clone()]);
+		assert_eq!(mempool.peek_mempool_transactions(2).await?, transactions);
+
+		mempool
+			.remove_mempool_transactions(vec![transaction1.id(), transaction3.id()])
+			.await?;
+		assert!(!mempool.has_mempool_transaction(transaction1.id()).await?);
+		assert!(!mempool.has_mempool_transaction(transaction3.id()).await?);
+		assert_eq!(mempool.peek_mempool_transactions(3).await?, vec![transaction2]);
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_transaction_slot_based_ordering() -> Result<(), Error> {
 		let temp_dir = tempdir().unwrap();
@@ -59,6 +59,23 @@ pub trait MempoolTransactionOperations {
 		Ok(mempool_transactions)
 	}
 
+	/// Gets the next n mempool transactions in mempool order, without removing them.
+	async fn peek_mempool_transactions(
+		&self,
+		n: usize,
+	) -> Result<Vec<MempoolTransaction>, anyhow::Error>;
+
+	/// Removes mempool transactions from the mempool.
+	async fn remove_mempool_transactions(
+		&self,
+		transaction_ids: Vec<transaction::Id>,
+	) -> Result<(), anyhow::Error> {
+		for transaction_id in transaction_ids {
+			self.remove_mempool_transaction(transaction_id).await?;
+		}
+		Ok(())
+	}
+
 	/// Garbage-collects transactions that have been submitted before the
 	/// given timestamp.
 	///
@@ -1,44 +1,138 @@
 use anyhow::Error;
 use aptos_api::Context;
 use futures::prelude::*;
+use movement_types::block::{BlockCommitment, Commitment, Id};
+use poem::http::StatusCode;
 use poem::listener::TcpListener;
 use poem::{
 	get, handler,
 	middleware::Tracing,
-	web::{Data, Path},
+	web::{Data, Json, Path},
 	EndpointExt, IntoResponse, Response, Route, Server,
 };
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use std::env;
 use std::future::Future;
 use std::sync::Arc;
 
-#[derive(Debug)]
+/// Where an executed block was read from the DA, and where it went in the ledger.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockProvenance {
+	/// The hex encoded id of the block.
+	pub block_id: String,
+	/// The hex encoded id of the DA blob the block was read from.
+	pub blob_id: String,
+	/// The DA height the block was read at.
+	pub da_height: u64,
+	/// The timestamp of the block, in microseconds.
+	pub block_timestamp: u64,
+	/// The height of the block in the ledger.
+	pub ledger_height: u64,
+	pub ledger_start_version: u64,
+	pub ledger_end_version: u64,
+	pub commitment: CommitmentResponse,
+}
+
+/// The state of the node which is kept outside of the Aptos DB.
+#[async_trait::async_trait]
+pub trait NodeState: Send + Sync {
+	/// The DA height up to which the node executed all blocks.
+	async fn da_synced_height(&self) -> Result<u64, Error>;
+
+	/// The block the node executed last.
+	async fn latest_executed_block(&self) -> Result<Option<BlockProvenance>, Error>;
+
+	/// The executed block with the given id.
+	async fn block_by_id(&self, block_id: Vec<u8>) -> Result<Option<BlockProvenance>, Error>;
+
+	/// The executed block which executed the transaction with the given hash.
+	async fn block_by_transaction(
+		&self,
+		transaction_hash: Vec<u8>,
+	) -> Result<Option<BlockProvenance>, Error>;
+
+	/// The executed blocks read at a DA height.
+	async fn blocks_at_da_height(&self, da_height: u64) -> Result<Vec<BlockProvenance>, Error>;
+
+	/// The executed block at a height of the ledger.
+	async fn block_at_ledger_height(
+		&self,
+		ledger_height: u64,
+	) -> Result<Option<BlockProvenance>, Error>;
+
+	/// The commitment the settlement contract accepted at a height, if the node settles.
+	async fn accepted_commitment_at_height(
+		&self,
+		height: u64,
+	) -> Result<Option<BlockCommitment>, Error>;
+}
+
+/// A block commitment, with its ids hex encoded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommitmentResponse {
+	pub height: u64,
+	pub block_id: String,
+	pub commitment: String,
+}
+
+impl From<BlockCommitment> for CommitmentResponse {
+	fn from(commitment: BlockCommitment) -> Self {
+		Self {
+			height: commitment.height(),
+			block_id: commitment.block_id().to_string(),
+			commitment: commitment.commitment().to_string(),
+		}
+	}
+}
+
+type NodeStateData = Option<Arc<dyn NodeState>>;
+
 pub struct MovementRest {
 	/// The URL to bind the REST service to.
 	pub url: String,
 	pub context: Option<Arc<Context>>,
+	pub node_state: NodeStateData,
 	// More fields to be added here, log verboisty, etc.
 }
 
+impl std::fmt::Debug for MovementRest {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("MovementRest")
+			.field("url", &self.url)
+			.field("context", &self.context.is_some())
+			.field("node_state", &self.node_state.is_some())
+			.finish()
+	}
+}
+
 impl MovementRest {
 	pub const MOVEMENT_REST_ENV_VAR: &'static str = "MOVEMENT_REST_URL";
 
 	pub fn try_from_env() -> Result<Self, Error> {
 		let url = env::var(Self::MOVEMENT_REST_ENV_VAR)
 			.unwrap_or_else(|_| "http://0.0.0.0:30832".to_string());
-		Ok(Self { url, context: None })
+		Ok(Self { url, context: None, node_state: None })
 	}
 
 	pub fn set_context(&mut self, context: Arc<Context>) {
 		self.context = Some(context);
 	}
 
+	pub fn set_node_state(&mut self, node_state: Arc<dyn NodeState>) {
+		self.node_state = Some(node_state);
+	}
+
+	/// The address to listen on, which is the URL without its scheme.
+	pub fn listen_address(&self) -> &str {
+		self.url.split_once("://").map_or(self.url.as_str(), |(_, address)| address)
+	}
+
 	pub fn run_service(&self) -> impl Future<Output = Result<(), Error>> + Send {
 		info!("Starting movement rest service at {}", self.url);
 		let movement_rest = self.create_routes();
-		Server::new(TcpListener::bind(self.url.clone()))
+		Server::new(TcpListener::bind(self.listen_address().to_string()))
 			.run(movement_rest)
 			.map_err(Into::into)
 	}
@@ -47,11 +141,33 @@ impl MovementRest {
 		Route::new()
 			.at("/health", get(health))
 			.at("/movement/v1/state-root-hash/:blockheight", get(state_root_hash))
+			.at("/movement/v1/commitment/:height", get(commitment))
+			.at("/movement/v1/accepted-commitment/:height", get(accepted_commitment))
+			.at("/movement/v1/da-synced-height", get(da_synced_height))
+			.at("/movement/v1/latest-executed-block", get(latest_executed_block))
+			.at("/movement/v1/blocks/:block_id", get(block_by_id))
+			.at("/movement/v1/transactions/:hash/block", get(block_by_transaction))
+			.at("/movement/v1/da-heights/:da_height/blocks", get(blocks_at_da_height))
+			.at("/movement/v1/ledger-versions/:version/block", get(block_at_ledger_version))
 			.data(self.context.clone())
+			.data(self.node_state.clone())
 			.with(Tracing)
 	}
 }
 
+fn unavailable(what: &str) -> Response {
+	(StatusCode::SERVICE_UNAVAILABLE, format!("The {} is not available yet", what)).into_response()
+}
+
+fn not_found(what: String) -> Response {
+	(StatusCode::NOT_FOUND, what).into_response()
+}
+
+fn decode_hex(id: &str) -> Result<Vec<u8>, Response> {
+	hex::decode(id.trim_start_matches("0x"))
+		.map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()).into_response())
+}
+
 #[handler]
 pub async fn health() -> Response {
 	"OK".into_response()
@@ -60,8 +176,12 @@ pub async fn health() -> Response {
 #[handler]
 pub async fn state_root_hash(
 	Path(blockheight): Path<u64>,
-	context: Data<&Arc<Context>>,
+	context: Data<&Option<Arc<Context>>>,
 ) -> Result<Response, anyhow::Error> {
+	let context = match context.0 {
+		Some(context) => context,
+		None => return Ok(unavailable("ledger")),
+	};
 	let latest_ledger_info = context.db.get_latest_ledger_info()?;
 	let (_, end_version, _) = context.db.get_block_info_by_height(blockheight)?;
 	tracing::info!("end_version: {}", end_version);
@@ -79,6 +199,132 @@ pub async fn state_root_hash(
 	Ok(state_root_hash.to_string().into_response())
 }
 
+/// The block commitment the node makes at a height, computed as the executor does.
+#[handler]
+pub async fn commitment(
+	Path(height): Path<u64>,
+	context: Data<&Option<Arc<Context>>>,
+) -> Result<Response, anyhow::Error> {
+	let context = match context.0 {
+		Some(context) => context,
+		None => return Ok(unavailable("ledger")),
+	};
+	let (_, block_end_version, _) = context.db.get_block_info_by_height(height)?;
+	let proof = context.db.get_state_proof(block_end_version)?;
+	let block_id = proof.latest_ledger_info().consensus_block_id();
+	let commitment = BlockCommitment::new(
+		height,
+		Id::new(*block_id.clone()),
+		Commitment::digest_state_proof(&proof),
+	);
+	Ok(Json(CommitmentResponse::from(commitment)).into_response())
+}
+
+#[handler]
+pub async fn accepted_commitment(
+	Path(height): Path<u64>,
+	node_state: Data<&NodeStateData>,
+) -> Result<Response, anyhow::Error> {
+	let node_state = match node_state.0 {
+		Some(node_state) => node_state,
+		None => return Ok(unavailable("node state")),
+	};
+	match node_state.accepted_commitment_at_height(height).await? {
+		Some(commitment) => Ok(Json(CommitmentResponse::from(commitment)).into_response()),
+		None => Ok(not_found(format!("No accepted commitment at height {}", height))),
+	}
+}
+
+#[handler]
+pub async fn da_synced_height(node_state: Data<&NodeStateData>) -> Result<Response, anyhow::Error> {
+	let node_state = match node_state.0 {
+		Some(node_state) => node_state,
+		None => return Ok(unavailable("node state")),
+	};
+	Ok(Json(node_state.da_synced_height().await?).into_response())
+}
+
+#[handler]
+pub async fn latest_executed_block(
+	node_state: Data<&NodeStateData>,
+) -> Result<Response, anyhow::Error> {
+	let node_state = match node_state.0 {
+		Some(node_state) => node_state,
+		None => return Ok(unavailable("node state")),
+	};
+	match node_state.latest_executed_block().await? {
+		Some(block) => Ok(Json(block).into_response()),
+		None => Ok(not_found("No block was executed yet".to_string())),
+	}
+}
+
+#[handler]
+pub async fn block_by_id(
+	Path(block_id): Path<String>,
+	node_state: Data<&NodeStateData>,
+) -> Result<Response, anyhow::Error> {
+	let node_state = match node_state.0 {
+		Some(node_state) => node_state,
+		None => return Ok(unavailable("node state")),
+	};
+	let id = match decode_hex(&block_id) {
+		Ok(id) => id,
+		Err(response) => return Ok(response),
+	};
+	match node_state.block_by_id(id).await? {
+		Some(block) => Ok(Json(block).into_response()),
+		None => Ok(not_found(format!("No executed block {}", block_id))),
+	}
+}
+
+#[handler]
+pub async fn block_by_transaction(
+	Path(hash): Path<String>,
+	node_state: Data<&NodeStateData>,
+) -> Result<Response, anyhow::Error> {
+	let node_state = match node_state.0 {
+		Some(node_state) => node_state,
+		None => return Ok(unavailable("node state")),
+	};
+	let transaction_hash = match decode_hex(&hash) {
+		Ok(transaction_hash) => transaction_hash,
+		Err(response) => return Ok(response),
+	};
+	match node_state.block_by_transaction(transaction_hash).await? {
+		Some(block) => Ok(Json(block).into_response()),
+		None => Ok(not_found(format!("No executed block contains transaction {}", hash))),
+	}
+}
+
+#[handler]
+pub async fn blocks_at_da_height(
+	Path(da_height): Path<u64>,
+	node_state: Data<&NodeStateData>,
+) -> Result<Response, anyhow::Error> {
+	let node_state = match node_state.0 {
+		Some(node_state) => node_state,
+		None => return Ok(unavailable("node state")),
+	};
+	Ok(Json(node_state.blocks_at_da_height(da_height).await?).into_response())
+}
+
+#[handler]
+pub async fn block_at_ledger_version(
+	Path(version): Path<u64>,
+	context: Data<&Option<Arc<Context>>>,
+	node_state: Data<&NodeStateData>,
+) -> Result<Response, anyhow::Error> {
+	let (context, node_state) = match (context.0, node_state.0) {
+		(Some(context), Some(node_state)) => (context, node_state),
+		_ => return Ok(unavailable("node state")),
+	};
+	let (_, _, block_event) = context.db.get_block_info_by_version(version)?;
+	match node_state.block_at_ledger_height(block_event.height).await? {
+		Some(block) => Ok(Json(block).into_response()),
+		None => Ok(not_found(format!("No executed block contains version {}", version))),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -95,4 +341,80 @@ mod tests {
 		let response = client.get("/health").send().await;
 		assert!(response.0.status().is_success());
 	}
+
+	#[test]
+	fn test_listen_address() {
+		let mut rest_service = MovementRest::try_from_env().expect("Failed to create MovementRest");
+		rest_service.url = "http://0.0.0.0:30832".to_string();
+		assert_eq!(rest_service.listen_address(), "0.0.0.0:30832");
+		rest_service.url = "127.0.0.1:30832".to_string();
+		assert_eq!(rest_service.listen_address(), "127.0.0.1:30832");
+	}
+
+	struct NoBlocks;
+
+	#[async_trait::async_trait]
+	impl NodeState for NoBlocks {
+		async fn da_synced_height(&self) -> Result<u64, Error> {
+			Ok(7)
+		}
+
+		async fn latest_executed_block(&self) -> Result<Option<BlockProvenance>, Error> {
+			Ok(None)
+		}
+
+		async fn block_by_id(&self, _block_id: Vec<u8>) -> Result<Option<BlockProvenance>, Error> {
+			Ok(None)
+		}
+
+		async fn block_by_transaction(
+			&self,
+			_transaction_hash: Vec<u8>,
+		) -> Result<Option<BlockProvenance>, Error> {
+			Ok(None)
+		}
+
+		async fn blocks_at_da_height(
+			&self,
+			_da_height: u64,
+		) -> Result<Vec<BlockProvenance>, Error> {
+			Ok(Vec::new())
+		}
+
+		async fn block_at_ledger_height(
+			&self,
+			_ledger_height: u64,
+		) -> Result<Option<BlockProvenance>, Error> {
+			Ok(None)
+		}
+
+		async fn accepted_commitment_at_height(
+			&self,
+			_height: u64,
+		) -> Result<Option<BlockCommitment>, Error> {
+			Ok(None)
+		}
+	}
+
+	#[tokio::test]
+	async fn test_node_state_endpoints() {
+		let mut rest_service = MovementRest::try_from_env().expect("Failed to create MovementRest");
+		let client = TestClient::new(rest_service.create_routes());
+		let response = client.get("/movement/v1/da-synced-height").send().await;
+		assert_eq!(response.0.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+		rest_service.set_node_state(Arc::new(NoBlocks));
+		let client = TestClient::new(rest_service.create_routes());
+		let response = client.get("/movement/v1/da-synced-height").send().await;
+		response.assert_status_is_ok();
+		response.assert_text("7").await;
+
+		let response = client.get("/movement/v1/accepted-commitment/3").send().await;
+		assert_eq!(response.0.status(), StatusCode::NOT_FOUND);
+
+		let response = client.get("/movement/v1/transactions/0xab/block").send().await;
+		assert_eq!(response.0.status(), StatusCode::NOT_FOUND);
+		let response = client.get("/movement/v1/transactions/0xnothex/block").send().await;
+		assert_eq!(response.0.status(), StatusCode::BAD_REQUEST);
+	}
 }
@@ -1,19 +1,31 @@
+pub mod policy;
+
 use mempool_util::MempoolTransactionOperations;
 pub use move_rocks::RocksdbMempool;
 pub use movement_types::{
 	block::{self, Block},
 	transaction::{self, Transaction},
 };
+pub use policy::{BlockOrdering, BlockPolicy, TransactionInspector};
 pub use sequencing_util::Sequencer;
 
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, info};
 
 use std::collections::BTreeSet;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use policy::BlockSelector;
+
+/// How many blocks worth of transactions a block policy picks from.
+const CANDIDATE_BLOCKS: u32 = 4;
+
+/// How many blocks worth of transactions in the mempool are scanned for the candidates of a block policy.
+const SCANNED_BLOCKS: u32 = 16;
+
 #[derive(Clone)]
 pub struct Memseq<T: MempoolTransactionOperations> {
 	/// The mempool to get transactions from.
@@ -24,6 +36,20 @@ pub struct Memseq<T: MempoolTransactionOperations> {
 	pub parent_block: Arc<RwLock<block::Id>>,
 	// this value should not be changed after initialization
 	building_time_ms: u64,
+	/// The height of the parent block.
+	/// This is only updated while holding the parent block lock.
+	parent_height: Arc<AtomicU64>,
+	/// The offset of the parent block within its height, if it is a part of a split block.
+	/// This is only updated while holding the parent block lock.
+	parent_offset: Arc<AtomicU64>,
+	/// The sec1 encoded public key of the sequencer, recorded as the proposer in the block metadata.
+	proposer: Vec<u8>,
+	/// Which of the transactions in the mempool go into a block.
+	block_policy: BlockPolicy,
+	/// Reads the senders and gas unit prices of transactions for the block policy.
+	inspector: Option<Arc<dyn TransactionInspector>>,
+	/// Held while the block policy picks transactions, which stay in the mempool until they are picked.
+	selection: Arc<Mutex<()>>,
 }
 
 impl<T: MempoolTransactionOperations> Memseq<T> {
@@ -33,7 +59,18 @@ impl<T: MempoolTransactionOperations> Memseq<T> {
 		parent_block: Arc<RwLock<block::Id>>,
 		building_time_ms: u64,
 	) -> Self {
-		Self { mempool, block_size, parent_block, building_time_ms }
+		Self {
+			mempool,
+			block_size,
+			parent_block,
+			building_time_ms,
+			parent_height: Arc::new(AtomicU64::new(0)),
+			parent_offset: Arc::new(AtomicU64::new(0)),
+			proposer: Vec::new(),
+			block_policy: BlockPolicy::default(),
+			inspector: None,
+			selection: Arc::new(Mutex::new(())),
+		}
 	}
 
 	pub fn with_block_size(mut self, block_size: u32) -> Self {
@@ -46,6 +83,21 @@ impl<T: MempoolTransactionOperations> Memseq<T> {
 		self
 	}
 
+	pub fn with_proposer(mut self, proposer: Vec<u8>) -> Self {
+		self.proposer = proposer;
+		self
+	}
+
+	pub fn with_block_policy(mut self, block_policy: BlockPolicy) -> Self {
+		self.block_policy = block_policy;
+		self
+	}
+
+	pub fn with_inspector(mut self, inspector: Arc<dyn TransactionInspector>) -> Self {
+		self.inspector = Some(inspector);
+		self
+	}
+
 	pub fn building_time_ms(&self) -> u64 {
 		self.building_time_ms
 	}
@@ -54,14 +106,135 @@ impl<T: MempoolTransactionOperations> Memseq<T> {
 		*self.parent_block.read().await
 	}
 
+	pub fn parent_height(&self) -> u64 {
+		self.parent_height.load(Ordering::SeqCst)
+	}
+
+	pub fn proposer(&self) -> &[u8] {
+		&self.proposer
+	}
+
+	/// Extends the chain with a block posted to the DA, by another proposer or by this one before a restart.
+	///
+	/// The block becomes the parent of the next block built here if it is higher than the current parent,
+	/// where the parts of a split block are ordered by their offset within the height.
+	/// Returns whether the parent was updated.
+	pub async fn observe_block(&self, block: &Block) -> bool {
+		let height = match block.metadata().height() {
+			Some(height) => height,
+			None => return false,
+		};
+		let offset = block.metadata().offset();
+		let mut parent_block = self.parent_block.write().await;
+		let parent_position =
+			(self.parent_height.load(Ordering::SeqCst), self.parent_offset.load(Ordering::SeqCst));
+		if (height, offset) <= parent_position {
+			return false;
+		}
+		*parent_block = block.id();
+		self.parent_height.store(height, Ordering::SeqCst);
+		self.parent_offset.store(offset, Ordering::SeqCst);
+		true
+	}
+
+	/// Takes a block built here back out of the chain, when it was not accepted by the followers.
+	///
+	/// If the block is the current parent, the parent goes back to the block it extended,
+	/// taken to be at the start of the height below so that any block observed there replaces it.
+	/// Returns whether the parent was updated.
+	pub async fn unwind_block(&self, block: &Block) -> bool {
+		let height = match block.metadata().height() {
+			Some(height) => height,
+			None => return false,
+		};
+		let mut parent_block = self.parent_block.write().await;
+		if *parent_block != block.id() {
+			return false;
+		}
+		*parent_block = block.parent();
+		self.parent_height.store(height.saturating_sub(1), Ordering::SeqCst);
+		self.parent_offset.store(0, Ordering::SeqCst);
+		true
+	}
+
+	/// Pops the first transactions of the mempool, waiting up to the building time for a full block.
+	async fn pop_transactions(&self) -> Result<Vec<Transaction>, anyhow::Error> {
+		let mut transactions = Vec::with_capacity(self.block_size as usize);
+
+		let now = Instant::now();
+
+		loop {
+			let current_transaction_count = transactions.len() as u32;
+			if current_transaction_count >= self.block_size {
+				break;
+			}
+
+			let remaining = self.block_size - current_transaction_count;
+			let mut transactions_to_add =
+				self.mempool.pop_mempool_transactions(remaining as usize).await?;
+			transactions.append(&mut transactions_to_add);
+
+			// sleep to yield to other tasks and wait for more transactions
+			tokio::task::yield_now().await;
+
+			if now.elapsed().as_millis() as u64 > self.building_time_ms {
+				break;
+			}
+		}
+
+		Ok(transactions.into_iter().map(|transaction| transaction.transaction).collect())
+	}
+
+	/// Picks the transactions of a block with the block policy, waiting up to the building time for enough candidates.
+	///
+	/// Only the picked transactions are removed from the mempool, so the rest keep their place even if the node stops.
+	async fn select_transactions(&self) -> Result<Vec<Transaction>, anyhow::Error> {
+		let _selection = self.selection.lock().await;
+		let selector = BlockSelector::new(&self.block_policy, self.inspector.as_deref());
+		let candidate_count = self.block_size.saturating_mul(CANDIDATE_BLOCKS);
+		let scan_count = self.block_size.saturating_mul(SCANNED_BLOCKS);
+
+		let now = Instant::now();
+
+		let candidates = loop {
+			let scanned = self.mempool.peek_mempool_transactions(scan_count as usize).await?;
+			let scanned_count = scanned.len() as u32;
+			let candidates = selector.candidates(scanned, candidate_count, self.block_size);
+
+			// more transactions cannot add candidates once the scan is full
+			if candidates.len() as u32 >= candidate_count
+				|| scanned_count >= scan_count
+				|| now.elapsed().as_millis() as u64 > self.building_time_ms
+			{
+				break candidates;
+			}
+
+			// sleep to yield to other tasks and wait for more transactions
+			tokio::task::yield_now().await;
+		};
+
+		let (transactions, _rest) = selector.select(candidates, self.block_size);
+		self.mempool
+			.remove_mempool_transactions(
+				transactions.iter().map(|transaction| transaction.transaction.id()).collect(),
+			)
+			.await?;
+
+		Ok(transactions.into_iter().map(|transaction| transaction.transaction).collect())
+	}
+
 	async fn build_next_block(
 		&self,
-		metadata: block::BlockMetadata,
 		transactions: Vec<Transaction>,
 	) -> Result<Block, anyhow::Error> {
 		let mut parent_block = self.parent_block.write().await;
+		let height = self.parent_height.load(Ordering::SeqCst) + 1;
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_micros() as u64;
+		let metadata = block::BlockMetadata::new(timestamp, self.proposer.clone(), height);
 		let new_block = Block::new(metadata, *parent_block, BTreeSet::from_iter(transactions));
 		*parent_block = new_block.id();
+		self.parent_height.store(height, Ordering::SeqCst);
+		self.parent_offset.store(0, Ordering::SeqCst);
 		Ok(new_block)
 	}
 }
@@ -96,33 +269,17 @@ impl<T: MempoolTransactionOperations> Sequencer for Memseq<T> {
 	}
 
 	async fn wait_for_next_block(&self) -> Result<Option<Block>, anyhow::Error> {
-		let mut transactions = Vec::with_capacity(self.block_size as usize);
-
-		let now = Instant::now();
-
-		loop {
-			let current_block_size = transactions.len() as u32;
-			if current_block_size >= self.block_size {
-				break;
-			}
-
-			let remaining = self.block_size - current_block_size;
-			let mut transactions_to_add = self.mempool.pop_transactions(remaining as usize).await?;
-			transactions.append(&mut transactions_to_add);
-
-			// sleep to yield to other tasks and wait for more transactions
-			tokio::task::yield_now().await;
-
-			if now.elapsed().as_millis() as u64 > self.building_time_ms {
-				break;
-			}
-		}
+		// a policy other than arrival order picks from more candidates than fit into the block
+		let transactions = if self.block_policy.is_arrival_order() {
+			self.pop_transactions().await?
+		} else {
+			self.select_transactions().await?
+		};
 
 		if transactions.is_empty() {
 			Ok(None)
 		} else {
-			let new_block =
-				self.build_next_block(block::BlockMetadata::default(), transactions).await?;
+			let new_block = self.build_next_block(transactions).await?;
 			Ok(Some(new_block))
 		}
 	}
@@ -179,6 +336,100 @@ pub mod test {
 		Ok(())
 	}
 
+	#[tokio::test]
+	async fn test_observe_block_from_other_proposer() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?.with_proposer(vec![1]);
+
+		memseq.publish(Transaction::new(vec![1], 0, 0)).await?;
+		let own_block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		assert_eq!(memseq.parent_height(), 1);
+
+		// a higher block from another proposer becomes the parent
+		let other_block = Block::new(
+			block::BlockMetadata::new(0, vec![2], 2),
+			own_block.id(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![2], 0, 0)]),
+		);
+		assert!(memseq.observe_block(&other_block).await);
+		assert_eq!(memseq.parent_block().await, other_block.id());
+		assert_eq!(memseq.parent_height(), 2);
+
+		// an older block does not roll the parent back
+		assert!(!memseq.observe_block(&own_block).await);
+		assert_eq!(memseq.parent_block().await, other_block.id());
+
+		memseq.publish(Transaction::new(vec![3], 0, 0)).await?;
+		let next_block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		assert_eq!(next_block.parent(), other_block.id());
+		assert_eq!(next_block.metadata().height(), Some(3));
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_unwind_block() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?.with_proposer(vec![1]);
+
+		memseq.publish(Transaction::new(vec![1], 0, 0)).await?;
+		let first_block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		memseq.publish(Transaction::new(vec![2], 0, 0)).await?;
+		let second_block =
+			memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+
+		// only the current parent is unwound
+		assert!(!memseq.unwind_block(&first_block).await);
+		assert!(memseq.unwind_block(&second_block).await);
+		assert_eq!(memseq.parent_block().await, first_block.id());
+		assert_eq!(memseq.parent_height(), 1);
+
+		// a block of another proposer at the unwound height becomes the parent
+		let other_block = Block::new(
+			block::BlockMetadata::new(0, vec![2], 2),
+			first_block.id(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![3], 0, 0)]),
+		);
+		assert!(memseq.observe_block(&other_block).await);
+		assert_eq!(memseq.parent_block().await, other_block.id());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_observe_split_block_parts() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 128, 250)?.with_proposer(vec![1]);
+
+		// the parts of a split block share a height and are ordered by their offset
+		let metadata = block::BlockMetadata::new(0, vec![2], 1);
+		let first_part = Block::new(
+			metadata.split_at(0),
+			block::Id::default(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![1], 0, 0)]),
+		);
+		let second_part = Block::new(
+			metadata.split_at(1),
+			block::Id::default(),
+			BTreeSet::from_iter(vec![Transaction::new(vec![2], 0, 0)]),
+		);
+		assert!(memseq.observe_block(&second_part).await);
+		assert!(!memseq.observe_block(&first_part).await);
+		assert_eq!(memseq.parent_block().await, second_part.id());
+		assert_eq!(memseq.parent_height(), 1);
+
+		memseq.publish(Transaction::new(vec![3], 0, 0)).await?;
+		let next_block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		assert_eq!(next_block.parent(), second_part.id());
+		assert_eq!(next_block.metadata().height(), Some(2));
+		assert_eq!(next_block.metadata().offset(), 0);
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_publish_error_propagation() -> Result<(), anyhow::Error> {
 		let mempool = MockMempool;
@@ -380,6 +631,89 @@ pub mod test {
 		Ok(())
 	}
 
+	/// Reads the sender from the first byte of the data.
+	struct FirstByteSender;
+	impl TransactionInspector for FirstByteSender {
+		fn sender(&self, transaction: &Transaction) -> Option<Vec<u8>> {
+			transaction.data().first().map(|sender| vec![*sender])
+		}
+
+		fn gas_unit_price(&self, _transaction: &Transaction) -> Option<u64> {
+			None
+		}
+	}
+
+	#[tokio::test]
+	async fn test_block_policy_caps_senders() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 10, 250)?
+			.with_block_policy(BlockPolicy {
+				max_transactions_per_sender: Some(2),
+				..BlockPolicy::default()
+			})
+			.with_inspector(Arc::new(FirstByteSender));
+
+		for sequence_number in 0..5 {
+			memseq.publish(Transaction::new(vec![1], 0, sequence_number)).await?;
+		}
+		memseq.publish(Transaction::new(vec![2], 0, 0)).await?;
+
+		let block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		let mut picked: Vec<(u8, u64)> = block
+			.transactions()
+			.map(|transaction| (transaction.data()[0], transaction.sequence_number()))
+			.collect();
+		picked.sort();
+		assert_eq!(picked, vec![(1, 0), (1, 1), (2, 0)]);
+
+		// the transactions left out go into the next block
+		let block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		let picked: Vec<(u8, u64)> = block
+			.transactions()
+			.map(|transaction| (transaction.data()[0], transaction.sequence_number()))
+			.collect();
+		assert_eq!(picked, vec![(1, 2), (1, 3)]);
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_block_policy_scans_past_a_bursty_sender() -> Result<(), anyhow::Error> {
+		let dir = tempdir()?;
+		let path = dir.path().to_path_buf();
+		let memseq = Memseq::try_move_rocks(path, 2, 250)?
+			.with_block_policy(BlockPolicy {
+				max_transactions_per_sender: Some(1),
+				..BlockPolicy::default()
+			})
+			.with_inspector(Arc::new(FirstByteSender));
+
+		// the first sender alone has more transactions than the candidates of a block
+		for sequence_number in 0..20 {
+			memseq.publish(Transaction::new(vec![1], 0, sequence_number)).await?;
+		}
+		memseq.publish(Transaction::new(vec![2], 0, 30)).await?;
+
+		let block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		let mut picked: Vec<(u8, u64)> = block
+			.transactions()
+			.map(|transaction| (transaction.data()[0], transaction.sequence_number()))
+			.collect();
+		picked.sort();
+		assert_eq!(picked, vec![(1, 0), (2, 30)]);
+
+		// the transactions left out were never taken out of the mempool
+		let block = memseq.wait_for_next_block().await?.ok_or(anyhow::anyhow!("No block"))?;
+		let picked: Vec<(u8, u64)> = block
+			.transactions()
+			.map(|transaction| (transaction.data()[0], transaction.sequence_number()))
+			.collect();
+		assert_eq!(picked, vec![(1, 1)]);
+
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_wait_next_block_respects_time() -> Result<(), anyhow::Error> {
 		let dir = tempdir()?;
@@ -449,11 +783,20 @@ pub mod test {
 			Transaction::new(vec![7, 8, 9], 0, 0),
 		];
 
-		let metadata = block::BlockMetadata::default();
-		let block = memseq.build_next_block(metadata, transactions).await?;
+		let memseq = memseq.with_proposer(vec![1; 33]);
+		let block = memseq.build_next_block(transactions).await?;
 
 		assert_eq!(block.transactions().len(), 3);
 		assert_eq!(block.id(), memseq.parent_block().await);
+		assert_eq!(block.metadata().version(), 1);
+		assert_eq!(block.metadata().proposer(), Some([1; 33].as_slice()));
+		assert_eq!(block.metadata().height(), Some(1));
+		assert!(block.metadata().timestamp().is_some());
+
+		let next_block = memseq.build_next_block(vec![Transaction::test()]).await?;
+		assert_eq!(next_block.parent(), block.id());
+		assert_eq!(next_block.metadata().height(), Some(2));
+		assert_eq!(memseq.parent_height(), 2);
 
 		Ok(())
 	}
@@ -495,6 +838,13 @@ pub mod test {
 			Err(anyhow::anyhow!("Mock pop_mempool_transaction"))
 		}
 
+		async fn peek_mempool_transactions(
+			&self,
+			_n: usize,
+		) -> Result<Vec<MempoolTransaction>, anyhow::Error> {
+			Err(anyhow::anyhow!("Mock peek_mempool_transactions"))
+		}
+
 		async fn gc_mempool_transactions(
 			&self,
 			_timestamp_threshold: u64,
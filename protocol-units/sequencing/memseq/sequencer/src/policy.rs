@@ -0,0 +1,282 @@
+use mempool_util::MempoolTransaction;
+pub use memseq_util::{BlockOrdering, BlockPolicy};
+use movement_types::transaction::Transaction;
+
+use std::collections::{HashMap, VecDeque};
+
+/// Reads what a block policy needs to know from the data of a transaction, which is opaque to memseq.
+pub trait TransactionInspector: Send + Sync {
+	/// The sender of the transaction, if the data can be decoded.
+	fn sender(&self, transaction: &Transaction) -> Option<Vec<u8>>;
+
+	/// The price the transaction pays per unit of gas, if the data can be decoded.
+	fn gas_unit_price(&self, transaction: &Transaction) -> Option<u64>;
+}
+
+/// A candidate transaction with what the policy knows about it.
+struct Candidate {
+	/// The position of the transaction in mempool order.
+	position: usize,
+	gas_unit_price: u64,
+	size: u64,
+	transaction: MempoolTransaction,
+}
+
+/// Picks the transactions of a block from the candidates popped from the mempool.
+///
+/// Transactions whose sender cannot be decoded are treated as if each had its own sender.
+/// Transactions whose gas unit price cannot be decoded are treated as paying nothing.
+pub struct BlockSelector<'a> {
+	policy: &'a BlockPolicy,
+	inspector: Option<&'a dyn TransactionInspector>,
+}
+
+impl<'a> BlockSelector<'a> {
+	pub fn new(policy: &'a BlockPolicy, inspector: Option<&'a dyn TransactionInspector>) -> Self {
+		Self { policy, inspector }
+	}
+
+	/// Picks the candidates for a block from transactions scanned in mempool order, keeping the mempool order.
+	///
+	/// Each sender contributes at most the transactions it could have picked into a block,
+	/// those with the lowest sequence numbers, so that one sender with many transactions cannot fill the candidates.
+	pub fn candidates(
+		&self,
+		scanned: Vec<MempoolTransaction>,
+		candidate_count: u32,
+		block_size: u32,
+	) -> Vec<MempoolTransaction> {
+		let per_sender = self
+			.policy
+			.max_transactions_per_sender
+			.map_or(block_size, |max| max.min(block_size)) as usize;
+
+		// group the transactions of each sender, keeping the position of each in mempool order
+		let mut groups: Vec<Vec<(usize, MempoolTransaction)>> = Vec::new();
+		let mut sender_groups: HashMap<Vec<u8>, usize> = HashMap::new();
+		for (position, transaction) in scanned.into_iter().enumerate() {
+			let sender =
+				self.inspector.and_then(|inspector| inspector.sender(&transaction.transaction));
+			let index = match sender {
+				Some(sender) => *sender_groups.entry(sender).or_insert_with(|| {
+					groups.push(Vec::new());
+					groups.len() - 1
+				}),
+				None => {
+					groups.push(Vec::new());
+					groups.len() - 1
+				}
+			};
+			groups[index].push((position, transaction));
+		}
+
+		let mut candidates: Vec<(usize, MempoolTransaction)> = groups
+			.into_iter()
+			.flat_map(|mut group| {
+				group.sort_by_key(|(position, transaction)| {
+					(transaction.transaction.sequence_number(), *position)
+				});
+				group.truncate(per_sender);
+				group
+			})
+			.collect();
+		candidates.sort_by_key(|(position, _)| *position);
+		candidates.truncate(candidate_count as usize);
+		candidates.into_iter().map(|(_, transaction)| transaction).collect()
+	}
+
+	/// Splits the candidates, given in mempool order, into the transactions of the block and the rest.
+	///
+	/// A sender whose next transaction does not fit into the block has no further transaction picked,
+	/// so the transactions of a sender are always picked in sequence number order.
+	pub fn select(
+		&self,
+		candidates: Vec<MempoolTransaction>,
+		block_size: u32,
+	) -> (Vec<MempoolTransaction>, Vec<MempoolTransaction>) {
+		// queue the candidates of each sender by sequence number
+		let mut queues: Vec<VecDeque<Candidate>> = Vec::new();
+		let mut sender_queues: HashMap<Vec<u8>, usize> = HashMap::new();
+		for (position, transaction) in candidates.into_iter().enumerate() {
+			let candidate = Candidate {
+				position,
+				gas_unit_price: self
+					.inspector
+					.and_then(|inspector| inspector.gas_unit_price(&transaction.transaction))
+					.unwrap_or(0),
+				size: transaction.transaction.data().len() as u64,
+				transaction,
+			};
+			let sender = self
+				.inspector
+				.and_then(|inspector| inspector.sender(&candidate.transaction.transaction));
+			match sender {
+				Some(sender) => match sender_queues.get(&sender) {
+					Some(index) => queues[*index].push_back(candidate),
+					None => {
+						sender_queues.insert(sender, queues.len());
+						queues.push(VecDeque::from([candidate]));
+					}
+				},
+				None => queues.push(VecDeque::from([candidate])),
+			}
+		}
+		for queue in queues.iter_mut() {
+			queue.make_contiguous().sort_by_key(|candidate| {
+				(candidate.transaction.transaction.sequence_number(), candidate.position)
+			});
+		}
+
+		let mut picked_per_queue = vec![0u32; queues.len()];
+		let mut closed = vec![false; queues.len()];
+		let mut block = Vec::new();
+		let mut block_bytes = 0u64;
+		while (block.len() as u32) < block_size {
+			// the open sender whose next transaction goes first
+			let next = queues
+				.iter()
+				.enumerate()
+				.filter(|(index, _)| !closed[*index])
+				.filter_map(|(index, queue)| queue.front().map(|candidate| (index, candidate)))
+				.min_by_key(|(_, candidate)| match self.policy.ordering {
+					BlockOrdering::Arrival => (0, candidate.position),
+					BlockOrdering::GasUnitPrice => {
+						(u64::MAX - candidate.gas_unit_price, candidate.position)
+					}
+				})
+				.map(|(index, candidate)| (index, candidate.size));
+			let (index, size) = match next {
+				Some(next) => next,
+				None => break,
+			};
+
+			// a transaction larger than the whole budget still goes into an otherwise empty block,
+			// so that it does not stay in the mempool forever
+			if let Some(max_block_bytes) = self.policy.max_block_bytes {
+				if block_bytes + size > max_block_bytes && !block.is_empty() {
+					closed[index] = true;
+					continue;
+				}
+			}
+
+			if let Some(candidate) = queues[index].pop_front() {
+				block_bytes += candidate.size;
+				block.push(candidate.transaction);
+			}
+			picked_per_queue[index] += 1;
+			if let Some(max_transactions_per_sender) = self.policy.max_transactions_per_sender {
+				if picked_per_queue[index] >= max_transactions_per_sender {
+					closed[index] = true;
+				}
+			}
+		}
+
+		let rest = queues.into_iter().flatten().map(|candidate| candidate.transaction).collect();
+		(block, rest)
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	/// Reads the sender from the first byte of the data and the gas unit price from the second.
+	struct ByteInspector;
+	impl TransactionInspector for ByteInspector {
+		fn sender(&self, transaction: &Transaction) -> Option<Vec<u8>> {
+			transaction.data().first().map(|sender| vec![*sender])
+		}
+
+		fn gas_unit_price(&self, transaction: &Transaction) -> Option<u64> {
+			transaction.data().get(1).map(|price| *price as u64)
+		}
+	}
+
+	fn candidate(sender: u8, gas_unit_price: u8, sequence_number: u64) -> MempoolTransaction {
+		MempoolTransaction::at_time(
+			Transaction::new(vec![sender, gas_unit_price], 0, sequence_number),
+			0,
+		)
+	}
+
+	fn picked(block: &[MempoolTransaction]) -> Vec<(u8, u64)> {
+		block
+			.iter()
+			.map(|transaction| {
+				(transaction.transaction.data()[0], transaction.transaction.sequence_number())
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_arrival_order_is_unchanged() {
+		let policy = BlockPolicy::default();
+		let candidates = vec![candidate(1, 1, 0), candidate(2, 9, 0), candidate(1, 1, 1)];
+
+		let (block, rest) = BlockSelector::new(&policy, Some(&ByteInspector)).select(candidates, 2);
+
+		assert_eq!(picked(&block), vec![(1, 0), (2, 0)]);
+		assert_eq!(picked(&rest), vec![(1, 1)]);
+	}
+
+	#[test]
+	fn test_gas_unit_price_keeps_sequence_numbers() {
+		let policy =
+			BlockPolicy { ordering: BlockOrdering::GasUnitPrice, ..BlockPolicy::default() };
+		// the second transaction of sender 1 pays the most, but cannot go before the first
+		let candidates =
+			vec![candidate(1, 9, 1), candidate(2, 5, 0), candidate(1, 1, 0), candidate(3, 3, 0)];
+
+		let (block, rest) = BlockSelector::new(&policy, Some(&ByteInspector)).select(candidates, 3);
+
+		assert_eq!(picked(&block), vec![(2, 0), (3, 0), (1, 0)]);
+		assert_eq!(picked(&rest), vec![(1, 1)]);
+	}
+
+	#[test]
+	fn test_max_transactions_per_sender() {
+		let policy = BlockPolicy { max_transactions_per_sender: Some(2), ..BlockPolicy::default() };
+		let candidates = (0..5).map(|i| candidate(1, 1, i)).chain([candidate(2, 1, 0)]).collect();
+
+		let (block, rest) =
+			BlockSelector::new(&policy, Some(&ByteInspector)).select(candidates, 10);
+
+		assert_eq!(picked(&block), vec![(1, 0), (1, 1), (2, 0)]);
+		assert_eq!(rest.len(), 3);
+	}
+
+	#[test]
+	fn test_max_block_bytes() {
+		let policy = BlockPolicy { max_block_bytes: Some(5), ..BlockPolicy::default() };
+		let large = MempoolTransaction::at_time(Transaction::new(vec![3, 1, 0, 0, 0, 0], 0, 0), 0);
+		let candidates = vec![candidate(1, 1, 0), candidate(1, 1, 1), candidate(2, 1, 0)];
+
+		let (block, rest) =
+			BlockSelector::new(&policy, Some(&ByteInspector)).select(candidates, 10);
+		assert_eq!(picked(&block), vec![(1, 0), (1, 1)]);
+		assert_eq!(picked(&rest), vec![(2, 0)]);
+
+		// a transaction above the budget goes into a block on its own
+		let (block, rest) = BlockSelector::new(&policy, Some(&ByteInspector))
+			.select(vec![large, candidate(1, 1, 0)], 10);
+		assert_eq!(picked(&block), vec![(3, 0)]);
+		assert_eq!(picked(&rest), vec![(1, 0)]);
+	}
+
+	#[test]
+	fn test_candidates_are_capped_per_sender() {
+		let policy = BlockPolicy { max_transactions_per_sender: Some(2), ..BlockPolicy::default() };
+		// the first sender crowds the scanned transactions, out of sequence number order
+		let scanned = [3, 0, 2, 1, 4]
+			.into_iter()
+			.map(|i| candidate(1, 1, i))
+			.chain([candidate(2, 1, 0), candidate(3, 1, 0)])
+			.collect();
+
+		let candidates =
+			BlockSelector::new(&policy, Some(&ByteInspector)).candidates(scanned, 3, 10);
+
+		assert_eq!(picked(&candidates), vec![(1, 0), (1, 1), (2, 0)]);
+	}
+}
@@ -21,12 +21,126 @@ pub struct Config {
 	/// The memseq max block size
 	#[serde(default = "default_memseq_max_block_size")]
 	pub memseq_max_block_size: u32,
+
+	/// How block production rotates between the known DA signers
+	#[serde(default = "default_memseq_proposer_rotation")]
+	pub memseq_proposer_rotation: ProposerRotation,
+
+	/// How long after accepting a transaction the sequencer commits to including it in a block
+	#[serde(default = "default_memseq_intent_deadline_ms")]
+	pub memseq_intent_deadline_ms: u64,
+
+	/// How the transactions of a block are picked from the mempool
+	#[serde(default = "default_memseq_block_policy")]
+	pub memseq_block_policy: BlockPolicy,
 }
 
 env_default!(default_memseq_build_time, "MEMSEQ_BUILD_TIME", u64, 1000);
 
 env_default!(default_memseq_max_block_size, "MEMSEQ_MAX_BLOCK_SIZE", u32, 2048);
 
+env_default!(default_memseq_rotation_slot_heights, "MEMSEQ_ROTATION_SLOT_HEIGHTS", u64, 4);
+
+env_default!(default_memseq_rotation_slot_ms, "MEMSEQ_ROTATION_SLOT_MS", u64, 5000);
+
+env_default!(default_memseq_intent_deadline_ms, "MEMSEQ_INTENT_DEADLINE_MS", u64, 30000);
+
+env_default!(default_memseq_max_transactions_per_sender, "MEMSEQ_MAX_TRANSACTIONS_PER_SENDER", u32);
+
+env_default!(default_memseq_max_block_bytes, "MEMSEQ_MAX_BLOCK_BYTES", u64);
+
+/// How block production rotates between sequencers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ProposerRotation {
+	/// This sequencer proposes every block.
+	Disabled,
+	/// The known DA signers take turns proposing for `slot_heights` DA heights each.
+	DaHeight { slot_heights: u64 },
+	/// The known DA signers take turns proposing for `slot_ms` milliseconds each.
+	Time { slot_ms: u64 },
+}
+
+/// The default proposer rotation, read from `MEMSEQ_PROPOSER_ROTATION` as `da-height` or `time`.
+///
+/// Unknown values are rejected by [try_default_memseq_proposer_rotation], which setup and startup check.
+pub fn default_memseq_proposer_rotation() -> ProposerRotation {
+	try_default_memseq_proposer_rotation().unwrap_or(ProposerRotation::Disabled)
+}
+
+/// Reads the proposer rotation from `MEMSEQ_PROPOSER_ROTATION`, failing on an unknown value.
+pub fn try_default_memseq_proposer_rotation() -> Result<ProposerRotation, anyhow::Error> {
+	match std::env::var("MEMSEQ_PROPOSER_ROTATION") {
+		Ok(val) => match val.as_str() {
+			"da-height" => Ok(ProposerRotation::DaHeight {
+				slot_heights: default_memseq_rotation_slot_heights(),
+			}),
+			"time" => Ok(ProposerRotation::Time { slot_ms: default_memseq_rotation_slot_ms() }),
+			"disabled" => Ok(ProposerRotation::Disabled),
+			_ => Err(anyhow::anyhow!(
+				"Unknown MEMSEQ_PROPOSER_ROTATION {:?}, expected da-height, time or disabled",
+				val
+			)),
+		},
+		Err(_) => Ok(ProposerRotation::Disabled),
+	}
+}
+
+/// The order in which transactions compete for a place in a block.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BlockOrdering {
+	/// Transactions are picked in mempool order, i.e., by application priority and then arrival.
+	Arrival,
+	/// Transactions paying the highest gas unit price are picked first.
+	GasUnitPrice,
+}
+
+/// Limits on which transactions the sequencer puts into a block, on top of the block size.
+///
+/// Whatever the policy, the transactions of a sender are picked in sequence number order.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BlockPolicy {
+	pub ordering: BlockOrdering,
+	/// The most transactions of a single sender in a block.
+	pub max_transactions_per_sender: Option<u32>,
+	/// The most transaction bytes in a block.
+	pub max_block_bytes: Option<u64>,
+}
+
+impl BlockPolicy {
+	/// Whether the policy picks the first transactions of the mempool, as if there were no policy.
+	pub fn is_arrival_order(&self) -> bool {
+		self.ordering == BlockOrdering::Arrival
+			&& self.max_transactions_per_sender.is_none()
+			&& self.max_block_bytes.is_none()
+	}
+}
+
+impl Default for BlockPolicy {
+	fn default() -> Self {
+		Self {
+			ordering: BlockOrdering::Arrival,
+			max_transactions_per_sender: None,
+			max_block_bytes: None,
+		}
+	}
+}
+
+/// The default block policy, ordered as read from `MEMSEQ_BLOCK_ORDERING` as `arrival` or `gas-unit-price`.
+pub fn default_memseq_block_policy() -> BlockPolicy {
+	let ordering = match std::env::var("MEMSEQ_BLOCK_ORDERING") {
+		Ok(val) => match val.as_str() {
+			"gas-unit-price" => BlockOrdering::GasUnitPrice,
+			_ => BlockOrdering::Arrival,
+		},
+		Err(_) => BlockOrdering::Arrival,
+	};
+	BlockPolicy {
+		ordering,
+		max_transactions_per_sender: default_memseq_max_transactions_per_sender(),
+		max_block_bytes: default_memseq_max_block_bytes(),
+	}
+}
+
 impl Default for Config {
 	fn default() -> Self {
 		Config {
@@ -34,6 +148,9 @@ impl Default for Config {
 			sequencer_database_path: Config::default_sequencer_database_path(),
 			memseq_build_time: default_memseq_build_time(),
 			memseq_max_block_size: default_memseq_max_block_size(),
+			memseq_proposer_rotation: default_memseq_proposer_rotation(),
+			memseq_intent_deadline_ms: default_memseq_intent_deadline_ms(),
+			memseq_block_policy: default_memseq_block_policy(),
 		}
 	}
 }
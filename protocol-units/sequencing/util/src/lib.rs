@@ -1,3 +1,6 @@
+pub mod rotation;
+pub use rotation::{ProposerSchedule, SlotClock};
+
 use movement_types::{
 	atomic_transaction_bundle::AtomicTransactionBundle, block::Block, transaction::Transaction,
 };
@@ -0,0 +1,154 @@
+/// How the slots of a [ProposerSchedule] are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlotClock {
+	/// Each slot spans this many DA heights.
+	DaHeight { slot_heights: u64 },
+	/// Each slot spans this many milliseconds.
+	Time { slot_ms: u64 },
+}
+
+/// A deterministic round robin assignment of slots to proposers.
+///
+/// Every node configured with the same proposers and clock computes the same schedule,
+/// so a proposer that misses its slot is simply replaced by the next one when the slot ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposerSchedule {
+	/// The proposer keys, sorted so that the order does not depend on the configuration order.
+	proposers: Vec<Vec<u8>>,
+	clock: SlotClock,
+}
+
+impl ProposerSchedule {
+	pub fn try_new(
+		proposers: impl IntoIterator<Item = Vec<u8>>,
+		clock: SlotClock,
+	) -> Result<Self, anyhow::Error> {
+		let mut proposers: Vec<Vec<u8>> = proposers.into_iter().collect();
+		proposers.sort();
+		proposers.dedup();
+		if proposers.is_empty() {
+			anyhow::bail!("A proposer schedule needs at least one proposer");
+		}
+		match clock {
+			SlotClock::DaHeight { slot_heights: 0 } | SlotClock::Time { slot_ms: 0 } => {
+				anyhow::bail!("Proposer slots must not be empty")
+			}
+			_ => {}
+		}
+		Ok(Self { proposers, clock })
+	}
+
+	pub fn clock(&self) -> SlotClock {
+		self.clock
+	}
+
+	pub fn proposers(&self) -> &[Vec<u8>] {
+		&self.proposers
+	}
+
+	/// The slot containing the given DA height and timestamp in microseconds.
+	///
+	/// Only the measure used by the clock is taken into account.
+	pub fn slot(&self, da_height: u64, timestamp_micros: u64) -> u64 {
+		match self.clock {
+			SlotClock::DaHeight { slot_heights } => da_height / slot_heights,
+			SlotClock::Time { slot_ms } => timestamp_micros / 1000 / slot_ms,
+		}
+	}
+
+	/// The proposer assigned to the slot.
+	pub fn proposer_for_slot(&self, slot: u64) -> &[u8] {
+		&self.proposers[(slot % self.proposers.len() as u64) as usize]
+	}
+
+	/// Whether the proposer is the one assigned to the slot.
+	pub fn is_proposer_for_slot(&self, proposer: &[u8], slot: u64) -> bool {
+		self.proposer_for_slot(slot) == proposer
+	}
+
+	/// The first slot at or after `from_slot` assigned to the proposer, if it is in the schedule.
+	pub fn next_slot_for(&self, proposer: &[u8], from_slot: u64) -> Option<u64> {
+		let index = self.proposers.iter().position(|p| p.as_slice() == proposer)? as u64;
+		let len = self.proposers.len() as u64;
+		let offset = (index + len - from_slot % len) % len;
+		Some(from_slot + offset)
+	}
+
+	/// The first DA height of a slot, for [SlotClock::DaHeight].
+	pub fn slot_start_height(&self, slot: u64) -> Option<u64> {
+		match self.clock {
+			SlotClock::DaHeight { slot_heights } => Some(slot * slot_heights),
+			SlotClock::Time { .. } => None,
+		}
+	}
+
+	/// The start of a slot in microseconds, for [SlotClock::Time].
+	pub fn slot_start_micros(&self, slot: u64) -> Option<u64> {
+		match self.clock {
+			SlotClock::Time { slot_ms } => Some(slot * slot_ms * 1000),
+			SlotClock::DaHeight { .. } => None,
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+
+	#[test]
+	fn test_round_robin_by_height() -> Result<(), anyhow::Error> {
+		let schedule = ProposerSchedule::try_new(
+			vec![vec![2], vec![1], vec![3], vec![1]],
+			SlotClock::DaHeight { slot_heights: 4 },
+		)?;
+		assert_eq!(schedule.proposers(), &[vec![1], vec![2], vec![3]]);
+
+		assert_eq!(schedule.slot(0, 0), 0);
+		assert_eq!(schedule.slot(7, 0), 1);
+		assert_eq!(schedule.proposer_for_slot(0), &[1]);
+		assert_eq!(schedule.proposer_for_slot(1), &[2]);
+		assert_eq!(schedule.proposer_for_slot(5), &[3]);
+		assert!(schedule.is_proposer_for_slot(&[3], 2));
+		assert!(!schedule.is_proposer_for_slot(&[1], 2));
+		assert_eq!(schedule.slot_start_height(2), Some(8));
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_round_robin_by_time() -> Result<(), anyhow::Error> {
+		let schedule =
+			ProposerSchedule::try_new(vec![vec![1], vec![2]], SlotClock::Time { slot_ms: 500 })?;
+
+		assert_eq!(schedule.slot(100, 1_499_999), 2);
+		assert_eq!(schedule.slot(100, 1_500_000), 3);
+		assert_eq!(schedule.slot_start_micros(3), Some(1_500_000));
+		assert_eq!(schedule.slot_start_height(3), None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_next_slot_for() -> Result<(), anyhow::Error> {
+		let schedule = ProposerSchedule::try_new(
+			vec![vec![1], vec![2], vec![3]],
+			SlotClock::DaHeight { slot_heights: 1 },
+		)?;
+
+		assert_eq!(schedule.next_slot_for(&[1], 0), Some(0));
+		assert_eq!(schedule.next_slot_for(&[1], 1), Some(3));
+		assert_eq!(schedule.next_slot_for(&[3], 4), Some(5));
+		assert_eq!(schedule.next_slot_for(&[4], 4), None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_schedules() {
+		assert!(ProposerSchedule::try_new(vec![], SlotClock::Time { slot_ms: 1 }).is_err());
+		assert!(ProposerSchedule::try_new(vec![vec![1]], SlotClock::Time { slot_ms: 0 }).is_err());
+		assert!(ProposerSchedule::try_new(vec![vec![1]], SlotClock::DaHeight { slot_heights: 0 })
+			.is_err());
+	}
+}
@@ -1,29 +1,31 @@
 pub mod types;
 pub mod rocksdb;
+pub mod storage;
 
-use anyhow::Ok;
+use std::sync::Mutex;
 use move_core_types::{
     account_address:: AccountAddress, 
     language_storage::{ModuleId, StructTag}, 
     resolver::{
         ModuleResolver, ResourceResolver
     },
-    effects::{
-        ChangeSet,
-        Event,
-        Op
-    }
+    effects::ChangeSet
 };
 use jmt::{
-    proof::SparseMerkleProof, storage::{NodeKey, NodeBatch, TreeReader, TreeWriter}, JellyfishMerkleTree, KeyHash, OwnedValue, SimpleHasher
+    proof::SparseMerkleProof, storage::TreeReader, JellyfishMerkleTree, KeyHash, OwnedValue, RootHash, SimpleHasher, Version
 };
+use move_vm_ext::storage::{MerkleProofs, Proven};
 use rocksdb::RocksdbJmt;
-use serde_json::ser;
+use storage::VersionedTreeWriter;
 
-#[derive()]
+/// Move state stored in a Jellyfish Merkle Tree.
+///
+/// Every write commits a new version of the tree, and reads without a version go to the latest one.
 pub struct JellyMove<'a, R : 'a + TreeReader, H : SimpleHasher> {
     jmt: JellyfishMerkleTree<'a, R, H>,
-    writer: &'a dyn TreeWriter
+    writer: &'a dyn VersionedTreeWriter,
+    // serializes commits, so that two writers cannot claim the same version
+    commit_lock: Mutex<()>
 }
 
 impl <'a, R : 'a + TreeReader, H : SimpleHasher> JellyMove<'a, R, H> {
@@ -31,18 +33,20 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> JellyMove<'a, R, H> {
     const MODULE_PREFIX: &'static str = "MODULE::";
     const RESOURCE_PREFIX: &'static str = "RESOURCE::";
 
-    pub fn new(jmt: JellyfishMerkleTree<'a, R, H>, writer: &'a dyn TreeWriter) -> Self {
+    pub fn new(jmt: JellyfishMerkleTree<'a, R, H>, writer: &'a dyn VersionedTreeWriter) -> Self {
         Self {
             jmt,
-            writer
+            writer,
+            commit_lock: Mutex::new(())
         }
     }
 
-    pub fn get_latest_vesion(&self) -> Result<u64, anyhow::Error> {
-        Ok(0)
+    /// The latest committed version, or `None` if the store is empty.
+    pub fn get_latest_version(&self) -> Result<Option<Version>, anyhow::Error> {
+        self.writer.latest_version()
     }
 
-    pub fn module_key(&self, id: &ModuleId) -> Result<Vec<u8>, anyhow::Error> {
+    pub fn module_key(id: &ModuleId) -> Result<Vec<u8>, anyhow::Error> {
         let mut key = Vec::new();
         key.extend_from_slice(Self::MODULE_PREFIX.as_bytes());
         key.extend_from_slice(
@@ -52,7 +56,6 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> JellyMove<'a, R, H> {
     }
 
     pub fn resource_key(
-        &self, 
         account_address : &AccountAddress,
         tag: &StructTag
     ) -> Result<Vec<u8>, anyhow::Error> {
@@ -69,45 +72,65 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> JellyMove<'a, R, H> {
 
     }
 
-}
+    /// Reads a module as of the given version.
+    pub fn get_module_at(&self, id: &ModuleId, version: Version) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let key = Self::module_key(id)?;
+        self.jmt.get(KeyHash::with::<H>(&key), version)
+    }
 
-impl <'a, R : 'a + TreeReader, H : SimpleHasher> ModuleResolver for JellyMove<'a, R, H>{
+    /// Reads a resource as of the given version.
+    pub fn get_resource_at(
+        &self,
+        account_address: &AccountAddress,
+        tag: &StructTag,
+        version: Version
+    ) -> Result<Option<Vec<u8>>, anyhow::Error> {
+        let key = Self::resource_key(account_address, tag)?;
+        self.jmt.get(KeyHash::with::<H>(&key), version)
+    }
 
-    type Error = anyhow::Error;
+    /// Applies the value set as the next version and returns that version with its root.
+    ///
+    /// Returns `None` without committing when the value set is empty.
+    pub fn commit(
+        &self,
+        value_set: Vec<(KeyHash, Option<OwnedValue>)>
+    ) -> Result<Option<(Version, RootHash)>, anyhow::Error> {
 
-    fn get_module(&self, id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
-        
-        let key = self.module_key(id)?;
+        if value_set.is_empty() {
+            return Ok(None);
+        }
 
-        let value = self.jmt.get(
-            KeyHash::with::<H>(&key),
-            self.get_latest_vesion()?
-        )?;
+        let _guard = self.commit_lock.lock().map_err(|_| anyhow::anyhow!("Commit lock poisoned"))?;
+        let version = match self.get_latest_version()? {
+            Some(latest) => latest + 1,
+            None => 0
+        };
 
-        Ok(value)
+        let (root_hash, tree_update_batch) = self.jmt.put_value_set(value_set, version)?;
+        self.writer.commit_version(version, &tree_update_batch)?;
 
-    }   
+        Ok(Some((version, root_hash)))
 
-}
+    }
 
-impl <'a, R : 'a + TreeReader, H : SimpleHasher> JellyMove<'a, R, H> {
+}
 
-    pub fn get_module_with_proof(&self, id: &ModuleId) -> Result<Option<(Vec<u8>, SparseMerkleProof<H>)>, anyhow::Error> {
-        
-        let key = self.module_key(id)?;
+impl <'a, R : 'a + TreeReader, H : SimpleHasher> ModuleResolver for JellyMove<'a, R, H>{
 
-        let (value, proof) = self.jmt.get_with_proof(
-            KeyHash::with::<H>(&key),
-            self.get_latest_vesion()?
-        )?;
+    type Error = anyhow::Error;
 
-        Ok(value.map(|v| (v, proof)))
+    fn get_module(&self, id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        
+        match self.get_latest_version()? {
+            Some(version) => self.get_module_at(id, version),
+            None => Ok(None)
+        }
 
-    }
+    }   
 
 }
 
-
 impl <'a, R : 'a + TreeReader, H : SimpleHasher> ResourceResolver for JellyMove<'a, R, H> {
 
     type Error = anyhow::Error;
@@ -118,37 +141,72 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> ResourceResolver for JellyMove<
         tag: &StructTag
     ) -> Result<Option<Vec<u8>>, Self::Error> {
         
-        let key = self.resource_key(account_address, tag)?;
+        match self.get_latest_version()? {
+            Some(version) => self.get_resource_at(account_address, tag, version),
+            None => Ok(None)
+        }
 
-        let value = self.jmt.get(
-            KeyHash::with::<H>(&key),
-            self.get_latest_vesion()?
-        )?;
+    }
+
+}
 
-        Ok(value)
+impl <'a, R : 'a + TreeReader, H : SimpleHasher> MerkleProofs for JellyMove<'a, R, H> {
 
+    type Root = RootHash;
+    type Proof = SparseMerkleProof<H>;
+
+    fn state_root(&self, version: u64) -> Result<Self::Root, anyhow::Error> {
+        self.jmt.get_root_hash(version)
     }
 
-}
+    fn get_module_with_proof(
+        &self,
+        id: &ModuleId,
+        version: u64
+    ) -> Result<Proven<Self::Proof>, anyhow::Error> {
+        
+        let key = Self::module_key(id)?;
+        let (value, proof) = self.jmt.get_with_proof(KeyHash::with::<H>(&key), version)?;
+        Ok(Proven { value, proof })
 
-impl <'a, R : 'a + TreeReader, H : SimpleHasher> JellyMove<'a, R, H> {
+    }
 
-    pub fn get_resource_with_proof(
-        &self, 
-        account_address: &AccountAddress,
-        tag: &StructTag
-    ) -> Result<Option<(Vec<u8>, SparseMerkleProof<H>)>, anyhow::Error> {
+    fn get_resource_with_proof(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+        version: u64
+    ) -> Result<Proven<Self::Proof>, anyhow::Error> {
         
-        let key = self.resource_key(account_address, tag)?;
+        let key = Self::resource_key(address, tag)?;
+        let (value, proof) = self.jmt.get_with_proof(KeyHash::with::<H>(&key), version)?;
+        Ok(Proven { value, proof })
 
-        let (value, proof) = self.jmt.get_with_proof(
-            KeyHash::with::<H>(&key),
-            self.get_latest_vesion()?
-        )?;
+    }
+
+    fn verify_module(
+        root: &Self::Root,
+        id: &ModuleId,
+        proven: &Proven<Self::Proof>
+    ) -> Result<(), anyhow::Error> {
+
+        let key = Self::module_key(id)?;
+        proven.proof.verify(*root, KeyHash::with::<H>(&key), proven.value.as_deref())
+
+    }
+
+    fn verify_resource(
+        root: &Self::Root,
+        address: &AccountAddress,
+        tag: &StructTag,
+        proven: &Proven<Self::Proof>
+    ) -> Result<(), anyhow::Error> {
 
-        Ok(value.map(|v| (v, proof)))
+        let key = Self::resource_key(address, tag)?;
+        proven.proof.verify(*root, KeyHash::with::<H>(&key), proven.value.as_deref())
 
     }
+
 }
 
 impl <'a, R : 'a + TreeReader, H : SimpleHasher> move_vm_ext::storage::ChangeSetWriter for JellyMove<'a, R, H> {
@@ -160,7 +218,7 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> move_vm_ext::storage::ChangeSet
         for (account_address, identifier, value) in change_set.modules() {
             
             let module_id = ModuleId::new(account_address, identifier.clone());
-            let key = self.module_key(&module_id)?;
+            let key = Self::module_key(&module_id)?;
 
             let key_hash = KeyHash::with::<H>(&key);
 
@@ -170,7 +228,7 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> move_vm_ext::storage::ChangeSet
 
         for (account_address, struct_tag, value) in change_set.resources() {
             
-            let key = self.resource_key(&account_address, &struct_tag)?;
+            let key = Self::resource_key(&account_address, &struct_tag)?;
 
             let key_hash = KeyHash::with::<H>(&key);
 
@@ -178,17 +236,8 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> move_vm_ext::storage::ChangeSet
 
         }
 
-        let (root_hash, tree_update_batch) = self.jmt.put_value_set(
-            value_sets,
-            self.get_latest_vesion()?,
-        )?;
-
+        self.commit(value_sets)?;
 
-        self.writer.write_node_batch(
-           &tree_update_batch.node_batch
-        )?;
-
-        // todo: add stale node writing
         Ok(())
     }
 
@@ -198,18 +247,11 @@ impl <'a, R : 'a + TreeReader, H : SimpleHasher> move_vm_ext::storage::BasicStor
 
     fn publish_or_overwrite_module(&self, id: ModuleId, blob: Vec<u8>) -> Result<(), anyhow::Error> {
         
-        let key = self.module_key(&id)?;
+        let key = Self::module_key(&id)?;
 
         let key_hash = KeyHash::with::<H>(&key);
 
-        let (root_hash, tree_update_batch) = self.jmt.put_value_set(
-            vec![(key_hash, Some(blob.to_owned()))],
-            self.get_latest_vesion()?
-        )?;
-
-        self.writer.write_node_batch(
-            &tree_update_batch.node_batch
-        )?;
+        self.commit(vec![(key_hash, Some(blob))])?;
     
         Ok(())
 
@@ -430,5 +472,45 @@ pub mod test {
         
     }
 
+    #[test]
+    fn test_versioned_reads_and_proofs() -> Result<(), anyhow::Error> {
+
+        let dir = TempDir::new()?;
+        let jmt = RocksdbJmt::new(dir.path().to_str().unwrap());
+        let storage : JellyMove<'_, RocksdbJmt, sha2::Sha256> = JellyMove::new(
+            JellyfishMerkleTree::new(&jmt),
+            &jmt
+        );
+        assert_eq!(storage.get_latest_version()?, None);
+
+        let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M")?);
+        let missing_id = ModuleId::new(TEST_ADDR, Identifier::new("N")?);
+
+        storage.publish_or_overwrite_module(module_id.clone(), vec![1u8])?;
+        storage.publish_or_overwrite_module(module_id.clone(), vec![2u8])?;
+        assert_eq!(storage.get_latest_version()?, Some(1));
+        assert_eq!(storage.get_module_at(&module_id, 0)?, Some(vec![1u8]));
+        assert_eq!(storage.get_module(&module_id)?, Some(vec![2u8]));
+
+        // inclusion
+        let root = storage.state_root(1)?;
+        let proven = storage.get_module_with_proof(&module_id, 1)?;
+        assert_eq!(proven.value, Some(vec![2u8]));
+        JellyMove::<'_, RocksdbJmt, sha2::Sha256>::verify_module(&root, &module_id, &proven)?;
+
+        // a proof does not verify against another version's root
+        let old_root = storage.state_root(0)?;
+        assert!(
+            JellyMove::<'_, RocksdbJmt, sha2::Sha256>::verify_module(&old_root, &module_id, &proven).is_err()
+        );
 
-}
\ No newline at end of file
+        // exclusion
+        let proven = storage.get_module_with_proof(&missing_id, 1)?;
+        assert_eq!(proven.value, None);
+        JellyMove::<'_, RocksdbJmt, sha2::Sha256>::verify_module(&root, &missing_id, &proven)?;
+
+        Ok(())
+
+    }
+
+}
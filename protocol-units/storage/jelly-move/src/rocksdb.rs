@@ -1,10 +1,12 @@
-use move_core_types::value;
-use rocksdb::{DB, Options, ColumnFamilyDescriptor, BoundColumnFamily};
-use std::sync::{Arc, RwLock};
+use rocksdb::{DB, Options, ColumnFamilyDescriptor, BoundColumnFamily, Direction, IteratorMode, WriteBatch};
+use std::sync::Arc;
 use jmt::{
     KeyHash,
     Version,
     storage::{
+        LeafNode,
+        Node,
+        NodeKey,
         NodeBatch,
         TreeReader,
         TreeWriter,
@@ -13,7 +15,8 @@ use jmt::{
         StaleNodeIndex
     }
 };
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshDeserialize;
+use crate::storage::VersionedTreeWriter;
 
 #[derive(Debug, Clone)]
 pub struct RocksdbJmt {
@@ -27,6 +30,9 @@ impl RocksdbJmt {
     const VALUE_HISTORY_CF: &'static str = "value_history";
     const STALE_NODE_CF: &'static str = "stale_nodes";
     const PREIMAGES_CF: &'static str = "preimages";
+    const METADATA_CF: &'static str = "metadata";
+
+    const LATEST_VERSION_KEY: &'static [u8] = b"latest_version";
 
     pub fn try_new(path: &str) -> Result<Self, anyhow::Error> {
         let mut options = Options::default();
@@ -38,13 +44,15 @@ impl RocksdbJmt {
         let value_history_cf = ColumnFamilyDescriptor::new(Self::VALUE_HISTORY_CF, Options::default());
         let stale_node_cf = ColumnFamilyDescriptor::new(Self::STALE_NODE_CF, Options::default());
         let preimages_cf = ColumnFamilyDescriptor::new(Self::PREIMAGES_CF, Options::default());
+        let metadata_cf = ColumnFamilyDescriptor::new(Self::METADATA_CF, Options::default());
         let db = DB::open_cf_descriptors(&options, path, vec![
             nodes_cf,
             value_history_cf,
             stale_node_cf,
-            preimages_cf
+            preimages_cf,
+            metadata_cf
         ])
-        .expect("Failed to open database with column families");
+        .map_err(|e| anyhow::anyhow!("Failed to open database with column families: {}", e))?;
 
         Ok(RocksdbJmt {
             db: Arc::new(db),
@@ -83,48 +91,90 @@ impl RocksdbJmt {
 
     }
 
+    pub fn metadata_cf(&self) -> Result<Arc<BoundColumnFamily>, anyhow::Error> {
 
-}
+        let cf = self.db.cf_handle(Self::METADATA_CF).ok_or(anyhow::anyhow!("Failed to get column family handle"))?;
+        Ok(cf)
 
-// https://github.com/penumbra-zone/jmt/blob/041ad5c7f6dfb9e2e16e09cf087e19c99008cc59/src/mock.rs#L98
-impl TreeWriter for RocksdbJmt {
-    fn write_node_batch(&self, node_batch: &NodeBatch) -> anyhow::Result<()> {
+    }
+
+    /// Value history keys are the key hash followed by the big endian version,
+    /// so that the latest value at or below a version is found with a single reverse seek.
+    fn value_history_key(key_hash: &KeyHash, version: Version) -> Vec<u8> {
+        let mut key = Vec::with_capacity(40);
+        key.extend_from_slice(&key_hash.0);
+        key.extend_from_slice(&version.to_be_bytes());
+        key
+    }
+
+    /// Stale node keys lead with the big endian version the node became stale at,
+    /// so that pruning can stop at the first index past the least readable version.
+    fn stale_node_key(stale_node_index: &StaleNodeIndex) -> Result<Vec<u8>, anyhow::Error> {
+        let mut key = stale_node_index.stale_since_version.to_be_bytes().to_vec();
+        key.extend_from_slice(&borsh::to_vec(&stale_node_index.node_key)?);
+        Ok(key)
+    }
+
+    fn batch_node_batch(&self, write_batch: &mut WriteBatch, node_batch: &NodeBatch) -> Result<(), anyhow::Error> {
 
-        // write nodes
         let cf_handle = self.nodes_cf()?;
         for (key, value) in node_batch.nodes() {
-            self.db.put_cf(
-                &cf_handle, 
+            write_batch.put_cf(
+                &cf_handle,
                 borsh::to_vec(key)?,
                 borsh::to_vec(value)?,
-            )?;
+            );
         }
 
-        /// Write value history
-        // todo: Place a value into the provided value history map. Versions must be pushed in non-decreasing order per key.
-        // todo: determine whether the above is true for our implementation
         let cf_handle = self.value_history_cf()?;
-        for (key, value) in node_batch.values() {
-            self.db.put_cf(
-                &cf_handle, 
-                borsh::to_vec(key)?,
+        for ((version, key_hash), value) in node_batch.values() {
+            write_batch.put_cf(
+                &cf_handle,
+                Self::value_history_key(key_hash, *version),
                 borsh::to_vec(value)?,
-            )?;
+            );
         }
 
+        Ok(())
+
+    }
+
+    fn batch_tree_update_batch(&self, write_batch: &mut WriteBatch, batch: &TreeUpdateBatch) -> Result<(), anyhow::Error> {
+
+        self.batch_node_batch(write_batch, &batch.node_batch)?;
+
+        let cf_handle = self.stale_nodes_cf()?;
+        for stale_node_index in batch.stale_node_index_batch.iter() {
+            write_batch.put_cf(&cf_handle, Self::stale_node_key(stale_node_index)?, []);
+        }
+
+        Ok(())
+
+    }
+
+}
+
+// https://github.com/penumbra-zone/jmt/blob/041ad5c7f6dfb9e2e16e09cf087e19c99008cc59/src/mock.rs#L98
+impl TreeWriter for RocksdbJmt {
+    fn write_node_batch(&self, node_batch: &NodeBatch) -> anyhow::Result<()> {
+
+        let mut write_batch = WriteBatch::default();
+        self.batch_node_batch(&mut write_batch, node_batch)?;
+        self.db.write(write_batch)?;
+
         Ok(())
     }
 }
 
 impl TreeReader for RocksdbJmt {
 
-    fn get_node_option(&self, node_key: &jmt::storage::NodeKey) -> anyhow::Result<Option<jmt::storage::Node>> {
+    fn get_node_option(&self, node_key: &NodeKey) -> anyhow::Result<Option<Node>> {
         let cf_handle = self.nodes_cf()?;
         let key = borsh::to_vec(node_key)?;
         let value = self.db.get_cf(&cf_handle, key)?;
         match value {
             Some(value) => {
-                let value = jmt::storage::Node::try_from_slice(&value)?;
+                let value = Node::try_from_slice(&value)?;
                 Ok(Some(value))
             }
             None => Ok(None)
@@ -138,29 +188,46 @@ impl TreeReader for RocksdbJmt {
             key_hash: KeyHash,
         ) -> anyhow::Result<Option<jmt::OwnedValue>> {
         let value_history_cf = self.value_history_cf()?;
-        
-        // move backwards from max_version (inclusive) until we find a value
-        for version in (0..max_version + 1).rev() {
-            let key = (version, key_hash.clone());
-            let value = self.db.get_cf(&value_history_cf, borsh::to_vec(&key)?)?;
-            if let Some(value) = value {
+
+        // seek backwards from max_version (inclusive) to the latest write of this key
+        let from = Self::value_history_key(&key_hash, max_version);
+        let mut iter = self.db.iterator_cf(
+            &value_history_cf,
+            IteratorMode::From(&from, Direction::Reverse)
+        );
+        match iter.next() {
+            Some(res) => {
+                let (key, value) = res?;
+                if !key.starts_with(&key_hash.0) {
+                    return Ok(None);
+                }
                 let value : Option<jmt::OwnedValue> = BorshDeserialize::try_from_slice(&value)?;
-                return Ok(value);
+                Ok(value)
             }
+            None => Ok(None)
         }
 
-        Ok(None)
-
     }
 
-    fn get_rightmost_leaf(&self) -> anyhow::Result<Option<(jmt::storage::NodeKey, jmt::storage::LeafNode)>> {
-        // todo: not sure if this is really the right most leaf
+    // https://github.com/penumbra-zone/jmt/blob/041ad5c7f6dfb9e2e16e09cf087e19c99008cc59/src/mock.rs#L53
+    fn get_rightmost_leaf(&self) -> anyhow::Result<Option<(NodeKey, LeafNode)>> {
+        // nodes are keyed by version, so the leaf with the greatest key hash needs a full scan;
+        // this is only used when restoring a tree
         let cf_handle = self.nodes_cf()?;
-        let mut iter = self.db.iterator_cf(&cf_handle, rocksdb::IteratorMode::End);
-        let (key, value) = iter.next().ok_or(anyhow::anyhow!("Failed to get rightmost leaf"))??;
-        let key = jmt::storage::NodeKey::try_from_slice(&key)?;
-        let value = jmt::storage::LeafNode::try_from_slice(&value)?;
-        Ok(Some((key, value)))
+        let mut rightmost : Option<(NodeKey, LeafNode)> = None;
+        for res in self.db.iterator_cf(&cf_handle, IteratorMode::Start) {
+            let (key, value) = res?;
+            if let Node::Leaf(leaf) = Node::try_from_slice(&value)? {
+                let is_rightmost = match &rightmost {
+                    Some((_, current)) => leaf.key_hash() > current.key_hash(),
+                    None => true
+                };
+                if is_rightmost {
+                    rightmost = Some((NodeKey::try_from_slice(&key)?, leaf));
+                }
+            }
+        }
+        Ok(rightmost)
     }
 
 }
@@ -168,44 +235,74 @@ impl TreeReader for RocksdbJmt {
 impl HasPreimage for RocksdbJmt {
     fn preimage(&self, key_hash: KeyHash) -> anyhow::Result<Option<Vec<u8>>> {
         let cf_handle = self.preimages_cf()?;
-        let value = self.db.get_cf(&cf_handle, borsh::to_vec(&key_hash)?)?;
+        let value = self.db.get_cf(&cf_handle, key_hash.0)?;
         Ok(value)
     }
 }
 
+impl VersionedTreeWriter for RocksdbJmt {
+
+    fn commit_version(&self, version: Version, batch: &TreeUpdateBatch) -> Result<(), anyhow::Error> {
+
+        let mut write_batch = WriteBatch::default();
+        self.batch_tree_update_batch(&mut write_batch, batch)?;
+        write_batch.put_cf(&self.metadata_cf()?, Self::LATEST_VERSION_KEY, version.to_be_bytes());
+        self.db.write(write_batch)?;
+
+        Ok(())
+
+    }
+
+    fn latest_version(&self) -> Result<Option<Version>, anyhow::Error> {
+
+        let value = self.db.get_cf(&self.metadata_cf()?, Self::LATEST_VERSION_KEY)?;
+        match value {
+            Some(value) => {
+                let bytes : [u8; 8] = value.as_slice().try_into()?;
+                Ok(Some(Version::from_be_bytes(bytes)))
+            }
+            None => Ok(None)
+        }
+
+    }
+
+}
+
 // Useful operations for actually writing to the database
-/// todo: extract into trait, maybe we also want to fork jmt
 impl RocksdbJmt {
 
     pub fn write_tree_update_batch(&self, batch: &TreeUpdateBatch) -> Result<(), anyhow::Error> {
 
-        // write the node batch
-        self.write_node_batch(&batch.node_batch)?;
+        let mut write_batch = WriteBatch::default();
+        self.batch_tree_update_batch(&mut write_batch, batch)?;
+        self.db.write(write_batch)?;
 
-        // write the stale nodes
-        let cf_handle = self.stale_nodes_cf()?;
-        for stale_node_index in batch.stale_node_index_batch.iter() {
-            self.db.put_cf(&cf_handle, borsh::to_vec(&stale_node_index)?, &[])?;
-        }
+        Ok(())
+    }
 
+    pub fn put_key_preimage(&self, key_hash: KeyHash, preimage: &[u8]) -> Result<(), anyhow::Error> {
+        let cf_handle = self.preimages_cf()?;
+        self.db.put_cf(&cf_handle, key_hash.0, preimage)?;
         Ok(())
     }
-   
+
    pub fn purge_stale_nodes(&self, last_readable_version: Version) -> Result<(), anyhow::Error> {
 
-        let cf_handle = self.stale_nodes_cf()?;
-        let mut iter = self.db.iterator_cf(&cf_handle, rocksdb::IteratorMode::Start);
-        while let Some(res) = iter.next() {
+        let nodes_cf = self.nodes_cf()?;
+        let stale_nodes_cf = self.stale_nodes_cf()?;
+        let mut write_batch = WriteBatch::default();
+        for res in self.db.iterator_cf(&stale_nodes_cf, IteratorMode::Start) {
             let (key, _) = res?;
-            let key : (Version, KeyHash) = BorshDeserialize::try_from_slice(&key)?;
-            if key.0 <= last_readable_version {
-                // remove the key from the nodes column family
-                let cf_handle = self.nodes_cf()?;
-                self.db.delete_cf(&cf_handle, borsh::to_vec(&key.1)?)?;
-                // remove the key from the stale nodes column family
-                self.db.delete_cf(&cf_handle, borsh::to_vec(&key)?)?;
+            let (version, node_key) = key.split_at(8);
+            let version = Version::from_be_bytes(version.try_into()?);
+            // only nodes retired at or before the last readable version can be removed
+            if version > last_readable_version {
+                break;
             }
+            write_batch.delete_cf(&nodes_cf, node_key);
+            write_batch.delete_cf(&stale_nodes_cf, &key);
         }
+        self.db.write(write_batch)?;
 
         Ok(())
 
@@ -327,4 +424,38 @@ pub mod test {
 
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_value_history_and_purge() -> Result<(), anyhow::Error> {
+        let dir = TempDir::new()?;
+        let db = RocksdbJmt::try_new(dir.path().to_str().unwrap())?;
+        let tree = JellyfishMerkleTree::<_, Sha256>::new(&db);
+
+        let key = KeyHash([1u8; 32]);
+        let neighbour = KeyHash([2u8; 32]);
+
+        for version in 0..4u64 {
+            let (_root_hash, batch) = tree.put_value_set(
+                vec![(key, Some(version.to_be_bytes().to_vec()))],
+                version
+            )?;
+            db.commit_version(version, &batch)?;
+        }
+        let (_root_hash, batch) = tree.put_value_set(vec![(neighbour, Some(vec![2u8]))], 4)?;
+        db.commit_version(4, &batch)?;
+
+        assert_eq!(db.latest_version()?, Some(4));
+        assert_eq!(db.get_value_option(2, key)?, Some(2u64.to_be_bytes().to_vec()));
+        assert_eq!(db.get_value_option(4, key)?, Some(3u64.to_be_bytes().to_vec()));
+        assert_eq!(db.get_value_option(3, neighbour)?, None);
+        assert_eq!(db.get_value_option(4, neighbour)?, Some(vec![2u8]));
+
+        // versions below 3 are no longer readable after the purge
+        db.purge_stale_nodes(3)?;
+        assert!(tree.get_root_hash(2).is_err());
+        assert_eq!(tree.get(key, 3)?, Some(3u64.to_be_bytes().to_vec()));
+        assert_eq!(tree.get(neighbour, 4)?, Some(vec![2u8]));
+
+        Ok(())
+    }
+
+}
@@ -0,0 +1,15 @@
+use jmt::{
+    Version,
+    storage::{TreeUpdateBatch, TreeWriter}
+};
+
+/// A tree store which commits whole versions of the tree.
+pub trait VersionedTreeWriter: TreeWriter {
+
+    /// Atomically writes the update batch for `version` and records it as the latest version.
+    fn commit_version(&self, version: Version, batch: &TreeUpdateBatch) -> Result<(), anyhow::Error>;
+
+    /// The latest committed version, or `None` if nothing has been committed yet.
+    fn latest_version(&self) -> Result<Option<Version>, anyhow::Error>;
+
+}
@@ -2,4 +2,5 @@ pub mod change_set;
 pub use change_set::ChangeSetWriter;
 pub mod basic_storage_operations;
 pub use basic_storage_operations::BasicStorageOperations;
-pub mod proofs;
\ No newline at end of file
+pub mod proofs;
+pub use proofs::{MerkleProofs, Proven};
\ No newline at end of file
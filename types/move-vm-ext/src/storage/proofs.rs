@@ -1,4 +1,60 @@
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag}
+};
+
+/// A value read from a Merkleized store together with its proof.
+///
+/// The proof shows inclusion of the value when it is `Some`, and exclusion of the key otherwise.
+#[derive(Debug, Clone)]
+pub struct Proven<P> {
+    pub value: Option<Vec<u8>>,
+    pub proof: P,
+}
+
 pub trait MerkleProofs {
 
+    /// The commitment to the whole state at a version.
+    type Root;
+
+    /// A proof for a single key against a [`MerkleProofs::Root`].
+    type Proof;
+
+    /// The root of the state at the given version.
+    fn state_root(&self, version: u64) -> Result<Self::Root, anyhow::Error>;
+
+    /// Reads a module at the given version with a proof against that version's root.
+    fn get_module_with_proof(
+        &self,
+        id: &ModuleId,
+        version: u64
+    ) -> Result<Proven<Self::Proof>, anyhow::Error>;
+
+    /// Reads a resource at the given version with a proof against that version's root.
+    fn get_resource_with_proof(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+        version: u64
+    ) -> Result<Proven<Self::Proof>, anyhow::Error>;
+
+    /// Checks a module read against a root, without access to the store.
+    fn verify_module(
+        root: &Self::Root,
+        id: &ModuleId,
+        proven: &Proven<Self::Proof>
+    ) -> Result<(), anyhow::Error>
+    where
+        Self: Sized;
+
+    /// Checks a resource read against a root, without access to the store.
+    fn verify_resource(
+        root: &Self::Root,
+        address: &AccountAddress,
+        tag: &StructTag,
+        proven: &Proven<Self::Proof>
+    ) -> Result<(), anyhow::Error>
+    where
+        Self: Sized;
 
-}
\ No newline at end of file
+}
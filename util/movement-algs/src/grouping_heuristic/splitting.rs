@@ -115,11 +115,13 @@ mod block {
 			// split the vector of transactions
 			let split_transactions = Vec::from_iter(transactions).split(factor)?;
 
-			// create a new block for each split transaction
+			// create a new block for each split transaction, each with the metadata of its part of the block
 			let mut blocks = Vec::new();
+			let mut offset = 0;
 			for split in split_transactions {
-				let parent =
-					Block::new(metadata.clone(), parent.clone(), BTreeSet::from_iter(split));
+				let split_metadata = metadata.split_at(offset);
+				offset += split.len() as u64;
+				let parent = Block::new(split_metadata, parent.clone(), BTreeSet::from_iter(split));
 				blocks.push(parent);
 			}
 
@@ -49,10 +49,130 @@ impl fmt::Display for Id {
 	}
 }
 
+/// The metadata of a block as set by its proposer.
+///
+/// New formats must only ever be appended as new variants, such that blocks already posted to DA keep decoding.
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum BlockMetadata {
+	/// The legacy metadata which carries no information.
 	#[default]
 	BlockMetadata,
+	/// The first versioned metadata format.
+	V1(BlockMetadataV1),
+	/// The metadata of a part of a block which was split for posting.
+	V2(BlockMetadataV2),
+}
+
+impl BlockMetadata {
+	/// Creates the metadata of a block built by a proposer.
+	pub fn new(timestamp: u64, proposer: Vec<u8>, height: u64) -> Self {
+		Self::V1(BlockMetadataV1 { timestamp, proposer, height })
+	}
+
+	/// The format version of the metadata. The legacy format is version 0.
+	pub fn version(&self) -> u64 {
+		match self {
+			Self::BlockMetadata => 0,
+			Self::V1(_) => 1,
+			Self::V2(_) => 2,
+		}
+	}
+
+	/// The proposer's timestamp in microseconds, if the format carries one.
+	pub fn timestamp(&self) -> Option<u64> {
+		match self {
+			Self::BlockMetadata => None,
+			Self::V1(metadata) => Some(metadata.timestamp),
+			Self::V2(metadata) => Some(metadata.timestamp),
+		}
+	}
+
+	/// The sec1 encoded public key of the proposing sequencer, if the format carries one.
+	pub fn proposer(&self) -> Option<&[u8]> {
+		match self {
+			Self::BlockMetadata => None,
+			Self::V1(metadata) => Some(metadata.proposer.as_slice()),
+			Self::V2(metadata) => Some(metadata.proposer.as_slice()),
+		}
+	}
+
+	/// The block height assigned by the proposer, if the format carries one.
+	pub fn height(&self) -> Option<u64> {
+		match self {
+			Self::BlockMetadata => None,
+			Self::V1(metadata) => Some(metadata.height),
+			Self::V2(metadata) => Some(metadata.height),
+		}
+	}
+
+	/// The position of the first transaction of the block among the transactions of the block built at its height.
+	///
+	/// This is 0 for a block which was not split.
+	pub fn offset(&self) -> u64 {
+		match self {
+			Self::BlockMetadata | Self::V1(_) => 0,
+			Self::V2(metadata) => metadata.offset,
+		}
+	}
+
+	/// Derives the metadata of the part of a block which starts at the given transaction of the block.
+	///
+	/// The part keeps the height of the block and is ordered after the preceding parts by its offset,
+	/// and its timestamp is moved by the offset so that the parts execute with increasing timestamps.
+	/// The legacy format carries nothing to derive from and is kept as is.
+	pub fn split_at(&self, offset: u64) -> Self {
+		match self {
+			Self::BlockMetadata => Self::BlockMetadata,
+			Self::V1(metadata) => Self::V2(BlockMetadataV2 {
+				timestamp: metadata.timestamp + offset,
+				proposer: metadata.proposer.clone(),
+				height: metadata.height,
+				offset,
+			}),
+			Self::V2(metadata) => Self::V2(BlockMetadataV2 {
+				timestamp: metadata.timestamp + offset,
+				proposer: metadata.proposer.clone(),
+				height: metadata.height,
+				offset: metadata.offset + offset,
+			}),
+		}
+	}
+
+	/// Recovers the metadata of the block a part was split from, undoing [BlockMetadata::split_at].
+	///
+	/// Metadata which is not of a part is kept as is.
+	pub fn unsplit(&self) -> Self {
+		match self {
+			Self::BlockMetadata | Self::V1(_) => self.clone(),
+			Self::V2(metadata) => Self::V1(BlockMetadataV1 {
+				timestamp: metadata.timestamp - metadata.offset,
+				proposer: metadata.proposer.clone(),
+				height: metadata.height,
+			}),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockMetadataV1 {
+	/// The proposer's timestamp in microseconds.
+	pub timestamp: u64,
+	/// The sec1 encoded public key of the proposing sequencer.
+	pub proposer: Vec<u8>,
+	/// The block height assigned by the proposer.
+	pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockMetadataV2 {
+	/// The proposer's timestamp in microseconds.
+	pub timestamp: u64,
+	/// The sec1 encoded public key of the proposing sequencer.
+	pub proposer: Vec<u8>,
+	/// The block height assigned by the proposer.
+	pub height: u64,
+	/// The position of the first transaction of this part among the transactions of the block it was split from.
+	pub offset: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -67,6 +187,10 @@ impl Block {
 	pub fn new(metadata: BlockMetadata, parent: Id, transactions: BTreeSet<Transaction>) -> Self {
 		let mut hasher = blake3::Hasher::new();
 		hasher.update(parent.as_bytes());
+		// legacy metadata is not part of the id, so that ids of blocks already posted to DA are unchanged
+		if metadata != BlockMetadata::BlockMetadata {
+			bcs::serialize_into(&mut hasher, &metadata).expect("unexpected serialization error");
+		}
 		for transaction in &transactions {
 			hasher.update(&transaction.id().as_ref());
 		}
@@ -207,3 +331,76 @@ pub enum BlockCommitmentEvent {
 	Accepted(BlockCommitment),
 	Rejected { height: u64, reason: BlockCommitmentRejectionReason },
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_legacy_block_metadata_decodes() -> Result<(), anyhow::Error> {
+		let block = Block::test();
+		let bytes = bcs::to_bytes(&block)?;
+
+		// the legacy variant must stay the first variant
+		assert_eq!(bcs::to_bytes(&BlockMetadata::BlockMetadata)?, vec![0]);
+
+		let decoded: Block = bcs::from_bytes(&bytes)?;
+		assert_eq!(decoded, block);
+		assert_eq!(decoded.metadata().version(), 0);
+		assert_eq!(decoded.metadata().timestamp(), None);
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_block_metadata_v1() -> Result<(), anyhow::Error> {
+		let metadata = BlockMetadata::new(1_000, vec![2; 33], 7);
+		let block = Block::new(
+			metadata.clone(),
+			Id::test(),
+			BTreeSet::from_iter(vec![Transaction::test()]),
+		);
+
+		let decoded: Block = bcs::from_bytes(&bcs::to_bytes(&block)?)?;
+		assert_eq!(decoded.metadata(), &metadata);
+		assert_eq!(decoded.metadata().version(), 1);
+		assert_eq!(decoded.metadata().timestamp(), Some(1_000));
+		assert_eq!(decoded.metadata().proposer(), Some([2; 33].as_slice()));
+		assert_eq!(decoded.metadata().height(), Some(7));
+
+		// versioned metadata is committed to by the id
+		assert_ne!(block.id(), Block::test().id());
+
+		Ok(())
+	}
+
+	#[test]
+	fn test_block_metadata_split_at() -> Result<(), anyhow::Error> {
+		let metadata = BlockMetadata::new(1_000, vec![2; 33], 7);
+
+		let first = metadata.split_at(0);
+		let second = metadata.split_at(3);
+		assert_eq!(first.version(), 2);
+		assert_eq!(first.timestamp(), Some(1_000));
+		assert_eq!(second.timestamp(), Some(1_003));
+		assert_eq!(second.height(), Some(7));
+		assert_eq!(second.offset(), 3);
+
+		// splitting a part again offsets from the start of the original block
+		let third = second.split_at(2);
+		assert_eq!(third.timestamp(), Some(1_005));
+		assert_eq!(third.offset(), 5);
+
+		let decoded: BlockMetadata = bcs::from_bytes(&bcs::to_bytes(&third)?)?;
+		assert_eq!(decoded, third);
+
+		assert_eq!(BlockMetadata::BlockMetadata.split_at(3), BlockMetadata::BlockMetadata);
+
+		// every part recovers the metadata of the original block
+		assert_eq!(first.unsplit(), metadata);
+		assert_eq!(third.unsplit(), metadata);
+		assert_eq!(metadata.unsplit(), metadata);
+
+		Ok(())
+	}
+}
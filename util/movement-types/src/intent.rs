@@ -0,0 +1,152 @@
+use crate::transaction;
+use ecdsa::{
+	elliptic_curve::{
+		generic_array::ArrayLength,
+		ops::Invert,
+		point::PointCompression,
+		sec1::{FromEncodedPoint, ModulusSize, ToEncodedPoint},
+		subtle::CtOption,
+		AffinePoint, CurveArithmetic, FieldBytesSize, PrimeCurve, Scalar,
+	},
+	hazmat::{DigestPrimitive, SignPrimitive, VerifyPrimitive},
+	signature::{digest::Digest, DigestVerifier},
+	SignatureSize, SigningKey, VerifyingKey,
+};
+use movement_signer::{cryptography::secp256k1::Secp256k1, Signing};
+use serde::{Deserialize, Serialize};
+
+/// A sequencer's commitment to include a transaction in a block by a deadline.
+///
+/// Signed intents serve as pre-confirmations: an intent whose transaction is not in any block
+/// up to the first block after the deadline is evidence against the sequencer that signed it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SequencedIntent {
+	/// The id of the accepted transaction.
+	pub transaction_id: transaction::Id,
+	/// The height of the block the transaction is expected to be included in.
+	pub target_block_height: u64,
+	/// The latest block timestamp in microseconds at which the transaction may be included.
+	pub deadline: u64,
+}
+
+impl SequencedIntent {
+	pub fn new(transaction_id: transaction::Id, target_block_height: u64, deadline: u64) -> Self {
+		Self { transaction_id, target_block_height, deadline }
+	}
+
+	/// The canonical bytes which are signed by the sequencer.
+	pub fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
+		Ok(bcs::to_bytes(self)?)
+	}
+
+	pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		Ok(bcs::from_bytes(bytes)?)
+	}
+
+	/// Signs the intent with the sequencer's DA signing key.
+	pub fn try_to_sign<C>(self, signing_key: &SigningKey<C>) -> Result<SignedIntent, anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let prehash = C::Digest::new_with_prefix(self.to_bytes()?).finalize();
+		let (signature, _recovery_id) = signing_key.sign_prehash_recoverable(prehash.as_slice())?;
+
+		Ok(SignedIntent {
+			intent: self,
+			signature: signature.to_vec(),
+			signer: signing_key.verifying_key().to_sec1_bytes().to_vec(),
+		})
+	}
+
+	/// Signs the intent with a possibly remote secp256k1 signer, such as a KMS.
+	pub async fn try_to_sign_with<S>(self, signer: &S) -> Result<SignedIntent, anyhow::Error>
+	where
+		S: Signing<Secp256k1>,
+	{
+		let signature = signer.sign(&self.to_bytes()?).await?;
+		let public_key = signer.public_key().await?;
+
+		Ok(SignedIntent {
+			intent: self,
+			signature: signature.as_bytes().to_vec(),
+			signer: public_key.as_bytes().to_vec(),
+		})
+	}
+}
+
+/// An intent together with the signature of the sequencer which issued it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SignedIntent {
+	pub intent: SequencedIntent,
+	pub signature: Vec<u8>,
+	/// The sec1 encoded public key of the signing sequencer.
+	pub signer: Vec<u8>,
+}
+
+impl SignedIntent {
+	/// Checks that the signature over the intent was made by the signer.
+	pub fn try_verify<C>(&self) -> Result<(), anyhow::Error>
+	where
+		C: PrimeCurve + CurveArithmetic + DigestPrimitive + PointCompression,
+		Scalar<C>: Invert<Output = CtOption<Scalar<C>>> + SignPrimitive<C>,
+		SignatureSize<C>: ArrayLength<u8>,
+		AffinePoint<C>: FromEncodedPoint<C> + ToEncodedPoint<C> + VerifyPrimitive<C>,
+		FieldBytesSize<C>: ModulusSize,
+	{
+		let digest = C::Digest::new_with_prefix(self.intent.to_bytes()?);
+		let verifying_key = VerifyingKey::<C>::from_sec1_bytes(self.signer.as_slice())?;
+		let signature = ecdsa::Signature::<C>::from_slice(self.signature.as_slice())?;
+
+		match verifying_key.verify_digest(digest, &signature) {
+			Ok(_) => Ok(()),
+			Err(_) => Err(anyhow::anyhow!("Failed to verify intent signature")),
+		}
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use k256::Secp256k1;
+
+	#[test]
+	fn test_signed_intent_round_trip() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let intent = SequencedIntent::new(transaction::Id::new([1; 32]), 3, 1_000_000);
+		assert_eq!(SequencedIntent::try_from_bytes(&intent.to_bytes()?)?, intent);
+
+		let signed = intent.try_to_sign(&signing_key)?;
+		signed.try_verify::<Secp256k1>()?;
+
+		// the signature does not carry over to another intent
+		let mut forged = signed.clone();
+		forged.intent.deadline += 1;
+		assert!(forged.try_verify::<Secp256k1>().is_err());
+
+		// nor to another signer
+		let other_key = SigningKey::<Secp256k1>::from_slice(&[8; 32])?;
+		let mut forged = signed;
+		forged.signer = other_key.verifying_key().to_sec1_bytes().to_vec();
+		assert!(forged.try_verify::<Secp256k1>().is_err());
+
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_remote_signed_intent_verifies() -> Result<(), anyhow::Error> {
+		let signing_key = SigningKey::<Secp256k1>::from_slice(&[7; 32])?;
+		let signer = movement_signer::local::LocalSigner::new(signing_key.clone());
+		let intent = SequencedIntent::new(transaction::Id::new([1; 32]), 3, 1_000_000);
+
+		let signed = intent.try_to_sign_with(&signer).await?;
+		signed.try_verify::<Secp256k1>()?;
+		assert_eq!(signed, intent.try_to_sign(&signing_key)?);
+
+		Ok(())
+	}
+}
@@ -2,4 +2,5 @@ pub mod actor;
 pub mod application;
 pub mod atomic_transaction_bundle;
 pub mod block;
+pub mod intent;
 pub mod transaction;
@@ -7,7 +7,8 @@ use k256::ecdsa::{self, signature::Verifier};
 #[derive(Debug, Clone, Copy)]
 pub struct Secp256k1;
 
-fixed_size!(pub struct PublicKey([u8; 32]));
+// A compressed sec1 encoded point.
+fixed_size!(pub struct PublicKey([u8; 33]));
 fixed_size!(pub struct Signature([u8; 64]));
 
 impl Curve for Secp256k1 {
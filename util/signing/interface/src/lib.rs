@@ -3,6 +3,7 @@ use std::future::Future;
 use std::marker::PhantomData;
 
 pub mod cryptography;
+pub mod local;
 pub mod manager;
 
 /// Errors thrown by Signer
@@ -0,0 +1,62 @@
+use crate::cryptography::secp256k1::{self, Secp256k1};
+use crate::cryptography::TryFromBytes;
+use crate::{SignerError, Signing};
+use k256::ecdsa::{self, signature::Signer as _};
+
+/// A signer holding its key in process memory.
+///
+/// This is meant for development and tests, where the key does not need the protection of a remote signing service.
+#[derive(Debug, Clone)]
+pub struct LocalSigner {
+	signing_key: ecdsa::SigningKey,
+}
+
+impl LocalSigner {
+	pub fn new(signing_key: ecdsa::SigningKey) -> Self {
+		Self { signing_key }
+	}
+
+	/// Creates a signer from a hex encoded private key.
+	pub fn try_from_hex(private_key_hex: &str) -> Result<Self, SignerError> {
+		let bytes =
+			hex::decode(private_key_hex).map_err(|e| SignerError::Internal(e.to_string()))?;
+		let signing_key =
+			ecdsa::SigningKey::from_slice(&bytes).map_err(|e| SignerError::Decode(e.into()))?;
+		Ok(Self::new(signing_key))
+	}
+}
+
+impl Signing<Secp256k1> for LocalSigner {
+	async fn sign(&self, message: &[u8]) -> Result<secp256k1::Signature, SignerError> {
+		let signature: ecdsa::Signature =
+			self.signing_key.try_sign(message).map_err(|e| SignerError::Sign(e.into()))?;
+		secp256k1::Signature::try_from_bytes(signature.to_bytes().as_slice())
+			.map_err(|e| SignerError::Sign(e.into()))
+	}
+
+	async fn public_key(&self) -> Result<secp256k1::PublicKey, SignerError> {
+		secp256k1::PublicKey::try_from_bytes(
+			self.signing_key.verifying_key().to_sec1_bytes().as_ref(),
+		)
+		.map_err(|e| SignerError::PublicKey(e.into()))
+	}
+}
+
+#[cfg(test)]
+pub mod test {
+
+	use super::*;
+	use crate::Verify;
+
+	#[tokio::test]
+	async fn test_local_signer_round_trip() -> Result<(), anyhow::Error> {
+		let signer = LocalSigner::try_from_hex(&"07".repeat(32))?;
+		let signature = signer.sign(b"message").await?;
+		let public_key = signer.public_key().await?;
+
+		assert!(Secp256k1.verify(b"message", &signature, &public_key)?);
+		assert!(!Secp256k1.verify(b"other message", &signature, &public_key)?);
+
+		Ok(())
+	}
+}
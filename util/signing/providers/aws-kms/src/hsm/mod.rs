@@ -0,0 +1,107 @@
+use crate::cryptography::AwsKmsCryptography;
+use anyhow::Context;
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_kms::types::MessageType;
+use aws_sdk_kms::Client;
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::pkcs8::DecodePublicKey;
+use movement_signer::cryptography::TryFromBytes;
+use movement_signer::{
+	cryptography::{secp256k1::Secp256k1, Curve},
+	SignerError, Signing,
+};
+
+/// An AWS KMS HSM.
+#[derive(Debug, Clone)]
+pub struct AwsKms<C: Curve + AwsKmsCryptography> {
+	client: Client,
+	key_id: String,
+	_cryptography_marker: std::marker::PhantomData<C>,
+}
+
+impl<C> AwsKms<C>
+where
+	C: Curve + AwsKmsCryptography,
+{
+	/// Creates a new AWS KMS HSM
+	pub fn new(client: Client, key_id: String) -> Self {
+		Self { client, key_id, _cryptography_marker: std::marker::PhantomData }
+	}
+
+	/// Creates a new AWS KMS HSM for the key, with the AWS configuration from the environment
+	pub async fn try_from_key_id(key_id: String) -> Result<Self, anyhow::Error> {
+		let config = aws_config::load_from_env().await;
+		Ok(Self::new(Client::new(&config), key_id))
+	}
+
+	/// Tries to create a new AWS KMS HSM from the environment
+	pub async fn try_from_env() -> Result<Self, anyhow::Error> {
+		let key_id = std::env::var("AWS_KMS_KEY_ID").context("AWS_KMS_KEY_ID not set")?;
+		Self::try_from_key_id(key_id).await
+	}
+
+	/// Creates a new key in AWS KMS, and returns an HSM for it.
+	pub async fn create_key(self) -> Result<Self, anyhow::Error> {
+		let res = self
+			.client
+			.create_key()
+			.key_spec(C::key_spec())
+			.key_usage(C::key_usage_type())
+			.send()
+			.await
+			.context("Failed to create key")?;
+
+		let key_id = res.key_metadata().context("No key metadata available")?.key_id().to_string();
+
+		Ok(Self::new(self.client, key_id))
+	}
+
+	pub fn key_id(&self) -> &str {
+		self.key_id.as_str()
+	}
+}
+
+impl Signing<Secp256k1> for AwsKms<Secp256k1> {
+	async fn sign(&self, message: &[u8]) -> Result<<Secp256k1 as Curve>::Signature, SignerError> {
+		// KMS hashes raw messages with SHA-256, as local secp256k1 keys do
+		let res = self
+			.client
+			.sign()
+			.key_id(&self.key_id)
+			.signing_algorithm(Secp256k1::signing_algorithm_spec())
+			.message_type(MessageType::Raw)
+			.message(Blob::new(message))
+			.send()
+			.await
+			.map_err(|e| SignerError::Sign(e.into()))?;
+
+		let der = res.signature().ok_or(SignerError::Internal("No signature available".into()))?;
+		let signature = k256::ecdsa::Signature::from_der(der.as_ref())
+			.map_err(|e| SignerError::Decode(e.to_string().into()))?;
+		// KMS does not normalize s, but secp256k1 verifiers only accept the low form
+		let signature = signature.normalize_s().unwrap_or(signature);
+
+		<Secp256k1 as Curve>::Signature::try_from_bytes(signature.to_bytes().as_slice())
+			.map_err(|e| SignerError::Decode(e.into()))
+	}
+
+	async fn public_key(&self) -> Result<<Secp256k1 as Curve>::PublicKey, SignerError> {
+		let res = self
+			.client
+			.get_public_key()
+			.key_id(&self.key_id)
+			.send()
+			.await
+			.map_err(|e| SignerError::PublicKey(e.into()))?;
+
+		// KMS returns a DER encoded SubjectPublicKeyInfo
+		let der = res.public_key().ok_or(SignerError::KeyNotFound)?;
+		let public_key = k256::PublicKey::from_public_key_der(der.as_ref())
+			.map_err(|e| SignerError::Decode(e.to_string().into()))?;
+
+		<Secp256k1 as Curve>::PublicKey::try_from_bytes(
+			public_key.to_encoded_point(true).as_bytes(),
+		)
+		.map_err(|e| SignerError::Decode(e.into()))
+	}
+}
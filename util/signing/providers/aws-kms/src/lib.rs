@@ -1 +1,2 @@
 pub mod cryptography;
+pub mod hsm;
@@ -0,0 +1,46 @@
+use anyhow::Context;
+use std::collections::HashMap;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use vaultrs::kv2;
+
+/// Secrets stored in the KV version 2 engine of HashiCorp Vault.
+///
+/// The transit engine has no secp256k1 keys, so secp256k1 signers keep their key in a secret
+/// and sign in process after reading it.
+pub struct HashiCorpVaultKv {
+	client: VaultClient,
+	mount_name: String,
+}
+
+impl HashiCorpVaultKv {
+	/// Creates a new store of secrets under the mount
+	pub fn new(client: VaultClient, mount_name: String) -> Self {
+		Self { client, mount_name }
+	}
+
+	/// Tries to create a new store of secrets from the address, token and namespace in the environment
+	pub fn try_from_env(mount_name: String) -> Result<Self, anyhow::Error> {
+		let address = std::env::var("VAULT_ADDRESS").context("VAULT_ADDRESS not set")?;
+		let token = std::env::var("VAULT_TOKEN").context("VAULT_TOKEN not set")?;
+		let namespace = std::env::var("VAULT_NAMESPACE").unwrap_or_else(|_| "admin".to_string());
+		let client = VaultClient::new(
+			VaultClientSettingsBuilder::default()
+				.address(address.as_str())
+				.token(token.as_str())
+				.namespace(Some(namespace))
+				.build()?,
+		)?;
+
+		Ok(Self::new(client, mount_name))
+	}
+
+	/// Reads a field of the latest version of the secret at the path
+	pub async fn read_field(&self, path: &str, field: &str) -> Result<String, anyhow::Error> {
+		let secret: HashMap<String, String> =
+			kv2::read(&self.client, self.mount_name.as_str(), path)
+				.await
+				.context("Failed to read secret")?;
+
+		secret.get(field).cloned().context(format!("Secret has no field {}", field))
+	}
+}
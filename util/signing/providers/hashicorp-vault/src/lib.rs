@@ -1,2 +1,3 @@
 pub mod cryptography;
 pub mod hsm;
+pub mod kv;